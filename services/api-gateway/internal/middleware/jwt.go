@@ -23,6 +23,8 @@ func JWTAuth(authServiceURL string) gin.HandlerFunc {
 			"/health",
 			"/api/auth/signup",
 			"/api/auth/signin",
+			"/api/auth/password-reset/",
+			"/api/auth/verify-email",
 		}
 
 		for _, route := range publicRoutes {
@@ -32,6 +34,23 @@ func JWTAuth(authServiceURL string) gin.HandlerFunc {
 			}
 		}
 
+		// ============================================================
+		// Routes the auth service authenticates itself
+		// ============================================================
+		// These accept restricted tokens (such as the password change token issued
+		// while a reset is required, or the unverified email token issued by signin
+		// under the restrict policy), which /token-verify rejects. The auth service
+		// checks the token, its session and its scope on every request.
+		selfAuthenticatedRoutes := map[string]string{
+			"/api/user/password": http.MethodPost,
+			"/api/user/profile":  http.MethodGet,
+		}
+
+		if method, ok := selfAuthenticatedRoutes[path]; ok && c.Request.Method == method {
+			c.Next()
+			return
+		}
+
 		// ============================================================
 		// Extract JWT Token from Authorization header
 		// ============================================================
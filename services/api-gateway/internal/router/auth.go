@@ -16,6 +16,12 @@ func SetupAuthRoutes(r *gin.Engine, authProxy *proxy.AuthProxy) {
 		auth.POST("/signin", authProxy.CreateHandler("/signin"))
 		auth.POST("/logout", authProxy.CreateHandler("/logout"))
 
+		// Password reset and email verification routes
+		auth.POST("/password-reset/request", authProxy.CreateHandler("/password-reset/request"))
+		auth.POST("/password-reset/confirm", authProxy.CreateHandler("/password-reset/confirm"))
+		auth.POST("/verify-email", authProxy.CreateHandler("/verify-email"))
+		auth.POST("/verify-email/resend", authProxy.CreateHandler("/verify-email/resend"))
+
 		// Token management routes
 		auth.POST("/token-verify", authProxy.CreateHandler("/token-verify"))
 		auth.POST("/token-info", authProxy.CreateHandler("/token-info"))
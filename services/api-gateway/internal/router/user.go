@@ -14,5 +14,6 @@ func SetupUserRoutes(r *gin.Engine, authProxy *proxy.AuthProxy) {
 		// Core user profile routes
 		user.GET("/profile", authProxy.CreateHandler("/user/profile"))
 		user.PATCH("/profile", authProxy.CreateHandler("/user/profile"))
+		user.POST("/password", authProxy.CreateHandler("/user/password"))
 	}
 }
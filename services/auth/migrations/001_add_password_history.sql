@@ -0,0 +1,24 @@
+-- Migration: auth.001_add_password_history.sql
+-- Service: auth
+-- Description: add password history
+-- Date: 2026-10-18
+
+\c venomous_auth_db;
+
+-- Previous password hashes per auth user, used to prevent password reuse
+CREATE TABLE IF NOT EXISTS password_history (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    auth_user_id UUID NOT NULL REFERENCES auth_users(id) ON DELETE CASCADE,
+    password_hash VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_password_history_auth_user_created
+    ON password_history(auth_user_id, created_at DESC);
+
+-- Seed history with each account's current password
+INSERT INTO password_history (auth_user_id, password_hash)
+SELECT id, password_hash FROM auth_users
+WHERE NOT EXISTS (
+    SELECT 1 FROM password_history ph WHERE ph.auth_user_id = auth_users.id
+);
@@ -0,0 +1,10 @@
+-- Migration: auth.004_add_password_reset_required.sql
+-- Service: auth
+-- Description: flag accounts that must set a new password at next sign-in
+-- Date: 2026-10-19
+
+\c venomous_auth_db;
+
+-- While set, sign-in only issues a token restricted to changing the password
+ALTER TABLE auth_users
+    ADD COLUMN IF NOT EXISTS password_reset_required BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,15 @@
+-- Migration: auth.005_add_password_rotation.sql
+-- Service: auth
+-- Description: track password age and add a per-role maximum password age
+-- Date: 2026-10-19
+
+\c venomous_auth_db;
+
+-- Existing accounts start their rotation clock when this migration runs
+ALTER TABLE auth_users
+    ADD COLUMN IF NOT EXISTS password_changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+-- Maximum password age in days for members of the role (NULL disables rotation)
+-- e.g. UPDATE roles SET password_max_age_days = 90 WHERE name IN ('admin', 'super_admin');
+ALTER TABLE roles
+    ADD COLUMN IF NOT EXISTS password_max_age_days INTEGER CHECK (password_max_age_days > 0);
@@ -0,0 +1,20 @@
+-- Migration: auth.006_add_email_verification_tokens.sql
+-- Service: auth
+-- Description: add email verification tokens
+-- Date: 2026-10-19
+
+\c venomous_auth_db;
+
+-- Single-use email verification tokens (only the SHA-256 hash is stored)
+CREATE TABLE IF NOT EXISTS email_verification_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) UNIQUE NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Resend rate limiting counts recent tokens per user
+CREATE INDEX IF NOT EXISTS idx_email_verification_tokens_user_created
+    ON email_verification_tokens(user_id, created_at);
@@ -0,0 +1,30 @@
+-- Migration: auth.007_add_email_outbox.sql
+-- Service: auth
+-- Description: add transactional email outbox
+-- Date: 2026-10-19
+
+\c venomous_auth_db;
+
+-- Outgoing email written in the same transaction as the state change that triggers it.
+-- A one-time token lives in `secret`, filled into the body's placeholder at send time, so
+-- stored bodies never hold a live link; it is cleared once the email is sent or dead.
+CREATE TABLE IF NOT EXISTS email_outbox (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    to_address VARCHAR(255) NOT NULL,
+    subject TEXT NOT NULL,
+    body TEXT NOT NULL,
+    secret TEXT,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'sent', 'dead')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    sent_at TIMESTAMPTZ,
+    CONSTRAINT email_outbox_secret_pending CHECK (secret IS NULL OR status = 'pending')
+);
+
+-- The worker polls pending rows that are due
+CREATE INDEX IF NOT EXISTS idx_email_outbox_pending
+    ON email_outbox(next_attempt_at) WHERE status = 'pending';
+CREATE INDEX IF NOT EXISTS idx_email_outbox_status_created
+    ON email_outbox(status, created_at);
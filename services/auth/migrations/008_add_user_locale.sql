@@ -0,0 +1,10 @@
+-- Migration: auth.008_add_user_locale.sql
+-- Service: auth
+-- Description: add users.locale display language preference
+-- Date: 2026-10-19
+
+\c venomous_auth_db;
+
+-- Language for API messages and emails (NULL follows the client's Accept-Language)
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS locale VARCHAR(10);
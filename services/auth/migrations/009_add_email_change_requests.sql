@@ -0,0 +1,24 @@
+-- Migration: auth.009_add_email_change_requests.sql
+-- Service: auth
+-- Description: add email change requests confirmed by the new address and cancellable from the old one
+-- Date: 2026-10-19
+
+\c venomous_auth_db;
+
+-- Pending and completed email changes (only SHA-256 hashes of the emailed tokens are stored)
+CREATE TABLE IF NOT EXISTS email_change_requests (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    old_email VARCHAR(255) NOT NULL,
+    new_email VARCHAR(255) NOT NULL,
+    token_hash VARCHAR(64) UNIQUE NOT NULL,
+    cancel_token_hash VARCHAR(64) UNIQUE NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    cancel_expires_at TIMESTAMPTZ NOT NULL,
+    confirmed_at TIMESTAMPTZ,
+    cancelled_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_email_change_requests_user_id
+    ON email_change_requests(user_id);
@@ -0,0 +1,21 @@
+-- Migration: auth.010_add_user_status.sql
+-- Service: auth
+-- Description: add users.status (active, disabled, suspended) with optional suspension end
+-- Date: 2026-10-19
+
+\c venomous_auth_db;
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS status VARCHAR(20) NOT NULL DEFAULT 'active',
+    ADD COLUMN IF NOT EXISTS status_reason TEXT,
+    ADD COLUMN IF NOT EXISTS suspended_until TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS status_changed_at TIMESTAMPTZ;
+
+ALTER TABLE users
+    ADD CONSTRAINT users_status_check
+        CHECK (status IN ('active', 'disabled', 'suspended')),
+    -- Only a suspension can have an end date (NULL suspends until lifted)
+    ADD CONSTRAINT users_suspended_until_check
+        CHECK (status = 'suspended' OR suspended_until IS NULL);
+
+CREATE INDEX IF NOT EXISTS idx_users_status ON users(status);
@@ -0,0 +1,10 @@
+-- Migration: auth.011_add_user_listing_indexes.sql
+-- Service: auth
+-- Description: indexes for keyset pagination of the admin user list (default sort and name sort)
+-- Date: 2026-10-19
+
+\c venomous_auth_db;
+
+-- The admin user list sorts by (column, id); these match the default and most common sorts
+CREATE INDEX IF NOT EXISTS idx_users_created_at_id ON users(created_at, id) WHERE deleted_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_users_name_id ON users(name, id) WHERE deleted_at IS NULL;
@@ -0,0 +1,23 @@
+-- Migration: auth.012_add_security_events.sql
+-- Service: auth
+-- Description: add security events (sign-ins, lockouts, account and admin actions)
+-- Date: 2026-10-19
+
+\c venomous_auth_db;
+
+-- Append-only log behind the admin security logs page; user_id is kept NULL for unknown accounts
+CREATE TABLE IF NOT EXISTS security_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    event_type VARCHAR(100) NOT NULL,
+    success BOOLEAN NOT NULL,
+    ip_address VARCHAR(64),
+    user_agent TEXT,
+    metadata JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Each admin filter (none, user, event type) is served newest first from its own index
+CREATE INDEX IF NOT EXISTS idx_security_events_created_at ON security_events(created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_security_events_user_id ON security_events(user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_security_events_event_type ON security_events(event_type, created_at DESC);
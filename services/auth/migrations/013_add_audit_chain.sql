@@ -0,0 +1,50 @@
+-- Migration: auth.013_add_audit_chain.sql
+-- Service: auth
+-- Description: hash-chain security events in parallel streams, add signed audit checkpoints and make both append-only
+-- Date: 2026-10-19
+
+\c venomous_auth_db;
+
+-- Each new event stores its stream, its position in that stream's chain, the previous entry's
+-- hash and its own hash. Streams are independent chains, so writers don't queue on one lock.
+-- Events recorded before this migration stay unchained (NULL).
+ALTER TABLE security_events
+    ADD COLUMN IF NOT EXISTS stream SMALLINT NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS sequence BIGINT,
+    ADD COLUMN IF NOT EXISTS prev_hash VARCHAR(64),
+    ADD COLUMN IF NOT EXISTS entry_hash VARCHAR(64);
+
+ALTER TABLE security_events DROP CONSTRAINT IF EXISTS security_events_stream_sequence_key;
+ALTER TABLE security_events
+    ADD CONSTRAINT security_events_stream_sequence_key UNIQUE (stream, sequence);
+
+-- The audit trail outlives the accounts it mentions; ON DELETE SET NULL would rewrite hashed rows
+ALTER TABLE security_events DROP CONSTRAINT IF EXISTS security_events_user_id_fkey;
+
+-- HMAC-signed snapshots of the chain head, so a rewritten chain can't be passed off as genuine
+CREATE TABLE IF NOT EXISTS audit_checkpoints (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    stream SMALLINT NOT NULL DEFAULT 0,
+    sequence BIGINT NOT NULL,
+    entry_hash VARCHAR(64) NOT NULL,
+    signature VARCHAR(64) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT audit_checkpoints_stream_sequence_key UNIQUE (stream, sequence)
+);
+
+-- Audit rows can be added but never changed or removed through normal access
+CREATE OR REPLACE FUNCTION reject_audit_modification() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION '% is append-only', TG_TABLE_NAME;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS security_events_append_only ON security_events;
+CREATE TRIGGER security_events_append_only
+    BEFORE UPDATE OR DELETE ON security_events
+    FOR EACH ROW EXECUTE FUNCTION reject_audit_modification();
+
+DROP TRIGGER IF EXISTS audit_checkpoints_append_only ON audit_checkpoints;
+CREATE TRIGGER audit_checkpoints_append_only
+    BEFORE UPDATE OR DELETE ON audit_checkpoints
+    FOR EACH ROW EXECUTE FUNCTION reject_audit_modification();
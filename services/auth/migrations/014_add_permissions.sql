@@ -0,0 +1,46 @@
+-- Migration: auth.014_add_permissions.sql
+-- Service: auth
+-- Description: add permissions and role_permissions so admin access is granted per permission
+-- Date: 2026-10-19
+
+\c venomous_auth_db;
+
+-- Fine-grained permissions, named resource:action. 'global' permissions gate the admin API
+-- and are never held inside an organization; 'organization' permissions are what roles,
+-- groups and direct grants give a member there
+CREATE TABLE IF NOT EXISTS permissions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(100) UNIQUE NOT NULL,
+    description TEXT,
+    scope VARCHAR(20) NOT NULL DEFAULT 'global' CHECK (scope IN ('global', 'organization')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Permissions granted to each role
+CREATE TABLE IF NOT EXISTS role_permissions (
+    role_id UUID NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    permission_id UUID NOT NULL REFERENCES permissions(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (role_id, permission_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_role_permissions_permission_id ON role_permissions(permission_id);
+
+INSERT INTO permissions (name, description) VALUES
+    ('users:read', 'List users and view their lock and password status'),
+    ('users:disable', 'Activate, disable or suspend accounts'),
+    ('users:reset_password', 'Reset passwords and require password changes'),
+    ('users:unlock', 'Unlock accounts locked after failed sign-ins'),
+    ('sessions:revoke', 'Revoke a user''s sessions'),
+    ('audit:read', 'Read and stream security events'),
+    ('audit:verify', 'Verify the audit chain'),
+    ('audit:export', 'Export security events'),
+    ('email_outbox:read', 'View the email delivery queue')
+ON CONFLICT (name) DO NOTHING;
+
+-- Admins keep the access they had before permissions existed; regular users get none
+INSERT INTO role_permissions (role_id, permission_id)
+SELECT roles.id, permissions.id
+FROM roles CROSS JOIN permissions
+WHERE roles.name IN ('admin', 'super_admin')
+ON CONFLICT DO NOTHING;
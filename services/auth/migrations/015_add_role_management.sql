@@ -0,0 +1,19 @@
+-- Migration: auth.015_add_role_management.sql
+-- Service: auth
+-- Description: add permissions for managing roles and assigning them to users
+-- Date: 2026-10-19
+
+\c venomous_auth_db;
+
+INSERT INTO permissions (name, description) VALUES
+    ('roles:read', 'List roles, their permissions and the permissions available'),
+    ('roles:manage', 'Create, edit and delete roles'),
+    ('users:assign_role', 'Change the role a user holds')
+ON CONFLICT (name) DO NOTHING;
+
+INSERT INTO role_permissions (role_id, permission_id)
+SELECT roles.id, permissions.id
+FROM roles CROSS JOIN permissions
+WHERE roles.name IN ('admin', 'super_admin')
+  AND permissions.name IN ('roles:read', 'roles:manage', 'users:assign_role')
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,57 @@
+-- Migration: auth.016_add_role_hierarchy.sql
+-- Service: auth
+-- Description: let roles inherit the permissions of a parent role and count changes so every instance can tell its cached hierarchy is stale
+-- Date: 2026-10-19
+
+\c venomous_auth_db;
+
+ALTER TABLE roles ADD COLUMN IF NOT EXISTS parent_role_id UUID REFERENCES roles(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_roles_parent_role_id ON roles(parent_role_id);
+
+-- super_admin inherits from admin, which inherits from user
+UPDATE roles SET parent_role_id = (SELECT id FROM roles WHERE name = 'user')
+WHERE name = 'admin';
+
+UPDATE roles SET parent_role_id = (SELECT id FROM roles WHERE name = 'admin')
+WHERE name = 'super_admin';
+
+-- super_admin no longer needs its own copy of admin's permissions
+DELETE FROM role_permissions
+WHERE role_id = (SELECT id FROM roles WHERE name = 'super_admin')
+  AND permission_id IN (
+      SELECT permission_id FROM role_permissions
+      WHERE role_id = (SELECT id FROM roles WHERE name = 'admin')
+  );
+
+-- Single row bumped by any statement that changes roles, role grants or permission names,
+-- whichever instance (or psql session) makes the change
+CREATE TABLE IF NOT EXISTS role_hierarchy_version (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    version BIGINT NOT NULL DEFAULT 0
+);
+
+INSERT INTO role_hierarchy_version (id, version) VALUES (TRUE, 0)
+ON CONFLICT (id) DO NOTHING;
+
+CREATE OR REPLACE FUNCTION bump_role_hierarchy_version() RETURNS trigger AS $$
+BEGIN
+    UPDATE role_hierarchy_version SET version = version + 1;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS roles_bump_hierarchy_version ON roles;
+CREATE TRIGGER roles_bump_hierarchy_version
+    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON roles
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_role_hierarchy_version();
+
+DROP TRIGGER IF EXISTS role_permissions_bump_hierarchy_version ON role_permissions;
+CREATE TRIGGER role_permissions_bump_hierarchy_version
+    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON role_permissions
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_role_hierarchy_version();
+
+DROP TRIGGER IF EXISTS permissions_bump_hierarchy_version ON permissions;
+CREATE TRIGGER permissions_bump_hierarchy_version
+    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON permissions
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_role_hierarchy_version();
@@ -0,0 +1,71 @@
+-- Migration: auth.017_add_organizations.sql
+-- Service: auth
+-- Description: add organizations, per-organization memberships, roles and permissions, invitations and the session's active organization
+-- Date: 2026-10-19
+
+\c venomous_auth_db;
+
+CREATE TABLE IF NOT EXISTS organizations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(100) NOT NULL,
+    slug VARCHAR(50) UNIQUE NOT NULL,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    deleted_at TIMESTAMPTZ
+);
+
+-- A user's role in each organization they belong to
+CREATE TABLE IF NOT EXISTS memberships (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    organization_id UUID NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role_id UUID NOT NULL REFERENCES roles(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (organization_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_memberships_user_id ON memberships(user_id);
+
+-- What a member may do with the organization's resources
+INSERT INTO permissions (name, description, scope) VALUES
+    ('notes:read', 'Read the organization''s notes', 'organization'),
+    ('notes:write', 'Create, edit and delete the organization''s notes', 'organization'),
+    ('medias:read', 'View the organization''s media', 'organization'),
+    ('medias:write', 'Upload and delete the organization''s media', 'organization'),
+    ('workflows:read', 'View the organization''s workflows and their runs', 'organization'),
+    ('workflows:run', 'Start and cancel the organization''s workflows', 'organization')
+ON CONFLICT (name) DO NOTHING;
+
+-- Members read everything in their organization; organization administrators (admin,
+-- inherited by super_admin) may also change it
+INSERT INTO role_permissions (role_id, permission_id)
+SELECT roles.id, permissions.id
+FROM roles CROSS JOIN permissions
+WHERE permissions.scope = 'organization'
+  AND (roles.name = 'admin' OR (roles.name = 'user' AND permissions.name LIKE '%:read'))
+ON CONFLICT DO NOTHING;
+
+-- Emailed invitations; only the SHA-256 hash of the token is stored
+CREATE TABLE IF NOT EXISTS organization_invitations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    organization_id UUID NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    email VARCHAR(255) NOT NULL,
+    role_id UUID NOT NULL REFERENCES roles(id),
+    token_hash VARCHAR(64) UNIQUE NOT NULL,
+    invited_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    accepted_at TIMESTAMPTZ,
+    accepted_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_organization_invitations_pending
+    ON organization_invitations(organization_id, LOWER(email))
+    WHERE accepted_at IS NULL AND revoked_at IS NULL;
+
+-- The organization a session is working in, carried in its tokens as the `org` claim
+ALTER TABLE user_sessions
+    ADD COLUMN IF NOT EXISTS organization_id UUID REFERENCES organizations(id) ON DELETE SET NULL;
@@ -0,0 +1,46 @@
+-- Migration: auth.018_add_groups.sql
+-- Service: auth
+-- Description: add groups inside organizations, group members and permissions, and direct member permission grants
+-- Date: 2026-10-19
+
+\c venomous_auth_db;
+
+-- Named groups of members, e.g. "editors", that hold permissions within one organization
+CREATE TABLE IF NOT EXISTS groups (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    organization_id UUID NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    name VARCHAR(50) NOT NULL,
+    description TEXT,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (organization_id, name)
+);
+
+-- Members belong to groups through their membership, so leaving the organization
+-- also takes them out of its groups
+CREATE TABLE IF NOT EXISTS group_members (
+    group_id UUID NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+    membership_id UUID NOT NULL REFERENCES memberships(id) ON DELETE CASCADE,
+    added_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (group_id, membership_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_group_members_membership_id ON group_members(membership_id);
+
+CREATE TABLE IF NOT EXISTS group_permissions (
+    group_id UUID NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+    permission_id UUID NOT NULL REFERENCES permissions(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (group_id, permission_id)
+);
+
+-- Permissions granted to one member directly, on top of their role and groups
+CREATE TABLE IF NOT EXISTS member_permissions (
+    membership_id UUID NOT NULL REFERENCES memberships(id) ON DELETE CASCADE,
+    permission_id UUID NOT NULL REFERENCES permissions(id) ON DELETE CASCADE,
+    granted_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (membership_id, permission_id)
+);
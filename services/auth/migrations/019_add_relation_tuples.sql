@@ -0,0 +1,47 @@
+-- Migration: auth.019_add_relation_tuples.sql
+-- Service: auth
+-- Description: add relation tuples (object#relation@subject) for sharing notes, articles and folders, with revisions for consistency tokens
+-- Date: 2026-10-19
+
+\c venomous_auth_db;
+
+-- One relationship between an object and a subject, e.g. note:42#viewer@user:<uuid>
+-- or folder:7#editor@group:9#member. Tuples are never updated: a delete stamps
+-- deleted_revision so reads at an earlier revision still see them. group:<id>#member
+-- means the users in that organization group (group_members), so no tuple is ever
+-- written on a group.
+CREATE TABLE IF NOT EXISTS relation_tuples (
+    id BIGSERIAL PRIMARY KEY,
+    namespace VARCHAR(32) NOT NULL,
+    object_id VARCHAR(128) NOT NULL,
+    relation VARCHAR(32) NOT NULL,
+    subject_namespace VARCHAR(32) NOT NULL,
+    subject_id VARCHAR(128) NOT NULL,
+    -- Set when the subject is a userset such as group:9#member
+    subject_relation VARCHAR(32),
+    created_revision BIGINT NOT NULL,
+    deleted_revision BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT relation_tuples_not_on_groups CHECK (namespace <> 'group')
+);
+
+-- At most one live copy of each tuple
+CREATE UNIQUE INDEX IF NOT EXISTS idx_relation_tuples_live ON relation_tuples(
+    namespace, object_id, relation, subject_namespace, subject_id, COALESCE(subject_relation, '')
+) WHERE deleted_revision IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_relation_tuples_object
+    ON relation_tuples(namespace, object_id, relation);
+CREATE INDEX IF NOT EXISTS idx_relation_tuples_subject
+    ON relation_tuples(subject_namespace, subject_id);
+
+-- The latest committed revision of the tuple store. Writes lock this row, so
+-- revisions are handed out in commit order and a consistency token names a
+-- snapshot that can't change afterwards.
+CREATE TABLE IF NOT EXISTS relation_tuple_revision (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    revision BIGINT NOT NULL DEFAULT 0
+);
+
+INSERT INTO relation_tuple_revision (id, revision) VALUES (TRUE, 0)
+ON CONFLICT (id) DO NOTHING;
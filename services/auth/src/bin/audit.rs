@@ -0,0 +1,196 @@
+//! Audit log maintenance
+//!
+//! ```text
+//! cargo run --bin audit -- verify       # walk the chain, exit 1 at the first bad entry
+//! cargo run --bin audit -- checkpoint   # sign the head of every chain stream
+//! cargo run --bin audit -- export --format csv --from 2026-01-01 --to 2026-01-31 \
+//!     [--event-type signin_failed] [--user-id <uuid>] [--gzip] [--output events.csv.gz]
+//! ```
+//!
+//! `export` writes to stdout unless `--output` is given; `--from`/`--to` take the
+//! same dates as the admin API (`--to` includes the whole day).
+
+use flate2::{write::GzEncoder, Compression};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::process::ExitCode;
+
+use venomous_dashboard_auth::database::Database;
+use venomous_dashboard_auth::handlers::admin::AuditExportQuery;
+use venomous_dashboard_auth::utils::{AuditChain, ExportFormat};
+
+const USAGE: &str = "usage: audit <verify|checkpoint|export> \
+[--format jsonl|csv|cef] [--from DATE] [--to DATE] [--event-type TYPE] [--user-id UUID] \
+[--gzip] [--output FILE]";
+
+/// Options of the `export` command
+struct ExportOptions {
+    format: ExportFormat,
+    query: AuditExportQuery,
+    output: Option<String>,
+}
+
+fn parse_export_options(args: &[String]) -> Result<ExportOptions, String> {
+    let mut query = AuditExportQuery {
+        format: None,
+        gzip: false,
+        event_type: None,
+        user_id: None,
+        start_date: None,
+        end_date: None,
+    };
+    let mut output = None;
+
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        if flag == "--gzip" {
+            query.gzip = true;
+            continue;
+        }
+        let value = args
+            .next()
+            .cloned()
+            .ok_or_else(|| format!("{} needs a value", flag))?;
+        match flag.as_str() {
+            "--format" => query.format = Some(value),
+            "--from" => query.start_date = Some(value),
+            "--to" => query.end_date = Some(value),
+            "--event-type" => query.event_type = Some(value),
+            "--user-id" => query.user_id = Some(value),
+            "--output" => output = Some(value),
+            other => return Err(format!("unknown option {}", other)),
+        }
+    }
+
+    let format = query.format.as_deref().unwrap_or("jsonl").parse()?;
+    Ok(ExportOptions {
+        format,
+        query,
+        output,
+    })
+}
+
+fn export(database: &Database, options: ExportOptions) -> ExitCode {
+    let Some(filter) = options.query.filter() else {
+        eprintln!("Invalid filters: user IDs must be UUIDs and dates YYYY-MM-DD or RFC 3339");
+        return ExitCode::from(2);
+    };
+
+    let out: Box<dyn Write> = match &options.output {
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(e) => {
+                eprintln!("Could not create {}: {}", path, e);
+                return ExitCode::from(2);
+            }
+        },
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+
+    let result = if options.query.gzip {
+        let mut encoder = GzEncoder::new(out, Compression::default());
+        database
+            .export_security_events(&filter, options.format, &mut encoder)
+            .and_then(|count| Ok(encoder.finish()?.flush().map(|_| count)?))
+    } else {
+        let mut out = out;
+        database
+            .export_security_events(&filter, options.format, &mut out)
+            .and_then(|count| Ok(out.flush().map(|_| count)?))
+    };
+
+    match result {
+        Ok(count) => {
+            eprintln!("Exported {} security events", count);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Could not export security events: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    dotenvy::dotenv().ok();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = args.first().cloned();
+    let export_options = match command.as_deref() {
+        Some("verify" | "checkpoint") if args.len() == 1 => None,
+        Some("export") => match parse_export_options(&args[1..]) {
+            Ok(options) => Some(options),
+            Err(e) => {
+                eprintln!("{}\n{}", e, USAGE);
+                return ExitCode::from(2);
+            }
+        },
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let database = match Database::new() {
+        Ok(database) => database,
+        Err(e) => {
+            eprintln!("Failed to initialize database: {}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    if let Some(options) = export_options {
+        return export(&database, options);
+    }
+
+    match command.as_deref() {
+        Some("verify") => match database.verify_audit_chain() {
+            Ok(report) => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&report).unwrap_or_default()
+                );
+                match &report.first_failure {
+                    Some(failure) => {
+                        eprintln!(
+                            "Audit chain is broken in stream {:?} at sequence {:?} (event {:?}): {}",
+                            failure.stream, failure.sequence, failure.event_id, failure.detail
+                        );
+                        ExitCode::FAILURE
+                    }
+                    None => ExitCode::SUCCESS,
+                }
+            }
+            Err(e) => {
+                eprintln!("Could not verify the audit chain: {}", e);
+                ExitCode::from(2)
+            }
+        },
+        Some("checkpoint") if AuditChain::signing_key().is_none() => {
+            eprintln!("AUDIT_SIGNING_KEY must be set to sign checkpoints");
+            ExitCode::from(2)
+        }
+        Some("checkpoint") => match database.create_audit_checkpoint() {
+            Ok(checkpoints) if checkpoints.is_empty() => {
+                println!(
+                    "Nothing to sign: every stream is empty or its head already has a checkpoint"
+                );
+                ExitCode::SUCCESS
+            }
+            Ok(checkpoints) => {
+                for checkpoint in checkpoints {
+                    println!(
+                        "Signed checkpoint for stream {} at sequence {} ({})",
+                        checkpoint.stream, checkpoint.sequence, checkpoint.entry_hash
+                    );
+                }
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("Could not create a checkpoint: {}", e);
+                ExitCode::from(2)
+            }
+        },
+        _ => unreachable!("command checked above"),
+    }
+}
@@ -8,17 +8,44 @@ impl ErrorCode {
     pub const SIGNIN_FAILED: &'static str = "SIGNIN_FAILED";
     pub const VALIDATION_ERROR: &'static str = "VALIDATION_ERROR";
     pub const WEAK_PASSWORD: &'static str = "WEAK_PASSWORD";
+    pub const PASSWORD_BREACHED: &'static str = "PASSWORD_BREACHED";
+    pub const PASSWORD_REUSED: &'static str = "PASSWORD_REUSED";
     pub const USER_ALREADY_EXISTS: &'static str = "USER_ALREADY_EXISTS";
     pub const DATABASE_ERROR: &'static str = "DATABASE_ERROR";
     pub const INTERNAL_SERVER_ERROR: &'static str = "INTERNAL_SERVER_ERROR";
     pub const INVALID_CREDENTIALS: &'static str = "INVALID_CREDENTIALS";
     pub const USER_NOT_FOUND: &'static str = "USER_NOT_FOUND";
     pub const ACCOUNT_LOCKED: &'static str = "ACCOUNT_LOCKED";
+    pub const ACCOUNT_DISABLED: &'static str = "ACCOUNT_DISABLED";
+    pub const ACCOUNT_SUSPENDED: &'static str = "ACCOUNT_SUSPENDED";
     pub const JWT_ERROR: &'static str = "JWT_ERROR";
     pub const TOKEN_NOT_FOUND: &'static str = "TOKEN_NOT_FOUND";
+    pub const RESET_TOKEN_INVALID: &'static str = "RESET_TOKEN_INVALID";
+    pub const PASSWORD_CHANGE_REQUIRED: &'static str = "PASSWORD_CHANGE_REQUIRED";
+    pub const PASSWORD_EXPIRED: &'static str = "PASSWORD_EXPIRED";
+    pub const PASSWORD_EXPIRING: &'static str = "PASSWORD_EXPIRING";
+    pub const EMAIL_NOT_VERIFIED: &'static str = "EMAIL_NOT_VERIFIED";
+    pub const VERIFICATION_TOKEN_INVALID: &'static str = "VERIFICATION_TOKEN_INVALID";
+    pub const EMAIL_CHANGE_TOKEN_INVALID: &'static str = "EMAIL_CHANGE_TOKEN_INVALID";
+    pub const SERVICE_UNAUTHORIZED: &'static str = "SERVICE_UNAUTHORIZED";
 
     // Admin-specific error codes
     pub const INSUFFICIENT_PERMISSIONS: &'static str = "INSUFFICIENT_PERMISSIONS";
     pub const CANNOT_DISABLE_SELF: &'static str = "CANNOT_DISABLE_SELF";
     pub const CANNOT_DISABLE_SUPER_ADMIN: &'static str = "CANNOT_DISABLE_SUPER_ADMIN";
+    pub const CANNOT_CHANGE_OWN_ROLE: &'static str = "CANNOT_CHANGE_OWN_ROLE";
+    pub const ROLE_NOT_FOUND: &'static str = "ROLE_NOT_FOUND";
+    pub const ROLE_ALREADY_EXISTS: &'static str = "ROLE_ALREADY_EXISTS";
+    pub const ROLE_IN_USE: &'static str = "ROLE_IN_USE";
+    pub const ROLE_PROTECTED: &'static str = "ROLE_PROTECTED";
+    pub const ROLE_HIERARCHY_CYCLE: &'static str = "ROLE_HIERARCHY_CYCLE";
+
+    // Organization-specific error codes
+    pub const ORGANIZATION_NOT_FOUND: &'static str = "ORGANIZATION_NOT_FOUND";
+    pub const ORGANIZATION_SLUG_TAKEN: &'static str = "ORGANIZATION_SLUG_TAKEN";
+    pub const ALREADY_ORGANIZATION_MEMBER: &'static str = "ALREADY_ORGANIZATION_MEMBER";
+    pub const LAST_ORGANIZATION_ADMIN: &'static str = "LAST_ORGANIZATION_ADMIN";
+    pub const INVITATION_INVALID: &'static str = "INVITATION_INVALID";
+    pub const GROUP_NOT_FOUND: &'static str = "GROUP_NOT_FOUND";
+    pub const GROUP_ALREADY_EXISTS: &'static str = "GROUP_ALREADY_EXISTS";
 }
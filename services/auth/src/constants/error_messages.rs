@@ -8,6 +8,8 @@ impl ErrorMessage {
         "Your authentication token is invalid or has expired. Please log in again to continue.";
     pub const TOKEN_REFRESH_FAILED: &'static str =
         "Unable to refresh your authentication token. Please log in again to obtain a new session.";
+    pub const SERVICE_KEY_INVALID: &'static str =
+        "This endpoint is only available to internal services. Send a valid service key in the X-Service-Key header.";
     pub const SIGNUP_CREATION_FAILED: &'static str =
         "We encountered an error while creating your account. Please try again or contact support if the problem persists.";
     pub const SIGNIN_AUTHENTICATION_FAILED: &'static str =
@@ -16,6 +18,20 @@ impl ErrorMessage {
         "The information you provided is not valid. Please check all required fields and ensure they meet the specified requirements.";
     pub const PASSWORD_TOO_WEAK: &'static str =
         "Your password doesn't meet our security requirements. Please use at least 8 characters including both letters and numbers.";
+    pub const PASSWORD_FOUND_IN_BREACH: &'static str =
+        "This password has appeared in a known data breach and can't be used. Please choose a different password that you haven't used elsewhere.";
+    pub const PASSWORD_RECENTLY_USED: &'static str =
+        "You've used this password recently. Please choose a password you haven't used before.";
+    pub const CURRENT_PASSWORD_INCORRECT: &'static str =
+        "The current password you entered is incorrect. Please try again.";
+    pub const PASSWORD_CHANGE_FAILED: &'static str =
+        "We couldn't update your password. Please try again or contact support if the problem persists.";
+    pub const PASSWORD_CHANGE_REQUIRED: &'static str =
+        "You need to set a new password before continuing. Please choose a new password to regain full access to your account.";
+    pub const PASSWORD_EXPIRED: &'static str =
+        "Your password has expired. Please choose a new password to continue using your account.";
+    pub const PASSWORD_EXPIRING_WITH_COUNTDOWN: &'static str =
+        "Your password will expire in {} days. Please change it soon to avoid interruption.";
     pub const EMAIL_ALREADY_REGISTERED: &'static str =
         "An account with this email address already exists. Please use a different email or try signing in instead.";
     pub const SERVER_ERROR_OCCURRED: &'static str =
@@ -30,6 +46,30 @@ impl ErrorMessage {
         "There was an error processing your authentication. Please log out and log back in to resolve this issue.";
     pub const TOKEN_NOT_FOUND: &'static str =
         "Authentication token is missing. Please log in to access this resource.";
+    pub const RESET_TOKEN_INVALID_OR_EXPIRED: &'static str =
+        "This password reset link is invalid, has expired or was already used. Please request a new one.";
+    pub const EMAIL_VERIFICATION_REQUIRED: &'static str =
+        "Please verify your email address to continue. Check your inbox for the verification link, or request a new one.";
+    pub const EMAIL_NOT_VERIFIED_NOTICE: &'static str =
+        "Your email address hasn't been verified yet. Please check your inbox for the verification link.";
+    pub const VERIFICATION_TOKEN_INVALID_OR_EXPIRED: &'static str =
+        "This verification link is invalid, has expired or was already used. Please request a new one.";
+    pub const ACCOUNT_DISABLED: &'static str =
+        "Your account has been disabled. Please contact your administrator if you believe this is a mistake.";
+    pub const ACCOUNT_SUSPENDED: &'static str =
+        "Your account is suspended. Please contact your administrator for more information.";
+    pub const ACCOUNT_SUSPENDED_UNTIL: &'static str =
+        "Your account is suspended until {}. Please contact your administrator if you need access sooner.";
+    pub const EMAIL_UNCHANGED: &'static str =
+        "The new email address is the same as your current one. Please enter a different address.";
+    pub const EMAIL_CHANGE_TOKEN_INVALID_OR_EXPIRED: &'static str =
+        "This email change link is invalid, has expired or was already used. Please start the change again from your account settings.";
+    pub const EMAIL_CHANGE_REVERT_BLOCKED: &'static str =
+        "Your previous email address is now used by another account, so this change can't be undone automatically. Please contact support.";
+    pub const EMAIL_CHANGE_FAILED: &'static str =
+        "We couldn't change your email address. Please try again or contact support if the problem persists.";
+    pub const LOCALE_NOT_SUPPORTED: &'static str =
+        "This language isn't supported. Please choose one of: {}.";
     pub const USER_NOT_FOUND: &'static str =
         "The requested user was not found in our system. Please verify the user information and try again.";
     pub const INTERNAL_SERVER_ERROR: &'static str =
@@ -50,6 +90,10 @@ impl ErrorMessage {
         "The user ID provided is not in the correct format. Please verify the ID and try again.";
     pub const SELF_ACCOUNT_DISABLE_FORBIDDEN: &'static str =
         "You cannot disable your own administrator account for security reasons. Please ask another administrator to perform this action.";
+    pub const ACCOUNT_STATUS_INVALID: &'static str =
+        "The account status must be active, disabled or suspended. Only a suspension can have an end date, and it must be in the future.";
+    pub const SUPER_ADMIN_STATUS_CHANGE_FORBIDDEN: &'static str =
+        "Super administrator accounts cannot be disabled or suspended.";
     pub const USER_LOOKUP_FAILED: &'static str =
         "Unable to locate the specified user in our system. The user may have been deleted or the ID may be incorrect.";
     pub const USER_STATUS_UPDATE_FAILED: &'static str =
@@ -58,12 +102,122 @@ impl ErrorMessage {
         "Unable to generate a new secure password. Please try the password reset operation again.";
     pub const PASSWORD_RESET_FAILED: &'static str =
         "Failed to reset the user's password. Please verify the user exists and try again, or contact technical support.";
+    pub const OUTBOX_STATUS_INVALID: &'static str =
+        "Invalid email status. It must be one of: pending, sent, dead.";
+    pub const SECURITY_LOG_FILTER_INVALID: &'static str =
+        "The security log filters are invalid. User IDs must be UUIDs and dates must be YYYY-MM-DD or RFC 3339 timestamps.";
     pub const SECURITY_LOGS_RETRIEVAL_FAILED: &'static str =
         "Unable to retrieve security audit logs at this time. This may be due to database connectivity issues - please try again later.";
+    pub const PASSWORD_CHANGE_REQUIREMENT_FAILED: &'static str =
+        "Failed to require a password change for this user. Please try again or contact technical support.";
+    pub const AUDIT_VERIFICATION_FAILED: &'static str =
+        "Unable to verify the audit log at this time. Check that the audit signing key is configured and try again.";
+    pub const AUDIT_EXPORT_FORMAT_INVALID: &'static str =
+        "The export format must be jsonl, csv or cef.";
+    pub const EMAIL_OUTBOX_RETRIEVAL_FAILED: &'static str =
+        "Unable to retrieve the email delivery queue at this time. This may be due to database connectivity issues - please try again later.";
     pub const SESSION_REVOCATION_FAILED: &'static str =
         "Failed to revoke user sessions. The user may still have active sessions - please try again or contact technical support.";
+    pub const USER_SORT_INVALID: &'static str =
+        "Users cannot be sorted by {}. Choose one of the columns shown in the user list.";
+    pub const SORT_ORDER_INVALID: &'static str = "The sort order must be either asc or desc.";
+    pub const PAGINATION_CURSOR_INVALID: &'static str =
+        "The pagination cursor is invalid or was issued for a different sort order. Start again from the first page.";
     pub const USERS_RETRIEVAL_FAILED: &'static str =
         "Unable to retrieve the user list from the database. This may be a temporary connectivity issue - please refresh and try again.";
+    pub const ROLE_ID_FORMAT_INVALID: &'static str =
+        "The role ID provided is not in the correct format. Please verify the ID and try again.";
+    pub const ROLE_NAME_INVALID: &'static str =
+        "Role names must be 2 to 50 characters, start with a lowercase letter and use only lowercase letters, digits and underscores.";
+    pub const ROLE_PASSWORD_MAX_AGE_INVALID: &'static str =
+        "The password rotation period must be a positive number of days.";
+    pub const ROLE_PERMISSIONS_INVALID: &'static str =
+        "One or more of the requested permissions do not exist. List the available permissions and try again.";
+    pub const ROLE_ALREADY_EXISTS: &'static str = "A role with this name already exists.";
+    pub const ROLE_NOT_FOUND: &'static str =
+        "The specified role does not exist or has been deleted.";
+    pub const BUILT_IN_ROLE_PROTECTED: &'static str =
+        "Built-in roles cannot be renamed, deleted or given a different parent role.";
+    pub const ROLE_IN_USE: &'static str =
+        "This role is still assigned to {} users. Move them to another role before deleting it.";
+    pub const ROLE_HIERARCHY_CYCLE: &'static str =
+        "A role cannot inherit from itself or from a role that already inherits from it.";
+    pub const ROLE_HAS_CHILDREN: &'static str =
+        "Other roles inherit from this role ({}). Give them a different parent before deleting it.";
+    pub const ADMIN_ROLE_GRANT_FORBIDDEN: &'static str =
+        "Only super administrators can grant permissions or give or take away admin access.";
+    pub const OWN_ROLE_CHANGE_FORBIDDEN: &'static str =
+        "You cannot change your own role. Please ask another administrator to perform this action.";
+    pub const ROLES_RETRIEVAL_FAILED: &'static str =
+        "Unable to retrieve roles at this time. This may be a temporary connectivity issue - please try again later.";
+    pub const ROLE_UPDATE_FAILED: &'static str =
+        "Failed to save the role change. Please try again or contact technical support if the issue persists.";
+
+    // Organization-specific messages
+    pub const ORGANIZATION_ID_FORMAT_INVALID: &'static str =
+        "The organization ID provided is not in the correct format. Please verify the ID and try again.";
+    pub const ORGANIZATION_NAME_INVALID: &'static str =
+        "Organization names must be 1 to 100 characters.";
+    pub const ORGANIZATION_SLUG_INVALID: &'static str =
+        "Organization slugs must be 2 to 50 characters, start with a lowercase letter and use only lowercase letters, digits and single hyphens.";
+    pub const ORGANIZATION_SLUG_TAKEN: &'static str =
+        "An organization with this slug already exists. Please choose another one.";
+    pub const ORGANIZATION_NOT_FOUND: &'static str =
+        "The organization does not exist or you are not a member of it.";
+    pub const ORGANIZATION_ADMIN_REQUIRED: &'static str =
+        "Only administrators of this organization can perform this action.";
+    pub const ORGANIZATION_ROLE_NOT_ALLOWED: &'static str =
+        "You can only give or take away roles that your own role in the organization includes.";
+    pub const ORGANIZATION_MEMBER_NOT_FOUND: &'static str =
+        "This user is not a member of the organization.";
+    pub const LAST_ORGANIZATION_ADMIN: &'static str =
+        "An organization must keep at least one administrator. Make another member an administrator first.";
+    pub const ALREADY_ORGANIZATION_MEMBER: &'static str =
+        "This user is already a member of the organization.";
+    pub const INVITATION_INVALID: &'static str =
+        "This invitation is invalid, has expired or has already been used. Ask an organization administrator for a new one.";
+    pub const INVITATION_EMAIL_MISMATCH: &'static str =
+        "This invitation was sent to a different email address. Sign in with the invited address to accept it.";
+    pub const INVITATION_NOT_FOUND: &'static str =
+        "The invitation does not exist or is no longer pending.";
+    pub const GROUP_ID_FORMAT_INVALID: &'static str =
+        "The group ID provided is not in the correct format. Please verify the ID and try again.";
+    pub const GROUP_NAME_INVALID: &'static str =
+        "Group names must be 2 to 50 characters, start with a lowercase letter and use only lowercase letters, digits, underscores and hyphens.";
+    pub const GROUP_ALREADY_EXISTS: &'static str =
+        "A group with this name already exists in the organization.";
+    pub const GROUP_NOT_FOUND: &'static str = "The group does not exist in this organization.";
+    pub const GROUP_MEMBER_NOT_FOUND: &'static str = "This user is not in the group.";
+    pub const ORGANIZATION_PERMISSIONS_INVALID: &'static str =
+        "One or more of the requested permissions do not exist or do not apply inside an organization. List the available permissions and try again.";
+    pub const ORGANIZATION_PERMISSION_GRANT_FORBIDDEN: &'static str =
+        "You can only grant or take away permissions you hold in this organization yourself.";
+    pub const GROUP_LOOKUP_INVALID: &'static str =
+        "Provide a valid organization_id and user_id, and either group_id or group.";
+    pub const ORGANIZATION_OPERATION_FAILED: &'static str =
+        "Unable to complete the organization request at this time. This may be a temporary connectivity issue - please try again later.";
+
+    // Authorization decision messages
+    pub const AUTHZ_REQUEST_INVALID: &'static str =
+        "Each check needs a valid subject user ID, an action and a resource type, and a valid organization_id if one is given.";
+    pub const AUTHZ_BATCH_SIZE_INVALID: &'static str =
+        "A batch must contain between 1 and {} checks.";
+    pub const AUTHZ_CHECK_FAILED: &'static str =
+        "Unable to make an authorization decision at this time. This may be a temporary connectivity issue - please try again later.";
+
+    // Relation tuple messages
+    pub const RELATION_TUPLE_INVALID: &'static str =
+        "Each tuple must look like object#relation@subject, using a relation defined on the object's namespace that accepts the subject's type.";
+    pub const RELATION_WRITE_SIZE_INVALID: &'static str =
+        "A write must contain between 1 and {} tuple writes and deletes.";
+    pub const RELATION_QUERY_INVALID: &'static str =
+        "The namespace, object, relation or subject is malformed or not defined in the relation schema.";
+    pub const LIST_CURSOR_INVALID: &'static str =
+        "The cursor is malformed. Pass the next_cursor from the previous page unchanged.";
+    pub const CONSISTENCY_TOKEN_INVALID: &'static str =
+        "The consistency token is malformed or names a revision that has not been written.";
+    pub const RELATION_OPERATION_FAILED: &'static str =
+        "Unable to read or write relation tuples at this time. This may be a temporary connectivity issue - please try again later.";
 
     // Account lockout specific messages
     pub const ACCOUNT_LOCKED_TEMPORARILY: &'static str =
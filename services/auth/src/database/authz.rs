@@ -0,0 +1,187 @@
+use anyhow::Result;
+use chrono::Utc;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use super::constants::{Authz, Permissions, Roles};
+use super::groups::{EffectivePermissions, PermissionSource};
+use super::Database;
+
+/// A question for the policy decision point: may `user_id` perform `permission`,
+/// within `organization_id` if given or with their own role otherwise?
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AuthzRequest {
+    pub user_id: Uuid,
+    pub organization_id: Option<Uuid>,
+    /// Permission name, `resource:action`
+    pub permission: String,
+}
+
+/// Why a request was denied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DenyReason {
+    SubjectNotFound,
+    /// Disabled, or suspended
+    SubjectInactive,
+    /// No permission with this name exists
+    UnknownAction,
+    NotOrganizationMember,
+    PermissionNotGranted,
+}
+
+/// An authorization decision and the reason for it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthzDecision {
+    Allow(PermissionSource),
+    Deny(DenyReason),
+}
+
+impl AuthzDecision {
+    /// Decide from the subject's grants, `None` meaning they aren't a member of the
+    /// organization the request is scoped to
+    pub fn from_grants(grants: Option<&EffectivePermissions>, permission: &str) -> Self {
+        match grants {
+            None => AuthzDecision::Deny(DenyReason::NotOrganizationMember),
+            Some(grants) => match grants.source(permission) {
+                Some(source) => AuthzDecision::Allow(source),
+                None => AuthzDecision::Deny(DenyReason::PermissionNotGranted),
+            },
+        }
+    }
+
+    pub fn is_allowed(&self) -> bool {
+        matches!(self, AuthzDecision::Allow(_))
+    }
+
+    /// Stable machine-readable reason
+    pub fn reason_code(&self) -> &'static str {
+        match self {
+            AuthzDecision::Allow(PermissionSource::Role(_)) => "granted_by_role",
+            AuthzDecision::Allow(PermissionSource::Group(_)) => "granted_by_group",
+            AuthzDecision::Allow(PermissionSource::Direct) => "granted_directly",
+            AuthzDecision::Deny(DenyReason::SubjectNotFound) => "subject_not_found",
+            AuthzDecision::Deny(DenyReason::SubjectInactive) => "subject_inactive",
+            AuthzDecision::Deny(DenyReason::UnknownAction) => "unknown_action",
+            AuthzDecision::Deny(DenyReason::NotOrganizationMember) => "not_organization_member",
+            AuthzDecision::Deny(DenyReason::PermissionNotGranted) => "permission_not_granted",
+        }
+    }
+
+    /// The role or group that granted the permission, if one did
+    pub fn granted_via(&self) -> Option<&str> {
+        match self {
+            AuthzDecision::Allow(PermissionSource::Role(name))
+            | AuthzDecision::Allow(PermissionSource::Group(name)) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+/// Recent decisions kept in memory for a few seconds, since other services ask the same
+/// question many times in a row
+#[derive(Default)]
+pub(super) struct AuthzDecisionCache {
+    entries: Mutex<HashMap<AuthzRequest, (Instant, AuthzDecision)>>,
+}
+
+impl AuthzDecisionCache {
+    fn get(&self, request: &AuthzRequest) -> Option<AuthzDecision> {
+        let ttl = Duration::from_secs(Authz::DECISION_CACHE_TTL_SECS);
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+
+        entries
+            .get(request)
+            .filter(|(decided_at, _)| decided_at.elapsed() < ttl)
+            .map(|(_, decision)| decision.clone())
+    }
+
+    fn store(&self, request: AuthzRequest, decision: AuthzDecision) {
+        let ttl = Duration::from_secs(Authz::DECISION_CACHE_TTL_SECS);
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+
+        if entries.len() >= Authz::DECISION_CACHE_MAX_ENTRIES {
+            entries.retain(|_, (decided_at, _)| decided_at.elapsed() < ttl);
+            if entries.len() >= Authz::DECISION_CACHE_MAX_ENTRIES {
+                entries.clear();
+            }
+        }
+        entries.insert(request, (Instant::now(), decision));
+    }
+
+    pub(super) fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
+
+impl Database {
+    // ========================================
+    // Authorization Decision Operations
+    // ========================================
+
+    /// Decide an authorization request from roles, groups and direct grants, returning
+    /// whether the decision came from the cache
+    pub fn authorize(&self, request: &AuthzRequest) -> Result<(AuthzDecision, bool)> {
+        if let Some(decision) = self.authz_cache.get(request) {
+            return Ok((decision, true));
+        }
+
+        let decision = self.decide(request)?;
+        self.authz_cache.store(request.clone(), decision.clone());
+
+        Ok((decision, false))
+    }
+
+    /// Drop cached decisions and relation checks; call after any change to who holds which
+    /// permission or is in which group
+    pub fn invalidate_authz_decisions(&self) {
+        self.authz_cache.clear();
+        self.relation_check_cache.clear();
+    }
+
+    fn decide(&self, request: &AuthzRequest) -> Result<AuthzDecision> {
+        // Organization resources are decided on organization permissions only, and the rest
+        // on global ones, so neither kind can answer for the other
+        let scope = match request.organization_id {
+            Some(_) => Permissions::ORGANIZATION_SCOPE,
+            None => Permissions::GLOBAL_SCOPE,
+        };
+        if !self
+            .unknown_permissions(std::slice::from_ref(&request.permission), Some(scope))?
+            .is_empty()
+        {
+            return Ok(AuthzDecision::Deny(DenyReason::UnknownAction));
+        }
+
+        let Some(user) = self.find_user_by_id(request.user_id)? else {
+            return Ok(AuthzDecision::Deny(DenyReason::SubjectNotFound));
+        };
+        if user.is_access_blocked(Utc::now()) {
+            return Ok(AuthzDecision::Deny(DenyReason::SubjectInactive));
+        }
+
+        let grants = match request.organization_id {
+            Some(organization_id) => self.organization_permissions(organization_id, user.id)?,
+            None => {
+                let role = self
+                    .get_user_role(user.id)?
+                    .unwrap_or_else(|| Roles::USER.to_string());
+                Some(EffectivePermissions::resolve(
+                    &role,
+                    self.role_hierarchy()?.effective_permissions(&role).to_vec(),
+                    BTreeMap::new(),
+                    Vec::new(),
+                ))
+            }
+        };
+
+        Ok(AuthzDecision::from_grants(
+            grants.as_ref(),
+            &request.permission,
+        ))
+    }
+}
@@ -10,6 +10,119 @@ impl Roles {
 
     /// Super administrator with all permissions
     pub const SUPER_ADMIN: &'static str = "super_admin";
+
+    /// Roles created with the database; they can't be renamed or deleted
+    pub const BUILT_IN: [&'static str; 3] = [Self::USER, Self::ADMIN, Self::SUPER_ADMIN];
+
+    /// Longest role name the `roles` table accepts
+    pub const MAX_NAME_LENGTH: usize = 50;
+
+    /// Postgres advisory lock key serializing changes to role parents
+    pub const HIERARCHY_LOCK_KEY: i64 = 0x726f_6c65_5f74_7265; // "role_tre"
+
+    pub fn is_built_in(name: &str) -> bool {
+        Self::BUILT_IN.contains(&name)
+    }
+
+    /// Role names are 2-50 characters of lowercase letters, digits and underscores, starting with a letter
+    pub fn is_valid_name(name: &str) -> bool {
+        (2..=Self::MAX_NAME_LENGTH).contains(&name.len())
+            && name.starts_with(|c: char| c.is_ascii_lowercase())
+            && name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    }
+}
+
+/// Permission names (`resource:action`) granted to roles through `role_permissions`
+pub struct Permissions;
+
+impl Permissions {
+    /// List users and view their lock and password status
+    pub const USERS_READ: &'static str = "users:read";
+
+    /// Activate, disable or suspend accounts
+    pub const USERS_DISABLE: &'static str = "users:disable";
+
+    /// Reset passwords and require password changes
+    pub const USERS_RESET_PASSWORD: &'static str = "users:reset_password";
+
+    /// Unlock accounts locked after failed sign-ins
+    pub const USERS_UNLOCK: &'static str = "users:unlock";
+
+    /// Revoke a user's sessions
+    pub const SESSIONS_REVOKE: &'static str = "sessions:revoke";
+
+    /// Read and stream security events
+    pub const AUDIT_READ: &'static str = "audit:read";
+
+    /// Verify the audit chain
+    pub const AUDIT_VERIFY: &'static str = "audit:verify";
+
+    /// Export security events
+    pub const AUDIT_EXPORT: &'static str = "audit:export";
+
+    /// View the email delivery queue
+    pub const EMAIL_OUTBOX_READ: &'static str = "email_outbox:read";
+
+    /// List roles and the permissions available
+    pub const ROLES_READ: &'static str = "roles:read";
+
+    /// Create, edit and delete roles
+    pub const ROLES_MANAGE: &'static str = "roles:manage";
+
+    /// Change the role a user holds
+    pub const USERS_ASSIGN_ROLE: &'static str = "users:assign_role";
+
+    /// Scope of permissions that gate the admin API; never held inside an organization
+    pub const GLOBAL_SCOPE: &'static str = "global";
+
+    /// Scope of permissions held inside an organization through roles, groups and direct grants
+    pub const ORGANIZATION_SCOPE: &'static str = "organization";
+
+    /// Read the organization's notes
+    pub const NOTES_READ: &'static str = "notes:read";
+
+    /// Create, edit and delete the organization's notes
+    pub const NOTES_WRITE: &'static str = "notes:write";
+
+    /// View the organization's media
+    pub const MEDIAS_READ: &'static str = "medias:read";
+
+    /// Upload and delete the organization's media
+    pub const MEDIAS_WRITE: &'static str = "medias:write";
+
+    /// View the organization's workflows and their runs
+    pub const WORKFLOWS_READ: &'static str = "workflows:read";
+
+    /// Start and cancel the organization's workflows
+    pub const WORKFLOWS_RUN: &'static str = "workflows:run";
+
+    /// Every global permission, as seeded by the permission migrations
+    pub const ALL: [&'static str; 12] = [
+        Self::USERS_READ,
+        Self::USERS_DISABLE,
+        Self::USERS_RESET_PASSWORD,
+        Self::USERS_UNLOCK,
+        Self::SESSIONS_REVOKE,
+        Self::AUDIT_READ,
+        Self::AUDIT_VERIFY,
+        Self::AUDIT_EXPORT,
+        Self::EMAIL_OUTBOX_READ,
+        Self::ROLES_READ,
+        Self::ROLES_MANAGE,
+        Self::USERS_ASSIGN_ROLE,
+    ];
+
+    /// Every organization permission, as seeded by the permission scope migration
+    pub const ORGANIZATION: [&'static str; 6] = [
+        Self::NOTES_READ,
+        Self::NOTES_WRITE,
+        Self::MEDIAS_READ,
+        Self::MEDIAS_WRITE,
+        Self::WORKFLOWS_READ,
+        Self::WORKFLOWS_RUN,
+    ];
 }
 
 /// Account locking constants
@@ -22,3 +135,233 @@ impl AccountLock {
     /// Auto-unlock time in minutes
     pub const AUTO_UNLOCK_MINUTES: i64 = 30;
 }
+
+/// Password reset constants
+pub struct PasswordReset;
+
+impl PasswordReset {
+    /// Lifetime of a password reset token in minutes
+    pub const TOKEN_TTL_MINUTES: i64 = 30;
+
+    /// Minimum seconds between two reset emails a user requests for themselves
+    pub const REQUEST_COOLDOWN_SECONDS: i64 = 60;
+
+    /// Maximum reset emails a user can request for themselves within one hour
+    pub const MAX_REQUESTS_PER_HOUR: i64 = 5;
+}
+
+/// Email verification constants
+pub struct EmailVerification;
+
+impl EmailVerification {
+    /// Lifetime of an email verification token in hours
+    pub const TOKEN_TTL_HOURS: i64 = 24;
+
+    /// Minimum seconds between two verification emails for the same account
+    pub const RESEND_COOLDOWN_SECONDS: i64 = 60;
+
+    /// Maximum verification emails per account within one hour
+    pub const MAX_SENDS_PER_HOUR: i64 = 5;
+}
+
+/// Email change constants
+pub struct EmailChange;
+
+impl EmailChange {
+    /// Lifetime of the confirmation link sent to the new address in hours
+    pub const TOKEN_TTL_HOURS: i64 = 24;
+
+    /// How long the old address can cancel (or undo) the change, in days
+    pub const CANCEL_TTL_DAYS: i64 = 7;
+}
+
+/// Admin user listing constants
+pub struct UserListing;
+
+impl UserListing {
+    /// Largest page the admin user list returns
+    pub const MAX_PAGE_SIZE: u32 = 100;
+}
+
+/// User session constants
+pub struct Sessions;
+
+impl Sessions {
+    /// Recent sessions a sign-in is compared against to decide whether its client is new
+    pub const KNOWN_CLIENT_LOOKBACK: i64 = 200;
+}
+
+/// Audit chain constants
+pub struct AuditLog;
+
+impl AuditLog {
+    /// A signed checkpoint is written after this many chained events
+    pub const CHECKPOINT_INTERVAL: i64 = 100;
+
+    /// Entries loaded per query while verifying the chain
+    pub const VERIFY_BATCH_SIZE: i64 = 1000;
+
+    /// Events loaded (and flushed to the output) per query while exporting
+    pub const EXPORT_BATCH_SIZE: i64 = 500;
+
+    /// Flushed export chunks buffered ahead of a slow client
+    pub const EXPORT_CHANNEL_CAPACITY: usize = 8;
+
+    /// Recent events kept for live stream subscribers; slower readers skip ahead
+    pub const STREAM_BUFFER_SIZE: usize = 1024;
+
+    /// Seconds between keep-alive comments on an idle live stream
+    pub const STREAM_KEEP_ALIVE_SECS: u64 = 15;
+
+    /// Independent chains events are spread over, so concurrent writers rarely wait
+    pub const CHAIN_STREAMS: i16 = 8;
+
+    /// Postgres advisory lock key serializing appends to a chain (plus the stream number)
+    pub const CHAIN_LOCK_KEY: i64 = 0x6175_6469_745f_6c67; // "audit_lg"
+
+    /// Largest page of security logs the admin API returns
+    pub const MAX_PAGE_SIZE: u32 = 100;
+}
+
+/// Email outbox constants
+pub struct EmailOutbox;
+
+impl EmailOutbox {
+    /// Waiting for (re)delivery
+    pub const STATUS_PENDING: &'static str = "pending";
+
+    /// Delivered to the mail backend
+    pub const STATUS_SENT: &'static str = "sent";
+
+    /// Gave up after `MAX_ATTEMPTS` failures
+    pub const STATUS_DEAD: &'static str = "dead";
+
+    /// Every message status
+    pub const STATUSES: [&'static str; 3] =
+        [Self::STATUS_PENDING, Self::STATUS_SENT, Self::STATUS_DEAD];
+
+    /// Delivery attempts before a message is marked dead
+    pub const MAX_ATTEMPTS: i32 = 8;
+
+    /// Delay before the first retry in seconds (doubled after each failure)
+    pub const BASE_RETRY_SECONDS: i64 = 30;
+
+    /// Upper bound on the retry delay in seconds
+    pub const MAX_RETRY_SECONDS: i64 = 3600;
+
+    /// Messages claimed per worker pass
+    pub const BATCH_SIZE: i64 = 20;
+
+    /// Seconds a claimed message is hidden from other workers while it is being sent
+    pub const CLAIM_LEASE_SECONDS: i64 = 300;
+
+    /// Seconds the worker sleeps when nothing is due
+    pub const POLL_INTERVAL_SECONDS: u64 = 5;
+
+    /// Largest page of messages the admin API returns
+    pub const MAX_PAGE_SIZE: u32 = 100;
+}
+
+/// Constants for the authorization decision API used by other services
+pub struct Authz;
+
+impl Authz {
+    /// How long a decision is reused before it is made again; changes made through this
+    /// service clear the cache right away, this bounds staleness from anything else
+    pub const DECISION_CACHE_TTL_SECS: u64 = 5;
+
+    /// Decisions kept in memory before expired ones are swept out
+    pub const DECISION_CACHE_MAX_ENTRIES: usize = 10_000;
+
+    /// Most checks accepted in one batch request
+    pub const MAX_BATCH_SIZE: usize = 100;
+}
+
+/// Constants for groups of members within an organization
+pub struct Groups;
+
+impl Groups {
+    /// Longest group name the `groups` table accepts
+    pub const MAX_NAME_LENGTH: usize = 50;
+
+    /// Group names are 2-50 characters of lowercase letters, digits, underscores and hyphens,
+    /// starting with a letter
+    pub fn is_valid_name(name: &str) -> bool {
+        (2..=Self::MAX_NAME_LENGTH).contains(&name.len())
+            && name.starts_with(|c: char| c.is_ascii_lowercase())
+            && name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+    }
+}
+
+/// Organization (tenant) constants
+pub struct Organizations;
+
+impl Organizations {
+    /// Longest organization name the `organizations` table accepts
+    pub const MAX_NAME_LENGTH: usize = 100;
+
+    /// Longest slug the `organizations` table accepts
+    pub const MAX_SLUG_LENGTH: usize = 50;
+
+    /// Lifetime of an emailed invitation in days
+    pub const INVITATION_TTL_DAYS: i64 = 7;
+
+    /// Role given to whoever creates an organization; members whose organization role
+    /// inherits it manage members and invitations. Inside the organization a role only
+    /// confers its organization-scoped permissions, never its global admin ones.
+    pub const ADMIN_ROLE: &'static str = Roles::ADMIN;
+
+    /// Role given to invited members when the invitation names none
+    pub const DEFAULT_MEMBER_ROLE: &'static str = Roles::USER;
+
+    pub fn is_valid_name(name: &str) -> bool {
+        let name = name.trim();
+        !name.is_empty() && name.chars().count() <= Self::MAX_NAME_LENGTH
+    }
+
+    /// Slugs are 2-50 characters of lowercase letters, digits and single hyphens, starting with a letter
+    pub fn is_valid_slug(slug: &str) -> bool {
+        (2..=Self::MAX_SLUG_LENGTH).contains(&slug.len())
+            && slug.starts_with(|c: char| c.is_ascii_lowercase())
+            && !slug.ends_with('-')
+            && !slug.contains("--")
+            && slug
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    }
+}
+
+/// Constants for the relation tuple store used to share notes, articles and folders
+pub struct Relations;
+
+impl Relations {
+    /// Longest chain of nested folders and usersets followed before a check gives up
+    pub const MAX_DEPTH: usize = 50;
+
+    /// Most tuple writes and deletes accepted in one request
+    pub const MAX_WRITE_SIZE: usize = 100;
+
+    /// Most objects in one page of list-objects results
+    pub const MAX_LIST_OBJECTS: usize = 1000;
+
+    /// Longest object ID, matching the `object_id` column
+    pub const MAX_ID_LENGTH: usize = 128;
+
+    /// How long a check result is reused when the caller doesn't ask for anything newer
+    pub const CHECK_CACHE_TTL_SECS: u64 = 5;
+
+    /// Check results kept in memory before expired ones are swept out
+    pub const CHECK_CACHE_MAX_ENTRIES: usize = 10_000;
+
+    /// Whether `id` can be used as an object or subject ID: 1-128 letters, digits and
+    /// `_`, `-`, `.`, `|`, `=` (UUIDs, slugs and base64 IDs all fit)
+    pub fn is_valid_id(id: &str) -> bool {
+        !id.is_empty()
+            && id.len() <= Self::MAX_ID_LENGTH
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '|' | '='))
+    }
+}
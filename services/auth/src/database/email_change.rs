@@ -0,0 +1,247 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use super::constants::EmailChange;
+use super::schema::{auth_users, email_change_requests, users};
+use super::Database;
+use crate::models::database::{EmailChangeRequest, NewEmailChangeRequest};
+use crate::utils::EmailMessage;
+
+/// Result of confirming an email change from the new address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmailChangeConfirmation {
+    /// Both tables now hold the new address
+    Confirmed {
+        user_id: Uuid,
+        old_email: String,
+        new_email: String,
+    },
+    /// Another account took the new address while the change was pending
+    EmailTaken,
+    /// The token is unknown, expired, cancelled or already used
+    Invalid,
+}
+
+/// Result of cancelling an email change from the old address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmailChangeCancellation {
+    /// The change was stopped; `reverted` is true if it had already been confirmed and was undone
+    Cancelled { user_id: Uuid, reverted: bool },
+    /// The change was confirmed and another account has since claimed the old address, so
+    /// it can't be undone; nothing was changed
+    AddressTaken { user_id: Uuid },
+    /// The token is unknown, expired or already used
+    Invalid,
+}
+
+impl Database {
+    // ========================================
+    // Email Change Operations
+    // ========================================
+
+    /// Store a change request from the user's current address, replacing any pending one
+    ///
+    /// The confirmation (to the new address) and notice (to the old address)
+    /// are queued in the same transaction. Returns when the confirmation expires.
+    pub fn create_email_change_request(
+        &self,
+        user_id: Uuid,
+        new_email: &str,
+        token_hash: &str,
+        cancel_token_hash: &str,
+        confirmation: &EmailMessage,
+        notice: &EmailMessage,
+    ) -> Result<DateTime<Utc>> {
+        let mut conn = self.get_connection()?;
+        let now = Utc::now();
+        let expires_at = now + Duration::hours(EmailChange::TOKEN_TTL_HOURS);
+
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let old_email = users::table
+                .filter(users::id.eq(user_id))
+                .filter(users::deleted_at.is_null())
+                .select(users::email)
+                .first::<String>(conn)?;
+
+            diesel::update(
+                email_change_requests::table
+                    .filter(email_change_requests::user_id.eq(user_id))
+                    .filter(email_change_requests::confirmed_at.is_null())
+                    .filter(email_change_requests::cancelled_at.is_null()),
+            )
+            .set(email_change_requests::cancelled_at.eq(Some(now)))
+            .execute(conn)?;
+
+            diesel::insert_into(email_change_requests::table)
+                .values(&NewEmailChangeRequest {
+                    user_id,
+                    old_email,
+                    new_email: new_email.to_string(),
+                    token_hash: token_hash.to_string(),
+                    cancel_token_hash: cancel_token_hash.to_string(),
+                    expires_at,
+                    cancel_expires_at: now + Duration::days(EmailChange::CANCEL_TTL_DAYS),
+                })
+                .execute(conn)?;
+
+            Self::queue_email_in(conn, confirmation)?;
+            Self::queue_email_in(conn, notice)
+        })?;
+
+        Ok(expires_at)
+    }
+
+    /// Consume a confirmation token and switch `users.email` and `auth_users.email` together
+    ///
+    /// The new address is verified by the confirmation itself.
+    pub fn confirm_email_change(&self, token_hash: &str) -> Result<EmailChangeConfirmation> {
+        let mut conn = self.get_connection()?;
+        let now = Utc::now();
+
+        let outcome = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let request = email_change_requests::table
+                .filter(email_change_requests::token_hash.eq(token_hash))
+                .filter(email_change_requests::confirmed_at.is_null())
+                .filter(email_change_requests::cancelled_at.is_null())
+                .filter(email_change_requests::expires_at.gt(now))
+                .select(EmailChangeRequest::as_select())
+                .for_update()
+                .first(conn)
+                .optional()?;
+            let Some(request) = request else {
+                return Ok(EmailChangeConfirmation::Invalid);
+            };
+
+            let taken = users::table
+                .filter(users::email.eq(&request.new_email))
+                .filter(users::id.ne(request.user_id))
+                .filter(users::deleted_at.is_null())
+                .count()
+                .get_result::<i64>(conn)?
+                > 0;
+            if taken {
+                return Ok(EmailChangeConfirmation::EmailTaken);
+            }
+
+            // Only switch if the account still has the address the request was made from
+            let updated = diesel::update(
+                users::table
+                    .filter(users::id.eq(request.user_id))
+                    .filter(users::email.eq(&request.old_email))
+                    .filter(users::deleted_at.is_null()),
+            )
+            .set((
+                users::email.eq(&request.new_email),
+                users::updated_at.eq(now),
+            ))
+            .execute(conn)?;
+            if updated == 0 {
+                return Ok(EmailChangeConfirmation::Invalid);
+            }
+
+            diesel::update(
+                auth_users::table
+                    .filter(auth_users::user_id.eq(request.user_id))
+                    .filter(auth_users::deleted_at.is_null()),
+            )
+            .set((
+                auth_users::email.eq(&request.new_email),
+                auth_users::email_verified.eq(true),
+            ))
+            .execute(conn)?;
+
+            diesel::update(email_change_requests::table.find(request.id))
+                .set(email_change_requests::confirmed_at.eq(Some(now)))
+                .execute(conn)?;
+
+            Ok(EmailChangeConfirmation::Confirmed {
+                user_id: request.user_id,
+                old_email: request.old_email,
+                new_email: request.new_email,
+            })
+        })?;
+
+        Ok(outcome)
+    }
+
+    /// Consume a cancel token from the old address
+    ///
+    /// A pending change is dropped; a confirmed one is undone by restoring the old
+    /// address in both tables.
+    pub fn cancel_email_change(&self, cancel_token_hash: &str) -> Result<EmailChangeCancellation> {
+        let mut conn = self.get_connection()?;
+        let now = Utc::now();
+
+        let outcome = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let request = email_change_requests::table
+                .filter(email_change_requests::cancel_token_hash.eq(cancel_token_hash))
+                .filter(email_change_requests::cancelled_at.is_null())
+                .filter(email_change_requests::cancel_expires_at.gt(now))
+                .select(EmailChangeRequest::as_select())
+                .for_update()
+                .first(conn)
+                .optional()?;
+            let Some(request) = request else {
+                return Ok(EmailChangeCancellation::Invalid);
+            };
+
+            let mut reverted = false;
+            if request.confirmed_at.is_some() {
+                // Any other row holding the address, even a deleted one, blocks the revert
+                let taken = users::table
+                    .filter(users::email.eq(&request.old_email))
+                    .filter(users::id.ne(request.user_id))
+                    .count()
+                    .get_result::<i64>(conn)?
+                    + auth_users::table
+                        .filter(auth_users::email.eq(&request.old_email))
+                        .filter(auth_users::user_id.ne(request.user_id))
+                        .count()
+                        .get_result::<i64>(conn)?
+                    > 0;
+                if taken {
+                    return Ok(EmailChangeCancellation::AddressTaken {
+                        user_id: request.user_id,
+                    });
+                }
+
+                // Undo only while the account still carries the address this request set
+                reverted = diesel::update(
+                    users::table
+                        .filter(users::id.eq(request.user_id))
+                        .filter(users::email.eq(&request.new_email))
+                        .filter(users::deleted_at.is_null()),
+                )
+                .set((
+                    users::email.eq(&request.old_email),
+                    users::updated_at.eq(now),
+                ))
+                .execute(conn)?
+                    > 0;
+
+                if reverted {
+                    diesel::update(
+                        auth_users::table
+                            .filter(auth_users::user_id.eq(request.user_id))
+                            .filter(auth_users::deleted_at.is_null()),
+                    )
+                    .set(auth_users::email.eq(&request.old_email))
+                    .execute(conn)?;
+                }
+            }
+
+            diesel::update(email_change_requests::table.find(request.id))
+                .set(email_change_requests::cancelled_at.eq(Some(now)))
+                .execute(conn)?;
+
+            Ok(EmailChangeCancellation::Cancelled {
+                user_id: request.user_id,
+                reverted,
+            })
+        })?;
+
+        Ok(outcome)
+    }
+}
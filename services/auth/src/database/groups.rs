@@ -0,0 +1,609 @@
+use anyhow::Result;
+use chrono::Utc;
+use diesel::dsl::count_star;
+use diesel::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use uuid::Uuid;
+
+use super::constants::Permissions;
+use super::schema::{
+    group_members, group_permissions, groups, member_permissions, memberships, organizations,
+    permissions, roles, users,
+};
+use super::Database;
+use crate::handlers::group::{GroupMemberView, GroupView};
+use crate::models::database::{Group, NewGroup};
+
+/// Fields of a group to change; `None` leaves a field as it is
+#[derive(Debug, Clone, Default)]
+pub struct GroupChanges {
+    pub name: Option<String>,
+    pub description: Option<Option<String>>,
+    pub permissions: Option<Vec<String>>,
+}
+
+/// Result of creating or updating a group
+#[derive(Debug, Clone)]
+pub enum GroupWrite {
+    Saved(Group),
+    /// Another group in the organization has the name
+    NameTaken,
+    NotFound,
+}
+
+/// Result of adding a member to a group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupMemberAddition {
+    Added,
+    AlreadyInGroup,
+    GroupNotFound,
+    /// Only members of the group's organization can join it
+    NotOrganizationMember,
+}
+
+/// How a group is named in a lookup
+#[derive(Debug, Clone, Copy)]
+pub enum GroupRef<'a> {
+    Id(Uuid),
+    Name(&'a str),
+}
+
+/// Where a member's permission comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PermissionSource {
+    /// The role (or a role it inherits from) holds it; carries the member's role
+    Role(String),
+    /// A group the member is in holds it; carries the group name
+    Group(String),
+    /// It was granted to the member directly
+    Direct,
+}
+
+/// A member's permissions in an organization and where each comes from
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EffectivePermissions {
+    /// The member's role in the organization
+    pub role: String,
+    /// Permissions of the role, including inherited ones
+    pub role_permissions: Vec<String>,
+    /// Permissions of each group the member is in, by group name
+    pub group_permissions: BTreeMap<String, Vec<String>>,
+    /// Permissions granted to the member directly
+    pub direct_permissions: Vec<String>,
+    /// Everything above combined, sorted
+    pub permissions: Vec<String>,
+}
+
+impl EffectivePermissions {
+    /// Combine role, group and direct grants; `role_permissions` include inherited ones
+    pub fn resolve(
+        role: &str,
+        role_permissions: Vec<String>,
+        group_permissions: BTreeMap<String, Vec<String>>,
+        direct_permissions: Vec<String>,
+    ) -> Self {
+        let permissions: BTreeSet<String> = role_permissions
+            .iter()
+            .chain(group_permissions.values().flatten())
+            .chain(direct_permissions.iter())
+            .cloned()
+            .collect();
+
+        EffectivePermissions {
+            role: role.to_string(),
+            role_permissions,
+            group_permissions,
+            direct_permissions,
+            permissions: permissions.into_iter().collect(),
+        }
+    }
+
+    pub fn has(&self, permission: &str) -> bool {
+        self.permissions
+            .binary_search_by(|granted| granted.as_str().cmp(permission))
+            .is_ok()
+    }
+
+    /// Where the permission comes from, checking the role, then direct grants, then groups
+    /// by name; `None` if it isn't held
+    pub fn source(&self, permission: &str) -> Option<PermissionSource> {
+        let grants = |granted: &[String]| granted.iter().any(|name| name == permission);
+
+        if grants(&self.role_permissions) {
+            Some(PermissionSource::Role(self.role.clone()))
+        } else if grants(&self.direct_permissions) {
+            Some(PermissionSource::Direct)
+        } else {
+            self.group_permissions
+                .iter()
+                .find(|(_, granted)| grants(granted))
+                .map(|(group, _)| PermissionSource::Group(group.clone()))
+        }
+    }
+}
+
+impl Database {
+    // ========================================
+    // Group Operations
+    // ========================================
+
+    /// Groups of an organization with their permissions and member counts, by name
+    pub fn list_groups(&self, organization_id: Uuid) -> Result<Vec<GroupView>> {
+        let mut conn = self.get_connection()?;
+
+        let groups = groups::table
+            .filter(groups::organization_id.eq(organization_id))
+            .order(groups::name.asc())
+            .select(Group::as_select())
+            .load::<Group>(&mut conn)?;
+        let group_ids: Vec<Uuid> = groups.iter().map(|group| group.id).collect();
+
+        let mut granted: HashMap<Uuid, Vec<String>> = HashMap::new();
+        for (group_id, name) in group_permissions::table
+            .inner_join(permissions::table)
+            .filter(group_permissions::group_id.eq_any(&group_ids))
+            .order(permissions::name.asc())
+            .select((group_permissions::group_id, permissions::name))
+            .load::<(Uuid, String)>(&mut conn)?
+        {
+            granted.entry(group_id).or_default().push(name);
+        }
+
+        let member_counts: HashMap<Uuid, i64> = group_members::table
+            .filter(group_members::group_id.eq_any(&group_ids))
+            .group_by(group_members::group_id)
+            .select((group_members::group_id, count_star()))
+            .load::<(Uuid, i64)>(&mut conn)?
+            .into_iter()
+            .collect();
+
+        Ok(groups
+            .into_iter()
+            .map(|group| GroupView {
+                id: group.id.to_string(),
+                name: group.name,
+                description: group.description,
+                permissions: granted.remove(&group.id).unwrap_or_default(),
+                member_count: member_counts.get(&group.id).copied().unwrap_or(0),
+                created_at: group.created_at.to_rfc3339(),
+                updated_at: group.updated_at.to_rfc3339(),
+            })
+            .collect())
+    }
+
+    /// Create a group with its permissions
+    pub fn create_group(&self, group: &NewGroup, permissions: &[String]) -> Result<GroupWrite> {
+        let mut conn = self.get_connection()?;
+
+        let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            if Self::group_name_taken_in(conn, group.organization_id, &group.name, None)? {
+                return Ok(GroupWrite::NameTaken);
+            }
+
+            let created = diesel::insert_into(groups::table)
+                .values(group)
+                .returning(Group::as_returning())
+                .get_result(conn)?;
+            Self::set_group_permissions_in(conn, created.id, permissions)?;
+
+            Ok(GroupWrite::Saved(created))
+        })?;
+
+        Ok(result)
+    }
+
+    /// Apply changes to a group of the organization
+    pub fn update_group(
+        &self,
+        organization_id: Uuid,
+        group_id: Uuid,
+        changes: &GroupChanges,
+    ) -> Result<GroupWrite> {
+        let mut conn = self.get_connection()?;
+
+        let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let exists = groups::table
+                .filter(groups::id.eq(group_id))
+                .filter(groups::organization_id.eq(organization_id))
+                .select(groups::id)
+                .for_update()
+                .first::<Uuid>(conn)
+                .optional()?;
+            if exists.is_none() {
+                return Ok(GroupWrite::NotFound);
+            }
+
+            if let Some(name) = &changes.name {
+                if Self::group_name_taken_in(conn, organization_id, name, Some(group_id))? {
+                    return Ok(GroupWrite::NameTaken);
+                }
+                diesel::update(groups::table.find(group_id))
+                    .set(groups::name.eq(name))
+                    .execute(conn)?;
+            }
+            if let Some(description) = &changes.description {
+                diesel::update(groups::table.find(group_id))
+                    .set(groups::description.eq(description))
+                    .execute(conn)?;
+            }
+            if let Some(permissions) = &changes.permissions {
+                Self::set_group_permissions_in(conn, group_id, permissions)?;
+            }
+
+            let updated = diesel::update(groups::table.find(group_id))
+                .set(groups::updated_at.eq(Utc::now()))
+                .returning(Group::as_returning())
+                .get_result(conn)?;
+
+            Ok(GroupWrite::Saved(updated))
+        })?;
+        self.invalidate_authz_decisions();
+
+        Ok(result)
+    }
+
+    /// Delete a group of the organization with its memberships and grants
+    pub fn delete_group(&self, organization_id: Uuid, group_id: Uuid) -> Result<Option<Group>> {
+        let mut conn = self.get_connection()?;
+
+        let deleted = diesel::delete(
+            groups::table
+                .filter(groups::id.eq(group_id))
+                .filter(groups::organization_id.eq(organization_id)),
+        )
+        .returning(Group::as_returning())
+        .get_result(&mut conn)
+        .optional()?;
+        self.invalidate_authz_decisions();
+
+        Ok(deleted)
+    }
+
+    /// A group of the organization with its permissions
+    pub fn find_group(
+        &self,
+        organization_id: Uuid,
+        group_id: Uuid,
+    ) -> Result<Option<(Group, Vec<String>)>> {
+        let mut conn = self.get_connection()?;
+
+        let Some(group) = groups::table
+            .filter(groups::id.eq(group_id))
+            .filter(groups::organization_id.eq(organization_id))
+            .select(Group::as_select())
+            .first::<Group>(&mut conn)
+            .optional()?
+        else {
+            return Ok(None);
+        };
+        let permissions = group_permissions::table
+            .inner_join(permissions::table)
+            .filter(group_permissions::group_id.eq(group_id))
+            .order(permissions::name.asc())
+            .select(permissions::name)
+            .load::<String>(&mut conn)?;
+
+        Ok(Some((group, permissions)))
+    }
+
+    // ========================================
+    // Group Membership Operations
+    // ========================================
+
+    /// Members of a group, by email
+    pub fn list_group_members(&self, group_id: Uuid) -> Result<Vec<GroupMemberView>> {
+        let mut conn = self.get_connection()?;
+
+        let rows = group_members::table
+            .inner_join(memberships::table.inner_join(users::table))
+            .filter(group_members::group_id.eq(group_id))
+            .filter(users::deleted_at.is_null())
+            .order(users::email.asc())
+            .select((
+                users::id,
+                users::email,
+                users::name,
+                group_members::created_at,
+            ))
+            .load::<(Uuid, String, String, chrono::DateTime<Utc>)>(&mut conn)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(user_id, email, name, added_at)| GroupMemberView {
+                user_id: user_id.to_string(),
+                email,
+                name,
+                added_at: added_at.to_rfc3339(),
+            })
+            .collect())
+    }
+
+    /// Add a member of the organization to one of its groups
+    pub fn add_group_member(
+        &self,
+        organization_id: Uuid,
+        group_id: Uuid,
+        user_id: Uuid,
+        added_by: Uuid,
+    ) -> Result<GroupMemberAddition> {
+        let mut conn = self.get_connection()?;
+
+        let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let group_exists = groups::table
+                .filter(groups::id.eq(group_id))
+                .filter(groups::organization_id.eq(organization_id))
+                .select(groups::id)
+                .first::<Uuid>(conn)
+                .optional()?
+                .is_some();
+            if !group_exists {
+                return Ok(GroupMemberAddition::GroupNotFound);
+            }
+
+            let Some(membership_id) = Self::membership_id_in(conn, organization_id, user_id)?
+            else {
+                return Ok(GroupMemberAddition::NotOrganizationMember);
+            };
+
+            let added = diesel::insert_into(group_members::table)
+                .values((
+                    group_members::group_id.eq(group_id),
+                    group_members::membership_id.eq(membership_id),
+                    group_members::added_by.eq(Some(added_by)),
+                ))
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+
+            Ok(if added == 0 {
+                GroupMemberAddition::AlreadyInGroup
+            } else {
+                GroupMemberAddition::Added
+            })
+        })?;
+        self.invalidate_authz_decisions();
+
+        Ok(result)
+    }
+
+    /// Take a member out of a group, returning whether they were in it
+    pub fn remove_group_member(
+        &self,
+        organization_id: Uuid,
+        group_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<bool> {
+        let mut conn = self.get_connection()?;
+
+        let Some(membership_id) = Self::membership_id_in(&mut conn, organization_id, user_id)?
+        else {
+            return Ok(false);
+        };
+        let removed = diesel::delete(
+            group_members::table
+                .filter(group_members::group_id.eq(group_id))
+                .filter(group_members::membership_id.eq(membership_id)),
+        )
+        .execute(&mut conn)?;
+        self.invalidate_authz_decisions();
+
+        Ok(removed > 0)
+    }
+
+    /// Whether the user is in the group, or `None` if the organization has no such group
+    ///
+    /// A single indexed lookup, for services that only need a yes or no.
+    pub fn is_group_member(
+        &self,
+        organization_id: Uuid,
+        group: GroupRef,
+        user_id: Uuid,
+    ) -> Result<Option<(Group, bool)>> {
+        let mut conn = self.get_connection()?;
+
+        let query = groups::table
+            .inner_join(organizations::table)
+            .filter(groups::organization_id.eq(organization_id))
+            .filter(organizations::deleted_at.is_null())
+            .select(Group::as_select())
+            .into_boxed();
+        let query = match group {
+            GroupRef::Id(id) => query.filter(groups::id.eq(id)),
+            GroupRef::Name(name) => query.filter(groups::name.eq(name)),
+        };
+        let Some(group) = query.first::<Group>(&mut conn).optional()? else {
+            return Ok(None);
+        };
+
+        let is_member = diesel::select(diesel::dsl::exists(
+            group_members::table
+                .inner_join(memberships::table)
+                .filter(group_members::group_id.eq(group.id))
+                .filter(memberships::user_id.eq(user_id)),
+        ))
+        .get_result::<bool>(&mut conn)?;
+
+        Ok(Some((group, is_member)))
+    }
+
+    // ========================================
+    // Member Permission Operations
+    // ========================================
+
+    /// Replace the permissions granted directly to a member, returning whether they are one
+    pub fn set_member_permissions(
+        &self,
+        organization_id: Uuid,
+        user_id: Uuid,
+        names: &[String],
+        granted_by: Uuid,
+    ) -> Result<bool> {
+        let mut conn = self.get_connection()?;
+
+        let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let Some(membership_id) = Self::membership_id_in(conn, organization_id, user_id)?
+            else {
+                return Ok(false);
+            };
+
+            diesel::delete(
+                member_permissions::table
+                    .filter(member_permissions::membership_id.eq(membership_id)),
+            )
+            .execute(conn)?;
+
+            let permission_ids: Vec<Uuid> = permissions::table
+                .filter(permissions::name.eq_any(names))
+                .select(permissions::id)
+                .load(conn)?;
+            let grants: Vec<_> = permission_ids
+                .into_iter()
+                .map(|permission_id| {
+                    (
+                        member_permissions::membership_id.eq(membership_id),
+                        member_permissions::permission_id.eq(permission_id),
+                        member_permissions::granted_by.eq(Some(granted_by)),
+                    )
+                })
+                .collect();
+            if !grants.is_empty() {
+                diesel::insert_into(member_permissions::table)
+                    .values(&grants)
+                    .execute(conn)?;
+            }
+
+            Ok(true)
+        })?;
+        self.invalidate_authz_decisions();
+
+        Ok(result)
+    }
+
+    /// A member's permissions in the organization from their role, groups and direct
+    /// grants, or `None` if they aren't a member
+    ///
+    /// Only organization-scoped permissions count: an organization administrator's role
+    /// never carries its global admin permissions into the organization.
+    pub fn organization_permissions(
+        &self,
+        organization_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<EffectivePermissions>> {
+        let hierarchy = self.role_hierarchy()?;
+        let mut conn = self.get_connection()?;
+
+        let Some((membership_id, role)) = memberships::table
+            .inner_join(organizations::table)
+            .inner_join(roles::table)
+            .filter(memberships::organization_id.eq(organization_id))
+            .filter(memberships::user_id.eq(user_id))
+            .filter(organizations::deleted_at.is_null())
+            .select((memberships::id, roles::name))
+            .first::<(Uuid, String)>(&mut conn)
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        let mut from_groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (group, permission, scope) in group_members::table
+            .inner_join(groups::table)
+            .left_join(
+                group_permissions::table
+                    .on(group_permissions::group_id.eq(group_members::group_id)),
+            )
+            .left_join(permissions::table.on(permissions::id.eq(group_permissions::permission_id)))
+            .filter(group_members::membership_id.eq(membership_id))
+            .order((groups::name.asc(), permissions::name.asc()))
+            .select((
+                groups::name,
+                permissions::name.nullable(),
+                permissions::scope.nullable(),
+            ))
+            .load::<(String, Option<String>, Option<String>)>(&mut conn)?
+        {
+            // Every group is listed, even one without organization permissions
+            let granted = from_groups.entry(group).or_default();
+            if scope.as_deref() == Some(Permissions::ORGANIZATION_SCOPE) {
+                granted.extend(permission);
+            }
+        }
+
+        let direct = member_permissions::table
+            .inner_join(permissions::table)
+            .filter(member_permissions::membership_id.eq(membership_id))
+            .filter(permissions::scope.eq(Permissions::ORGANIZATION_SCOPE))
+            .order(permissions::name.asc())
+            .select(permissions::name)
+            .load::<String>(&mut conn)?;
+
+        Ok(Some(EffectivePermissions::resolve(
+            &role,
+            hierarchy.effective_organization_permissions(&role).to_vec(),
+            from_groups,
+            direct,
+        )))
+    }
+
+    fn membership_id_in(
+        conn: &mut PgConnection,
+        organization_id: Uuid,
+        user_id: Uuid,
+    ) -> QueryResult<Option<Uuid>> {
+        memberships::table
+            .filter(memberships::organization_id.eq(organization_id))
+            .filter(memberships::user_id.eq(user_id))
+            .select(memberships::id)
+            .first::<Uuid>(conn)
+            .optional()
+    }
+
+    fn group_name_taken_in(
+        conn: &mut PgConnection,
+        organization_id: Uuid,
+        name: &str,
+        except: Option<Uuid>,
+    ) -> QueryResult<bool> {
+        let mut query = groups::table
+            .filter(groups::organization_id.eq(organization_id))
+            .filter(groups::name.eq(name))
+            .select(groups::id)
+            .into_boxed();
+        if let Some(id) = except {
+            query = query.filter(groups::id.ne(id));
+        }
+
+        Ok(query.first::<Uuid>(conn).optional()?.is_some())
+    }
+
+    /// Replace a group's permission grants with the named permissions
+    fn set_group_permissions_in(
+        conn: &mut PgConnection,
+        group_id: Uuid,
+        names: &[String],
+    ) -> QueryResult<()> {
+        diesel::delete(group_permissions::table.filter(group_permissions::group_id.eq(group_id)))
+            .execute(conn)?;
+
+        let permission_ids: Vec<Uuid> = permissions::table
+            .filter(permissions::name.eq_any(names))
+            .select(permissions::id)
+            .load(conn)?;
+        let grants: Vec<_> = permission_ids
+            .into_iter()
+            .map(|permission_id| {
+                (
+                    group_permissions::group_id.eq(group_id),
+                    group_permissions::permission_id.eq(permission_id),
+                )
+            })
+            .collect();
+        if !grants.is_empty() {
+            diesel::insert_into(group_permissions::table)
+                .values(&grants)
+                .execute(conn)?;
+        }
+
+        Ok(())
+    }
+}
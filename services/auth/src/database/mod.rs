@@ -1,17 +1,64 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+mod authz;
 pub mod constants;
+mod email_change;
+mod groups;
+mod organizations;
+mod outbox;
+mod relations;
+mod role_hierarchy;
+mod role_management;
 pub mod schema;
+mod security_events;
+mod user_listing;
 
 use diesel::prelude::*;
 use diesel::r2d2::{self, ConnectionManager, Pool};
 use std::env;
+use tokio::sync::broadcast;
 use uuid::Uuid;
 
-use crate::handlers::admin::{GetUsersQuery, SecurityLogEntry, SecurityLogsQuery, UserAdminView};
-use crate::models::database::{AuthUser, NewAuthUser, NewUser, User};
-use constants::{AccountLock, Roles};
-use schema::{auth_users, roles, users};
+use crate::handlers::admin::ExpiredPasswordView;
+use crate::models::database::{
+    AccountStatus, AuthUser, NewAuthUser, NewEmailVerificationToken, NewPasswordHistory,
+    NewPasswordResetToken, NewUser, NewUserSession, SecurityEvent, User,
+};
+use crate::utils::{Claims, ClientInfo, EmailMessage, JwtService, PasswordService};
+use constants::{AccountLock, AuditLog, EmailVerification, PasswordReset, Roles, Sessions};
+use schema::{
+    auth_users, email_verification_tokens, password_history, password_reset_tokens, roles,
+    user_sessions, users,
+};
+
+pub use authz::{AuthzDecision, AuthzRequest, DenyReason};
+pub use email_change::{EmailChangeCancellation, EmailChangeConfirmation};
+pub use groups::{
+    EffectivePermissions, GroupChanges, GroupMemberAddition, GroupRef, GroupWrite, PermissionSource,
+};
+pub use organizations::{
+    is_organization_admin, InvitationAcceptance, MembershipChange, MembershipRefusal,
+    OrganizationCreation,
+};
+pub use relations::{
+    check_relation_in, expand_relation_in, list_relation_objects_in, namespace, ConsistencyToken,
+    ExpandTree, ListCursor, MemoryTuples, NamespaceConfig, ObjectPage, ObjectRef, RelationCheck,
+    RelationConfig, RelationTuple, RelationWrite, Rewrite, SubjectRef, TupleReader, NAMESPACES,
+};
+pub use role_hierarchy::{RoleHierarchy, RoleNode};
+pub use role_management::{RoleChanges, RoleCreation, RoleDeletion, RoleWrite, UserRoleChange};
+pub use security_events::SecurityEventFilter;
+pub use user_listing::{SortOrder, UserListCursor, UserListFilter, UserListPage, UserSortField};
+
+/// Result of completing a password reset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordUpdate {
+    Updated,
+    /// The new password matches the current or a recent one; nothing changed
+    Reused,
+    /// The reset token was used concurrently; nothing changed
+    TokenUsed,
+}
 
 pub type DbPool = Pool<ConnectionManager<PgConnection>>;
 pub type DbConnection = r2d2::PooledConnection<ConnectionManager<PgConnection>>;
@@ -19,6 +66,11 @@ pub type DbConnection = r2d2::PooledConnection<ConnectionManager<PgConnection>>;
 /// Database service for managing connections and operations
 pub struct Database {
     pub pool: DbPool,
+    /// Security events as they are recorded, for live admin streams
+    security_event_feed: broadcast::Sender<SecurityEvent>,
+    role_hierarchy_cache: role_hierarchy::RoleHierarchyCache,
+    authz_cache: authz::AuthzDecisionCache,
+    relation_check_cache: relations::RelationCheckCache,
 }
 
 impl Database {
@@ -30,7 +82,20 @@ impl Database {
         let manager = ConnectionManager::<PgConnection>::new(database_url);
         let pool = Pool::builder().max_size(15).build(manager)?;
 
-        Ok(Database { pool })
+        Ok(Self::from_pool(pool))
+    }
+
+    /// Wrap an existing connection pool
+    pub fn from_pool(pool: DbPool) -> Self {
+        let (security_event_feed, _) = broadcast::channel(AuditLog::STREAM_BUFFER_SIZE);
+
+        Database {
+            pool,
+            security_event_feed,
+            role_hierarchy_cache: Default::default(),
+            authz_cache: Default::default(),
+            relation_check_cache: Default::default(),
+        }
     }
 
     /// Get a connection from the pool
@@ -39,7 +104,7 @@ impl Database {
     }
 
     /// Create a new user (main user table - business information only)
-    pub fn create_user(&self, email: &str, name: &str) -> Result<User> {
+    pub fn create_user(&self, email: &str, name: &str, locale: Option<&str>) -> Result<User> {
         let mut conn = self.get_connection()?;
 
         // Get default user role ID
@@ -52,6 +117,7 @@ impl Database {
             name: name.to_string(),
             avatar_path: None,
             role_id: default_role_id,
+            locale: locale.map(str::to_string),
         };
 
         let user = diesel::insert_into(users::table)
@@ -78,10 +144,16 @@ impl Database {
             email_verified: false,
         };
 
-        let auth_user = diesel::insert_into(auth_users::table)
-            .values(&new_auth_user)
-            .returning(AuthUser::as_returning())
-            .get_result(&mut conn)?;
+        let auth_user = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let auth_user = diesel::insert_into(auth_users::table)
+                .values(&new_auth_user)
+                .returning(AuthUser::as_returning())
+                .get_result(conn)?;
+
+            Self::record_password_history(conn, auth_user.id, password_hash)?;
+
+            Ok(auth_user)
+        })?;
 
         Ok(auth_user)
     }
@@ -112,6 +184,19 @@ impl Database {
         Ok(auth_user)
     }
 
+    /// Find auth user by user ID (excluding soft deleted)
+    pub fn find_auth_user_by_user_id(&self, user_id: Uuid) -> Result<Option<AuthUser>> {
+        let mut conn = self.get_connection()?;
+
+        let auth_user = auth_users::table
+            .filter(auth_users::user_id.eq(user_id))
+            .filter(auth_users::deleted_at.is_null())
+            .first::<AuthUser>(&mut conn)
+            .optional()?;
+
+        Ok(auth_user)
+    }
+
     /// Update user last login (successful login)
     pub fn update_last_login(&self, user_id: Uuid) -> Result<()> {
         let mut conn = self.get_connection()?;
@@ -170,6 +255,15 @@ impl Database {
         Ok(role_name)
     }
 
+    /// Permissions the user's role grants, including inherited ones, sorted by name
+    pub fn get_user_permissions(&self, user_id: Uuid) -> Result<Vec<String>> {
+        let Some(role) = self.get_user_role(user_id)? else {
+            return Ok(Vec::new());
+        };
+
+        Ok(self.role_hierarchy()?.effective_permissions(&role).to_vec())
+    }
+
     /// Get user by ID (excluding soft deleted)
     pub fn find_user_by_id(&self, user_id: Uuid) -> Result<Option<User>> {
         let mut conn = self.get_connection()?;
@@ -184,16 +278,18 @@ impl Database {
     }
 
     /// Update failed login attempts and lock if threshold is reached
-    pub fn increment_failed_login_attempts(&self, email: &str) -> Result<()> {
+    ///
+    /// Returns true when this failure is the one that locked the account.
+    pub fn increment_failed_login_attempts(&self, email: &str) -> Result<bool> {
         let mut conn = self.get_connection()?;
 
         // Get current failed attempts count
-        let current_attempts: i32 = auth_users::table
+        let (current_attempts, was_locked): (i32, bool) = auth_users::table
             .filter(auth_users::email.eq(email))
             .filter(auth_users::deleted_at.is_null())
-            .select(auth_users::login_failure_count)
+            .select((auth_users::login_failure_count, auth_users::is_login_locked))
             .first(&mut conn)
-            .unwrap_or(0);
+            .unwrap_or((0, false));
 
         let new_attempts = current_attempts + 1;
         let now = Utc::now();
@@ -214,7 +310,7 @@ impl Database {
         ))
         .execute(&mut conn)?;
 
-        Ok(())
+        Ok(is_locked && !was_locked)
     }
 
     /// Reset failed login attempts and clear lock time
@@ -244,15 +340,16 @@ impl Database {
             .filter(auth_users::email.eq(email))
             .filter(auth_users::deleted_at.is_null())
             .select((
+                auth_users::user_id,
                 auth_users::login_failure_count,
                 auth_users::is_login_locked,
                 auth_users::last_login,
             ))
-            .first::<(i32, bool, Option<DateTime<Utc>>)>(&mut conn)
+            .first::<(Uuid, i32, bool, Option<DateTime<Utc>>)>(&mut conn)
             .optional()?;
 
         match auth_user {
-            Some((failed_attempts, is_locked, last_login)) => {
+            Some((user_id, failed_attempts, is_locked, last_login)) => {
                 // Check against max failed attempts threshold
                 const MAX_FAILED_ATTEMPTS: i32 = AccountLock::MAX_FAILED_ATTEMPTS;
 
@@ -268,6 +365,13 @@ impl Database {
                             if now >= unlock_time {
                                 // Lockout period has passed, automatically unlock and reset counters
                                 self.reset_failed_login_attempts(email)?;
+                                let _ = self.log_security_event(
+                                    Some(user_id),
+                                    "account_auto_unlocked",
+                                    None,
+                                    true,
+                                    None,
+                                );
                                 Ok(false) // Account is no longer locked
                             } else {
                                 Ok(true) // Account is still locked
@@ -365,120 +469,687 @@ impl Database {
     // Admin-specific database operations
     // ========================================
 
-    /// Get users for admin panel with filtering and pagination
-    pub fn get_users_admin(&self, params: &GetUsersQuery) -> Result<Vec<UserAdminView>> {
-        // TODO: Implement actual database query with proper filtering and pagination
-        // This is a placeholder that returns empty results until real implementation
-        let _ = params; // Suppress unused parameter warning
-        Ok(vec![])
-    }
-
-    /// Count users for admin panel
-    pub fn count_users_admin(&self, params: &GetUsersQuery) -> Result<i64> {
-        // TODO: Implement actual user count query
-        let _ = params; // Suppress unused parameter warning
-        Ok(0)
-    }
-
-    /// Update user status (admin function)
+    /// Update user status (admin function), revoking all sessions in the same transaction
+    ///
+    /// Returns the previous status and the number of revoked sessions, or `None` if the user doesn't exist.
     pub fn update_user_status(
         &self,
         user_id: Uuid,
-        status: &str,
+        status: AccountStatus,
+        suspended_until: Option<DateTime<Utc>>,
         admin_id: Uuid,
         reason: Option<&str>,
-    ) -> Result<()> {
-        let _conn = self.get_connection()?;
+    ) -> Result<Option<(AccountStatus, u32)>> {
+        let mut conn = self.get_connection()?;
+        let now = Utc::now();
 
-        // In a real implementation, you'd have a status column in users table
-        // For now, we'll just log this operation
         tracing::info!(
-            "Admin {} updated user {} status to {}: {:?}",
+            "Admin {} updating user {} status to {}: {:?}",
             admin_id,
             user_id,
             status,
             reason
         );
 
-        // This would be the actual update:
-        // diesel::update(users::table.filter(users::id.eq(user_id)))
-        //     .set((
-        //         users::status.eq(status),
-        //         users::updated_at.eq(Utc::now()),
-        //         // Add other status-related fields
-        //     ))
-        //     .execute(&mut conn)?;
+        let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let previous = users::table
+                .filter(users::id.eq(user_id))
+                .filter(users::deleted_at.is_null())
+                .select(users::status)
+                .for_update()
+                .first::<AccountStatus>(conn)
+                .optional()?;
+            let Some(previous) = previous else {
+                return Ok(None);
+            };
 
-        Ok(())
+            diesel::update(users::table.filter(users::id.eq(user_id)))
+                .set((
+                    users::status.eq(status),
+                    users::status_reason.eq(reason),
+                    users::suspended_until.eq(suspended_until),
+                    users::status_changed_at.eq(Some(now)),
+                    users::updated_at.eq(now),
+                ))
+                .execute(conn)?;
+
+            // Any status change ends existing sessions so the new status applies immediately
+            let revoked = diesel::update(
+                user_sessions::table
+                    .filter(user_sessions::user_id.eq(user_id))
+                    .filter(user_sessions::revoked_at.is_null()),
+            )
+            .set((
+                user_sessions::revoked_at.eq(Some(now)),
+                user_sessions::revoked_reason.eq(Some(format!("status_{}", status))),
+            ))
+            .execute(conn)?;
+
+            Ok(Some((previous, revoked as u32)))
+        })?;
+        self.invalidate_authz_decisions();
+
+        Ok(result)
     }
 
-    /// Admin reset user password
-    pub fn admin_reset_user_password(&self, user_id: Uuid, password_hash: &str) -> Result<()> {
+    /// Revoke all user sessions (admin function)
+    pub fn revoke_all_user_sessions(&self, user_id: Uuid, reason: &str) -> Result<u32> {
+        let mut conn = self.get_connection()?;
+
+        tracing::info!(
+            "Revoking all sessions for user {} with reason: {}",
+            user_id,
+            reason
+        );
+
+        let revoked = diesel::update(
+            user_sessions::table
+                .filter(user_sessions::user_id.eq(user_id))
+                .filter(user_sessions::revoked_at.is_null()),
+        )
+        .set((
+            user_sessions::revoked_at.eq(Some(Utc::now())),
+            user_sessions::revoked_reason.eq(Some(reason)),
+        ))
+        .execute(&mut conn)?;
+
+        Ok(revoked as u32)
+    }
+
+    // ========================================
+    // User Session Operations
+    // ========================================
+
+    /// Create a session for a successful sign-in, returning its ID for the `sid` claim
+    pub fn create_user_session(
+        &self,
+        user_id: Uuid,
+        user_agent: Option<&str>,
+        ip_address: Option<&str>,
+    ) -> Result<Uuid> {
+        let mut conn = self.get_connection()?;
+
+        let session_id = diesel::insert_into(user_sessions::table)
+            .values(&NewUserSession {
+                user_id,
+                user_agent: user_agent.map(|ua| ua.to_string()),
+                ip_address: ip_address.map(|ip| ip.to_string()),
+            })
+            .returning(user_sessions::id)
+            .get_result::<Uuid>(&mut conn)?;
+
+        Ok(session_id)
+    }
+
+    /// Check whether a sign-in comes from a client not seen in the user's recent sessions
+    ///
+    /// Clients are compared by device (the user agent without versions) and network
+    /// prefix, so browser updates and a new address from the same network don't count as
+    /// new. A user's very first session is not treated as new, so signing up doesn't raise
+    /// an alert.
+    pub fn is_new_sign_in_client(
+        &self,
+        user_id: Uuid,
+        user_agent: Option<&str>,
+        ip_address: Option<&str>,
+    ) -> Result<bool> {
+        let mut conn = self.get_connection()?;
+
+        let recent = user_sessions::table
+            .filter(user_sessions::user_id.eq(user_id))
+            .order(user_sessions::created_at.desc())
+            .limit(Sessions::KNOWN_CLIENT_LOOKBACK)
+            .select((user_sessions::user_agent, user_sessions::ip_address))
+            .load::<(Option<String>, Option<String>)>(&mut conn)?;
+        if recent.is_empty() {
+            return Ok(false);
+        }
+
+        let key = |user_agent: Option<&str>, ip_address: Option<&str>| {
+            (
+                user_agent.map(ClientInfo::device_key),
+                ip_address.map(ClientInfo::network_prefix),
+            )
+        };
+        let client = key(user_agent, ip_address);
+        let seen_before = recent.iter().any(|(user_agent, ip_address)| {
+            key(user_agent.as_deref(), ip_address.as_deref()) == client
+        });
+
+        Ok(!seen_before)
+    }
+
+    /// Check if a session exists for the user, has not been revoked and the account's status allows access
+    pub fn is_session_active(&self, user_id: Uuid, session_id: Uuid) -> Result<bool> {
+        let mut conn = self.get_connection()?;
+        let now = Utc::now();
+
+        let count = user_sessions::table
+            .inner_join(users::table.on(users::id.eq(user_sessions::user_id)))
+            .filter(user_sessions::id.eq(session_id))
+            .filter(user_sessions::user_id.eq(user_id))
+            .filter(user_sessions::revoked_at.is_null())
+            .filter(users::deleted_at.is_null())
+            .filter(
+                users::status.eq(AccountStatus::Active).or(users::status
+                    .eq(AccountStatus::Suspended)
+                    .and(users::suspended_until.le(now))),
+            )
+            .count()
+            .get_result::<i64>(&mut conn)?;
+
+        Ok(count > 0)
+    }
+
+    /// Check that validated token claims belong to an active session
+    ///
+    /// Tokens without a `sid` are refused: no session backs them, so signing out or
+    /// revoking sessions could never end them.
+    pub fn is_token_session_active(&self, user_id: Uuid, claims: &Claims) -> Result<bool> {
+        match JwtService::session_id_from_claims(claims) {
+            Some(session_id) => self.is_session_active(user_id, session_id),
+            None => Ok(false),
+        }
+    }
+
+    /// Revoke a single session (logout)
+    pub fn revoke_user_session(&self, session_id: Uuid, reason: &str) -> Result<()> {
         let mut conn = self.get_connection()?;
 
         diesel::update(
-            auth_users::table
-                .filter(auth_users::user_id.eq(user_id))
-                .filter(auth_users::deleted_at.is_null()),
+            user_sessions::table
+                .filter(user_sessions::id.eq(session_id))
+                .filter(user_sessions::revoked_at.is_null()),
         )
         .set((
-            auth_users::password_hash.eq(password_hash),
-            // In a real implementation, you might have a password_reset_required field:
-            // auth_users::password_reset_required.eq(true),
+            user_sessions::revoked_at.eq(Some(Utc::now())),
+            user_sessions::revoked_reason.eq(Some(reason)),
         ))
         .execute(&mut conn)?;
 
         Ok(())
     }
 
-    /// Revoke all user sessions (admin function)
-    pub fn revoke_all_user_sessions(&self, user_id: Uuid, reason: &str) -> Result<u32> {
-        // This would require a user_sessions table, which we haven't implemented yet
-        // For now, return a mock count
-        tracing::info!(
-            "Revoking all sessions for user {} with reason: {}",
-            user_id,
-            reason
-        );
-        Ok(0)
+    /// Revoke every session of a user except the one making the request
+    pub fn revoke_other_user_sessions(
+        &self,
+        user_id: Uuid,
+        current_session_id: Uuid,
+        reason: &str,
+    ) -> Result<u32> {
+        let mut conn = self.get_connection()?;
+
+        let revoked = diesel::update(
+            user_sessions::table
+                .filter(user_sessions::user_id.eq(user_id))
+                .filter(user_sessions::id.ne(current_session_id))
+                .filter(user_sessions::revoked_at.is_null()),
+        )
+        .set((
+            user_sessions::revoked_at.eq(Some(Utc::now())),
+            user_sessions::revoked_reason.eq(Some(reason)),
+        ))
+        .execute(&mut conn)?;
+
+        Ok(revoked as u32)
     }
 
-    /// Log security event
-    pub fn log_security_event(
+    // ========================================
+    // Password Reset Operations
+    // ========================================
+
+    /// Store a new reset token for a user, invalidating any earlier unused ones
+    ///
+    /// The email carrying the token is queued in the same transaction. A reset requested
+    /// by an admin also requires a password change at next sign-in, set first so the
+    /// account is never left with a pending admin reset but no requirement.
+    pub fn create_password_reset_token(
         &self,
-        user_id: Option<Uuid>,
-        event_type: &str,
-        _metadata: Option<serde_json::Value>,
-        success: bool,
-        ip_address: Option<&str>,
+        user_id: Uuid,
+        token_hash: &str,
+        requested_by: Option<Uuid>,
+        email: &EmailMessage,
     ) -> Result<()> {
-        // This would require a security_events table
-        tracing::info!(
-            "Security event: user_id={:?}, event_type={}, success={}, ip={:?}",
-            user_id,
-            event_type,
-            success,
-            ip_address
-        );
+        let mut conn = self.get_connection()?;
+        let now = Utc::now();
+
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            if requested_by.is_some() {
+                let flagged = diesel::update(
+                    auth_users::table
+                        .filter(auth_users::user_id.eq(user_id))
+                        .filter(auth_users::deleted_at.is_null()),
+                )
+                .set(auth_users::password_reset_required.eq(true))
+                .execute(conn)?;
+
+                if flagged == 0 {
+                    return Err(diesel::result::Error::NotFound);
+                }
+            }
+
+            diesel::update(
+                password_reset_tokens::table
+                    .filter(password_reset_tokens::user_id.eq(user_id))
+                    .filter(password_reset_tokens::used_at.is_null()),
+            )
+            .set(password_reset_tokens::used_at.eq(Some(now)))
+            .execute(conn)?;
+
+            diesel::insert_into(password_reset_tokens::table)
+                .values(&NewPasswordResetToken {
+                    user_id,
+                    token_hash: token_hash.to_string(),
+                    requested_by,
+                    expires_at: now + chrono::Duration::minutes(PasswordReset::TOKEN_TTL_MINUTES),
+                })
+                .execute(conn)?;
+
+            Self::queue_email_in(conn, email)
+        })?;
+
+        Ok(())
+    }
+
+    /// Count reset tokens a user requested for themselves since `since`, with the latest
+    /// request time (tokens issued by admins are not counted)
+    pub fn get_password_reset_request_stats(
+        &self,
+        user_id: Uuid,
+        since: DateTime<Utc>,
+    ) -> Result<(i64, Option<DateTime<Utc>>)> {
+        let mut conn = self.get_connection()?;
+
+        let (count, latest) = password_reset_tokens::table
+            .filter(password_reset_tokens::user_id.eq(user_id))
+            .filter(password_reset_tokens::requested_by.is_null())
+            .filter(password_reset_tokens::created_at.ge(since))
+            .select((
+                diesel::dsl::count_star(),
+                diesel::dsl::max(password_reset_tokens::created_at),
+            ))
+            .first::<(i64, Option<DateTime<Utc>>)>(&mut conn)?;
+
+        Ok((count, latest))
+    }
+
+    /// Find the user for an unused, unexpired reset token as `(token_id, user_id)`
+    pub fn find_valid_password_reset_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<(Uuid, Uuid)>> {
+        let mut conn = self.get_connection()?;
+
+        let token = password_reset_tokens::table
+            .filter(password_reset_tokens::token_hash.eq(token_hash))
+            .filter(password_reset_tokens::used_at.is_null())
+            .filter(password_reset_tokens::expires_at.gt(Utc::now()))
+            .select((password_reset_tokens::id, password_reset_tokens::user_id))
+            .first::<(Uuid, Uuid)>(&mut conn)
+            .optional()?;
+
+        Ok(token)
+    }
+
+    /// Consume a reset token and set the new password in one transaction
+    ///
+    /// `password` is checked against the user's recent passwords in the same transaction,
+    /// so two concurrent changes can't both slip past the history. Verifying the history
+    /// runs bcrypt, so call this off the async runtime.
+    pub fn complete_password_reset(
+        &self,
+        token_id: Uuid,
+        user_id: Uuid,
+        password: &str,
+        password_hash: &str,
+    ) -> Result<PasswordUpdate> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction(|conn| {
+            if Self::is_password_reused(conn, user_id, password)? {
+                return Ok(PasswordUpdate::Reused);
+            }
+
+            let consumed = diesel::update(
+                password_reset_tokens::table
+                    .filter(password_reset_tokens::id.eq(token_id))
+                    .filter(password_reset_tokens::used_at.is_null()),
+            )
+            .set(password_reset_tokens::used_at.eq(Some(Utc::now())))
+            .execute(conn)?;
+
+            if consumed == 0 {
+                return Ok(PasswordUpdate::TokenUsed);
+            }
+
+            let auth_user_id = diesel::update(
+                auth_users::table
+                    .filter(auth_users::user_id.eq(user_id))
+                    .filter(auth_users::deleted_at.is_null()),
+            )
+            .set((
+                auth_users::password_hash.eq(password_hash),
+                // A successful reset also clears any login lockout
+                auth_users::login_failure_count.eq(0),
+                auth_users::is_login_locked.eq(false),
+                auth_users::password_reset_required.eq(false),
+                auth_users::password_changed_at.eq(Utc::now()),
+            ))
+            .returning(auth_users::id)
+            .get_result::<Uuid>(conn)?;
+
+            Self::record_password_history(conn, auth_user_id, password_hash)?;
+
+            Ok(PasswordUpdate::Updated)
+        })
+    }
+
+    // ========================================
+    // Email Verification Operations
+    // ========================================
+
+    /// Store a new verification token for a user, invalidating any earlier unused ones
+    ///
+    /// The email carrying the token is queued in the same transaction.
+    pub fn create_email_verification_token(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        email: &EmailMessage,
+    ) -> Result<()> {
+        let mut conn = self.get_connection()?;
+        let now = Utc::now();
+
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            diesel::update(
+                email_verification_tokens::table
+                    .filter(email_verification_tokens::user_id.eq(user_id))
+                    .filter(email_verification_tokens::used_at.is_null()),
+            )
+            .set(email_verification_tokens::used_at.eq(Some(now)))
+            .execute(conn)?;
+
+            diesel::insert_into(email_verification_tokens::table)
+                .values(&NewEmailVerificationToken {
+                    user_id,
+                    token_hash: token_hash.to_string(),
+                    expires_at: now + chrono::Duration::hours(EmailVerification::TOKEN_TTL_HOURS),
+                })
+                .execute(conn)?;
+
+            Self::queue_email_in(conn, email)
+        })?;
+
         Ok(())
     }
 
-    /// Get security logs for admin panel
-    pub fn get_security_logs(
+    /// Count verification emails issued to a user since `since`, with the latest issue time
+    pub fn get_email_verification_send_stats(
         &self,
-        params: &SecurityLogsQuery,
-        offset: u32,
-    ) -> Result<Vec<SecurityLogEntry>> {
-        // TODO: Implement actual security logs query from security_events table
-        let _ = (params, offset); // Suppress unused parameter warnings
-        Ok(vec![])
+        user_id: Uuid,
+        since: DateTime<Utc>,
+    ) -> Result<(i64, Option<DateTime<Utc>>)> {
+        let mut conn = self.get_connection()?;
+
+        let (count, latest) = email_verification_tokens::table
+            .filter(email_verification_tokens::user_id.eq(user_id))
+            .filter(email_verification_tokens::created_at.ge(since))
+            .select((
+                diesel::dsl::count_star(),
+                diesel::dsl::max(email_verification_tokens::created_at),
+            ))
+            .first::<(i64, Option<DateTime<Utc>>)>(&mut conn)?;
+
+        Ok((count, latest))
     }
 
-    /// Count security logs
-    pub fn count_security_logs(&self, params: &SecurityLogsQuery) -> Result<i64> {
-        // TODO: Implement actual security logs count query
-        let _ = params; // Suppress unused parameter warning
-        Ok(0)
+    /// Consume a verification token and mark the email verified in one transaction
+    ///
+    /// Returns the verified user's ID, or `None` if the token is unknown, used or expired.
+    pub fn verify_email_with_token(&self, token_hash: &str) -> Result<Option<Uuid>> {
+        let mut conn = self.get_connection()?;
+        let now = Utc::now();
+
+        let user_id = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let user_id = diesel::update(
+                email_verification_tokens::table
+                    .filter(email_verification_tokens::token_hash.eq(token_hash))
+                    .filter(email_verification_tokens::used_at.is_null())
+                    .filter(email_verification_tokens::expires_at.gt(now)),
+            )
+            .set(email_verification_tokens::used_at.eq(Some(now)))
+            .returning(email_verification_tokens::user_id)
+            .get_result::<Uuid>(conn)
+            .optional()?;
+
+            if let Some(user_id) = user_id {
+                diesel::update(
+                    auth_users::table
+                        .filter(auth_users::user_id.eq(user_id))
+                        .filter(auth_users::deleted_at.is_null()),
+                )
+                .set(auth_users::email_verified.eq(true))
+                .execute(conn)?;
+            }
+
+            Ok(user_id)
+        })?;
+
+        Ok(user_id)
+    }
+
+    // ========================================
+    // Password History Operations
+    // ========================================
+
+    /// Replace a user's password hash, recording it in history and pruning old entries
+    ///
+    /// `password` is checked against the user's recent passwords in the same transaction;
+    /// returns `false` if it was used recently, in which case nothing changes. Verifying
+    /// the history runs bcrypt, so call this off the async runtime.
+    pub fn set_password_hash(
+        &self,
+        user_id: Uuid,
+        password: &str,
+        password_hash: &str,
+    ) -> Result<bool> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction(|conn| {
+            if Self::is_password_reused(conn, user_id, password)? {
+                return Ok(false);
+            }
+
+            let auth_user_id = diesel::update(
+                auth_users::table
+                    .filter(auth_users::user_id.eq(user_id))
+                    .filter(auth_users::deleted_at.is_null()),
+            )
+            .set((
+                auth_users::password_hash.eq(password_hash),
+                // A compliant new password satisfies any pending change requirement
+                auth_users::password_reset_required.eq(false),
+                auth_users::password_changed_at.eq(Utc::now()),
+            ))
+            .returning(auth_users::id)
+            .get_result::<Uuid>(conn)?;
+
+            Self::record_password_history(conn, auth_user_id, password_hash)?;
+
+            Ok(true)
+        })
+    }
+
+    /// Get the maximum password age in days for a user's role (None if rotation is off)
+    pub fn get_password_max_age_days(&self, user_id: Uuid) -> Result<Option<i32>> {
+        let mut conn = self.get_connection()?;
+
+        let max_age_days = users::table
+            .inner_join(roles::table.on(users::role_id.eq(roles::id)))
+            .filter(users::id.eq(user_id))
+            .filter(users::deleted_at.is_null())
+            .select(roles::password_max_age_days)
+            .first::<Option<i32>>(&mut conn)
+            .optional()?;
+
+        Ok(max_age_days.flatten())
+    }
+
+    /// List a page of accounts whose password is past their role's maximum age, oldest
+    /// first, filtered in the database
+    pub fn get_expired_password_users(
+        &self,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<ExpiredPasswordView>> {
+        let mut conn = self.get_connection()?;
+
+        let rows = users::table
+            .inner_join(auth_users::table.on(auth_users::user_id.eq(users::id)))
+            .inner_join(roles::table.on(users::role_id.eq(roles::id)))
+            .filter(users::deleted_at.is_null())
+            .filter(auth_users::deleted_at.is_null())
+            .filter(roles::password_max_age_days.gt(0))
+            .filter(password_expired())
+            .order((auth_users::password_changed_at.asc(), users::id.asc()))
+            .limit(limit)
+            .offset(offset)
+            .select((
+                users::id,
+                users::email,
+                users::name,
+                roles::name,
+                roles::password_max_age_days,
+                auth_users::password_changed_at,
+                auth_users::last_login,
+            ))
+            .load::<(
+                Uuid,
+                String,
+                String,
+                String,
+                Option<i32>,
+                DateTime<Utc>,
+                Option<DateTime<Utc>>,
+            )>(&mut conn)?;
+
+        let now = Utc::now();
+        let expired = rows
+            .into_iter()
+            .map(
+                |(id, email, name, role, max_age_days, changed_at, last_login)| {
+                    let max_age_days = max_age_days.unwrap_or_default();
+                    let expired_at = changed_at + chrono::Duration::days(max_age_days as i64);
+                    ExpiredPasswordView {
+                        id: id.to_string(),
+                        email,
+                        name,
+                        role,
+                        password_max_age_days: max_age_days,
+                        password_changed_at: changed_at.to_rfc3339(),
+                        password_expired_at: expired_at.to_rfc3339(),
+                        days_overdue: (now - expired_at).num_days(),
+                        last_login_at: last_login.map(|t| t.to_rfc3339()),
+                    }
+                },
+            )
+            .collect();
+
+        Ok(expired)
+    }
+
+    /// Count the accounts whose password is past their role's maximum age
+    pub fn count_expired_password_users(&self) -> Result<i64> {
+        let mut conn = self.get_connection()?;
+
+        let count = users::table
+            .inner_join(auth_users::table.on(auth_users::user_id.eq(users::id)))
+            .inner_join(roles::table.on(users::role_id.eq(roles::id)))
+            .filter(users::deleted_at.is_null())
+            .filter(auth_users::deleted_at.is_null())
+            .filter(roles::password_max_age_days.gt(0))
+            .filter(password_expired())
+            .count()
+            .get_result::<i64>(&mut conn)?;
+
+        Ok(count)
+    }
+
+    /// Set or clear the requirement to change password at next sign-in
+    pub fn set_password_change_required(&self, user_id: Uuid, required: bool) -> Result<()> {
+        let mut conn = self.get_connection()?;
+
+        let updated = diesel::update(
+            auth_users::table
+                .filter(auth_users::user_id.eq(user_id))
+                .filter(auth_users::deleted_at.is_null()),
+        )
+        .set(auth_users::password_reset_required.eq(required))
+        .execute(&mut conn)?;
+
+        if updated == 0 {
+            return Err(anyhow::anyhow!("Auth user not found"));
+        }
+
+        Ok(())
+    }
+
+    /// Check whether a password matches the current or any recent password of a user
+    ///
+    /// Locks the user's auth row until the transaction ends, so a concurrent change waits
+    /// and then sees this one's history.
+    fn is_password_reused(conn: &mut PgConnection, user_id: Uuid, password: &str) -> Result<bool> {
+        let (auth_user_id, current_hash) = match auth_users::table
+            .filter(auth_users::user_id.eq(user_id))
+            .filter(auth_users::deleted_at.is_null())
+            .select((auth_users::id, auth_users::password_hash))
+            .for_update()
+            .first::<(Uuid, String)>(conn)
+            .optional()?
+        {
+            Some(row) => row,
+            None => return Ok(false),
+        };
+
+        let mut hashes = password_history::table
+            .filter(password_history::auth_user_id.eq(auth_user_id))
+            .order(password_history::created_at.desc())
+            .limit(PasswordService::get_history_size())
+            .select(password_history::password_hash)
+            .load::<String>(conn)?;
+        hashes.push(current_hash);
+
+        Ok(PasswordService::matches_any(password, &hashes)?)
+    }
+
+    /// Insert a password history entry and drop everything beyond the configured size
+    fn record_password_history(
+        conn: &mut PgConnection,
+        auth_user_id: Uuid,
+        password_hash: &str,
+    ) -> QueryResult<()> {
+        diesel::insert_into(password_history::table)
+            .values(&NewPasswordHistory {
+                auth_user_id,
+                password_hash: password_hash.to_string(),
+            })
+            .execute(conn)?;
+
+        let keep_ids = password_history::table
+            .filter(password_history::auth_user_id.eq(auth_user_id))
+            .order(password_history::created_at.desc())
+            .limit(PasswordService::get_history_size().max(1))
+            .select(password_history::id)
+            .load::<Uuid>(conn)?;
+
+        diesel::delete(
+            password_history::table
+                .filter(password_history::auth_user_id.eq(auth_user_id))
+                .filter(password_history::id.ne_all(keep_ids)),
+        )
+        .execute(conn)?;
+
+        Ok(())
     }
 
     // ========================================
@@ -706,4 +1377,42 @@ impl Database {
 
         Ok(updated_user)
     }
+
+    /// Get a user's saved display language (None when they follow the client's language)
+    pub fn get_user_locale(&self, user_id: Uuid) -> Result<Option<String>> {
+        let mut conn = self.get_connection()?;
+
+        let locale = users::table
+            .filter(users::id.eq(user_id))
+            .filter(users::deleted_at.is_null())
+            .select(users::locale)
+            .first::<Option<String>>(&mut conn)
+            .optional()?;
+
+        Ok(locale.flatten())
+    }
+
+    /// Save or clear a user's display language
+    pub fn update_user_locale(&self, user_id: Uuid, locale: Option<&str>) -> Result<User> {
+        let mut conn = self.get_connection()?;
+
+        let user = diesel::update(
+            users::table
+                .filter(users::id.eq(user_id))
+                .filter(users::deleted_at.is_null()),
+        )
+        .set((users::locale.eq(locale), users::updated_at.eq(Utc::now())))
+        .returning(User::as_returning())
+        .get_result(&mut conn)?;
+
+        Ok(user)
+    }
+}
+
+/// SQL condition: the password is at least its role's maximum age (needs `auth_users`
+/// and `roles` in the query)
+fn password_expired() -> diesel::expression::SqlLiteral<diesel::sql_types::Bool> {
+    diesel::dsl::sql(
+        "auth_users.password_changed_at + make_interval(days => roles.password_max_age_days) <= NOW()",
+    )
 }
@@ -0,0 +1,527 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use super::constants::Organizations;
+use super::schema::{
+    memberships, organization_invitations, organizations, roles, user_sessions, users,
+};
+use super::{Database, RoleHierarchy};
+use crate::handlers::organization::{
+    OrganizationInvitationView, OrganizationMemberView, OrganizationView,
+};
+use crate::models::database::{
+    Membership, NewMembership, NewOrganization, NewOrganizationInvitation, Organization,
+    OrganizationInvitation,
+};
+use crate::utils::{ActiveOrganization, EmailMessage};
+
+/// Result of creating an organization
+#[derive(Debug, Clone)]
+pub enum OrganizationCreation {
+    Created(Organization),
+    /// Another organization (possibly deleted) already uses the slug
+    SlugTaken,
+}
+
+/// Result of accepting an organization invitation
+#[derive(Debug, Clone)]
+pub enum InvitationAcceptance {
+    Accepted {
+        organization: Organization,
+        role: String,
+    },
+    /// The user was already a member; the invitation is used up all the same
+    AlreadyMember { organization: Organization },
+    /// The invitation was sent to another address and stays pending
+    EmailMismatch,
+    /// The token is unknown, expired, revoked or already used
+    Invalid,
+}
+
+/// Result of changing a member's role or removing them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MembershipChange {
+    /// Done; `previous_role` is the role the member held before
+    Changed {
+        previous_role: String,
+    },
+    Refused(MembershipRefusal),
+}
+
+/// Why a member's role change or removal was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MembershipRefusal {
+    NotMember,
+    /// The change would leave the organization without an administrator
+    LastAdmin,
+}
+
+impl Database {
+    // ========================================
+    // Organization Operations
+    // ========================================
+
+    /// Create an organization with its creator as the first administrator
+    pub fn create_organization(
+        &self,
+        name: &str,
+        slug: &str,
+        creator_id: Uuid,
+    ) -> Result<OrganizationCreation> {
+        let admin_role_id = self
+            .get_role_id_by_name(Organizations::ADMIN_ROLE)?
+            .ok_or_else(|| anyhow::anyhow!("Role {} not found", Organizations::ADMIN_ROLE))?;
+        let mut conn = self.get_connection()?;
+
+        let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let slug_taken = organizations::table
+                .filter(organizations::slug.eq(slug))
+                .select(organizations::id)
+                .first::<Uuid>(conn)
+                .optional()?
+                .is_some();
+            if slug_taken {
+                return Ok(OrganizationCreation::SlugTaken);
+            }
+
+            let organization = diesel::insert_into(organizations::table)
+                .values(&NewOrganization {
+                    name: name.to_string(),
+                    slug: slug.to_string(),
+                    created_by: Some(creator_id),
+                })
+                .returning(Organization::as_returning())
+                .get_result(conn)?;
+
+            diesel::insert_into(memberships::table)
+                .values(&NewMembership {
+                    organization_id: organization.id,
+                    user_id: creator_id,
+                    role_id: admin_role_id,
+                })
+                .execute(conn)?;
+
+            Ok(OrganizationCreation::Created(organization))
+        })?;
+
+        Ok(result)
+    }
+
+    /// Organizations the user belongs to, with their role in each, by name
+    pub fn list_user_organizations(&self, user_id: Uuid) -> Result<Vec<OrganizationView>> {
+        let mut conn = self.get_connection()?;
+
+        let rows = memberships::table
+            .inner_join(organizations::table)
+            .inner_join(roles::table)
+            .filter(memberships::user_id.eq(user_id))
+            .filter(organizations::deleted_at.is_null())
+            .order((organizations::name.asc(), organizations::id.asc()))
+            .select((
+                Organization::as_select(),
+                roles::name,
+                memberships::created_at,
+            ))
+            .load::<(Organization, String, chrono::DateTime<Utc>)>(&mut conn)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(organization, role, joined_at)| OrganizationView {
+                id: organization.id.to_string(),
+                name: organization.name,
+                slug: organization.slug,
+                role,
+                joined_at: joined_at.to_rfc3339(),
+                created_at: organization.created_at.to_rfc3339(),
+            })
+            .collect())
+    }
+
+    /// An active organization and the user's role in it, if they are a member
+    pub fn find_organization_membership(
+        &self,
+        organization_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<(Organization, String)>> {
+        let mut conn = self.get_connection()?;
+
+        let membership = memberships::table
+            .inner_join(organizations::table)
+            .inner_join(roles::table)
+            .filter(memberships::organization_id.eq(organization_id))
+            .filter(memberships::user_id.eq(user_id))
+            .filter(organizations::deleted_at.is_null())
+            .select((Organization::as_select(), roles::name))
+            .first::<(Organization, String)>(&mut conn)
+            .optional()?;
+
+        Ok(membership)
+    }
+
+    /// Members of an organization with their roles, by email
+    pub fn list_organization_members(
+        &self,
+        organization_id: Uuid,
+    ) -> Result<Vec<OrganizationMemberView>> {
+        let mut conn = self.get_connection()?;
+
+        let rows = memberships::table
+            .inner_join(users::table)
+            .inner_join(roles::table)
+            .filter(memberships::organization_id.eq(organization_id))
+            .filter(users::deleted_at.is_null())
+            .order(users::email.asc())
+            .select((
+                users::id,
+                users::email,
+                users::name,
+                roles::name,
+                memberships::created_at,
+            ))
+            .load::<(Uuid, String, String, String, chrono::DateTime<Utc>)>(&mut conn)?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(user_id, email, name, role, joined_at)| OrganizationMemberView {
+                    user_id: user_id.to_string(),
+                    email,
+                    name,
+                    role,
+                    joined_at: joined_at.to_rfc3339(),
+                },
+            )
+            .collect())
+    }
+
+    /// Give a member another role, keeping at least one administrator
+    pub fn update_membership_role(
+        &self,
+        organization_id: Uuid,
+        user_id: Uuid,
+        role_id: Uuid,
+    ) -> Result<MembershipChange> {
+        let hierarchy = self.role_hierarchy()?;
+        let mut conn = self.get_connection()?;
+
+        let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let members = Self::lock_organization_members_in(conn, organization_id)?;
+            let Some(previous_role) = members
+                .iter()
+                .find(|(member, _)| member.user_id == user_id)
+                .map(|(_, role)| role.clone())
+            else {
+                return Ok(MembershipChange::Refused(MembershipRefusal::NotMember));
+            };
+
+            let new_role = hierarchy.get_by_id(role_id).map(|role| role.name.as_str());
+            let still_admin = new_role.is_some_and(|role| is_organization_admin(&hierarchy, role));
+            if !still_admin && is_last_admin(&hierarchy, &members, user_id) {
+                return Ok(MembershipChange::Refused(MembershipRefusal::LastAdmin));
+            }
+
+            diesel::update(
+                memberships::table
+                    .filter(memberships::organization_id.eq(organization_id))
+                    .filter(memberships::user_id.eq(user_id)),
+            )
+            .set((
+                memberships::role_id.eq(role_id),
+                memberships::updated_at.eq(Utc::now()),
+            ))
+            .execute(conn)?;
+
+            Ok(MembershipChange::Changed { previous_role })
+        })?;
+        self.invalidate_authz_decisions();
+
+        Ok(result)
+    }
+
+    /// Remove a member, keeping at least one administrator
+    ///
+    /// Sessions working in the organization drop it, so refreshed tokens no longer carry it.
+    pub fn remove_membership(
+        &self,
+        organization_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<MembershipChange> {
+        let hierarchy = self.role_hierarchy()?;
+        let mut conn = self.get_connection()?;
+
+        let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let members = Self::lock_organization_members_in(conn, organization_id)?;
+            let Some(previous_role) = members
+                .iter()
+                .find(|(member, _)| member.user_id == user_id)
+                .map(|(_, role)| role.clone())
+            else {
+                return Ok(MembershipChange::Refused(MembershipRefusal::NotMember));
+            };
+            if is_last_admin(&hierarchy, &members, user_id) {
+                return Ok(MembershipChange::Refused(MembershipRefusal::LastAdmin));
+            }
+
+            diesel::delete(
+                memberships::table
+                    .filter(memberships::organization_id.eq(organization_id))
+                    .filter(memberships::user_id.eq(user_id)),
+            )
+            .execute(conn)?;
+
+            diesel::update(
+                user_sessions::table
+                    .filter(user_sessions::user_id.eq(user_id))
+                    .filter(user_sessions::organization_id.eq(organization_id)),
+            )
+            .set(user_sessions::organization_id.eq(None::<Uuid>))
+            .execute(conn)?;
+
+            Ok(MembershipChange::Changed { previous_role })
+        })?;
+        self.invalidate_authz_decisions();
+
+        Ok(result)
+    }
+
+    /// Memberships of an organization with role names, locked until the transaction ends
+    fn lock_organization_members_in(
+        conn: &mut PgConnection,
+        organization_id: Uuid,
+    ) -> QueryResult<Vec<(Membership, String)>> {
+        memberships::table
+            .inner_join(roles::table)
+            .filter(memberships::organization_id.eq(organization_id))
+            .select((Membership::as_select(), roles::name))
+            .for_update()
+            .load(conn)
+    }
+
+    // ========================================
+    // Organization Invitation Operations
+    // ========================================
+
+    /// Store an invitation and queue its email, replacing any pending invitation to the same address
+    pub fn create_organization_invitation(
+        &self,
+        organization_id: Uuid,
+        email: &str,
+        role_id: Uuid,
+        invited_by: Uuid,
+        token_hash: &str,
+        message: &EmailMessage,
+    ) -> Result<OrganizationInvitation> {
+        let mut conn = self.get_connection()?;
+        let now = Utc::now();
+
+        let invitation = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            diesel::update(
+                organization_invitations::table
+                    .filter(organization_invitations::organization_id.eq(organization_id))
+                    .filter(organization_invitations::email.eq(email))
+                    .filter(organization_invitations::accepted_at.is_null())
+                    .filter(organization_invitations::revoked_at.is_null()),
+            )
+            .set(organization_invitations::revoked_at.eq(Some(now)))
+            .execute(conn)?;
+
+            let invitation = diesel::insert_into(organization_invitations::table)
+                .values(&NewOrganizationInvitation {
+                    organization_id,
+                    email: email.to_string(),
+                    role_id,
+                    token_hash: token_hash.to_string(),
+                    invited_by: Some(invited_by),
+                    expires_at: now + Duration::days(Organizations::INVITATION_TTL_DAYS),
+                })
+                .returning(OrganizationInvitation::as_returning())
+                .get_result(conn)?;
+
+            Self::queue_email_in(conn, message)?;
+
+            Ok(invitation)
+        })?;
+
+        Ok(invitation)
+    }
+
+    /// Invitations of an organization that can still be accepted, newest first
+    pub fn list_pending_invitations(
+        &self,
+        organization_id: Uuid,
+    ) -> Result<Vec<OrganizationInvitationView>> {
+        let mut conn = self.get_connection()?;
+
+        let rows = organization_invitations::table
+            .inner_join(roles::table)
+            .filter(organization_invitations::organization_id.eq(organization_id))
+            .filter(organization_invitations::accepted_at.is_null())
+            .filter(organization_invitations::revoked_at.is_null())
+            .filter(organization_invitations::expires_at.gt(Utc::now()))
+            .order(organization_invitations::created_at.desc())
+            .select((OrganizationInvitation::as_select(), roles::name))
+            .load::<(OrganizationInvitation, String)>(&mut conn)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(invitation, role)| OrganizationInvitationView {
+                id: invitation.id.to_string(),
+                email: invitation.email,
+                role,
+                invited_by: invitation.invited_by.map(|id| id.to_string()),
+                expires_at: invitation.expires_at.to_rfc3339(),
+                created_at: invitation.created_at.to_rfc3339(),
+            })
+            .collect())
+    }
+
+    /// Revoke a pending invitation, returning its email if there was one to revoke
+    pub fn revoke_organization_invitation(
+        &self,
+        organization_id: Uuid,
+        invitation_id: Uuid,
+    ) -> Result<Option<String>> {
+        let mut conn = self.get_connection()?;
+
+        let email = diesel::update(
+            organization_invitations::table
+                .filter(organization_invitations::id.eq(invitation_id))
+                .filter(organization_invitations::organization_id.eq(organization_id))
+                .filter(organization_invitations::accepted_at.is_null())
+                .filter(organization_invitations::revoked_at.is_null()),
+        )
+        .set(organization_invitations::revoked_at.eq(Some(Utc::now())))
+        .returning(organization_invitations::email)
+        .get_result::<String>(&mut conn)
+        .optional()?;
+
+        Ok(email)
+    }
+
+    /// Redeem an invitation token for the signed-in user, whose email must match it
+    pub fn accept_organization_invitation(
+        &self,
+        token_hash: &str,
+        user_id: Uuid,
+        user_email: &str,
+    ) -> Result<InvitationAcceptance> {
+        let mut conn = self.get_connection()?;
+        let now = Utc::now();
+
+        let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let invitation = organization_invitations::table
+                .inner_join(organizations::table)
+                .inner_join(roles::table)
+                .filter(organization_invitations::token_hash.eq(token_hash))
+                .filter(organization_invitations::accepted_at.is_null())
+                .filter(organization_invitations::revoked_at.is_null())
+                .filter(organization_invitations::expires_at.gt(now))
+                .filter(organizations::deleted_at.is_null())
+                .filter(roles::deleted_at.is_null())
+                .select((
+                    OrganizationInvitation::as_select(),
+                    Organization::as_select(),
+                    roles::name,
+                ))
+                .for_update()
+                .first::<(OrganizationInvitation, Organization, String)>(conn)
+                .optional()?;
+            let Some((invitation, organization, role)) = invitation else {
+                return Ok(InvitationAcceptance::Invalid);
+            };
+            if !invitation.email.eq_ignore_ascii_case(user_email) {
+                return Ok(InvitationAcceptance::EmailMismatch);
+            }
+
+            diesel::update(organization_invitations::table.find(invitation.id))
+                .set((
+                    organization_invitations::accepted_at.eq(Some(now)),
+                    organization_invitations::accepted_by.eq(Some(user_id)),
+                ))
+                .execute(conn)?;
+
+            let joined = diesel::insert_into(memberships::table)
+                .values(&NewMembership {
+                    organization_id: organization.id,
+                    user_id,
+                    role_id: invitation.role_id,
+                })
+                .on_conflict((memberships::organization_id, memberships::user_id))
+                .do_nothing()
+                .execute(conn)?;
+
+            if joined == 0 {
+                Ok(InvitationAcceptance::AlreadyMember { organization })
+            } else {
+                Ok(InvitationAcceptance::Accepted { organization, role })
+            }
+        })?;
+        self.invalidate_authz_decisions();
+
+        Ok(result)
+    }
+
+    // ========================================
+    // Active Organization Operations
+    // ========================================
+
+    /// Switch a session into an organization, or out of any with `None`
+    pub fn set_session_organization(
+        &self,
+        session_id: Uuid,
+        organization_id: Option<Uuid>,
+    ) -> Result<()> {
+        let mut conn = self.get_connection()?;
+
+        diesel::update(user_sessions::table.find(session_id))
+            .set(user_sessions::organization_id.eq(organization_id))
+            .execute(&mut conn)?;
+
+        Ok(())
+    }
+
+    /// The session's active organization and the user's current role there, while they
+    /// are still a member of it
+    pub fn get_session_organization(&self, session_id: Uuid) -> Result<Option<ActiveOrganization>> {
+        let mut conn = self.get_connection()?;
+
+        let active = user_sessions::table
+            .inner_join(organizations::table)
+            .inner_join(
+                memberships::table.on(memberships::organization_id
+                    .eq(organizations::id)
+                    .and(memberships::user_id.eq(user_sessions::user_id))),
+            )
+            .inner_join(roles::table.on(roles::id.eq(memberships::role_id)))
+            .filter(user_sessions::id.eq(session_id))
+            .filter(organizations::deleted_at.is_null())
+            .select((organizations::id, roles::name))
+            .first::<(Uuid, String)>(&mut conn)
+            .optional()?;
+
+        Ok(active.map(|(id, role)| ActiveOrganization { id, role }))
+    }
+}
+
+/// Whether an organization role lets its holder manage members and invitations
+pub fn is_organization_admin(hierarchy: &RoleHierarchy, role: &str) -> bool {
+    hierarchy.inherits(role, Organizations::ADMIN_ROLE)
+}
+
+/// Whether `user_id` is the only administrator among `members`
+fn is_last_admin(
+    hierarchy: &RoleHierarchy,
+    members: &[(Membership, String)],
+    user_id: Uuid,
+) -> bool {
+    let is_admin = |role: &str| is_organization_admin(hierarchy, role);
+
+    members
+        .iter()
+        .any(|(member, role)| member.user_id == user_id && is_admin(role))
+        && !members
+            .iter()
+            .any(|(member, role)| member.user_id != user_id && is_admin(role))
+}
@@ -0,0 +1,172 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use super::constants::EmailOutbox;
+use super::schema::email_outbox;
+use super::Database;
+use crate::models::database::{EmailOutboxEntry, NewEmailOutboxEntry};
+use crate::utils::EmailMessage;
+
+impl Database {
+    // ========================================
+    // Email Outbox Operations
+    // ========================================
+
+    /// Queue an email on an existing connection, so it commits with the caller's transaction
+    ///
+    /// The message's secret goes in its own column, which is cleared once the message is
+    /// sent or given up on.
+    pub fn queue_email_in(conn: &mut PgConnection, message: &EmailMessage) -> QueryResult<()> {
+        diesel::insert_into(email_outbox::table)
+            .values(&NewEmailOutboxEntry {
+                to_address: message.to.clone(),
+                subject: message.subject.clone(),
+                body: message.body.clone(),
+                secret: message.secret.clone(),
+            })
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    /// Queue an email on its own
+    pub fn queue_email(&self, message: &EmailMessage) -> Result<()> {
+        let mut conn = self.get_connection()?;
+        Self::queue_email_in(&mut conn, message)?;
+        Ok(())
+    }
+
+    /// Claim up to `limit` due messages for delivery
+    ///
+    /// Claimed rows are pushed back by a lease so concurrent workers skip them;
+    /// if this worker dies mid-send they become due again once the lease ends.
+    pub fn claim_due_emails(&self, limit: i64) -> Result<Vec<EmailOutboxEntry>> {
+        let mut conn = self.get_connection()?;
+        let now = Utc::now();
+
+        let claimed = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let due = email_outbox::table
+                .filter(email_outbox::status.eq(EmailOutbox::STATUS_PENDING))
+                .filter(email_outbox::next_attempt_at.le(now))
+                .order(email_outbox::next_attempt_at.asc())
+                .limit(limit)
+                .for_update()
+                .skip_locked()
+                .select(EmailOutboxEntry::as_select())
+                .load(conn)?;
+
+            let ids: Vec<Uuid> = due.iter().map(|entry| entry.id).collect();
+            diesel::update(email_outbox::table.filter(email_outbox::id.eq_any(&ids)))
+                .set(
+                    email_outbox::next_attempt_at
+                        .eq(now + Duration::seconds(EmailOutbox::CLAIM_LEASE_SECONDS)),
+                )
+                .execute(conn)?;
+
+            Ok(due)
+        })?;
+
+        Ok(claimed)
+    }
+
+    /// Record a successful delivery
+    pub fn mark_email_sent(&self, id: Uuid, attempts: i32) -> Result<()> {
+        let mut conn = self.get_connection()?;
+
+        diesel::update(email_outbox::table.filter(email_outbox::id.eq(id)))
+            .set((
+                email_outbox::status.eq(EmailOutbox::STATUS_SENT),
+                email_outbox::attempts.eq(attempts),
+                email_outbox::sent_at.eq(Some(Utc::now())),
+                email_outbox::last_error.eq(None::<String>),
+                email_outbox::secret.eq(None::<String>),
+            ))
+            .execute(&mut conn)?;
+
+        Ok(())
+    }
+
+    /// Record a failed delivery, retrying at `retry_at` or marking the message dead if `None`
+    pub fn mark_email_failed(
+        &self,
+        id: Uuid,
+        attempts: i32,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let mut conn = self.get_connection()?;
+
+        let entry = email_outbox::table.filter(email_outbox::id.eq(id));
+
+        match retry_at {
+            Some(retry_at) => diesel::update(entry)
+                .set((
+                    email_outbox::status.eq(EmailOutbox::STATUS_PENDING),
+                    email_outbox::attempts.eq(attempts),
+                    email_outbox::last_error.eq(Some(error)),
+                    email_outbox::next_attempt_at.eq(retry_at),
+                ))
+                .execute(&mut conn)?,
+            // A dead message is never sent, so its token has no further use
+            None => diesel::update(entry)
+                .set((
+                    email_outbox::status.eq(EmailOutbox::STATUS_DEAD),
+                    email_outbox::attempts.eq(attempts),
+                    email_outbox::last_error.eq(Some(error)),
+                    email_outbox::next_attempt_at.eq(Utc::now()),
+                    email_outbox::secret.eq(None::<String>),
+                ))
+                .execute(&mut conn)?,
+        };
+
+        Ok(())
+    }
+
+    /// Count messages per status
+    pub fn count_emails_by_status(&self) -> Result<Vec<(String, i64)>> {
+        let mut conn = self.get_connection()?;
+
+        let counts = email_outbox::table
+            .group_by(email_outbox::status)
+            .select((email_outbox::status, diesel::dsl::count_star()))
+            .load::<(String, i64)>(&mut conn)?;
+
+        Ok(counts)
+    }
+
+    /// Creation time of the oldest message still waiting for delivery
+    pub fn get_oldest_pending_email_time(&self) -> Result<Option<DateTime<Utc>>> {
+        let mut conn = self.get_connection()?;
+
+        let oldest = email_outbox::table
+            .filter(email_outbox::status.eq(EmailOutbox::STATUS_PENDING))
+            .select(diesel::dsl::min(email_outbox::created_at))
+            .first::<Option<DateTime<Utc>>>(&mut conn)?;
+
+        Ok(oldest)
+    }
+
+    /// List outbox messages, newest first, optionally filtered by status
+    pub fn get_outbox_emails(
+        &self,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<EmailOutboxEntry>> {
+        let mut conn = self.get_connection()?;
+
+        let mut query = email_outbox::table
+            .order(email_outbox::created_at.desc())
+            .limit(limit)
+            .offset(offset)
+            .select(EmailOutboxEntry::as_select())
+            .into_boxed();
+        if let Some(status) = status {
+            query = query.filter(email_outbox::status.eq(status));
+        }
+
+        Ok(query.load(&mut conn)?)
+    }
+}
@@ -0,0 +1,1129 @@
+use anyhow::{bail, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use diesel::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use super::constants::Relations;
+use super::schema::{group_members, memberships, relation_tuple_revision, relation_tuples};
+use super::Database;
+use crate::models::database::NewRelationTuple;
+
+// ========================================
+// Namespace Configuration
+// ========================================
+
+/// One way a subject can hold a relation; a relation is the union of its rewrites
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rewrite {
+    /// Subjects written on the relation itself
+    This,
+    /// Holders of another relation on the same object, e.g. editors can view
+    ComputedUserset(&'static str),
+    /// Holders of `computed` on each object the `tupleset` relation points at, e.g.
+    /// viewers of the parent folder can view what's in it
+    TupleToUserset {
+        tupleset: &'static str,
+        computed: &'static str,
+    },
+    /// Users in the organization group whose ID is the object's, from `group_members`
+    GroupMembers,
+}
+
+/// A relation the objects of a namespace can have
+#[derive(Debug)]
+pub struct RelationConfig {
+    pub name: &'static str,
+    /// Subject types that can be written on it: a namespace (`user`) or a userset
+    /// (`group#member`)
+    pub subject_types: &'static [&'static str],
+    pub rewrites: &'static [Rewrite],
+}
+
+/// A type of object and its relations
+#[derive(Debug)]
+pub struct NamespaceConfig {
+    pub name: &'static str,
+    pub relations: &'static [RelationConfig],
+}
+
+impl NamespaceConfig {
+    pub fn relation(&'static self, name: &str) -> Option<&'static RelationConfig> {
+        self.relations.iter().find(|relation| relation.name == name)
+    }
+}
+
+/// Users and group members can be given any sharing relation
+const SHARING_SUBJECTS: &[&str] = &["user", "group#member"];
+
+/// Folders, notes and articles share the same relations: owners can edit, editors can
+/// view, and each is inherited from the parent folder
+const SHARED_RESOURCE_RELATIONS: &[RelationConfig] = &[
+    RelationConfig {
+        name: "parent",
+        subject_types: &["folder"],
+        rewrites: &[Rewrite::This],
+    },
+    RelationConfig {
+        name: "owner",
+        subject_types: SHARING_SUBJECTS,
+        rewrites: &[
+            Rewrite::This,
+            Rewrite::TupleToUserset {
+                tupleset: "parent",
+                computed: "owner",
+            },
+        ],
+    },
+    RelationConfig {
+        name: "editor",
+        subject_types: SHARING_SUBJECTS,
+        rewrites: &[
+            Rewrite::This,
+            Rewrite::ComputedUserset("owner"),
+            Rewrite::TupleToUserset {
+                tupleset: "parent",
+                computed: "editor",
+            },
+        ],
+    },
+    RelationConfig {
+        name: "viewer",
+        subject_types: SHARING_SUBJECTS,
+        rewrites: &[
+            Rewrite::This,
+            Rewrite::ComputedUserset("editor"),
+            Rewrite::TupleToUserset {
+                tupleset: "parent",
+                computed: "viewer",
+            },
+        ],
+    },
+];
+
+/// Every namespace tuples can be written in
+pub static NAMESPACES: &[NamespaceConfig] = &[
+    NamespaceConfig {
+        name: "user",
+        relations: &[],
+    },
+    NamespaceConfig {
+        name: "group",
+        // Members are managed through the organization's groups, so nothing is written here
+        relations: &[RelationConfig {
+            name: "member",
+            subject_types: &[],
+            rewrites: &[Rewrite::GroupMembers],
+        }],
+    },
+    NamespaceConfig {
+        name: "folder",
+        relations: SHARED_RESOURCE_RELATIONS,
+    },
+    NamespaceConfig {
+        name: "note",
+        relations: SHARED_RESOURCE_RELATIONS,
+    },
+    NamespaceConfig {
+        name: "article",
+        relations: SHARED_RESOURCE_RELATIONS,
+    },
+];
+
+/// Look up a namespace by name
+pub fn namespace(name: &str) -> Option<&'static NamespaceConfig> {
+    NAMESPACES.iter().find(|namespace| namespace.name == name)
+}
+
+/// Look up a relation of a namespace
+fn relation_config(namespace_name: &str, relation: &str) -> Option<&'static RelationConfig> {
+    namespace(namespace_name).and_then(|namespace| namespace.relation(relation))
+}
+
+// ========================================
+// Tuples
+// ========================================
+
+/// An object such as `note:42`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjectRef {
+    pub namespace: String,
+    pub id: String,
+}
+
+impl ObjectRef {
+    pub fn new(namespace: &str, id: &str) -> Self {
+        ObjectRef {
+            namespace: namespace.to_string(),
+            id: id.to_string(),
+        }
+    }
+
+    /// Parse `namespace:id`, rejecting unknown namespaces and malformed IDs
+    pub fn parse(value: &str) -> Option<Self> {
+        let (namespace_name, id) = value.trim().split_once(':')?;
+        (namespace(namespace_name).is_some() && Relations::is_valid_id(id))
+            .then(|| Self::new(namespace_name, id))
+    }
+}
+
+impl fmt::Display for ObjectRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.namespace, self.id)
+    }
+}
+
+/// A subject: an object such as `user:<uuid>`, or a userset such as `group:9#member`
+/// meaning everyone holding that relation
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SubjectRef {
+    pub object: ObjectRef,
+    pub relation: Option<String>,
+}
+
+impl SubjectRef {
+    pub fn object(object: ObjectRef) -> Self {
+        SubjectRef {
+            object,
+            relation: None,
+        }
+    }
+
+    pub fn userset(object: ObjectRef, relation: &str) -> Self {
+        SubjectRef {
+            object,
+            relation: Some(relation.to_string()),
+        }
+    }
+
+    /// Parse `namespace:id` or `namespace:id#relation`, rejecting relations the
+    /// namespace doesn't define
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().split_once('#') {
+            Some((object, relation)) => {
+                let object = ObjectRef::parse(object)?;
+                relation_config(&object.namespace, relation)?;
+                Some(Self::userset(object, relation))
+            }
+            None => Some(Self::object(ObjectRef::parse(value)?)),
+        }
+    }
+
+    /// `user`, `folder` or `group#member`, as listed in `RelationConfig::subject_types`
+    pub fn type_name(&self) -> String {
+        match &self.relation {
+            Some(relation) => format!("{}#{}", self.object.namespace, relation),
+            None => self.object.namespace.clone(),
+        }
+    }
+}
+
+impl fmt::Display for SubjectRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.relation {
+            Some(relation) => write!(f, "{}#{}", self.object, relation),
+            None => write!(f, "{}", self.object),
+        }
+    }
+}
+
+/// A relation tuple, `object#relation@subject`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RelationTuple {
+    pub object: ObjectRef,
+    pub relation: String,
+    pub subject: SubjectRef,
+}
+
+impl RelationTuple {
+    /// Parse `object#relation@subject`, checking the relation is defined on the object's
+    /// namespace and accepts the subject's type
+    pub fn parse(value: &str) -> Option<Self> {
+        let (userset, subject) = value.trim().split_once('@')?;
+        let (object, relation) = userset.split_once('#')?;
+        let object = ObjectRef::parse(object)?;
+        let subject = SubjectRef::parse(subject)?;
+
+        relation_config(&object.namespace, relation)?
+            .subject_types
+            .contains(&subject.type_name().as_str())
+            .then(|| RelationTuple {
+                object,
+                relation: relation.to_string(),
+                subject,
+            })
+    }
+}
+
+impl fmt::Display for RelationTuple {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}#{}@{}", self.object, self.relation, self.subject)
+    }
+}
+
+/// Names a revision of the tuple store. Writes return one; reads given one see at least
+/// every write up to it, so a caller can read its own writes through the check cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ConsistencyToken(pub i64);
+
+impl ConsistencyToken {
+    /// Encode as an opaque URL-safe token
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("rev:{}", self.0))
+    }
+
+    /// Decode a token from `encode`
+    pub fn decode(token: &str) -> Option<Self> {
+        let raw = URL_SAFE_NO_PAD.decode(token.trim()).ok()?;
+        let revision: i64 = std::str::from_utf8(&raw)
+            .ok()?
+            .strip_prefix("rev:")?
+            .parse()
+            .ok()?;
+        (revision >= 0).then_some(ConsistencyToken(revision))
+    }
+}
+
+// ========================================
+// Evaluation
+// ========================================
+
+/// Reads tuples from one snapshot of the store, and group members as they are now
+pub trait TupleReader {
+    /// Subjects written directly on `object#relation`
+    fn subjects(&mut self, object: &ObjectRef, relation: &str) -> Result<Vec<SubjectRef>>;
+
+    /// Objects and relations that `subject` is written on directly
+    fn subject_tuples(&mut self, subject: &SubjectRef) -> Result<Vec<(ObjectRef, String)>>;
+
+    /// IDs of the users in a group
+    fn group_members(&mut self, group_id: &str) -> Result<Vec<String>>;
+
+    /// IDs of the groups a user is in
+    fn user_groups(&mut self, user_id: &str) -> Result<Vec<String>>;
+}
+
+/// Tuples and group members held in memory
+#[derive(Debug, Clone, Default)]
+pub struct MemoryTuples {
+    pub tuples: Vec<RelationTuple>,
+    /// User IDs in each group, by group ID
+    pub groups: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl TupleReader for MemoryTuples {
+    fn subjects(&mut self, object: &ObjectRef, relation: &str) -> Result<Vec<SubjectRef>> {
+        Ok(self
+            .tuples
+            .iter()
+            .filter(|tuple| tuple.object == *object && tuple.relation == relation)
+            .map(|tuple| tuple.subject.clone())
+            .collect())
+    }
+
+    fn subject_tuples(&mut self, subject: &SubjectRef) -> Result<Vec<(ObjectRef, String)>> {
+        Ok(self
+            .tuples
+            .iter()
+            .filter(|tuple| tuple.subject == *subject)
+            .map(|tuple| (tuple.object.clone(), tuple.relation.clone()))
+            .collect())
+    }
+
+    fn group_members(&mut self, group_id: &str) -> Result<Vec<String>> {
+        Ok(self
+            .groups
+            .get(group_id)
+            .map(|members| members.iter().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    fn user_groups(&mut self, user_id: &str) -> Result<Vec<String>> {
+        Ok(self
+            .groups
+            .iter()
+            .filter(|(_, members)| members.contains(user_id))
+            .map(|(group, _)| group.clone())
+            .collect())
+    }
+}
+
+/// Whether `subject` holds `relation` on `object`, directly, through a group or through
+/// the namespace's rewrites
+pub fn check_relation_in<R: TupleReader + ?Sized>(
+    reader: &mut R,
+    object: &ObjectRef,
+    relation: &str,
+    subject: &SubjectRef,
+) -> Result<bool> {
+    Checker::new(reader, subject).check(object, relation)
+}
+
+/// Continues a list-objects request after the last object of the previous page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListCursor(pub String);
+
+impl ListCursor {
+    /// Encode as an opaque URL-safe cursor
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("after:{}", self.0))
+    }
+
+    /// Decode a cursor from `encode`
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let raw = URL_SAFE_NO_PAD.decode(cursor.trim()).ok()?;
+        let id = std::str::from_utf8(&raw).ok()?.strip_prefix("after:")?;
+        Relations::is_valid_id(id).then(|| ListCursor(id.to_string()))
+    }
+}
+
+/// A page of object IDs, in order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectPage {
+    pub objects: Vec<String>,
+    /// Where the next page starts; `None` on the last page
+    pub next_cursor: Option<ListCursor>,
+}
+
+/// IDs of the objects in `namespace` on which `subject` holds `relation`, in order, at
+/// most `limit` of them after `after`
+///
+/// Works outwards from the subject instead of checking every object: the subject's
+/// groups, the tuples naming the subject or those groups, the relations each implies on
+/// the same object, and the contents of every folder reached.
+pub fn list_relation_objects_in<R: TupleReader + ?Sized>(
+    reader: &mut R,
+    namespace_name: &str,
+    relation: &str,
+    subject: &SubjectRef,
+    after: Option<&ListCursor>,
+    limit: usize,
+) -> Result<ObjectPage> {
+    let mut held: HashSet<(ObjectRef, String)> = HashSet::new();
+    let mut pending: VecDeque<(ObjectRef, String, usize)> = VecDeque::new();
+
+    // A userset holds itself, and a user holds `member` on each of their groups
+    match &subject.relation {
+        Some(own) => pending.push_back((subject.object.clone(), own.clone(), 0)),
+        None if subject.object.namespace == "user" => {
+            for group in reader.user_groups(&subject.object.id)? {
+                pending.push_back((ObjectRef::new("group", &group), "member".to_string(), 0));
+            }
+        }
+        None => {}
+    }
+    for (object, relation) in reader.subject_tuples(subject)? {
+        pending.push_back((object, relation, 0));
+    }
+
+    // Breadth first, so each userset is reached at its shallowest depth
+    let mut found = BTreeSet::new();
+    while let Some((object, held_relation, depth)) = pending.pop_front() {
+        if held.contains(&(object.clone(), held_relation.clone())) {
+            continue;
+        }
+        if depth > Relations::MAX_DEPTH {
+            bail!(
+                "relations nested deeper than {} levels at {}#{}",
+                Relations::MAX_DEPTH,
+                object,
+                held_relation
+            );
+        }
+        held.insert((object.clone(), held_relation.clone()));
+        if object.namespace == namespace_name && held_relation == relation {
+            found.insert(object.id.clone());
+        }
+
+        // Relations of the same object computed from this one, e.g. an owner is an editor
+        if let Some(config) = namespace(&object.namespace) {
+            for implied in config.relations {
+                let computed = implied.rewrites.iter().any(
+                    |rewrite| matches!(rewrite, Rewrite::ComputedUserset(name) if *name == held_relation),
+                );
+                if computed {
+                    pending.push_back((object.clone(), implied.name.to_string(), depth + 1));
+                }
+            }
+        }
+
+        // Tuples naming this userset, e.g. folder:docs#viewer@group:9#member, if any
+        // relation accepts usersets of its type
+        let userset = SubjectRef::userset(object.clone(), &held_relation);
+        let type_name = userset.type_name();
+        let accepted = NAMESPACES
+            .iter()
+            .flat_map(|config| config.relations)
+            .any(|config| config.subject_types.contains(&type_name.as_str()));
+        if accepted {
+            for (target, target_relation) in reader.subject_tuples(&userset)? {
+                pending.push_back((target, target_relation, depth + 1));
+            }
+        }
+
+        // Objects inheriting from this one, e.g. viewers of a folder view what's in it
+        let inherited = inherited_from(&held_relation);
+        if !inherited.is_empty() {
+            for (child, via) in reader.subject_tuples(&SubjectRef::object(object.clone()))? {
+                for (child_namespace, tupleset, inheriting) in &inherited {
+                    if child.namespace == *child_namespace && via == *tupleset {
+                        pending.push_back((child.clone(), inheriting.to_string(), depth + 1));
+                    }
+                }
+            }
+        }
+    }
+
+    let mut remaining = found
+        .into_iter()
+        .filter(|id| after.is_none_or(|cursor| *id > cursor.0));
+    let objects: Vec<String> = remaining.by_ref().take(limit).collect();
+    let next_cursor = remaining
+        .next()
+        .and_then(|_| objects.last().map(|last| ListCursor(last.clone())));
+    Ok(ObjectPage {
+        objects,
+        next_cursor,
+    })
+}
+
+/// `(namespace, tupleset, relation)` for each relation inherited through a tupleset from
+/// holders of `computed`, e.g. `("note", "parent", "viewer")` for `viewer`
+fn inherited_from(computed: &str) -> Vec<(&'static str, &'static str, &'static str)> {
+    let mut inherited = Vec::new();
+    for namespace in NAMESPACES {
+        for relation in namespace.relations {
+            for rewrite in relation.rewrites {
+                if let Rewrite::TupleToUserset {
+                    tupleset,
+                    computed: from,
+                } = *rewrite
+                {
+                    if from == computed {
+                        inherited.push((namespace.name, tupleset, relation.name));
+                    }
+                }
+            }
+        }
+    }
+    inherited
+}
+
+/// Expand `object#relation` into the tree of subjects and usersets that hold it
+pub fn expand_relation_in<R: TupleReader + ?Sized>(
+    reader: &mut R,
+    object: &ObjectRef,
+    relation: &str,
+) -> Result<ExpandTree> {
+    Expander {
+        reader,
+        expanded: HashSet::new(),
+    }
+    .expand(object, relation, 0)
+}
+
+/// Answers whether one subject holds relations, remembering what it has worked out so
+/// shared folders aren't walked again for every relation that reaches them
+struct Checker<'a, R: TupleReader + ?Sized> {
+    reader: &'a mut R,
+    subject: &'a SubjectRef,
+    known: HashMap<(ObjectRef, String), bool>,
+    visiting: HashSet<(ObjectRef, String)>,
+}
+
+impl<'a, R: TupleReader + ?Sized> Checker<'a, R> {
+    fn new(reader: &'a mut R, subject: &'a SubjectRef) -> Self {
+        Checker {
+            reader,
+            subject,
+            known: HashMap::new(),
+            visiting: HashSet::new(),
+        }
+    }
+
+    fn check(&mut self, object: &ObjectRef, relation: &str) -> Result<bool> {
+        let held = self.holds(object, relation, 0)?;
+        if held {
+            // A "no" worked out while a cycle back to an enclosing relation was cut off
+            // may be wrong once that relation turns out to be held
+            self.known.retain(|_, held| *held);
+        }
+        Ok(held)
+    }
+
+    fn holds(&mut self, object: &ObjectRef, relation: &str, depth: usize) -> Result<bool> {
+        // A userset holds itself: group:9#member is a member of group:9
+        if self.subject.object == *object && self.subject.relation.as_deref() == Some(relation) {
+            return Ok(true);
+        }
+
+        let key = (object.clone(), relation.to_string());
+        if let Some(&held) = self.known.get(&key) {
+            return Ok(held);
+        }
+        if depth > Relations::MAX_DEPTH {
+            bail!(
+                "relations nested deeper than {} levels at {}#{}",
+                Relations::MAX_DEPTH,
+                object,
+                relation
+            );
+        }
+        let Some(config) = relation_config(&object.namespace, relation) else {
+            return Ok(false);
+        };
+
+        // Reaching a relation that is already being worked out adds nothing new
+        if !self.visiting.insert(key.clone()) {
+            return Ok(false);
+        }
+        let held = self.evaluate(object, config, depth);
+        self.visiting.remove(&key);
+
+        let held = held?;
+        self.known.insert(key, held);
+        Ok(held)
+    }
+
+    fn evaluate(
+        &mut self,
+        object: &ObjectRef,
+        config: &'static RelationConfig,
+        depth: usize,
+    ) -> Result<bool> {
+        for rewrite in config.rewrites {
+            match *rewrite {
+                Rewrite::This => {
+                    for subject in self.reader.subjects(object, config.name)? {
+                        if subject == *self.subject {
+                            return Ok(true);
+                        }
+                        if let Some(relation) = &subject.relation {
+                            if self.holds(&subject.object, relation, depth + 1)? {
+                                return Ok(true);
+                            }
+                        }
+                    }
+                }
+                Rewrite::ComputedUserset(computed) => {
+                    if self.holds(object, computed, depth + 1)? {
+                        return Ok(true);
+                    }
+                }
+                Rewrite::TupleToUserset { tupleset, computed } => {
+                    for target in self.reader.subjects(object, tupleset)? {
+                        if self.holds(&target.object, computed, depth + 1)? {
+                            return Ok(true);
+                        }
+                    }
+                }
+                Rewrite::GroupMembers => {
+                    if self.subject.relation.is_none()
+                        && self.subject.object.namespace == "user"
+                        && self
+                            .reader
+                            .group_members(&object.id)?
+                            .contains(&self.subject.object.id)
+                    {
+                        return Ok(true);
+                    }
+                }
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// The holders of a userset as a tree: subjects written on it directly, and the
+/// usersets it includes through groups, computed relations and parent folders
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExpandTree {
+    /// `object#relation`
+    pub userset: String,
+    pub subjects: Vec<String>,
+    pub children: Vec<ExpandTree>,
+    /// Set on a userset expanded elsewhere in the tree, which is left empty here
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub reference: bool,
+}
+
+impl ExpandTree {
+    /// Every subject anywhere in the tree
+    pub fn leaves(&self) -> BTreeSet<String> {
+        let mut leaves: BTreeSet<String> = self.subjects.iter().cloned().collect();
+        for child in &self.children {
+            leaves.extend(child.leaves());
+        }
+        leaves
+    }
+}
+
+/// Expands each userset once: a folder or group reached again, through another path or
+/// a cycle, becomes a reference node, so the tree grows with the number of usersets
+/// rather than the number of paths to them
+struct Expander<'a, R: TupleReader + ?Sized> {
+    reader: &'a mut R,
+    expanded: HashSet<(ObjectRef, String)>,
+}
+
+impl<R: TupleReader + ?Sized> Expander<'_, R> {
+    fn expand(&mut self, object: &ObjectRef, relation: &str, depth: usize) -> Result<ExpandTree> {
+        let mut tree = ExpandTree {
+            userset: format!("{}#{}", object, relation),
+            subjects: Vec::new(),
+            children: Vec::new(),
+            reference: false,
+        };
+
+        if depth > Relations::MAX_DEPTH {
+            bail!(
+                "relations nested deeper than {} levels at {}",
+                Relations::MAX_DEPTH,
+                tree.userset
+            );
+        }
+        let Some(config) = relation_config(&object.namespace, relation) else {
+            return Ok(tree);
+        };
+
+        if !self.expanded.insert((object.clone(), relation.to_string())) {
+            tree.reference = true;
+            return Ok(tree);
+        }
+        self.fill(&mut tree, object, config, depth)?;
+
+        Ok(tree)
+    }
+
+    fn fill(
+        &mut self,
+        tree: &mut ExpandTree,
+        object: &ObjectRef,
+        config: &'static RelationConfig,
+        depth: usize,
+    ) -> Result<()> {
+        for rewrite in config.rewrites {
+            match *rewrite {
+                Rewrite::This => {
+                    for subject in self.reader.subjects(object, config.name)? {
+                        match &subject.relation {
+                            Some(relation) => tree.children.push(self.expand(
+                                &subject.object,
+                                relation,
+                                depth + 1,
+                            )?),
+                            None => tree.subjects.push(subject.to_string()),
+                        }
+                    }
+                }
+                Rewrite::ComputedUserset(computed) => {
+                    tree.children
+                        .push(self.expand(object, computed, depth + 1)?);
+                }
+                Rewrite::TupleToUserset { tupleset, computed } => {
+                    for target in self.reader.subjects(object, tupleset)? {
+                        tree.children
+                            .push(self.expand(&target.object, computed, depth + 1)?);
+                    }
+                }
+                Rewrite::GroupMembers => {
+                    for user in self.reader.group_members(&object.id)? {
+                        tree.subjects.push(format!("user:{}", user));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// ========================================
+// Storage
+// ========================================
+
+/// Tuples live at one revision, loaded from the database as evaluation needs them.
+/// Group members come from `group_members` as they are now, since groups aren't
+/// revisioned with the tuples.
+struct SnapshotReader<'c> {
+    conn: &'c mut PgConnection,
+    revision: i64,
+    loaded: HashMap<(ObjectRef, String), Vec<SubjectRef>>,
+    naming: HashMap<SubjectRef, Vec<(ObjectRef, String)>>,
+    members: HashMap<String, Vec<String>>,
+}
+
+impl<'c> SnapshotReader<'c> {
+    fn new(conn: &'c mut PgConnection, revision: i64) -> Self {
+        SnapshotReader {
+            conn,
+            revision,
+            loaded: HashMap::new(),
+            naming: HashMap::new(),
+            members: HashMap::new(),
+        }
+    }
+}
+
+impl TupleReader for SnapshotReader<'_> {
+    fn subjects(&mut self, object: &ObjectRef, relation: &str) -> Result<Vec<SubjectRef>> {
+        let key = (object.clone(), relation.to_string());
+        if let Some(subjects) = self.loaded.get(&key) {
+            return Ok(subjects.clone());
+        }
+
+        let rows: Vec<(String, String, Option<String>)> = relation_tuples::table
+            .filter(relation_tuples::namespace.eq(&object.namespace))
+            .filter(relation_tuples::object_id.eq(&object.id))
+            .filter(relation_tuples::relation.eq(relation))
+            .filter(relation_tuples::created_revision.le(self.revision))
+            .filter(
+                relation_tuples::deleted_revision
+                    .is_null()
+                    .or(relation_tuples::deleted_revision.gt(self.revision)),
+            )
+            .order(relation_tuples::id.asc())
+            .select((
+                relation_tuples::subject_namespace,
+                relation_tuples::subject_id,
+                relation_tuples::subject_relation,
+            ))
+            .load(self.conn)?;
+
+        let subjects: Vec<SubjectRef> = rows
+            .into_iter()
+            .map(|(namespace, id, relation)| SubjectRef {
+                object: ObjectRef { namespace, id },
+                relation,
+            })
+            .collect();
+        self.loaded.insert(key, subjects.clone());
+        Ok(subjects)
+    }
+
+    fn subject_tuples(&mut self, subject: &SubjectRef) -> Result<Vec<(ObjectRef, String)>> {
+        if let Some(tuples) = self.naming.get(subject) {
+            return Ok(tuples.clone());
+        }
+
+        // Served by idx_relation_tuples_subject
+        let query = relation_tuples::table
+            .filter(relation_tuples::subject_namespace.eq(&subject.object.namespace))
+            .filter(relation_tuples::subject_id.eq(&subject.object.id))
+            .filter(relation_tuples::created_revision.le(self.revision))
+            .filter(
+                relation_tuples::deleted_revision
+                    .is_null()
+                    .or(relation_tuples::deleted_revision.gt(self.revision)),
+            )
+            .order(relation_tuples::id.asc())
+            .select((
+                relation_tuples::namespace,
+                relation_tuples::object_id,
+                relation_tuples::relation,
+            ))
+            .into_boxed();
+        let query = match &subject.relation {
+            Some(relation) => query.filter(relation_tuples::subject_relation.eq(relation)),
+            None => query.filter(relation_tuples::subject_relation.is_null()),
+        };
+        let rows: Vec<(String, String, String)> = query.load(self.conn)?;
+
+        let tuples: Vec<(ObjectRef, String)> = rows
+            .into_iter()
+            .map(|(namespace, id, relation)| (ObjectRef { namespace, id }, relation))
+            .collect();
+        self.naming.insert(subject.clone(), tuples.clone());
+        Ok(tuples)
+    }
+
+    fn group_members(&mut self, group_id: &str) -> Result<Vec<String>> {
+        if let Some(members) = self.members.get(group_id) {
+            return Ok(members.clone());
+        }
+        // Group objects are named by the group's ID; anything else has no members
+        let Ok(id) = group_id.parse::<Uuid>() else {
+            return Ok(Vec::new());
+        };
+
+        let users: Vec<Uuid> = group_members::table
+            .inner_join(memberships::table)
+            .filter(group_members::group_id.eq(id))
+            .order(memberships::user_id.asc())
+            .select(memberships::user_id)
+            .load(self.conn)?;
+
+        let members: Vec<String> = users.iter().map(Uuid::to_string).collect();
+        self.members.insert(group_id.to_string(), members.clone());
+        Ok(members)
+    }
+
+    fn user_groups(&mut self, user_id: &str) -> Result<Vec<String>> {
+        let Ok(id) = user_id.parse::<Uuid>() else {
+            return Ok(Vec::new());
+        };
+
+        let groups: Vec<Uuid> = group_members::table
+            .inner_join(memberships::table)
+            .filter(memberships::user_id.eq(id))
+            .order(group_members::group_id.asc())
+            .select(group_members::group_id)
+            .load(self.conn)?;
+        Ok(groups.iter().map(Uuid::to_string).collect())
+    }
+}
+
+/// Recent check results and the revision each was worked out at
+#[derive(Default)]
+pub(super) struct RelationCheckCache {
+    entries: Mutex<HashMap<RelationTuple, (Instant, i64, bool)>>,
+}
+
+impl RelationCheckCache {
+    /// A result still within its TTL and at least as new as `at_least`
+    fn get(
+        &self,
+        query: &RelationTuple,
+        at_least: Option<ConsistencyToken>,
+    ) -> Option<(bool, ConsistencyToken)> {
+        let ttl = Duration::from_secs(Relations::CHECK_CACHE_TTL_SECS);
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+
+        entries
+            .get(query)
+            .filter(|(checked_at, revision, _)| {
+                checked_at.elapsed() < ttl && at_least.is_none_or(|token| *revision >= token.0)
+            })
+            .map(|(_, revision, held)| (*held, ConsistencyToken(*revision)))
+    }
+
+    fn store(&self, query: RelationTuple, revision: i64, held: bool) {
+        let ttl = Duration::from_secs(Relations::CHECK_CACHE_TTL_SECS);
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+
+        if entries.len() >= Relations::CHECK_CACHE_MAX_ENTRIES {
+            entries.retain(|_, (checked_at, _, _)| checked_at.elapsed() < ttl);
+            if entries.len() >= Relations::CHECK_CACHE_MAX_ENTRIES {
+                entries.clear();
+            }
+        }
+        entries.insert(query, (Instant::now(), revision, held));
+    }
+
+    pub(super) fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
+
+/// Outcome of writing relation tuples
+#[derive(Debug, Clone, Copy)]
+pub struct RelationWrite {
+    pub token: ConsistencyToken,
+    pub written: usize,
+    pub deleted: usize,
+}
+
+/// A check result and the revision it holds at
+#[derive(Debug, Clone, Copy)]
+pub struct RelationCheck {
+    pub allowed: bool,
+    pub token: ConsistencyToken,
+    pub cached: bool,
+}
+
+impl Database {
+    // ========================================
+    // Relation Tuple Operations
+    // ========================================
+
+    /// Apply `deletes` then `writes` as one new revision. Writing a tuple that exists or
+    /// deleting one that doesn't changes nothing; if nothing changes the current
+    /// revision is returned.
+    pub fn write_relation_tuples(
+        &self,
+        writes: &[RelationTuple],
+        deletes: &[RelationTuple],
+    ) -> Result<RelationWrite> {
+        let mut conn = self.get_connection()?;
+
+        let write = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            // Locking the revision row serializes writers, so revisions are committed in order
+            let current: i64 = relation_tuple_revision::table
+                .select(relation_tuple_revision::revision)
+                .for_update()
+                .first(conn)?;
+            let revision = current + 1;
+
+            let mut deleted = 0;
+            for tuple in deletes {
+                if let Some(id) = live_tuple_id(conn, tuple)? {
+                    diesel::update(relation_tuples::table.find(id))
+                        .set(relation_tuples::deleted_revision.eq(revision))
+                        .execute(conn)?;
+                    deleted += 1;
+                }
+            }
+
+            let mut written = 0;
+            for tuple in writes {
+                if live_tuple_id(conn, tuple)?.is_none() {
+                    diesel::insert_into(relation_tuples::table)
+                        .values(&NewRelationTuple {
+                            namespace: tuple.object.namespace.clone(),
+                            object_id: tuple.object.id.clone(),
+                            relation: tuple.relation.clone(),
+                            subject_namespace: tuple.subject.object.namespace.clone(),
+                            subject_id: tuple.subject.object.id.clone(),
+                            subject_relation: tuple.subject.relation.clone(),
+                            created_revision: revision,
+                        })
+                        .execute(conn)?;
+                    written += 1;
+                }
+            }
+
+            if written + deleted == 0 {
+                return Ok(RelationWrite {
+                    token: ConsistencyToken(current),
+                    written,
+                    deleted,
+                });
+            }
+
+            diesel::update(relation_tuple_revision::table)
+                .set(relation_tuple_revision::revision.eq(revision))
+                .execute(conn)?;
+
+            Ok(RelationWrite {
+                token: ConsistencyToken(revision),
+                written,
+                deleted,
+            })
+        })?;
+
+        Ok(write)
+    }
+
+    /// Whether `query.subject` holds `query.relation` on `query.object`. A result a few
+    /// seconds old may be returned unless `at_least` asks for a newer revision; `None`
+    /// means `at_least` names a revision that hasn't been written.
+    pub fn check_relation(
+        &self,
+        query: &RelationTuple,
+        at_least: Option<ConsistencyToken>,
+    ) -> Result<Option<RelationCheck>> {
+        if let Some((allowed, token)) = self.relation_check_cache.get(query, at_least) {
+            return Ok(Some(RelationCheck {
+                allowed,
+                token,
+                cached: true,
+            }));
+        }
+
+        let mut conn = self.get_connection()?;
+        let Some(revision) = relation_snapshot(&mut conn, at_least)? else {
+            return Ok(None);
+        };
+
+        let allowed = check_relation_in(
+            &mut SnapshotReader::new(&mut conn, revision),
+            &query.object,
+            &query.relation,
+            &query.subject,
+        )?;
+        self.relation_check_cache
+            .store(query.clone(), revision, allowed);
+
+        Ok(Some(RelationCheck {
+            allowed,
+            token: ConsistencyToken(revision),
+            cached: false,
+        }))
+    }
+
+    /// Expand `object#relation` at the latest revision; `None` means `at_least` names a
+    /// revision that hasn't been written
+    pub fn expand_relation(
+        &self,
+        object: &ObjectRef,
+        relation: &str,
+        at_least: Option<ConsistencyToken>,
+    ) -> Result<Option<(ExpandTree, ConsistencyToken)>> {
+        let mut conn = self.get_connection()?;
+        let Some(revision) = relation_snapshot(&mut conn, at_least)? else {
+            return Ok(None);
+        };
+
+        let tree = expand_relation_in(
+            &mut SnapshotReader::new(&mut conn, revision),
+            object,
+            relation,
+        )?;
+        Ok(Some((tree, ConsistencyToken(revision))))
+    }
+
+    /// A page of the objects in `namespace` on which `subject` holds `relation`, at the
+    /// latest revision; `None` means `at_least` names a revision that hasn't been written
+    pub fn list_relation_objects(
+        &self,
+        namespace: &str,
+        relation: &str,
+        subject: &SubjectRef,
+        after: Option<&ListCursor>,
+        at_least: Option<ConsistencyToken>,
+    ) -> Result<Option<(ObjectPage, ConsistencyToken)>> {
+        let mut conn = self.get_connection()?;
+        let Some(revision) = relation_snapshot(&mut conn, at_least)? else {
+            return Ok(None);
+        };
+
+        let page = list_relation_objects_in(
+            &mut SnapshotReader::new(&mut conn, revision),
+            namespace,
+            relation,
+            subject,
+            after,
+            Relations::MAX_LIST_OBJECTS,
+        )?;
+        Ok(Some((page, ConsistencyToken(revision))))
+    }
+}
+
+/// The latest revision, or `None` if `at_least` is newer than it
+fn relation_snapshot(
+    conn: &mut PgConnection,
+    at_least: Option<ConsistencyToken>,
+) -> Result<Option<i64>> {
+    let revision: i64 = relation_tuple_revision::table
+        .select(relation_tuple_revision::revision)
+        .first(conn)?;
+
+    Ok(at_least
+        .is_none_or(|token| token.0 <= revision)
+        .then_some(revision))
+}
+
+/// ID of the live copy of `tuple`, if there is one
+fn live_tuple_id(conn: &mut PgConnection, tuple: &RelationTuple) -> QueryResult<Option<i64>> {
+    let query = relation_tuples::table
+        .filter(relation_tuples::namespace.eq(&tuple.object.namespace))
+        .filter(relation_tuples::object_id.eq(&tuple.object.id))
+        .filter(relation_tuples::relation.eq(&tuple.relation))
+        .filter(relation_tuples::subject_namespace.eq(&tuple.subject.object.namespace))
+        .filter(relation_tuples::subject_id.eq(&tuple.subject.object.id))
+        .filter(relation_tuples::deleted_revision.is_null())
+        .select(relation_tuples::id)
+        .into_boxed();
+
+    let query = match &tuple.subject.relation {
+        Some(relation) => query.filter(relation_tuples::subject_relation.eq(relation)),
+        None => query.filter(relation_tuples::subject_relation.is_null()),
+    };
+    query.first(conn).optional()
+}
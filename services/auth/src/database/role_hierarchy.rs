@@ -0,0 +1,274 @@
+use anyhow::Result;
+use diesel::prelude::*;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::constants::{Permissions, Roles};
+use super::schema::{permissions, role_hierarchy_version, role_permissions, roles};
+use super::Database;
+
+/// A role as the hierarchy sees it
+#[derive(Debug, Clone)]
+pub struct RoleNode {
+    pub id: Uuid,
+    pub name: String,
+    pub parent_id: Option<Uuid>,
+    /// Global permissions granted to the role itself, not inherited ones
+    pub permissions: Vec<String>,
+    /// Organization permissions granted to the role itself, held where it is a member's role
+    pub organization_permissions: Vec<String>,
+}
+
+/// Active roles with their parents and the permissions each one ends up with
+#[derive(Debug, Clone, Default)]
+pub struct RoleHierarchy {
+    nodes: HashMap<Uuid, RoleNode>,
+    ids_by_name: HashMap<String, Uuid>,
+    effective: HashMap<Uuid, Vec<String>>,
+    effective_organization: HashMap<Uuid, Vec<String>>,
+}
+
+impl RoleHierarchy {
+    pub fn new(nodes: Vec<RoleNode>) -> Self {
+        let mut hierarchy = RoleHierarchy {
+            ids_by_name: nodes
+                .iter()
+                .map(|node| (node.name.clone(), node.id))
+                .collect(),
+            nodes: nodes.into_iter().map(|node| (node.id, node)).collect(),
+            effective: HashMap::new(),
+            effective_organization: HashMap::new(),
+        };
+
+        hierarchy.effective = hierarchy.inherited(|node| &node.permissions);
+        hierarchy.effective_organization =
+            hierarchy.inherited(|node| &node.organization_permissions);
+
+        hierarchy
+    }
+
+    /// For every role, the sorted union of a grant list over the role and its ancestors
+    fn inherited(&self, grants: impl Fn(&RoleNode) -> &Vec<String>) -> HashMap<Uuid, Vec<String>> {
+        self.nodes
+            .keys()
+            .map(|id| {
+                let permissions: BTreeSet<String> = self
+                    .lineage(*id)
+                    .into_iter()
+                    .flat_map(|node| grants(node).iter().cloned())
+                    .collect();
+                (*id, permissions.into_iter().collect())
+            })
+            .collect()
+    }
+
+    /// The role followed by its parent, grandparent and so on. A parent that is
+    /// missing (e.g. deleted) or already visited ends the walk, so a cycle that
+    /// slipped into the table can't loop forever.
+    fn lineage(&self, id: Uuid) -> Vec<&RoleNode> {
+        let mut seen = HashSet::new();
+        let mut lineage = Vec::new();
+        let mut next = Some(id);
+
+        while let Some(id) = next {
+            let Some(node) = self.nodes.get(&id) else {
+                break;
+            };
+            if !seen.insert(id) {
+                break;
+            }
+            lineage.push(node);
+            next = node.parent_id;
+        }
+
+        lineage
+    }
+
+    pub fn get(&self, role: &str) -> Option<&RoleNode> {
+        self.ids_by_name.get(role).and_then(|id| self.nodes.get(id))
+    }
+
+    pub fn get_by_id(&self, id: Uuid) -> Option<&RoleNode> {
+        self.nodes.get(&id)
+    }
+
+    /// Names of the role and every role it inherits from, nearest first
+    pub fn ancestors(&self, role: &str) -> Vec<&str> {
+        self.ids_by_name
+            .get(role)
+            .map(|id| {
+                self.lineage(*id)
+                    .into_iter()
+                    .map(|node| node.name.as_str())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Whether `role` is `ancestor` or inherits from it, e.g. `super_admin` inherits `admin`
+    pub fn inherits(&self, role: &str, ancestor: &str) -> bool {
+        self.ancestors(role).contains(&ancestor)
+    }
+
+    /// Global permissions of the role and all its ancestors, sorted; empty for unknown roles
+    pub fn effective_permissions(&self, role: &str) -> &[String] {
+        self.ids_by_name
+            .get(role)
+            .and_then(|id| self.effective.get(id))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Organization permissions of the role and all its ancestors, sorted; what the role
+    /// gives a member inside an organization
+    pub fn effective_organization_permissions(&self, role: &str) -> &[String] {
+        self.ids_by_name
+            .get(role)
+            .and_then(|id| self.effective_organization.get(id))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn has_permission(&self, role: &str, permission: &str) -> bool {
+        self.effective_permissions(role)
+            .iter()
+            .any(|granted| granted == permission)
+    }
+
+    /// Whether holding the role gives access to the admin API
+    pub fn grants_admin_access(&self, role: &str) -> bool {
+        self.inherits(role, Roles::ADMIN) || !self.effective_permissions(role).is_empty()
+    }
+
+    /// Whether making `parent_id` the parent of `role_id` would close a loop
+    pub fn would_create_cycle(&self, role_id: Uuid, parent_id: Uuid) -> bool {
+        role_id == parent_id
+            || self
+                .lineage(parent_id)
+                .iter()
+                .any(|node| node.id == role_id)
+    }
+}
+
+/// Role hierarchy kept in memory between requests, with the `role_hierarchy_version` it was loaded at
+#[derive(Default)]
+pub(super) struct RoleHierarchyCache {
+    cached: std::sync::RwLock<Option<(i64, Arc<RoleHierarchy>)>>,
+}
+
+impl RoleHierarchyCache {
+    /// The cached hierarchy if it was loaded at `version`
+    fn fresh(&self, version: i64) -> Option<Arc<RoleHierarchy>> {
+        let cached = self.cached.read().unwrap_or_else(|e| e.into_inner());
+
+        cached
+            .as_ref()
+            .filter(|(loaded_at, _)| *loaded_at == version)
+            .map(|(_, hierarchy)| hierarchy.clone())
+    }
+
+    /// Whether something is cached from a version other than `version`
+    fn stale(&self, version: i64) -> bool {
+        let cached = self.cached.read().unwrap_or_else(|e| e.into_inner());
+
+        cached
+            .as_ref()
+            .is_some_and(|(loaded_at, _)| *loaded_at != version)
+    }
+
+    fn store(&self, version: i64, hierarchy: Arc<RoleHierarchy>) {
+        *self.cached.write().unwrap_or_else(|e| e.into_inner()) = Some((version, hierarchy));
+    }
+
+    fn clear(&self) {
+        *self.cached.write().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+impl Database {
+    // ========================================
+    // Role Hierarchy Operations
+    // ========================================
+
+    /// The current role hierarchy, from memory unless any instance has changed it since
+    ///
+    /// Costs one primary key lookup of `role_hierarchy_version`, which triggers on
+    /// `roles`, `role_permissions` and `permissions` bump on every change.
+    pub fn role_hierarchy(&self) -> Result<Arc<RoleHierarchy>> {
+        let mut conn = self.get_connection()?;
+
+        // Read the version before the roles: a change committed in between only
+        // makes the next call reload again, never keeps stale roles under a newer version
+        let version = role_hierarchy_version::table
+            .select(role_hierarchy_version::version)
+            .first::<i64>(&mut conn)
+            .optional()?
+            .unwrap_or(0);
+        if let Some(hierarchy) = self.role_hierarchy_cache.fresh(version) {
+            return Ok(hierarchy);
+        }
+        if self.role_hierarchy_cache.stale(version) {
+            // Changed elsewhere; decisions made from the old roles are stale too
+            self.invalidate_authz_decisions();
+        }
+
+        let hierarchy = Arc::new(Self::load_role_hierarchy_in(&mut conn)?);
+        self.role_hierarchy_cache.store(version, hierarchy.clone());
+
+        Ok(hierarchy)
+    }
+
+    /// Drop the cached hierarchy, and the decisions made from it; call after any change
+    /// to roles or their permissions
+    pub fn invalidate_role_hierarchy(&self) {
+        self.role_hierarchy_cache.clear();
+        self.invalidate_authz_decisions();
+    }
+
+    /// Whether `role` is `ancestor` or inherits from it
+    pub fn role_inherits(&self, role: &str, ancestor: &str) -> Result<bool> {
+        Ok(self.role_hierarchy()?.inherits(role, ancestor))
+    }
+
+    pub(super) fn load_role_hierarchy_in(conn: &mut PgConnection) -> QueryResult<RoleHierarchy> {
+        let roles = roles::table
+            .filter(roles::deleted_at.is_null())
+            .select((roles::id, roles::name, roles::parent_role_id))
+            .load::<(Uuid, String, Option<Uuid>)>(conn)?;
+
+        let mut granted: HashMap<Uuid, Vec<String>> = HashMap::new();
+        let mut granted_in_organizations: HashMap<Uuid, Vec<String>> = HashMap::new();
+        for (role_id, name, scope) in role_permissions::table
+            .inner_join(permissions::table)
+            .select((
+                role_permissions::role_id,
+                permissions::name,
+                permissions::scope,
+            ))
+            .load::<(Uuid, String, String)>(conn)?
+        {
+            let grants = if scope == Permissions::ORGANIZATION_SCOPE {
+                &mut granted_in_organizations
+            } else {
+                &mut granted
+            };
+            grants.entry(role_id).or_default().push(name);
+        }
+
+        Ok(RoleHierarchy::new(
+            roles
+                .into_iter()
+                .map(|(id, name, parent_id)| RoleNode {
+                    id,
+                    name,
+                    parent_id,
+                    permissions: granted.remove(&id).unwrap_or_default(),
+                    organization_permissions: granted_in_organizations
+                        .remove(&id)
+                        .unwrap_or_default(),
+                })
+                .collect(),
+        ))
+    }
+}
@@ -0,0 +1,458 @@
+use anyhow::Result;
+use chrono::Utc;
+use diesel::dsl::count_star;
+use diesel::prelude::*;
+use diesel::sql_types::BigInt;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::constants::Roles;
+use super::schema::{memberships, permissions, role_permissions, roles, user_sessions, users};
+use super::Database;
+use crate::handlers::admin::RoleAdminView;
+use crate::models::database::{NewRole, Permission, Role};
+
+/// Fields of a role to change; `None` leaves a field as it is
+#[derive(Debug, Clone, Default)]
+pub struct RoleChanges {
+    pub name: Option<String>,
+    pub description: Option<Option<String>>,
+    pub password_max_age_days: Option<Option<i32>>,
+    pub parent_role_id: Option<Option<Uuid>>,
+    pub permissions: Option<Vec<String>>,
+}
+
+/// Result of creating a role
+#[derive(Debug, Clone)]
+pub enum RoleCreation {
+    Created(Role),
+    /// Another role (possibly deleted) already uses the name
+    NameTaken,
+}
+
+/// Result of editing a role
+#[derive(Debug, Clone)]
+pub enum RoleWrite {
+    Saved(Role),
+    /// Another role (possibly deleted) already uses the name
+    NameTaken,
+    /// The new parent is the role itself or inherits from it
+    Cycle,
+    NotFound,
+}
+
+/// Result of deleting a role
+#[derive(Debug, Clone)]
+pub enum RoleDeletion {
+    Deleted(Role),
+    /// Users still hold the role, globally or in an organization
+    InUse {
+        users: i64,
+    },
+    /// Other roles inherit from the role
+    HasChildren {
+        roles: Vec<String>,
+    },
+    NotFound,
+}
+
+/// Result of moving a user to another role
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UserRoleChange {
+    Changed {
+        previous_role: String,
+        revoked_sessions: u32,
+    },
+    /// The user already had the role
+    Unchanged,
+    UserNotFound,
+}
+
+impl Database {
+    // ========================================
+    // Role Operations
+    // ========================================
+
+    /// Every permission, sorted by name
+    pub fn list_permissions(&self) -> Result<Vec<Permission>> {
+        let mut conn = self.get_connection()?;
+
+        let permissions = permissions::table
+            .order(permissions::name.asc())
+            .select(Permission::as_select())
+            .load(&mut conn)?;
+
+        Ok(permissions)
+    }
+
+    /// Active roles with their parents, permissions and how many users hold them
+    pub fn list_roles_admin(&self) -> Result<Vec<RoleAdminView>> {
+        let hierarchy = self.role_hierarchy()?;
+        let mut conn = self.get_connection()?;
+
+        let roles = roles::table
+            .filter(roles::deleted_at.is_null())
+            .order(roles::name.asc())
+            .select(Role::as_select())
+            .load(&mut conn)?;
+
+        let user_counts: HashMap<Uuid, i64> = users::table
+            .filter(users::deleted_at.is_null())
+            .group_by(users::role_id)
+            .select((users::role_id, count_star()))
+            .load::<(Uuid, i64)>(&mut conn)?
+            .into_iter()
+            .collect();
+
+        Ok(roles
+            .into_iter()
+            .map(|role| RoleAdminView {
+                id: role.id.to_string(),
+                built_in: Roles::is_built_in(&role.name),
+                parent: role
+                    .parent_role_id
+                    .and_then(|id| hierarchy.get_by_id(id))
+                    .map(|parent| parent.name.clone()),
+                permissions: {
+                    let mut permissions = hierarchy
+                        .get(&role.name)
+                        .map(|node| node.permissions.clone())
+                        .unwrap_or_default();
+                    permissions.sort();
+                    permissions
+                },
+                effective_permissions: hierarchy.effective_permissions(&role.name).to_vec(),
+                user_count: user_counts.get(&role.id).copied().unwrap_or(0),
+                name: role.name,
+                description: role.description,
+                password_max_age_days: role.password_max_age_days,
+                created_at: role.created_at.to_rfc3339(),
+                updated_at: role.updated_at.to_rfc3339(),
+            })
+            .collect())
+    }
+
+    /// Active role by ID
+    pub fn find_role_by_id(&self, role_id: Uuid) -> Result<Option<Role>> {
+        let mut conn = self.get_connection()?;
+
+        let role = roles::table
+            .filter(roles::id.eq(role_id))
+            .filter(roles::deleted_at.is_null())
+            .select(Role::as_select())
+            .first(&mut conn)
+            .optional()?;
+
+        Ok(role)
+    }
+
+    /// Active role by name
+    pub fn find_role_by_name(&self, name: &str) -> Result<Option<Role>> {
+        let mut conn = self.get_connection()?;
+
+        let role = roles::table
+            .filter(roles::name.eq(name))
+            .filter(roles::deleted_at.is_null())
+            .select(Role::as_select())
+            .first(&mut conn)
+            .optional()?;
+
+        Ok(role)
+    }
+
+    /// Permissions granted to a role, sorted by name
+    pub fn get_role_permissions(&self, role_id: Uuid) -> Result<Vec<String>> {
+        let mut conn = self.get_connection()?;
+
+        let names = role_permissions::table
+            .inner_join(permissions::table)
+            .filter(role_permissions::role_id.eq(role_id))
+            .order(permissions::name.asc())
+            .select(permissions::name)
+            .load::<String>(&mut conn)?;
+
+        Ok(names)
+    }
+
+    /// Names in `requested` that aren't known permissions, of `scope` if given
+    pub fn unknown_permissions(
+        &self,
+        requested: &[String],
+        scope: Option<&str>,
+    ) -> Result<Vec<String>> {
+        let mut conn = self.get_connection()?;
+
+        let mut query = permissions::table
+            .filter(permissions::name.eq_any(requested))
+            .select(permissions::name)
+            .into_boxed();
+        if let Some(scope) = scope {
+            query = query.filter(permissions::scope.eq(scope.to_string()));
+        }
+        let known: Vec<String> = query.load(&mut conn)?;
+
+        Ok(requested
+            .iter()
+            .filter(|name| !known.contains(name))
+            .cloned()
+            .collect())
+    }
+
+    /// Create a role with its permissions
+    pub fn create_role(&self, role: &NewRole, permissions: &[String]) -> Result<RoleCreation> {
+        let mut conn = self.get_connection()?;
+
+        let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            if Self::role_name_taken_in(conn, &role.name, None)? {
+                return Ok(RoleCreation::NameTaken);
+            }
+
+            let created = diesel::insert_into(roles::table)
+                .values(role)
+                .returning(Role::as_returning())
+                .get_result(conn)?;
+            Self::set_role_permissions_in(conn, created.id, permissions)?;
+
+            Ok(RoleCreation::Created(created))
+        })?;
+        self.invalidate_role_hierarchy();
+
+        Ok(result)
+    }
+
+    /// Apply changes to an active role
+    pub fn update_role(&self, role_id: Uuid, changes: &RoleChanges) -> Result<RoleWrite> {
+        let mut conn = self.get_connection()?;
+
+        let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let exists = roles::table
+                .filter(roles::id.eq(role_id))
+                .filter(roles::deleted_at.is_null())
+                .select(roles::id)
+                .for_update()
+                .first::<Uuid>(conn)
+                .optional()?;
+            if exists.is_none() {
+                return Ok(RoleWrite::NotFound);
+            }
+
+            if let Some(parent_id) = changes.parent_role_id {
+                // One parent change at a time, so two changes can't close a loop together
+                diesel::sql_query("SELECT pg_advisory_xact_lock($1)")
+                    .bind::<BigInt, _>(Roles::HIERARCHY_LOCK_KEY)
+                    .execute(conn)?;
+
+                if let Some(parent_id) = parent_id {
+                    let hierarchy = Self::load_role_hierarchy_in(conn)?;
+                    if hierarchy.get_by_id(parent_id).is_none() {
+                        return Ok(RoleWrite::NotFound);
+                    }
+                    if hierarchy.would_create_cycle(role_id, parent_id) {
+                        return Ok(RoleWrite::Cycle);
+                    }
+                }
+                diesel::update(roles::table.filter(roles::id.eq(role_id)))
+                    .set(roles::parent_role_id.eq(parent_id))
+                    .execute(conn)?;
+            }
+
+            if let Some(name) = &changes.name {
+                if Self::role_name_taken_in(conn, name, Some(role_id))? {
+                    return Ok(RoleWrite::NameTaken);
+                }
+                diesel::update(roles::table.filter(roles::id.eq(role_id)))
+                    .set(roles::name.eq(name))
+                    .execute(conn)?;
+            }
+            if let Some(description) = &changes.description {
+                diesel::update(roles::table.filter(roles::id.eq(role_id)))
+                    .set(roles::description.eq(description))
+                    .execute(conn)?;
+            }
+            if let Some(max_age) = changes.password_max_age_days {
+                diesel::update(roles::table.filter(roles::id.eq(role_id)))
+                    .set(roles::password_max_age_days.eq(max_age))
+                    .execute(conn)?;
+            }
+            if let Some(permissions) = &changes.permissions {
+                Self::set_role_permissions_in(conn, role_id, permissions)?;
+            }
+
+            let updated = diesel::update(roles::table.filter(roles::id.eq(role_id)))
+                .set(roles::updated_at.eq(Utc::now()))
+                .returning(Role::as_returning())
+                .get_result(conn)?;
+
+            Ok(RoleWrite::Saved(updated))
+        })?;
+        self.invalidate_role_hierarchy();
+
+        Ok(result)
+    }
+
+    /// Soft delete a role nobody holds; its permission grants are removed
+    pub fn delete_role(&self, role_id: Uuid) -> Result<RoleDeletion> {
+        let mut conn = self.get_connection()?;
+
+        let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let role = roles::table
+                .filter(roles::id.eq(role_id))
+                .filter(roles::deleted_at.is_null())
+                .select(Role::as_select())
+                .for_update()
+                .first(conn)
+                .optional()?;
+            let Some(role) = role else {
+                return Ok(RoleDeletion::NotFound);
+            };
+
+            diesel::sql_query("SELECT pg_advisory_xact_lock($1)")
+                .bind::<BigInt, _>(Roles::HIERARCHY_LOCK_KEY)
+                .execute(conn)?;
+            let children = roles::table
+                .filter(roles::parent_role_id.eq(role_id))
+                .filter(roles::deleted_at.is_null())
+                .order(roles::name.asc())
+                .select(roles::name)
+                .load::<String>(conn)?;
+            if !children.is_empty() {
+                return Ok(RoleDeletion::HasChildren { roles: children });
+            }
+
+            let holders = users::table
+                .filter(users::role_id.eq(role_id))
+                .filter(users::deleted_at.is_null())
+                .count()
+                .get_result::<i64>(conn)?
+                + memberships::table
+                    .filter(memberships::role_id.eq(role_id))
+                    .count()
+                    .get_result::<i64>(conn)?;
+            if holders > 0 {
+                return Ok(RoleDeletion::InUse { users: holders });
+            }
+
+            diesel::delete(role_permissions::table.filter(role_permissions::role_id.eq(role_id)))
+                .execute(conn)?;
+            let now = Utc::now();
+            let deleted = diesel::update(roles::table.filter(roles::id.eq(role.id)))
+                .set((roles::deleted_at.eq(Some(now)), roles::updated_at.eq(now)))
+                .returning(Role::as_returning())
+                .get_result(conn)?;
+
+            Ok(RoleDeletion::Deleted(deleted))
+        })?;
+        self.invalidate_role_hierarchy();
+
+        Ok(result)
+    }
+
+    /// Move a user to another role, revoking their sessions so new tokens carry the new permissions
+    pub fn change_user_role(
+        &self,
+        user_id: Uuid,
+        role_id: Uuid,
+        admin_id: Uuid,
+        reason: Option<&str>,
+    ) -> Result<UserRoleChange> {
+        let mut conn = self.get_connection()?;
+
+        tracing::info!(
+            "Admin {} moving user {} to role {}: {:?}",
+            admin_id,
+            user_id,
+            role_id,
+            reason
+        );
+
+        let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let previous = users::table
+                .inner_join(roles::table.on(users::role_id.eq(roles::id)))
+                .filter(users::id.eq(user_id))
+                .filter(users::deleted_at.is_null())
+                .select((users::role_id, roles::name))
+                .for_update()
+                .first::<(Uuid, String)>(conn)
+                .optional()?;
+            let Some((previous_id, previous_role)) = previous else {
+                return Ok(UserRoleChange::UserNotFound);
+            };
+            if previous_id == role_id {
+                return Ok(UserRoleChange::Unchanged);
+            }
+
+            let now = Utc::now();
+            diesel::update(users::table.filter(users::id.eq(user_id)))
+                .set((users::role_id.eq(role_id), users::updated_at.eq(now)))
+                .execute(conn)?;
+
+            let revoked = diesel::update(
+                user_sessions::table
+                    .filter(user_sessions::user_id.eq(user_id))
+                    .filter(user_sessions::revoked_at.is_null()),
+            )
+            .set((
+                user_sessions::revoked_at.eq(Some(now)),
+                user_sessions::revoked_reason.eq(Some("role_changed")),
+            ))
+            .execute(conn)?;
+
+            Ok(UserRoleChange::Changed {
+                previous_role,
+                revoked_sessions: revoked as u32,
+            })
+        })?;
+        self.invalidate_authz_decisions();
+
+        Ok(result)
+    }
+
+    /// Whether a role other than `except` (deleted ones included) uses the name
+    fn role_name_taken_in(
+        conn: &mut PgConnection,
+        name: &str,
+        except: Option<Uuid>,
+    ) -> QueryResult<bool> {
+        let mut query = roles::table.filter(roles::name.eq(name)).into_boxed();
+        if let Some(except) = except {
+            query = query.filter(roles::id.ne(except));
+        }
+
+        Ok(query
+            .select(roles::id)
+            .first::<Uuid>(conn)
+            .optional()?
+            .is_some())
+    }
+
+    /// Replace a role's permission grants with the named permissions
+    fn set_role_permissions_in(
+        conn: &mut PgConnection,
+        role_id: Uuid,
+        names: &[String],
+    ) -> QueryResult<()> {
+        diesel::delete(role_permissions::table.filter(role_permissions::role_id.eq(role_id)))
+            .execute(conn)?;
+
+        let permission_ids: Vec<Uuid> = permissions::table
+            .filter(permissions::name.eq_any(names))
+            .select(permissions::id)
+            .load(conn)?;
+        let grants: Vec<_> = permission_ids
+            .into_iter()
+            .map(|permission_id| {
+                (
+                    role_permissions::role_id.eq(role_id),
+                    role_permissions::permission_id.eq(permission_id),
+                )
+            })
+            .collect();
+        if !grants.is_empty() {
+            diesel::insert_into(role_permissions::table)
+                .values(&grants)
+                .execute(conn)?;
+        }
+
+        Ok(())
+    }
+}
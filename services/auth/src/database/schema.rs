@@ -1,5 +1,16 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    audit_checkpoints (id) {
+        id -> Uuid,
+        sequence -> Int8,
+        entry_hash -> Varchar,
+        signature -> Varchar,
+        created_at -> Timestamptz,
+        stream -> Int2,
+    }
+}
+
 diesel::table! {
     auth_users (id) {
         id -> Uuid,
@@ -11,6 +22,196 @@ diesel::table! {
         login_failure_count -> Int4,
         is_login_locked -> Bool,
         deleted_at -> Nullable<Timestamptz>,
+        password_reset_required -> Bool,
+        password_changed_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    email_change_requests (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        old_email -> Varchar,
+        new_email -> Varchar,
+        token_hash -> Varchar,
+        cancel_token_hash -> Varchar,
+        expires_at -> Timestamptz,
+        cancel_expires_at -> Timestamptz,
+        confirmed_at -> Nullable<Timestamptz>,
+        cancelled_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    email_outbox (id) {
+        id -> Uuid,
+        to_address -> Varchar,
+        subject -> Text,
+        body -> Text,
+        status -> Varchar,
+        attempts -> Int4,
+        next_attempt_at -> Timestamptz,
+        last_error -> Nullable<Text>,
+        created_at -> Timestamptz,
+        sent_at -> Nullable<Timestamptz>,
+        secret -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    email_verification_tokens (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        token_hash -> Varchar,
+        expires_at -> Timestamptz,
+        used_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    group_members (group_id, membership_id) {
+        group_id -> Uuid,
+        membership_id -> Uuid,
+        added_by -> Nullable<Uuid>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    group_permissions (group_id, permission_id) {
+        group_id -> Uuid,
+        permission_id -> Uuid,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    groups (id) {
+        id -> Uuid,
+        organization_id -> Uuid,
+        name -> Varchar,
+        description -> Nullable<Text>,
+        created_by -> Nullable<Uuid>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    member_permissions (membership_id, permission_id) {
+        membership_id -> Uuid,
+        permission_id -> Uuid,
+        granted_by -> Nullable<Uuid>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    memberships (id) {
+        id -> Uuid,
+        organization_id -> Uuid,
+        user_id -> Uuid,
+        role_id -> Uuid,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    organization_invitations (id) {
+        id -> Uuid,
+        organization_id -> Uuid,
+        email -> Varchar,
+        role_id -> Uuid,
+        token_hash -> Varchar,
+        invited_by -> Nullable<Uuid>,
+        expires_at -> Timestamptz,
+        accepted_at -> Nullable<Timestamptz>,
+        accepted_by -> Nullable<Uuid>,
+        revoked_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    organizations (id) {
+        id -> Uuid,
+        name -> Varchar,
+        slug -> Varchar,
+        created_by -> Nullable<Uuid>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        deleted_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    password_history (id) {
+        id -> Uuid,
+        auth_user_id -> Uuid,
+        password_hash -> Varchar,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    password_reset_tokens (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        token_hash -> Varchar,
+        requested_by -> Nullable<Uuid>,
+        expires_at -> Timestamptz,
+        used_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    permissions (id) {
+        id -> Uuid,
+        name -> Varchar,
+        description -> Nullable<Text>,
+        created_at -> Timestamptz,
+        scope -> Varchar,
+    }
+}
+
+diesel::table! {
+    role_hierarchy_version (id) {
+        id -> Bool,
+        version -> Int8,
+    }
+}
+
+diesel::table! {
+    relation_tuple_revision (id) {
+        id -> Bool,
+        revision -> Int8,
+    }
+}
+
+diesel::table! {
+    relation_tuples (id) {
+        id -> Int8,
+        namespace -> Varchar,
+        object_id -> Varchar,
+        relation -> Varchar,
+        subject_namespace -> Varchar,
+        subject_id -> Varchar,
+        subject_relation -> Nullable<Varchar>,
+        created_revision -> Int8,
+        deleted_revision -> Nullable<Int8>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    role_permissions (role_id, permission_id) {
+        role_id -> Uuid,
+        permission_id -> Uuid,
+        created_at -> Timestamptz,
     }
 }
 
@@ -22,6 +223,38 @@ diesel::table! {
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
         deleted_at -> Nullable<Timestamptz>,
+        password_max_age_days -> Nullable<Int4>,
+        parent_role_id -> Nullable<Uuid>,
+    }
+}
+
+diesel::table! {
+    security_events (id) {
+        id -> Uuid,
+        user_id -> Nullable<Uuid>,
+        event_type -> Varchar,
+        success -> Bool,
+        ip_address -> Nullable<Varchar>,
+        user_agent -> Nullable<Text>,
+        metadata -> Nullable<Jsonb>,
+        created_at -> Timestamptz,
+        sequence -> Nullable<Int8>,
+        prev_hash -> Nullable<Varchar>,
+        entry_hash -> Nullable<Varchar>,
+        stream -> Int2,
+    }
+}
+
+diesel::table! {
+    user_sessions (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        user_agent -> Nullable<Text>,
+        ip_address -> Nullable<Varchar>,
+        created_at -> Timestamptz,
+        revoked_at -> Nullable<Timestamptz>,
+        revoked_reason -> Nullable<Varchar>,
+        organization_id -> Nullable<Uuid>,
     }
 }
 
@@ -35,10 +268,58 @@ diesel::table! {
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
         deleted_at -> Nullable<Timestamptz>,
+        locale -> Nullable<Varchar>,
+        status -> Varchar,
+        status_reason -> Nullable<Text>,
+        suspended_until -> Nullable<Timestamptz>,
+        status_changed_at -> Nullable<Timestamptz>,
     }
 }
 
 diesel::joinable!(auth_users -> users (user_id));
+diesel::joinable!(email_change_requests -> users (user_id));
+diesel::joinable!(email_verification_tokens -> users (user_id));
+diesel::joinable!(group_members -> groups (group_id));
+diesel::joinable!(group_members -> memberships (membership_id));
+diesel::joinable!(group_permissions -> groups (group_id));
+diesel::joinable!(group_permissions -> permissions (permission_id));
+diesel::joinable!(groups -> organizations (organization_id));
+diesel::joinable!(member_permissions -> memberships (membership_id));
+diesel::joinable!(member_permissions -> permissions (permission_id));
+diesel::joinable!(memberships -> organizations (organization_id));
+diesel::joinable!(memberships -> roles (role_id));
+diesel::joinable!(memberships -> users (user_id));
+diesel::joinable!(organization_invitations -> organizations (organization_id));
+diesel::joinable!(organization_invitations -> roles (role_id));
+diesel::joinable!(password_history -> auth_users (auth_user_id));
+diesel::joinable!(user_sessions -> organizations (organization_id));
+diesel::joinable!(user_sessions -> users (user_id));
+diesel::joinable!(role_permissions -> permissions (permission_id));
+diesel::joinable!(role_permissions -> roles (role_id));
 diesel::joinable!(users -> roles (role_id));
 
-diesel::allow_tables_to_appear_in_same_query!(auth_users, roles, users);
+diesel::allow_tables_to_appear_in_same_query!(
+    audit_checkpoints,
+    auth_users,
+    email_change_requests,
+    email_outbox,
+    email_verification_tokens,
+    group_members,
+    group_permissions,
+    groups,
+    member_permissions,
+    memberships,
+    organization_invitations,
+    organizations,
+    password_history,
+    password_reset_tokens,
+    permissions,
+    relation_tuple_revision,
+    relation_tuples,
+    role_hierarchy_version,
+    role_permissions,
+    roles,
+    security_events,
+    user_sessions,
+    users,
+);
@@ -0,0 +1,465 @@
+use anyhow::Result;
+use chrono::{DateTime, SubsecRound, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::BigInt;
+use rand::Rng;
+use std::collections::{BTreeSet, HashMap};
+use std::io::Write;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use super::constants::AuditLog;
+use super::schema::{audit_checkpoints, security_events, users};
+use super::Database;
+use crate::handlers::admin::SecurityLogEntry;
+use crate::models::database::{
+    AuditCheckpoint, NewAuditCheckpoint, NewSecurityEvent, SecurityEvent,
+};
+use crate::utils::{
+    AuditAnchor, AuditChain, AuditChainReport, AuditFailure, AuditFailureKind, AuditRecord,
+    AuditVerifier, ClientInfo, ExportFormat,
+};
+
+/// Filters for reading security events; every field narrows the result
+#[derive(Debug, Clone, Default)]
+pub struct SecurityEventFilter {
+    pub event_type: Option<String>,
+    pub user_id: Option<Uuid>,
+    pub since: Option<DateTime<Utc>>, // Inclusive
+    pub until: Option<DateTime<Utc>>, // Exclusive
+}
+
+impl SecurityEventFilter {
+    /// Whether an event passes the filter, as the database query would decide
+    pub fn matches(&self, event: &SecurityEvent) -> bool {
+        self.event_type
+            .as_ref()
+            .is_none_or(|event_type| *event_type == event.event_type)
+            && self
+                .user_id
+                .is_none_or(|user_id| event.user_id == Some(user_id))
+            && self.since.is_none_or(|since| event.created_at >= since)
+            && self.until.is_none_or(|until| event.created_at < until)
+    }
+}
+
+impl Database {
+    /// Log security event, appending it to the audit chain and publishing it to live streams
+    pub fn log_security_event(
+        &self,
+        user_id: Option<Uuid>,
+        event_type: &str,
+        metadata: Option<serde_json::Value>,
+        success: bool,
+        client: Option<&ClientInfo>,
+    ) -> Result<()> {
+        let ip_address = client.and_then(|client| client.ip_address.clone());
+        let user_agent = client.and_then(|client| client.user_agent.clone());
+
+        tracing::info!(
+            "Security event: user_id={:?}, event_type={}, success={}, ip={:?}",
+            user_id,
+            event_type,
+            success,
+            ip_address
+        );
+
+        let mut conn = self.get_connection()?;
+        let stream = rand::thread_rng().gen_range(0..AuditLog::CHAIN_STREAMS);
+
+        let (event, checkpoint) = conn
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                let (sequence, prev_hash) = match Self::lock_audit_stream(conn, stream)? {
+                    Some((sequence, entry_hash)) => (sequence + 1, entry_hash),
+                    None => (1, AuditChain::GENESIS_HASH.to_string()),
+                };
+
+                let mut event = NewSecurityEvent {
+                    id: Uuid::new_v4(),
+                    user_id,
+                    event_type: event_type.to_string(),
+                    success,
+                    ip_address,
+                    user_agent,
+                    metadata,
+                    created_at: Utc::now().trunc_subsecs(6),
+                    sequence: Some(sequence),
+                    prev_hash: None,
+                    entry_hash: None,
+                    stream,
+                };
+                let entry_hash = AuditChain::entry_hash(
+                    &prev_hash,
+                    &AuditRecord {
+                        stream,
+                        sequence,
+                        id: event.id,
+                        user_id: event.user_id,
+                        event_type: &event.event_type,
+                        success: event.success,
+                        ip_address: event.ip_address.as_deref(),
+                        user_agent: event.user_agent.as_deref(),
+                        metadata: event.metadata.as_ref(),
+                        created_at: event.created_at,
+                    },
+                );
+                event.prev_hash = Some(prev_hash);
+                event.entry_hash = Some(entry_hash.clone());
+
+                diesel::insert_into(security_events::table)
+                    .values(&event)
+                    .execute(conn)?;
+
+                let checkpoint = if sequence % AuditLog::CHECKPOINT_INTERVAL == 0 {
+                    Self::insert_audit_checkpoint_in(conn, stream, sequence, &entry_hash)?
+                } else {
+                    None
+                };
+
+                Ok((event, checkpoint))
+            })
+            .inspect_err(|e| tracing::error!("Could not record security event: {}", e))?;
+
+        if let Some(checkpoint) = checkpoint {
+            Self::anchor_audit_checkpoint(&checkpoint);
+        }
+
+        // Only committed events are published; sending fails harmlessly with no subscribers
+        let _ = self.security_event_feed.send(SecurityEvent {
+            id: event.id,
+            user_id: event.user_id,
+            event_type: event.event_type,
+            success: event.success,
+            ip_address: event.ip_address,
+            user_agent: event.user_agent,
+            metadata: event.metadata,
+            created_at: event.created_at,
+            sequence: event.sequence,
+            prev_hash: event.prev_hash,
+            entry_hash: event.entry_hash,
+            stream: event.stream,
+        });
+
+        Ok(())
+    }
+
+    /// Receive security events as they are recorded from now on
+    ///
+    /// The channel keeps the last `AuditLog::STREAM_BUFFER_SIZE` events; a receiver
+    /// that falls further behind gets `RecvError::Lagged` and resumes at the oldest kept.
+    pub fn subscribe_security_events(&self) -> broadcast::Receiver<SecurityEvent> {
+        self.security_event_feed.subscribe()
+    }
+
+    /// Take a stream's append lock for the rest of the transaction and read its head
+    ///
+    /// One writer per stream at a time, so every entry links to the true head of its stream.
+    fn lock_audit_stream(
+        conn: &mut PgConnection,
+        stream: i16,
+    ) -> QueryResult<Option<(i64, String)>> {
+        diesel::sql_query("SELECT pg_advisory_xact_lock($1)")
+            .bind::<BigInt, _>(AuditLog::CHAIN_LOCK_KEY + i64::from(stream))
+            .execute(conn)?;
+
+        let head = security_events::table
+            .filter(security_events::stream.eq(stream))
+            .filter(security_events::sequence.is_not_null())
+            .order(security_events::sequence.desc())
+            .select((security_events::sequence, security_events::entry_hash))
+            .first::<(Option<i64>, Option<String>)>(conn)
+            .optional()?;
+
+        Ok(match head {
+            Some((Some(sequence), Some(entry_hash))) => Some((sequence, entry_hash)),
+            _ => None,
+        })
+    }
+
+    /// Sign and store a checkpoint for a chain position (skipped without a signing key,
+    /// which is reported once at startup)
+    fn insert_audit_checkpoint_in(
+        conn: &mut PgConnection,
+        stream: i16,
+        sequence: i64,
+        entry_hash: &str,
+    ) -> QueryResult<Option<AuditCheckpoint>> {
+        let Some(key) = AuditChain::signing_key() else {
+            tracing::debug!("No AUDIT_SIGNING_KEY set; audit checkpoint skipped");
+            return Ok(None);
+        };
+
+        diesel::insert_into(audit_checkpoints::table)
+            .values(&NewAuditCheckpoint {
+                stream,
+                sequence,
+                entry_hash: entry_hash.to_string(),
+                signature: AuditChain::sign_checkpoint(&key, stream, sequence, entry_hash),
+            })
+            .on_conflict((audit_checkpoints::stream, audit_checkpoints::sequence))
+            .do_nothing()
+            .returning(AuditCheckpoint::as_returning())
+            .get_result(conn)
+            .optional()
+    }
+
+    /// Copy a committed checkpoint to the anchor file, if one is configured
+    fn anchor_audit_checkpoint(checkpoint: &AuditCheckpoint) {
+        let Some(path) = AuditChain::anchor_file() else {
+            return;
+        };
+        if let Err(e) = AuditChain::append_anchor(&path, &AuditAnchor::from(checkpoint)) {
+            tracing::error!(
+                "Could not anchor audit checkpoint {}:{} to {}: {}",
+                checkpoint.stream,
+                checkpoint.sequence,
+                path.display(),
+                e
+            );
+        }
+    }
+
+    /// Sign every stream's current head now (e.g. before an export), skipping heads already signed
+    pub fn create_audit_checkpoint(&self) -> Result<Vec<AuditCheckpoint>> {
+        let mut conn = self.get_connection()?;
+
+        let mut checkpoints = Vec::new();
+        for stream in 0..AuditLog::CHAIN_STREAMS {
+            let checkpoint = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                match Self::lock_audit_stream(conn, stream)? {
+                    Some((sequence, entry_hash)) => {
+                        Self::insert_audit_checkpoint_in(conn, stream, sequence, &entry_hash)
+                    }
+                    None => Ok(None),
+                }
+            })?;
+            if let Some(checkpoint) = checkpoint {
+                Self::anchor_audit_checkpoint(&checkpoint);
+                checkpoints.push(checkpoint);
+            }
+        }
+
+        Ok(checkpoints)
+    }
+
+    /// Walk every audit stream and report the first gap, edit, bad checkpoint or missing tail
+    ///
+    /// Heads are compared against the anchor file when `AUDIT_ANCHOR_FILE` is set;
+    /// without it, deleting a stream's tail together with its checkpoints goes unnoticed.
+    pub fn verify_audit_chain(&self) -> Result<AuditChainReport> {
+        let key = AuditChain::signing_key()
+            .ok_or_else(|| anyhow::anyhow!("AUDIT_SIGNING_KEY must be set"))?;
+        let anchor_file = AuditChain::anchor_file();
+        let anchors = match &anchor_file {
+            Some(path) => AuditChain::read_anchors(path)?,
+            None => {
+                tracing::warn!("No AUDIT_ANCHOR_FILE set; audit chain heads are not anchored");
+                Vec::new()
+            }
+        };
+        let mut conn = self.get_connection()?;
+
+        let checkpoints = audit_checkpoints::table
+            .select(AuditCheckpoint::as_select())
+            .load(&mut conn)?;
+
+        // Streams that were ever checkpointed are walked even if they are gone from the table
+        let streams: BTreeSet<i16> = (0..AuditLog::CHAIN_STREAMS)
+            .chain(checkpoints.iter().map(|checkpoint| checkpoint.stream))
+            .chain(anchors.iter().map(|anchor| anchor.stream))
+            .collect();
+
+        let mut reports = Vec::with_capacity(streams.len());
+        for stream in streams {
+            let mut verifier =
+                AuditVerifier::new(&key, stream, checkpoints.clone(), anchors.clone());
+
+            // Chained entries in order, one batch at a time
+            let mut after = 0;
+            loop {
+                let batch = security_events::table
+                    .filter(security_events::stream.eq(stream))
+                    .filter(security_events::sequence.gt(after))
+                    .order(security_events::sequence.asc())
+                    .limit(AuditLog::VERIFY_BATCH_SIZE)
+                    .select(SecurityEvent::as_select())
+                    .load(&mut conn)?;
+
+                let Some(last) = batch.last().and_then(|event| event.sequence) else {
+                    break;
+                };
+                for event in &batch {
+                    if !verifier.push(event) {
+                        break;
+                    }
+                }
+                if verifier.failed() || (batch.len() as i64) < AuditLog::VERIFY_BATCH_SIZE {
+                    break;
+                }
+                after = last;
+            }
+
+            reports.push(verifier.finish());
+        }
+
+        // Unchained rows are only legitimate if they predate the chain
+        let mut unchained_failure = None;
+        let chain_start = security_events::table
+            .filter(security_events::sequence.eq(1))
+            .select(diesel::dsl::min(security_events::created_at))
+            .first::<Option<DateTime<Utc>>>(&mut conn)?;
+        if let Some(chain_start) = chain_start {
+            let unchained = security_events::table
+                .filter(security_events::sequence.is_null())
+                .filter(security_events::created_at.ge(chain_start))
+                .order(security_events::created_at.asc())
+                .select(security_events::id)
+                .first::<Uuid>(&mut conn)
+                .optional()?;
+            if let Some(event_id) = unchained {
+                unchained_failure = Some(AuditFailure {
+                    kind: AuditFailureKind::Unchained,
+                    stream: None,
+                    sequence: None,
+                    event_id: Some(event_id),
+                    detail: "entry without a chain position was added after chaining began"
+                        .to_string(),
+                });
+            }
+        }
+
+        Ok(AuditChainReport::new(
+            reports,
+            anchor_file.is_some(),
+            unchained_failure,
+        ))
+    }
+
+    /// Security events matching the filter, newest first
+    fn filtered_security_events(
+        filter: &SecurityEventFilter,
+    ) -> security_events::BoxedQuery<'static, diesel::pg::Pg> {
+        let mut query = security_events::table.into_boxed();
+
+        if let Some(event_type) = &filter.event_type {
+            query = query.filter(security_events::event_type.eq(event_type.clone()));
+        }
+        if let Some(user_id) = filter.user_id {
+            query = query.filter(security_events::user_id.eq(user_id));
+        }
+        if let Some(since) = filter.since {
+            query = query.filter(security_events::created_at.ge(since));
+        }
+        if let Some(until) = filter.until {
+            query = query.filter(security_events::created_at.lt(until));
+        }
+
+        query
+    }
+
+    /// Get security logs for admin panel, newest first, with the user's current email
+    pub fn get_security_logs(
+        &self,
+        filter: &SecurityEventFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<SecurityLogEntry>> {
+        let mut conn = self.get_connection()?;
+
+        let events = Self::filtered_security_events(filter)
+            .order((
+                security_events::created_at.desc(),
+                security_events::id.desc(),
+            ))
+            .limit(limit)
+            .offset(offset)
+            .select(SecurityEvent::as_select())
+            .load(&mut conn)?;
+
+        let user_ids: Vec<Uuid> = events.iter().filter_map(|event| event.user_id).collect();
+        let emails: HashMap<Uuid, String> = users::table
+            .filter(users::id.eq_any(user_ids))
+            .select((users::id, users::email))
+            .load::<(Uuid, String)>(&mut conn)?
+            .into_iter()
+            .collect();
+
+        Ok(events
+            .into_iter()
+            .map(|event| SecurityLogEntry {
+                id: event.id.to_string(),
+                user_id: event.user_id.map(|id| id.to_string()),
+                user_email: event.user_id.and_then(|id| emails.get(&id).cloned()),
+                event_type: event.event_type,
+                ip_address: event.ip_address,
+                user_agent: event.user_agent,
+                success: event.success,
+                metadata: event.metadata,
+                created_at: event.created_at.to_rfc3339(),
+            })
+            .collect())
+    }
+
+    /// Write security events matching the filter to `out`, oldest first, one batch at a time
+    ///
+    /// Pages by `(created_at, id)` so memory stays flat however large the range;
+    /// `out` is flushed after every batch. Returns the number of events written.
+    pub fn export_security_events(
+        &self,
+        filter: &SecurityEventFilter,
+        format: ExportFormat,
+        out: &mut dyn Write,
+    ) -> Result<u64> {
+        let mut conn = self.get_connection()?;
+
+        if let Some(header) = format.header() {
+            out.write_all(header.as_bytes())?;
+        }
+
+        let mut exported = 0;
+        let mut after: Option<(DateTime<Utc>, Uuid)> = None;
+        loop {
+            let mut query = Self::filtered_security_events(filter);
+            if let Some((created_at, id)) = after {
+                query = query.filter(
+                    security_events::created_at
+                        .gt(created_at)
+                        .or(security_events::created_at
+                            .eq(created_at)
+                            .and(security_events::id.gt(id))),
+                );
+            }
+            let batch = query
+                .order((security_events::created_at.asc(), security_events::id.asc()))
+                .limit(AuditLog::EXPORT_BATCH_SIZE)
+                .select(SecurityEvent::as_select())
+                .load(&mut conn)?;
+
+            for event in &batch {
+                out.write_all(format.format_event(event).as_bytes())?;
+            }
+            out.flush()?;
+            exported += batch.len() as u64;
+
+            match batch.last() {
+                Some(last) if batch.len() as i64 == AuditLog::EXPORT_BATCH_SIZE => {
+                    after = Some((last.created_at, last.id));
+                }
+                _ => break,
+            }
+        }
+
+        Ok(exported)
+    }
+
+    /// Count security logs matching the same filter as the list
+    pub fn count_security_logs(&self, filter: &SecurityEventFilter) -> Result<i64> {
+        let mut conn = self.get_connection()?;
+
+        let total = Self::filtered_security_events(filter)
+            .count()
+            .get_result::<i64>(&mut conn)?;
+
+        Ok(total)
+    }
+}
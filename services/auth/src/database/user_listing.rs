@@ -0,0 +1,428 @@
+use anyhow::Result;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use diesel::dsl::{Eq, InnerJoinOn, IntoBoxed};
+use diesel::pg::Pg;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::str::FromStr;
+use uuid::Uuid;
+
+use super::schema::{auth_users, roles, users};
+use super::Database;
+use crate::handlers::admin::UserAdminView;
+use crate::models::database::AccountStatus;
+
+/// Column the admin user list is sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UserSortField {
+    Email,
+    Name,
+    Role,
+    Status,
+    SuspendedUntil,
+    EmailVerified,
+    LoginFailureCount,
+    LastLoginAt,
+    CreatedAt,
+}
+
+impl UserSortField {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UserSortField::Email => "email",
+            UserSortField::Name => "name",
+            UserSortField::Role => "role",
+            UserSortField::Status => "status",
+            UserSortField::SuspendedUntil => "suspended_until",
+            UserSortField::EmailVerified => "email_verified",
+            UserSortField::LoginFailureCount => "login_failure_count",
+            UserSortField::LastLoginAt => "last_login_at",
+            UserSortField::CreatedAt => "created_at",
+        }
+    }
+
+    /// Whether a cursor value has the type this column sorts on
+    fn accepts(self, value: &Value) -> bool {
+        fn is_time(value: &Value) -> bool {
+            serde_json::from_value::<DateTime<Utc>>(value.clone()).is_ok()
+        }
+
+        match self {
+            UserSortField::Email | UserSortField::Name | UserSortField::Role => value.is_string(),
+            UserSortField::Status => serde_json::from_value::<AccountStatus>(value.clone()).is_ok(),
+            UserSortField::EmailVerified => value.is_boolean(),
+            UserSortField::LoginFailureCount => value
+                .as_i64()
+                .is_some_and(|count| i32::try_from(count).is_ok()),
+            UserSortField::CreatedAt => is_time(value),
+            UserSortField::SuspendedUntil | UserSortField::LastLoginAt => {
+                value.is_null() || is_time(value)
+            }
+        }
+    }
+}
+
+impl FromStr for UserSortField {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "email" => Ok(UserSortField::Email),
+            "name" => Ok(UserSortField::Name),
+            "role" => Ok(UserSortField::Role),
+            "status" => Ok(UserSortField::Status),
+            "suspended_until" => Ok(UserSortField::SuspendedUntil),
+            "email_verified" => Ok(UserSortField::EmailVerified),
+            "login_failure_count" => Ok(UserSortField::LoginFailureCount),
+            "last_login_at" => Ok(UserSortField::LastLoginAt),
+            "created_at" => Ok(UserSortField::CreatedAt),
+            other => Err(format!("Unknown sort field: {}", other)),
+        }
+    }
+}
+
+/// Sort direction; NULLs always sort last
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        }
+    }
+}
+
+impl FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "asc" => Ok(SortOrder::Asc),
+            "desc" => Ok(SortOrder::Desc),
+            other => Err(format!("Unknown sort order: {}", other)),
+        }
+    }
+}
+
+/// Filters for the admin user list; the same filter drives the list and its total count
+#[derive(Debug, Clone, Default)]
+pub struct UserListFilter {
+    pub status: Option<AccountStatus>,
+    pub role: Option<String>,
+    pub search: Option<String>, // Case-insensitive substring of email or name
+}
+
+/// Opaque keyset position: the sort value and id of the last user on the previous page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserListCursor {
+    pub sort: UserSortField,
+    pub order: SortOrder,
+    pub value: Value,
+    pub id: Uuid,
+}
+
+impl UserListCursor {
+    /// Encode as a URL-safe token
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).unwrap_or_default();
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    /// Decode a token from `encode`, rejecting malformed tokens and values of the wrong type
+    pub fn decode(token: &str) -> Option<Self> {
+        let json = URL_SAFE_NO_PAD.decode(token.trim()).ok()?;
+        let cursor: Self = serde_json::from_slice(&json).ok()?;
+        cursor.sort.accepts(&cursor.value).then_some(cursor)
+    }
+}
+
+/// Which page of the admin user list to load
+#[derive(Debug, Clone)]
+pub enum UserListPage {
+    Offset(i64),
+    After(UserListCursor),
+}
+
+/// One row of the admin user list before formatting
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(check_for_backend(Pg))]
+struct UserListRow {
+    #[diesel(select_expression = users::id)]
+    id: Uuid,
+    #[diesel(select_expression = users::email)]
+    email: String,
+    #[diesel(select_expression = users::name)]
+    name: String,
+    #[diesel(select_expression = roles::name)]
+    role: String,
+    #[diesel(select_expression = users::status)]
+    status: AccountStatus,
+    #[diesel(select_expression = users::status_reason)]
+    status_reason: Option<String>,
+    #[diesel(select_expression = users::suspended_until)]
+    suspended_until: Option<DateTime<Utc>>,
+    #[diesel(select_expression = auth_users::email_verified)]
+    email_verified: bool,
+    #[diesel(select_expression = auth_users::login_failure_count)]
+    login_failure_count: i32,
+    #[diesel(select_expression = auth_users::last_login)]
+    last_login: Option<DateTime<Utc>>,
+    #[diesel(select_expression = users::created_at)]
+    created_at: DateTime<Utc>,
+}
+
+impl UserListRow {
+    fn sort_value(&self, sort: UserSortField) -> Value {
+        match sort {
+            UserSortField::Email => Value::from(self.email.as_str()),
+            UserSortField::Name => Value::from(self.name.as_str()),
+            UserSortField::Role => Value::from(self.role.as_str()),
+            UserSortField::Status => Value::from(self.status.as_str()),
+            UserSortField::SuspendedUntil => json!(self.suspended_until),
+            UserSortField::EmailVerified => Value::from(self.email_verified),
+            UserSortField::LoginFailureCount => Value::from(self.login_failure_count),
+            UserSortField::LastLoginAt => json!(self.last_login),
+            UserSortField::CreatedAt => json!(self.created_at),
+        }
+    }
+
+    fn into_view(self) -> UserAdminView {
+        UserAdminView {
+            id: self.id.to_string(),
+            email: self.email,
+            name: self.name,
+            role: self.role,
+            status: self.status,
+            status_reason: self.status_reason,
+            suspended_until: self.suspended_until.map(|t| t.to_rfc3339()),
+            email_verified: self.email_verified,
+            login_failure_count: self.login_failure_count,
+            last_login_at: self.last_login.map(|t| t.to_rfc3339()),
+            created_at: self.created_at.to_rfc3339(),
+        }
+    }
+}
+
+type UserListSource = InnerJoinOn<
+    InnerJoinOn<users::table, auth_users::table, Eq<auth_users::user_id, users::id>>,
+    roles::table,
+    Eq<users::role_id, roles::id>,
+>;
+type BoxedUserListQuery<'a> = IntoBoxed<'a, UserListSource, Pg>;
+
+/// Order by a NOT NULL column (ties broken by id) and start after the cursor's row
+macro_rules! order_by_column {
+    ($query:expr, $column:expr, $order:expr, $after:expr, $ty:ty) => {{
+        let query = $query;
+        let after = match $after {
+            Some(cursor) => Some((
+                serde_json::from_value::<$ty>(cursor.value.clone())?,
+                cursor.id,
+            )),
+            None => None,
+        };
+        match $order {
+            SortOrder::Asc => {
+                let query = query.order_by(($column.asc(), users::id.asc()));
+                match after {
+                    Some((value, id)) => query.filter(
+                        $column
+                            .gt(value.clone())
+                            .or($column.eq(value).and(users::id.gt(id))),
+                    ),
+                    None => query,
+                }
+            }
+            SortOrder::Desc => {
+                let query = query.order_by(($column.desc(), users::id.desc()));
+                match after {
+                    Some((value, id)) => query.filter(
+                        $column
+                            .lt(value.clone())
+                            .or($column.eq(value).and(users::id.lt(id))),
+                    ),
+                    None => query,
+                }
+            }
+        }
+    }};
+}
+
+/// Like `order_by_column!` for a nullable column, keeping NULLs last in both directions
+macro_rules! order_by_nullable_column {
+    ($query:expr, $column:expr, $order:expr, $after:expr, $ty:ty) => {{
+        let query = $query;
+        let after = match $after {
+            Some(cursor) => Some((
+                serde_json::from_value::<Option<$ty>>(cursor.value.clone())?,
+                cursor.id,
+            )),
+            None => None,
+        };
+        match $order {
+            SortOrder::Asc => {
+                let query = query.order_by(($column.asc().nulls_last(), users::id.asc()));
+                match after {
+                    Some((Some(value), id)) => query.filter(
+                        $column
+                            .assume_not_null()
+                            .gt(value)
+                            .or($column.assume_not_null().eq(value).and(users::id.gt(id)))
+                            .or($column.is_null()),
+                    ),
+                    Some((None, id)) => query.filter($column.is_null().and(users::id.gt(id))),
+                    None => query,
+                }
+            }
+            SortOrder::Desc => {
+                let query = query.order_by(($column.desc().nulls_last(), users::id.desc()));
+                match after {
+                    Some((Some(value), id)) => query.filter(
+                        $column
+                            .assume_not_null()
+                            .lt(value)
+                            .or($column.assume_not_null().eq(value).and(users::id.lt(id)))
+                            .or($column.is_null()),
+                    ),
+                    Some((None, id)) => query.filter($column.is_null().and(users::id.lt(id))),
+                    None => query,
+                }
+            }
+        }
+    }};
+}
+
+impl Database {
+    /// Users matching the filter, excluding deleted accounts
+    fn filtered_users(filter: &UserListFilter) -> BoxedUserListQuery<'static> {
+        let mut query = users::table
+            .inner_join(auth_users::table.on(auth_users::user_id.eq(users::id)))
+            .inner_join(roles::table.on(users::role_id.eq(roles::id)))
+            .filter(users::deleted_at.is_null())
+            .filter(auth_users::deleted_at.is_null())
+            .into_boxed();
+
+        if let Some(status) = filter.status {
+            query = query.filter(users::status.eq(status));
+        }
+        if let Some(role) = &filter.role {
+            query = query.filter(roles::name.eq(role.clone()));
+        }
+        if let Some(search) = &filter.search {
+            // Match the text literally; backslash is Postgres' default LIKE escape
+            let pattern = format!(
+                "%{}%",
+                search
+                    .replace('\\', "\\\\")
+                    .replace('%', "\\%")
+                    .replace('_', "\\_")
+            );
+            query = query.filter(
+                users::email
+                    .ilike(pattern.clone())
+                    .or(users::name.ilike(pattern)),
+            );
+        }
+
+        query
+    }
+
+    /// Get users for admin panel with filtering, sorting and offset or keyset pagination
+    ///
+    /// Returns the page and, when more users follow, the cursor for the next page.
+    pub fn get_users_admin(
+        &self,
+        filter: &UserListFilter,
+        sort: UserSortField,
+        order: SortOrder,
+        page: &UserListPage,
+        limit: i64,
+    ) -> Result<(Vec<UserAdminView>, Option<UserListCursor>)> {
+        let mut conn = self.get_connection()?;
+
+        let query = Self::filtered_users(filter);
+        let after = match page {
+            UserListPage::After(cursor) => Some(cursor),
+            UserListPage::Offset(_) => None,
+        };
+        let query = match sort {
+            UserSortField::Email => order_by_column!(query, users::email, order, after, String),
+            UserSortField::Name => order_by_column!(query, users::name, order, after, String),
+            UserSortField::Role => order_by_column!(query, roles::name, order, after, String),
+            UserSortField::Status => {
+                order_by_column!(query, users::status, order, after, AccountStatus)
+            }
+            UserSortField::SuspendedUntil => order_by_nullable_column!(
+                query,
+                users::suspended_until,
+                order,
+                after,
+                DateTime<Utc>
+            ),
+            UserSortField::EmailVerified => {
+                order_by_column!(query, auth_users::email_verified, order, after, bool)
+            }
+            UserSortField::LoginFailureCount => {
+                order_by_column!(query, auth_users::login_failure_count, order, after, i32)
+            }
+            UserSortField::LastLoginAt => order_by_nullable_column!(
+                query,
+                auth_users::last_login,
+                order,
+                after,
+                DateTime<Utc>
+            ),
+            UserSortField::CreatedAt => {
+                order_by_column!(query, users::created_at, order, after, DateTime<Utc>)
+            }
+        };
+        let query = match page {
+            UserListPage::Offset(offset) => query.offset(*offset),
+            UserListPage::After(_) => query,
+        };
+
+        // Fetch one extra row to learn whether another page follows
+        let mut rows = query
+            .limit(limit + 1)
+            .select(UserListRow::as_select())
+            .load::<UserListRow>(&mut conn)?;
+
+        let next_cursor = if rows.len() as i64 > limit {
+            rows.truncate(limit as usize);
+            rows.last().map(|row| UserListCursor {
+                sort,
+                order,
+                value: row.sort_value(sort),
+                id: row.id,
+            })
+        } else {
+            None
+        };
+
+        Ok((
+            rows.into_iter().map(UserListRow::into_view).collect(),
+            next_cursor,
+        ))
+    }
+
+    /// Count users for admin panel matching the same filter as the list
+    pub fn count_users_admin(&self, filter: &UserListFilter) -> Result<i64> {
+        let mut conn = self.get_connection()?;
+
+        let total = Self::filtered_users(filter)
+            .count()
+            .get_result::<i64>(&mut conn)?;
+
+        Ok(total)
+    }
+}
@@ -1,18 +1,37 @@
 use axum::{
-    extract::{Path, Query, State},
-    http::{HeaderMap, StatusCode},
-    response::Json,
+    async_trait,
+    body::{Body, Bytes},
+    extract::{FromRequestParts, Path, Query, State},
+    http::{header, request::Parts, HeaderMap, HeaderValue, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Json, Response,
+    },
 };
+use chrono::{DateTime, NaiveDate, Utc};
+use flate2::{write::GzEncoder, Compression};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::convert::Infallible;
+use std::io::{self, Write};
+use std::marker::PhantomData;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream, ReceiverStream};
+use tokio_stream::{Stream, StreamExt};
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::database::Database;
-use crate::models::ApiResponse;
-use crate::utils::{JwtService, PasswordService};
-use crate::{ErrorCode, ErrorMessage, Roles};
+use super::auth::send_password_reset_email;
+use crate::database::{
+    Database, RoleChanges, RoleCreation, RoleDeletion, RoleHierarchy, RoleNode, RoleWrite,
+    SecurityEventFilter, UserListCursor, UserListFilter, UserListPage, UserRoleChange,
+};
+use crate::models::database::NewRole;
+use crate::models::{AccountStatus, ApiResponse};
+use crate::utils::{ClientInfo, ExportFormat, I18nService, JwtService};
+use crate::{AuditLog, EmailOutbox, ErrorCode, ErrorMessage, Permissions, Roles, UserListing};
 
 /// Request models for admin operations
 #[derive(Debug, Deserialize, Validate)]
@@ -21,9 +40,12 @@ pub struct GetUsersQuery {
     pub page: u32,
     #[serde(default = "default_limit")]
     pub limit: u32,
-    pub status: Option<String>,
+    pub status: Option<String>, // "active", "disabled", "suspended" or "all"
     pub role: Option<String>,
     pub search: Option<String>,
+    pub sort: Option<String>, // Any `UserAdminView` column, default "created_at"
+    pub order: Option<String>, // "asc" or "desc" (default)
+    pub cursor: Option<String>, // `next_cursor` of the previous page; replaces `page`
 }
 
 fn default_page() -> u32 {
@@ -32,11 +54,27 @@ fn default_page() -> u32 {
 fn default_limit() -> u32 {
     20
 }
+/// Rows skipped before `page` (1-based), saturating rather than overflowing on huge pages
+fn page_offset(page: u32, limit: u32) -> i64 {
+    (i64::from(page.max(1)) - 1).saturating_mul(i64::from(limit))
+}
+fn default_required() -> bool {
+    true
+}
 
 #[derive(Debug, Deserialize)]
 pub struct UpdateUserStatusRequest {
     pub status: String, // "active", "disabled", "suspended"
     pub reason: Option<String>,
+    pub suspended_until: Option<DateTime<Utc>>, // Only for "suspended"; omit to suspend until lifted
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExpiredPasswordsQuery {
+    #[serde(default = "default_page")]
+    pub page: u32,
+    #[serde(default = "default_limit")]
+    pub limit: u32,
 }
 
 #[derive(Debug, Deserialize)]
@@ -47,16 +85,158 @@ pub struct SecurityLogsQuery {
     pub limit: u32,
     pub event_type: Option<String>,
     pub user_id: Option<String>,
-    pub start_date: Option<String>,
+    pub start_date: Option<String>, // YYYY-MM-DD or RFC 3339, inclusive
+    pub end_date: Option<String>,   // YYYY-MM-DD (whole day included) or RFC 3339, exclusive
+}
+
+impl SecurityLogsQuery {
+    /// Typed filter, or `None` if the user ID or a date can't be parsed
+    pub fn filter(&self) -> Option<SecurityEventFilter> {
+        security_event_filter(
+            &self.event_type,
+            &self.user_id,
+            &self.start_date,
+            &self.end_date,
+        )
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditExportQuery {
+    pub format: Option<String>, // "jsonl" (default), "csv" or "cef"
+    #[serde(default)]
+    pub gzip: bool,
+    pub event_type: Option<String>,
+    pub user_id: Option<String>,
+    pub start_date: Option<String>, // Same formats as the security log filters
     pub end_date: Option<String>,
 }
 
+impl AuditExportQuery {
+    /// Typed filter, or `None` if the user ID or a date can't be parsed
+    pub fn filter(&self) -> Option<SecurityEventFilter> {
+        security_event_filter(
+            &self.event_type,
+            &self.user_id,
+            &self.start_date,
+            &self.end_date,
+        )
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SecurityStreamQuery {
+    pub event_type: Option<String>,
+    pub user_id: Option<String>,
+}
+
+impl SecurityStreamQuery {
+    /// Typed filter, or `None` if the user ID can't be parsed
+    pub fn filter(&self) -> Option<SecurityEventFilter> {
+        security_event_filter(&self.event_type, &self.user_id, &None, &None)
+    }
+}
+
+/// Parse the security event filters shared by the log list, the export and the live stream
+fn security_event_filter(
+    event_type: &Option<String>,
+    user_id: &Option<String>,
+    start_date: &Option<String>,
+    end_date: &Option<String>,
+) -> Option<SecurityEventFilter> {
+    fn parse_date(value: &str, end_of_range: bool) -> Option<DateTime<Utc>> {
+        if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+            return Some(time.with_timezone(&Utc));
+        }
+        let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+        let date = if end_of_range { date.succ_opt()? } else { date };
+        Some(date.and_hms_opt(0, 0, 0)?.and_utc())
+    }
+    let present = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+
+    Some(SecurityEventFilter {
+        event_type: present(event_type),
+        user_id: match present(user_id) {
+            Some(user_id) => Some(user_id.parse().ok()?),
+            None => None,
+        },
+        since: match present(start_date) {
+            Some(date) => Some(parse_date(&date, false)?),
+            None => None,
+        },
+        until: match present(end_date) {
+            Some(date) => Some(parse_date(&date, true)?),
+            None => None,
+        },
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateRoleRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub password_max_age_days: Option<i32>,
+    pub parent: Option<String>, // Name of the role to inherit permissions from
+    #[serde(default)]
+    pub permissions: Vec<String>, // Only super admins may grant any
+}
+
+/// Omitted fields stay as they are; `null` clears `description`, `password_max_age_days` and `parent`
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateRoleRequest {
+    pub name: Option<String>,
+    #[serde(default, deserialize_with = "present")]
+    pub description: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub password_max_age_days: Option<Option<i32>>,
+    #[serde(default, deserialize_with = "present")]
+    pub parent: Option<Option<String>>, // Super admins only
+    pub permissions: Option<Vec<String>>, // Replaces the role's grants; super admins only
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateUserRoleRequest {
+    pub role: String, // Role name
+    pub reason: Option<String>,
+}
+
+/// Tell a field sent as `null` (`Some(None)`) apart from one left out (`None`)
+pub(crate) fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EmailOutboxQuery {
+    #[serde(default = "default_page")]
+    pub page: u32,
+    #[serde(default = "default_limit")]
+    pub limit: u32,
+    pub status: Option<String>, // "pending", "sent", "dead"
+}
+
 #[derive(Debug, Deserialize)]
 pub struct RevokeSessionsRequest {
     pub user_id: String,
     pub reason: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RequirePasswordChangeRequest {
+    #[serde(default = "default_required")]
+    pub required: bool,
+    pub reason: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct UnlockAccountRequest {
     pub user_id: Option<String>,
@@ -78,13 +258,57 @@ pub struct UserAdminView {
     pub id: String,
     pub email: String,
     pub name: String,
-    // TODO: status and roles will be added back when role management is redesigned
+    pub role: String,
+    pub status: AccountStatus,
+    pub status_reason: Option<String>,
+    pub suspended_until: Option<String>,
     pub email_verified: bool,
     pub login_failure_count: i32,
     pub last_login_at: Option<String>,
     pub created_at: String,
 }
 
+#[derive(Debug, Serialize)]
+pub struct RoleAdminView {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub password_max_age_days: Option<i32>,
+    pub parent: Option<String>,
+    pub permissions: Vec<String>,           // Granted to the role itself
+    pub effective_permissions: Vec<String>, // Including those inherited from its parents
+    pub user_count: i64,
+    pub built_in: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExpiredPasswordView {
+    pub id: String,
+    pub email: String,
+    pub name: String,
+    pub role: String,
+    pub password_max_age_days: i32,
+    pub password_changed_at: String,
+    pub password_expired_at: String,
+    pub days_overdue: i64,
+    pub last_login_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmailOutboxView {
+    pub id: String,
+    pub to_address: String,
+    pub subject: String,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: String,
+    pub last_error: Option<String>,
+    pub created_at: String,
+    pub sent_at: Option<String>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct SecurityLogEntry {
     pub id: String,
@@ -92,6 +316,7 @@ pub struct SecurityLogEntry {
     pub user_email: Option<String>,
     pub event_type: String,
     pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
     pub success: bool,
     pub metadata: Option<Value>,
     pub created_at: String,
@@ -104,11 +329,85 @@ pub struct SecurityLogsResponse {
     pub page: u32,
 }
 
-/// Helper function to extract and verify admin token
+/// A permission an admin route requires, named by a marker type
+pub trait RequiredPermission {
+    const NAME: &'static str;
+}
+
+macro_rules! required_permissions {
+    ($($marker:ident => $name:expr,)*) => {
+        $(
+            #[doc = concat!("Requires `", stringify!($name), "`")]
+            pub struct $marker;
+
+            impl RequiredPermission for $marker {
+                const NAME: &'static str = $name;
+            }
+        )*
+    };
+}
+
+required_permissions! {
+    UsersRead => Permissions::USERS_READ,
+    UsersDisable => Permissions::USERS_DISABLE,
+    UsersResetPassword => Permissions::USERS_RESET_PASSWORD,
+    UsersUnlock => Permissions::USERS_UNLOCK,
+    SessionsRevoke => Permissions::SESSIONS_REVOKE,
+    AuditRead => Permissions::AUDIT_READ,
+    AuditVerify => Permissions::AUDIT_VERIFY,
+    AuditExport => Permissions::AUDIT_EXPORT,
+    EmailOutboxRead => Permissions::EMAIL_OUTBOX_READ,
+    RolesRead => Permissions::ROLES_READ,
+    RolesManage => Permissions::ROLES_MANAGE,
+    UsersAssignRole => Permissions::USERS_ASSIGN_ROLE,
+}
+
+/// An authenticated caller whose current role grants permission `P`
+///
+/// Use as a handler argument to guard a route, e.g. `admin: AdminUser<UsersRead>`:
+/// missing or invalid tokens get 401, signed-in users without `P` get 403.
+#[derive(Debug, Clone)]
+pub struct AdminUser<P> {
+    pub user_id: Uuid,
+    pub role: String,
+    permission: PhantomData<P>,
+}
+
+#[async_trait]
+impl<P: RequiredPermission> FromRequestParts<Arc<Database>> for AdminUser<P> {
+    type Rejection = (StatusCode, Json<Value>);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        db: &Arc<Database>,
+    ) -> Result<Self, Self::Rejection> {
+        let (user_id, role) = verify_admin_token(&parts.headers, db, P::NAME).await?;
+        Ok(AdminUser {
+            user_id,
+            role,
+            permission: PhantomData,
+        })
+    }
+}
+
+/// Verify the bearer token belongs to an active session whose user currently holds `permission`
+///
+/// Returns the user ID and role name.
 async fn verify_admin_token(
     headers: &HeaderMap,
     db: &Database,
-) -> Result<Uuid, (StatusCode, Json<Value>)> {
+    permission: &str,
+) -> Result<(Uuid, String), (StatusCode, Json<Value>)> {
+    let forbidden = || {
+        (
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::error(
+                ErrorCode::INSUFFICIENT_PERMISSIONS,
+                ErrorMessage::ADMIN_PRIVILEGES_REQUIRED,
+            )),
+        )
+    };
+
     let auth_header = headers
         .get("authorization")
         .and_then(|header| header.to_str().ok())
@@ -154,15 +453,22 @@ async fn verify_admin_token(
         }
     };
 
-    // Check if user has admin role
-    let _admin_user = match db.find_user_by_id(user_id) {
-        Ok(Some(user)) => user,
-        Ok(None) => {
+    // Signed permission claims turn most callers away without a database round trip
+    if let Some(granted) = &claims.claims.permissions {
+        if !granted.iter().any(|granted| granted == permission) {
+            return Err(forbidden());
+        }
+    }
+
+    // Reject tokens whose session has been revoked
+    match db.is_token_session_active(user_id, &claims.claims) {
+        Ok(true) => {}
+        Ok(false) => {
             return Err((
                 StatusCode::UNAUTHORIZED,
                 Json(ApiResponse::error(
-                    ErrorCode::USER_NOT_FOUND,
-                    ErrorMessage::USER_DOES_NOT_EXIST,
+                    ErrorCode::TOKEN_INVALID,
+                    ErrorMessage::TOKEN_EXPIRED_OR_INVALID,
                 )),
             ));
         }
@@ -175,40 +481,43 @@ async fn verify_admin_token(
                 )),
             ));
         }
-    };
-
-    // Define admin role patterns
-    const ADMIN_ROLES: &[&str] = &[
-        Roles::ADMIN,
-        Roles::SUPER_ADMIN,
-        "user_admin",
-        "security_admin",
-    ];
-
-    // Default to admin role for admin endpoints
-    let admin_role = Roles::ADMIN.to_string();
+    }
 
-    if !ADMIN_ROLES.contains(&admin_role.as_str()) {
-        return Err((
-            StatusCode::FORBIDDEN,
+    // Claims were true when the token was issued; the current role decides
+    let role_and_permissions = db.get_user_role(user_id).and_then(|role| {
+        Ok(match role {
+            Some(role) => Some((role, db.get_user_permissions(user_id)?)),
+            None => None,
+        })
+    });
+    match role_and_permissions {
+        Ok(Some((role, granted))) if granted.iter().any(|granted| granted == permission) => {
+            Ok((user_id, role))
+        }
+        Ok(Some(_)) => Err(forbidden()),
+        Ok(None) => Err((
+            StatusCode::UNAUTHORIZED,
             Json(ApiResponse::error(
-                ErrorCode::INSUFFICIENT_PERMISSIONS,
-                ErrorMessage::ADMIN_PRIVILEGES_REQUIRED,
+                ErrorCode::USER_NOT_FOUND,
+                ErrorMessage::USER_DOES_NOT_EXIST,
             )),
-        ));
+        )),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error(
+                ErrorCode::DATABASE_ERROR,
+                ErrorMessage::USER_INFO_RETRIEVAL_FAILED,
+            )),
+        )),
     }
-
-    Ok(user_id)
 }
 
 /// Get all users with pagination and filtering
 pub async fn get_users_handler(
     State(db): State<Arc<Database>>,
+    _admin: AdminUser<UsersRead>,
     Query(params): Query<GetUsersQuery>,
-    headers: HeaderMap,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    let _admin_id = verify_admin_token(&headers, &db).await?;
-
     tracing::info!(
         "Admin get users request: page={}, limit={}, status={:?}, role={:?}, search={:?}",
         params.page,
@@ -218,76 +527,75 @@ pub async fn get_users_handler(
         params.search
     );
 
-    // Calculate offset
-    let offset = (params.page - 1) * params.limit;
-
-    // TODO: Role management will be redesigned - simplified query for now
-    let mut query = r#"
-        SELECT u.id, u.email, u.name, u.created_at,
-               au.email_verified, au.login_failure_count, au.last_login
-        FROM users u
-        LEFT JOIN auth_users au ON u.id = au.user_id
-        WHERE u.deleted_at IS NULL
-        AND (au.deleted_at IS NULL OR au.deleted_at IS NULL)
-    "#
-    .to_string();
-
-    let mut bind_params = Vec::new();
-    let mut param_count = 0;
-
-    // Add status filter
-    if let Some(status) = &params.status {
-        if status != "all" {
-            param_count += 1;
-            query.push_str(&format!(" AND u.status = ${}", param_count));
-            bind_params.push(status.clone());
-        }
-    }
+    let invalid_query = |message: &str| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(ErrorCode::VALIDATION_ERROR, message)),
+        )
+    };
 
-    // Add role filter
-    if let Some(role) = &params.role {
-        if role != "all" {
-            param_count += 1;
-            query.push_str(&format!(" AND r.name = ${}", param_count));
-            bind_params.push(role.clone());
-        }
-    }
+    // "all" (or nothing) disables a filter
+    let status = match params.status.as_deref() {
+        None | Some("all") => None,
+        Some(status) => match status.parse::<AccountStatus>() {
+            Ok(status) => Some(status),
+            Err(_) => return Err(invalid_query(ErrorMessage::ACCOUNT_STATUS_INVALID)),
+        },
+    };
+    let filter = UserListFilter {
+        status,
+        role: params.role.clone().filter(|role| role != "all"),
+        search: params
+            .search
+            .as_deref()
+            .map(str::trim)
+            .filter(|search| !search.is_empty())
+            .map(str::to_string),
+    };
 
-    // Add search filter
-    if let Some(search) = &params.search {
-        if !search.is_empty() {
-            param_count += 1;
-            query.push_str(&format!(
-                " AND (u.email ILIKE ${} OR u.name ILIKE ${})",
-                param_count, param_count
+    let sort = match params.sort.as_deref().unwrap_or("created_at").parse() {
+        Ok(sort) => sort,
+        Err(_) => {
+            return Err(invalid_query(
+                &ErrorMessage::USER_SORT_INVALID
+                    .replace("{}", params.sort.as_deref().unwrap_or_default()),
             ));
-            bind_params.push(format!("%{}%", search));
         }
-    }
-
-    // Add ordering and pagination
-    query.push_str(" ORDER BY u.created_at DESC");
-
-    param_count += 1;
-    query.push_str(&format!(" LIMIT ${}", param_count));
-    bind_params.push(params.limit.to_string());
-
-    param_count += 1;
-    query.push_str(&format!(" OFFSET ${}", param_count));
-    bind_params.push(offset.to_string());
+    };
+    let order = match params.order.as_deref().unwrap_or("desc").parse() {
+        Ok(order) => order,
+        Err(_) => return Err(invalid_query(ErrorMessage::SORT_ORDER_INVALID)),
+    };
 
-    // Execute query (Note: This is a simplified version - in real implementation you'd use sqlx with proper binding)
-    match db.get_users_admin(&params) {
-        Ok(users) => {
-            let total = db.count_users_admin(&params).unwrap_or(0);
+    // A cursor continues the listing it came from, so it must match the requested sort
+    let limit = params.limit.clamp(1, UserListing::MAX_PAGE_SIZE);
+    let page = match params.cursor.as_deref() {
+        Some(token) => match UserListCursor::decode(token) {
+            Some(cursor) if cursor.sort == sort && cursor.order == order => {
+                UserListPage::After(cursor)
+            }
+            _ => return Err(invalid_query(ErrorMessage::PAGINATION_CURSOR_INVALID)),
+        },
+        None => UserListPage::Offset(page_offset(params.page, limit)),
+    };
 
-            Ok(Json(ApiResponse::success(json!({
-                "users": users,
-                "total": total,
-                "page": params.page,
-                "limit": params.limit
-            }))))
-        }
+    let result = db
+        .get_users_admin(&filter, sort, order, &page, limit as i64)
+        .and_then(|(users, next_cursor)| Ok((users, next_cursor, db.count_users_admin(&filter)?)));
+
+    match result {
+        Ok((users, next_cursor, total)) => Ok(Json(ApiResponse::success(json!({
+            "users": users,
+            "total": total,
+            "page": match page {
+                UserListPage::Offset(_) => Some(params.page.max(1)),
+                UserListPage::After(_) => None,
+            },
+            "limit": limit,
+            "sort": sort.as_str(),
+            "order": order.as_str(),
+            "next_cursor": next_cursor.map(|cursor| cursor.encode())
+        })))),
         Err(e) => {
             tracing::error!("Database error getting users: {}", e);
             Err((
@@ -304,11 +612,12 @@ pub async fn get_users_handler(
 /// Update user status (enable/disable/suspend)
 pub async fn update_user_status_handler(
     State(db): State<Arc<Database>>,
+    admin: AdminUser<UsersDisable>,
     Path(user_id): Path<String>,
     headers: HeaderMap,
     Json(payload): Json<UpdateUserStatusRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    let admin_id = verify_admin_token(&headers, &db).await?;
+    let admin_id = admin.user_id;
 
     tracing::info!(
         "Admin update user status: user_id={}, status={}, reason={:?}",
@@ -330,8 +639,34 @@ pub async fn update_user_status_handler(
         }
     };
 
+    let status: AccountStatus = match payload.status.parse() {
+        Ok(status) => status,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error(
+                    ErrorCode::VALIDATION_ERROR,
+                    ErrorMessage::ACCOUNT_STATUS_INVALID,
+                )),
+            ));
+        }
+    };
+
+    // Only a suspension can end on its own, and not in the past
+    if let Some(until) = payload.suspended_until {
+        if status != AccountStatus::Suspended || until <= Utc::now() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error(
+                    ErrorCode::VALIDATION_ERROR,
+                    ErrorMessage::ACCOUNT_STATUS_INVALID,
+                )),
+            ));
+        }
+    }
+
     // Prevent admin from disabling themselves
-    if admin_id == target_user_id && payload.status != "active" {
+    if admin_id == target_user_id && status != AccountStatus::Active {
         return Err((
             StatusCode::BAD_REQUEST,
             Json(ApiResponse::error(
@@ -341,68 +676,80 @@ pub async fn update_user_status_handler(
         ));
     }
 
-    // Check if target user exists
-    let target_user = match db.find_user_by_id(target_user_id) {
-        Ok(Some(user)) => user,
-        Ok(None) => {
-            return Err((
-                StatusCode::NOT_FOUND,
-                Json(ApiResponse::error(
-                    ErrorCode::USER_NOT_FOUND,
-                    ErrorMessage::USER_DOES_NOT_EXIST,
-                )),
-            ));
-        }
-        Err(e) => {
-            tracing::error!("Database error finding user: {}", e);
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::error(
-                    ErrorCode::DATABASE_ERROR,
-                    ErrorMessage::USER_LOOKUP_FAILED,
-                )),
-            ));
+    // Super admins (and roles inheriting from super_admin) can't be locked out by status
+    if status != AccountStatus::Active {
+        let is_super_admin = db
+            .get_user_role(target_user_id)
+            .and_then(|role| match role {
+                Some(role) => db.role_inherits(&role, Roles::SUPER_ADMIN),
+                None => Ok(false),
+            });
+        match is_super_admin {
+            Ok(true) => {
+                return Err((
+                    StatusCode::FORBIDDEN,
+                    Json(ApiResponse::error(
+                        ErrorCode::CANNOT_DISABLE_SUPER_ADMIN,
+                        ErrorMessage::SUPER_ADMIN_STATUS_CHANGE_FORBIDDEN,
+                    )),
+                ));
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::error!("Database error getting user role: {}", e);
+                return Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::error(
+                        ErrorCode::DATABASE_ERROR,
+                        ErrorMessage::USER_LOOKUP_FAILED,
+                    )),
+                ));
+            }
         }
-    };
+    }
 
-    // Update user status
+    // Update user status (existing sessions are revoked with it)
     match db.update_user_status(
         target_user_id,
-        &payload.status,
+        status,
+        payload.suspended_until,
         admin_id,
         payload.reason.as_deref(),
     ) {
-        Ok(_) => {
-            // If disabling user, revoke all their sessions
-            if payload.status == "disabled" {
-                if let Err(e) = db.revoke_all_user_sessions(target_user_id, "account_disabled") {
-                    tracing::warn!("Failed to revoke user sessions: {}", e);
-                }
-            }
-
+        Ok(Some((old_status, revoked_sessions))) => {
             // Log the admin action
             let _ = db.log_security_event(
                 Some(admin_id),
-                &format!("admin_user_status_changed"),
+                "admin_user_status_changed",
                 Some(json!({
                     "target_user_id": target_user_id,
-                    "old_status": "active", // Would need to get this from DB in real implementation
-                    "new_status": payload.status,
-                    "reason": payload.reason
+                    "old_status": old_status,
+                    "new_status": status,
+                    "suspended_until": payload.suspended_until,
+                    "reason": payload.reason,
+                    "revoked_sessions": revoked_sessions
                 })),
                 true,
-                None,
+                Some(&ClientInfo::from_headers(&headers)),
             );
 
             Ok(Json(ApiResponse::success(json!({
-                "message": format!("User status updated to {}", payload.status),
+                "message": format!("User status updated to {}", status),
                 "user": {
-                    "id": target_user.id,
-                    "email": target_user.email,
-                    "status": payload.status
-                }
+                    "id": target_user_id,
+                    "status": status,
+                    "suspended_until": payload.suspended_until,
+                },
+                "revoked_sessions": revoked_sessions
             }))))
         }
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error(
+                ErrorCode::USER_NOT_FOUND,
+                ErrorMessage::USER_DOES_NOT_EXIST,
+            )),
+        )),
         Err(e) => {
             tracing::error!("Database error updating user status: {}", e);
             Err((
@@ -419,10 +766,11 @@ pub async fn update_user_status_handler(
 /// Reset user password (admin function)
 pub async fn reset_user_password_handler(
     State(db): State<Arc<Database>>,
+    admin: AdminUser<UsersResetPassword>,
     Path(user_id): Path<String>,
     headers: HeaderMap,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    let admin_id = verify_admin_token(&headers, &db).await?;
+    let admin_id = admin.user_id;
 
     tracing::info!("Admin reset password for user: {}", user_id);
 
@@ -463,25 +811,19 @@ pub async fn reset_user_password_handler(
         }
     };
 
-    // Generate temporary password
-    let temp_password = generate_temp_password();
-    let password_hash = match PasswordService::hash_password(&temp_password) {
-        Ok(hash) => hash,
-        Err(e) => {
-            tracing::error!("Password hashing error: {}", e);
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::error(
-                    ErrorCode::INTERNAL_SERVER_ERROR,
-                    ErrorMessage::PASSWORD_GENERATION_FAILED,
-                )),
-            ));
-        }
-    };
-
-    // Update password in database
-    match db.admin_reset_user_password(target_user_id, &password_hash) {
-        Ok(_) => {
+    // Send the user a single-use reset link instead of handing out a password; until a new
+    // password is set, sign-in only grants a password change token (the requirement is
+    // stored with the reset token)
+    // The email goes to the user, so it follows their language rather than the admin's
+    let locale = I18nService::resolve(target_user.locale.as_deref(), None);
+    match send_password_reset_email(
+        &db,
+        target_user_id,
+        &target_user.email,
+        Some(admin_id),
+        locale,
+    ) {
+        Ok(()) => {
             // Revoke all user sessions
             if let Err(e) = db.revoke_all_user_sessions(target_user_id, "password_reset_by_admin") {
                 tracing::warn!("Failed to revoke user sessions: {}", e);
@@ -496,16 +838,15 @@ pub async fn reset_user_password_handler(
                     "target_user_email": target_user.email
                 })),
                 true,
-                None,
+                Some(&ClientInfo::from_headers(&headers)),
             );
 
             Ok(Json(ApiResponse::success(json!({
-                "message": "Password reset successfully",
-                "temp_password": temp_password, // In production, send via email instead
+                "message": "Password reset link sent to the user's email address",
             }))))
         }
         Err(e) => {
-            tracing::error!("Database error resetting password: {}", e);
+            tracing::error!("Failed to issue password reset: {}", e);
             Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(ApiResponse::error(
@@ -517,33 +858,170 @@ pub async fn reset_user_password_handler(
     }
 }
 
-/// Get security logs with filtering
-pub async fn get_security_logs_handler(
+/// Require (or stop requiring) a user to set a new password at next sign-in
+pub async fn require_password_change_handler(
     State(db): State<Arc<Database>>,
-    Query(params): Query<SecurityLogsQuery>,
+    admin: AdminUser<UsersResetPassword>,
+    Path(user_id): Path<String>,
     headers: HeaderMap,
+    Json(payload): Json<RequirePasswordChangeRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    let _admin_id = verify_admin_token(&headers, &db).await?;
-
-    tracing::info!("Admin get security logs request: {:?}", params);
-
-    let offset = (params.page - 1) * params.limit;
+    let admin_id = admin.user_id;
 
-    match db.get_security_logs(&params, offset) {
-        Ok(logs) => {
-            let total = db.count_security_logs(&params).unwrap_or(0);
+    tracing::info!(
+        "Admin set password change requirement for user {}: {}",
+        user_id,
+        payload.required
+    );
 
-            Ok(Json(ApiResponse::success(json!({
-                "logs": logs,
-                "total": total,
-                "page": params.page,
-                "limit": params.limit
-            }))))
-        }
-        Err(e) => {
-            tracing::error!("Database error getting security logs: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+    let target_user_id: Uuid = match user_id.parse() {
+        Ok(id) => id,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error(
+                    ErrorCode::VALIDATION_ERROR,
+                    ErrorMessage::USER_ID_FORMAT_INVALID,
+                )),
+            ));
+        }
+    };
+
+    match db.find_user_by_id(target_user_id) {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::error(
+                    ErrorCode::USER_NOT_FOUND,
+                    ErrorMessage::USER_DOES_NOT_EXIST,
+                )),
+            ));
+        }
+        Err(e) => {
+            tracing::error!("Database error finding user: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    ErrorCode::DATABASE_ERROR,
+                    ErrorMessage::USER_LOOKUP_FAILED,
+                )),
+            ));
+        }
+    }
+
+    if let Err(e) = db.set_password_change_required(target_user_id, payload.required) {
+        tracing::error!("Database error updating password change requirement: {}", e);
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error(
+                ErrorCode::DATABASE_ERROR,
+                ErrorMessage::PASSWORD_CHANGE_REQUIREMENT_FAILED,
+            )),
+        ));
+    }
+
+    // Existing full-access sessions must not outlive the requirement
+    let revoked_sessions = if payload.required {
+        db.revoke_all_user_sessions(target_user_id, "password_change_required")
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to revoke user sessions: {}", e);
+                0
+            })
+    } else {
+        0
+    };
+
+    let _ = db.log_security_event(
+        Some(admin_id),
+        "admin_password_change_required",
+        Some(json!({
+            "target_user_id": target_user_id,
+            "required": payload.required,
+            "reason": payload.reason,
+            "revoked_sessions": revoked_sessions
+        })),
+        true,
+        Some(&ClientInfo::from_headers(&headers)),
+    );
+
+    Ok(Json(ApiResponse::success(json!({
+        "user_id": target_user_id,
+        "password_change_required": payload.required,
+        "revoked_sessions": revoked_sessions
+    }))))
+}
+
+/// Report accounts whose password is past their role's maximum age
+pub async fn get_expired_passwords_handler(
+    State(db): State<Arc<Database>>,
+    _admin: AdminUser<UsersRead>,
+    Query(params): Query<ExpiredPasswordsQuery>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!("Admin expired password report request");
+
+    let limit = params.limit.clamp(1, UserListing::MAX_PAGE_SIZE);
+    let offset = page_offset(params.page, limit);
+
+    let result = db
+        .get_expired_password_users(limit as i64, offset)
+        .and_then(|users| Ok((users, db.count_expired_password_users()?)));
+
+    match result {
+        Ok((users, total)) => Ok(Json(ApiResponse::success(json!({
+            "users": users,
+            "total": total,
+            "page": params.page.max(1),
+            "limit": limit
+        })))),
+        Err(e) => {
+            tracing::error!("Database error getting expired passwords: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    ErrorCode::DATABASE_ERROR,
+                    ErrorMessage::USERS_RETRIEVAL_FAILED,
+                )),
+            ))
+        }
+    }
+}
+
+/// Get security logs with filtering
+pub async fn get_security_logs_handler(
+    State(db): State<Arc<Database>>,
+    _admin: AdminUser<AuditRead>,
+    Query(params): Query<SecurityLogsQuery>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!("Admin get security logs request: {:?}", params);
+
+    let Some(filter) = params.filter() else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                ErrorCode::VALIDATION_ERROR,
+                ErrorMessage::SECURITY_LOG_FILTER_INVALID,
+            )),
+        ));
+    };
+    let limit = params.limit.clamp(1, AuditLog::MAX_PAGE_SIZE);
+    let offset = page_offset(params.page, limit);
+
+    match db.get_security_logs(&filter, limit as i64, offset) {
+        Ok(logs) => {
+            let total = db.count_security_logs(&filter).unwrap_or(0);
+
+            Ok(Json(ApiResponse::success(json!({
+                "logs": logs,
+                "total": total,
+                "page": params.page.max(1),
+                "limit": limit
+            }))))
+        }
+        Err(e) => {
+            tracing::error!("Database error getting security logs: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
                 Json(ApiResponse::error(
                     ErrorCode::DATABASE_ERROR,
                     ErrorMessage::SECURITY_LOGS_RETRIEVAL_FAILED,
@@ -553,13 +1031,335 @@ pub async fn get_security_logs_handler(
     }
 }
 
+/// Push security events to the admin as they happen, over Server-Sent Events (admin function)
+///
+/// Each match is sent as a `security_event` with the event ID as the SSE id. A client
+/// too slow to keep up skips ahead instead of holding back other subscribers, and is
+/// told how many events it missed by a `lagged` event (backfill from `/admin/security-logs`).
+pub async fn stream_security_events_handler(
+    State(db): State<Arc<Database>>,
+    admin: AdminUser<AuditRead>,
+    Query(params): Query<SecurityStreamQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, (StatusCode, Json<Value>)> {
+    let admin_id = admin.user_id;
+
+    tracing::info!("Admin security event stream request: {:?}", params);
+
+    let Some(filter) = params.filter() else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                ErrorCode::VALIDATION_ERROR,
+                ErrorMessage::SECURITY_LOG_FILTER_INVALID,
+            )),
+        ));
+    };
+
+    let _ = db.log_security_event(
+        Some(admin_id),
+        "admin_security_stream_opened",
+        Some(json!({
+            "event_type": filter.event_type,
+            "user_id": filter.user_id
+        })),
+        true,
+        Some(&ClientInfo::from_headers(&headers)),
+    );
+
+    let events = BroadcastStream::new(db.subscribe_security_events())
+        .filter_map(move |received| match received {
+            Ok(event) => filter
+                .matches(&event)
+                .then(|| {
+                    Event::default()
+                        .event("security_event")
+                        .id(event.id.to_string())
+                        .json_data(&event)
+                })
+                .and_then(Result::ok),
+            Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                tracing::warn!("Security event stream lagged, skipped {} events", skipped);
+                Event::default()
+                    .event("lagged")
+                    .json_data(json!({ "skipped": skipped }))
+                    .ok()
+            }
+        })
+        .map(Ok);
+
+    Ok(Sse::new(events).keep_alive(
+        KeepAlive::new().interval(Duration::from_secs(AuditLog::STREAM_KEEP_ALIVE_SECS)),
+    ))
+}
+
+/// Verify the tamper-evident audit chain and report the first bad entry (admin function)
+pub async fn verify_audit_log_handler(
+    State(db): State<Arc<Database>>,
+    admin: AdminUser<AuditVerify>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let admin_id = admin.user_id;
+
+    tracing::info!("Admin audit log verification request");
+
+    // Walking the whole chain can take a while, so keep it off the async workers
+    let verifier = db.clone();
+    let result = tokio::task::spawn_blocking(move || verifier.verify_audit_chain())
+        .await
+        .unwrap_or_else(|e| Err(anyhow::anyhow!(e)));
+
+    match result {
+        Ok(report) => {
+            if let Some(failure) = &report.first_failure {
+                tracing::error!("Audit chain verification failed: {:?}", failure);
+            }
+            let _ = db.log_security_event(
+                Some(admin_id),
+                "admin_audit_verified",
+                Some(json!({
+                    "valid": report.valid,
+                    "checked_entries": report.checked_entries,
+                    "first_failure": report.first_failure
+                })),
+                report.valid,
+                Some(&ClientInfo::from_headers(&headers)),
+            );
+
+            Ok(Json(ApiResponse::success(json!(report))))
+        }
+        Err(e) => {
+            tracing::error!("Audit chain verification error: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    ErrorCode::DATABASE_ERROR,
+                    ErrorMessage::AUDIT_VERIFICATION_FAILED,
+                )),
+            ))
+        }
+    }
+}
+
+/// Stream security events for a time range as JSON Lines, CSV or CEF (admin function)
+pub async fn export_audit_log_handler(
+    State(db): State<Arc<Database>>,
+    admin: AdminUser<AuditExport>,
+    Query(params): Query<AuditExportQuery>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    let admin_id = admin.user_id;
+
+    tracing::info!("Admin audit log export request: {:?}", params);
+
+    let format = match params
+        .format
+        .as_deref()
+        .unwrap_or("jsonl")
+        .parse::<ExportFormat>()
+    {
+        Ok(format) => format,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error(
+                    ErrorCode::VALIDATION_ERROR,
+                    ErrorMessage::AUDIT_EXPORT_FORMAT_INVALID,
+                )),
+            ));
+        }
+    };
+    let Some(filter) = params.filter() else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                ErrorCode::VALIDATION_ERROR,
+                ErrorMessage::SECURITY_LOG_FILTER_INVALID,
+            )),
+        ));
+    };
+
+    let _ = db.log_security_event(
+        Some(admin_id),
+        "admin_audit_exported",
+        Some(json!({
+            "format": format.as_str(),
+            "gzip": params.gzip,
+            "event_type": filter.event_type,
+            "user_id": filter.user_id,
+            "since": filter.since,
+            "until": filter.until
+        })),
+        true,
+        Some(&ClientInfo::from_headers(&headers)),
+    );
+
+    // The exporter writes batch by batch into a bounded channel, so a slow client
+    // holds back the database reads instead of buffering the range in memory
+    let (sender, receiver) = mpsc::channel(AuditLog::EXPORT_CHANNEL_CAPACITY);
+    let gzip = params.gzip;
+    let exporter = db.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut channel = ChannelWriter::new(sender.clone());
+        let result = if gzip {
+            let mut encoder = GzEncoder::new(&mut channel, Compression::default());
+            exporter
+                .export_security_events(&filter, format, &mut encoder)
+                .and_then(|count| Ok(encoder.finish().map(|_| count)?))
+        } else {
+            exporter.export_security_events(&filter, format, &mut channel)
+        }
+        .and_then(|count| Ok(channel.flush().map(|_| count)?));
+
+        match result {
+            Ok(count) => tracing::info!("Exported {} security events", count),
+            Err(e) => {
+                // Abort the body so the client sees a truncated download, not a short file
+                tracing::error!("Security event export failed: {}", e);
+                let _ = sender.blocking_send(Err(io::Error::other(e.to_string())));
+            }
+        }
+    });
+
+    let extension = if gzip {
+        format!("{}.gz", format.as_str())
+    } else {
+        format.as_str().to_string()
+    };
+    let filename = format!(
+        "security-events-{}.{}",
+        Utc::now().format("%Y%m%dT%H%M%SZ"),
+        extension
+    );
+    let content_type = if gzip {
+        "application/gzip"
+    } else {
+        format.content_type()
+    };
+
+    let mut response = Response::new(Body::from_stream(ReceiverStream::new(receiver)));
+    let response_headers = response.headers_mut();
+    response_headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    if let Ok(disposition) =
+        HeaderValue::from_str(&format!("attachment; filename=\"{}\"", filename))
+    {
+        response_headers.insert(header::CONTENT_DISPOSITION, disposition);
+    }
+
+    Ok(response)
+}
+
+/// Buffers writes and hands each flushed chunk to the response body
+struct ChannelWriter {
+    sender: mpsc::Sender<io::Result<Bytes>>,
+    buffer: Vec<u8>,
+}
+
+impl ChannelWriter {
+    fn new(sender: mpsc::Sender<io::Result<Bytes>>) -> Self {
+        ChannelWriter {
+            sender,
+            buffer: Vec::new(),
+        }
+    }
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        // Blocks while the channel is full; fails once the client has gone away
+        self.sender
+            .blocking_send(Ok(Bytes::from(std::mem::take(&mut self.buffer))))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "export client disconnected"))
+    }
+}
+
+/// Get the email outbox: delivery counts by status and recent messages (admin function)
+pub async fn get_email_outbox_handler(
+    State(db): State<Arc<Database>>,
+    _admin: AdminUser<EmailOutboxRead>,
+    Query(params): Query<EmailOutboxQuery>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!("Admin get email outbox request: {:?}", params);
+
+    if let Some(status) = params.status.as_deref() {
+        if !EmailOutbox::STATUSES.contains(&status) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error(
+                    ErrorCode::VALIDATION_ERROR,
+                    ErrorMessage::OUTBOX_STATUS_INVALID,
+                )),
+            ));
+        }
+    }
+    let limit = params.limit.clamp(1, EmailOutbox::MAX_PAGE_SIZE);
+    let offset = page_offset(params.page, limit);
+
+    let result = db.count_emails_by_status().and_then(|counts| {
+        let oldest_pending = db.get_oldest_pending_email_time()?;
+        let emails = db.get_outbox_emails(params.status.as_deref(), limit as i64, offset)?;
+        Ok((counts, oldest_pending, emails))
+    });
+
+    match result {
+        Ok((counts, oldest_pending, emails)) => {
+            let counts: serde_json::Map<String, Value> = counts
+                .into_iter()
+                .map(|(status, count)| (status, json!(count)))
+                .collect();
+            let emails: Vec<EmailOutboxView> = emails
+                .into_iter()
+                .map(|email| EmailOutboxView {
+                    id: email.id.to_string(),
+                    to_address: email.to_address,
+                    subject: email.subject,
+                    status: email.status,
+                    attempts: email.attempts,
+                    next_attempt_at: email.next_attempt_at.to_rfc3339(),
+                    last_error: email.last_error,
+                    created_at: email.created_at.to_rfc3339(),
+                    sent_at: email.sent_at.map(|t| t.to_rfc3339()),
+                })
+                .collect();
+
+            Ok(Json(ApiResponse::success(json!({
+                "counts": counts,
+                "oldest_pending_at": oldest_pending.map(|t| t.to_rfc3339()),
+                "oldest_pending_seconds": oldest_pending.map(|t| (chrono::Utc::now() - t).num_seconds()),
+                "emails": emails,
+                "page": params.page.max(1),
+                "limit": limit
+            }))))
+        }
+        Err(e) => {
+            tracing::error!("Database error getting email outbox: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    ErrorCode::DATABASE_ERROR,
+                    ErrorMessage::EMAIL_OUTBOX_RETRIEVAL_FAILED,
+                )),
+            ))
+        }
+    }
+}
+
 /// Revoke user sessions (admin function)
 pub async fn revoke_user_sessions_handler(
     State(db): State<Arc<Database>>,
+    admin: AdminUser<SessionsRevoke>,
     headers: HeaderMap,
     Json(payload): Json<RevokeSessionsRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    let admin_id = verify_admin_token(&headers, &db).await?;
+    let admin_id = admin.user_id;
 
     tracing::info!("Admin revoke sessions for user: {}", payload.user_id);
 
@@ -592,7 +1392,7 @@ pub async fn revoke_user_sessions_handler(
                     "revoked_count": revoked_count
                 })),
                 true,
-                None,
+                Some(&ClientInfo::from_headers(&headers)),
             );
 
             Ok(Json(ApiResponse::success(json!({
@@ -616,10 +1416,11 @@ pub async fn revoke_user_sessions_handler(
 /// Unlock user account (admin function)
 pub async fn unlock_user_account_handler(
     State(db): State<Arc<Database>>,
+    admin: AdminUser<UsersUnlock>,
     headers: HeaderMap,
     Json(payload): Json<UnlockAccountRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    let admin_id = verify_admin_token(&headers, &db).await?;
+    let admin_id = admin.user_id;
 
     tracing::info!("Admin unlock account request: {:?}", payload);
 
@@ -730,7 +1531,7 @@ pub async fn unlock_user_account_handler(
                 "reason": reason
             })),
             true,
-            None,
+            Some(&ClientInfo::from_headers(&headers)),
         );
 
         Ok(Json(ApiResponse::success(json!({
@@ -753,11 +1554,9 @@ pub async fn unlock_user_account_handler(
 /// Get account lock status (admin function)
 pub async fn get_account_lock_status_handler(
     State(db): State<Arc<Database>>,
+    _admin: AdminUser<UsersRead>,
     Path(user_id): Path<String>,
-    headers: HeaderMap,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    let _admin_id = verify_admin_token(&headers, &db).await?;
-
     let target_user_id: Uuid = match user_id.parse() {
         Ok(id) => id,
         Err(_) => {
@@ -835,18 +1634,555 @@ pub async fn get_account_lock_status_handler(
     }
 }
 
-/// Helper function to generate temporary password
-fn generate_temp_password() -> String {
-    use rand::Rng;
-    const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ\
-                            abcdefghijklmnopqrstuvwxyz\
-                            0123456789";
-    let mut rng = rand::thread_rng();
+// ========================================
+// Role Management
+// ========================================
 
-    (0..12)
-        .map(|_| {
-            let idx = rng.gen_range(0..CHARSET.len());
-            CHARSET[idx] as char
-        })
-        .collect()
+fn role_error(status: StatusCode, code: &str, message: &str) -> (StatusCode, Json<Value>) {
+    (status, Json(ApiResponse::error(code, message)))
+}
+
+/// The cached role hierarchy, as a 500 if it can't be loaded
+fn load_role_hierarchy(db: &Database) -> Result<Arc<RoleHierarchy>, (StatusCode, Json<Value>)> {
+    db.role_hierarchy().map_err(|e| {
+        tracing::error!("Database error loading role hierarchy: {}", e);
+        role_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::DATABASE_ERROR,
+            ErrorMessage::ROLES_RETRIEVAL_FAILED,
+        )
+    })
+}
+
+/// Look up a parent role by name, as a 404 if there is none
+fn find_parent_role<'a>(
+    hierarchy: &'a RoleHierarchy,
+    name: &str,
+) -> Result<&'a RoleNode, (StatusCode, Json<Value>)> {
+    hierarchy.get(name).ok_or_else(|| {
+        role_error(
+            StatusCode::NOT_FOUND,
+            ErrorCode::ROLE_NOT_FOUND,
+            ErrorMessage::ROLE_NOT_FOUND,
+        )
+    })
+}
+
+/// Check requested permission names exist, as a 400 if not
+fn check_permissions_exist(
+    db: &Database,
+    permissions: &[String],
+) -> Result<(), (StatusCode, Json<Value>)> {
+    match db.unknown_permissions(permissions, None) {
+        Ok(unknown) if unknown.is_empty() => Ok(()),
+        Ok(unknown) => {
+            tracing::warn!("Unknown permissions requested: {:?}", unknown);
+            Err(role_error(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::VALIDATION_ERROR,
+                ErrorMessage::ROLE_PERMISSIONS_INVALID,
+            ))
+        }
+        Err(e) => {
+            tracing::error!("Database error checking permissions: {}", e);
+            Err(role_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorCode::DATABASE_ERROR,
+                ErrorMessage::ROLE_UPDATE_FAILED,
+            ))
+        }
+    }
+}
+
+/// List every permission that can be granted to roles (admin function)
+pub async fn get_permissions_handler(
+    State(db): State<Arc<Database>>,
+    _admin: AdminUser<RolesRead>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    match db.list_permissions() {
+        Ok(permissions) => Ok(Json(ApiResponse::success(json!({
+            "permissions": permissions
+        })))),
+        Err(e) => {
+            tracing::error!("Database error listing permissions: {}", e);
+            Err(role_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorCode::DATABASE_ERROR,
+                ErrorMessage::ROLES_RETRIEVAL_FAILED,
+            ))
+        }
+    }
+}
+
+/// List roles with their permissions and user counts (admin function)
+pub async fn get_roles_handler(
+    State(db): State<Arc<Database>>,
+    _admin: AdminUser<RolesRead>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    match db.list_roles_admin() {
+        Ok(roles) => Ok(Json(ApiResponse::success(json!({ "roles": roles })))),
+        Err(e) => {
+            tracing::error!("Database error listing roles: {}", e);
+            Err(role_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorCode::DATABASE_ERROR,
+                ErrorMessage::ROLES_RETRIEVAL_FAILED,
+            ))
+        }
+    }
+}
+
+/// Create a role (admin function); granting permissions requires a super admin
+pub async fn create_role_handler(
+    State(db): State<Arc<Database>>,
+    admin: AdminUser<RolesManage>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateRoleRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!("Admin create role request: {:?}", payload);
+
+    if !Roles::is_valid_name(&payload.name) {
+        return Err(role_error(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::VALIDATION_ERROR,
+            ErrorMessage::ROLE_NAME_INVALID,
+        ));
+    }
+    if payload.password_max_age_days.is_some_and(|days| days <= 0) {
+        return Err(role_error(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::VALIDATION_ERROR,
+            ErrorMessage::ROLE_PASSWORD_MAX_AGE_INVALID,
+        ));
+    }
+    check_permissions_exist(&db, &payload.permissions)?;
+
+    let hierarchy = load_role_hierarchy(&db)?;
+    let parent = match &payload.parent {
+        Some(name) => Some(find_parent_role(&hierarchy, name)?),
+        None => None,
+    };
+    let grants_access = !payload.permissions.is_empty()
+        || parent.is_some_and(|parent| hierarchy.grants_admin_access(&parent.name));
+    if grants_access && !hierarchy.inherits(&admin.role, Roles::SUPER_ADMIN) {
+        return Err(role_error(
+            StatusCode::FORBIDDEN,
+            ErrorCode::INSUFFICIENT_PERMISSIONS,
+            ErrorMessage::ADMIN_ROLE_GRANT_FORBIDDEN,
+        ));
+    }
+
+    let new_role = NewRole {
+        name: payload.name.clone(),
+        description: payload.description.clone(),
+        password_max_age_days: payload.password_max_age_days,
+        parent_role_id: parent.map(|parent| parent.id),
+    };
+    match db.create_role(&new_role, &payload.permissions) {
+        Ok(RoleCreation::Created(role)) => {
+            let _ = db.log_security_event(
+                Some(admin.user_id),
+                "admin_role_created",
+                Some(json!({
+                    "role_id": role.id,
+                    "name": role.name,
+                    "description": role.description,
+                    "password_max_age_days": role.password_max_age_days,
+                    "parent": payload.parent,
+                    "permissions": payload.permissions
+                })),
+                true,
+                Some(&ClientInfo::from_headers(&headers)),
+            );
+
+            Ok(Json(ApiResponse::success(json!({
+                "role": role,
+                "permissions": payload.permissions
+            }))))
+        }
+        Ok(RoleCreation::NameTaken) => Err(role_error(
+            StatusCode::CONFLICT,
+            ErrorCode::ROLE_ALREADY_EXISTS,
+            ErrorMessage::ROLE_ALREADY_EXISTS,
+        )),
+        Err(e) => {
+            tracing::error!("Database error creating role: {}", e);
+            Err(role_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorCode::DATABASE_ERROR,
+                ErrorMessage::ROLE_UPDATE_FAILED,
+            ))
+        }
+    }
+}
+
+/// Edit a role's name, description, password rotation or permissions (admin function)
+///
+/// Built-in roles keep their names and super admins keep every permission.
+/// Only super admins change what a role may do.
+pub async fn update_role_handler(
+    State(db): State<Arc<Database>>,
+    admin: AdminUser<RolesManage>,
+    Path(role_id): Path<String>,
+    headers: HeaderMap,
+    Json(payload): Json<UpdateRoleRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!(
+        "Admin update role request: role_id={}, {:?}",
+        role_id,
+        payload
+    );
+
+    let role = find_role_for_admin(&db, &role_id)?;
+
+    if let Some(name) = &payload.name {
+        if !Roles::is_valid_name(name) {
+            return Err(role_error(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::VALIDATION_ERROR,
+                ErrorMessage::ROLE_NAME_INVALID,
+            ));
+        }
+        if *name != role.name && Roles::is_built_in(&role.name) {
+            return Err(role_error(
+                StatusCode::FORBIDDEN,
+                ErrorCode::ROLE_PROTECTED,
+                ErrorMessage::BUILT_IN_ROLE_PROTECTED,
+            ));
+        }
+    }
+    if payload
+        .password_max_age_days
+        .flatten()
+        .is_some_and(|days| days <= 0)
+    {
+        return Err(role_error(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::VALIDATION_ERROR,
+            ErrorMessage::ROLE_PASSWORD_MAX_AGE_INVALID,
+        ));
+    }
+    if let Some(permissions) = &payload.permissions {
+        check_permissions_exist(&db, permissions)?;
+    }
+
+    let hierarchy = load_role_hierarchy(&db)?;
+    let previous_parent = role
+        .parent_role_id
+        .and_then(|id| hierarchy.get_by_id(id))
+        .map(|parent| parent.name.clone());
+    let parent_role_id = match &payload.parent {
+        Some(parent) if *parent == previous_parent => None,
+        Some(_) if Roles::is_built_in(&role.name) => {
+            return Err(role_error(
+                StatusCode::FORBIDDEN,
+                ErrorCode::ROLE_PROTECTED,
+                ErrorMessage::BUILT_IN_ROLE_PROTECTED,
+            ));
+        }
+        Some(Some(name)) => Some(Some(find_parent_role(&hierarchy, name)?.id)),
+        Some(None) => Some(None),
+        None => None,
+    };
+
+    // What a role may do, directly or through its parent, is up to super admins
+    if (payload.permissions.is_some() || parent_role_id.is_some())
+        && !hierarchy.inherits(&admin.role, Roles::SUPER_ADMIN)
+    {
+        return Err(role_error(
+            StatusCode::FORBIDDEN,
+            ErrorCode::INSUFFICIENT_PERMISSIONS,
+            ErrorMessage::ADMIN_ROLE_GRANT_FORBIDDEN,
+        ));
+    }
+
+    let previous_permissions = db.get_role_permissions(role.id).unwrap_or_default();
+    let changes = RoleChanges {
+        name: payload.name.clone(),
+        description: payload.description.clone(),
+        password_max_age_days: payload.password_max_age_days,
+        parent_role_id,
+        permissions: payload.permissions.clone(),
+    };
+    match db.update_role(role.id, &changes) {
+        Ok(RoleWrite::Saved(updated)) => {
+            let _ = db.log_security_event(
+                Some(admin.user_id),
+                "admin_role_updated",
+                Some(json!({
+                    "role_id": updated.id,
+                    "old_name": role.name,
+                    "new_name": updated.name,
+                    "old_description": role.description,
+                    "new_description": updated.description,
+                    "old_password_max_age_days": role.password_max_age_days,
+                    "new_password_max_age_days": updated.password_max_age_days,
+                    "old_parent": previous_parent,
+                    "new_parent": payload.parent.clone().unwrap_or(previous_parent.clone()),
+                    "old_permissions": previous_permissions,
+                    "new_permissions": payload.permissions
+                })),
+                true,
+                Some(&ClientInfo::from_headers(&headers)),
+            );
+
+            Ok(Json(ApiResponse::success(json!({
+                "role": updated,
+                "permissions": db.get_role_permissions(updated.id).unwrap_or_default()
+            }))))
+        }
+        Ok(RoleWrite::NameTaken) => Err(role_error(
+            StatusCode::CONFLICT,
+            ErrorCode::ROLE_ALREADY_EXISTS,
+            ErrorMessage::ROLE_ALREADY_EXISTS,
+        )),
+        Ok(RoleWrite::Cycle) => Err(role_error(
+            StatusCode::CONFLICT,
+            ErrorCode::ROLE_HIERARCHY_CYCLE,
+            ErrorMessage::ROLE_HIERARCHY_CYCLE,
+        )),
+        Ok(RoleWrite::NotFound) => Err(role_error(
+            StatusCode::NOT_FOUND,
+            ErrorCode::ROLE_NOT_FOUND,
+            ErrorMessage::ROLE_NOT_FOUND,
+        )),
+        Err(e) => {
+            tracing::error!("Database error updating role: {}", e);
+            Err(role_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorCode::DATABASE_ERROR,
+                ErrorMessage::ROLE_UPDATE_FAILED,
+            ))
+        }
+    }
+}
+
+/// Soft delete a role nobody holds (admin function); built-in roles can't be deleted
+pub async fn delete_role_handler(
+    State(db): State<Arc<Database>>,
+    admin: AdminUser<RolesManage>,
+    Path(role_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!("Admin delete role request: role_id={}", role_id);
+
+    let role = find_role_for_admin(&db, &role_id)?;
+    if Roles::is_built_in(&role.name) {
+        return Err(role_error(
+            StatusCode::FORBIDDEN,
+            ErrorCode::ROLE_PROTECTED,
+            ErrorMessage::BUILT_IN_ROLE_PROTECTED,
+        ));
+    }
+
+    let permissions = db.get_role_permissions(role.id).unwrap_or_default();
+    match db.delete_role(role.id) {
+        Ok(RoleDeletion::Deleted(deleted)) => {
+            let _ = db.log_security_event(
+                Some(admin.user_id),
+                "admin_role_deleted",
+                Some(json!({
+                    "role_id": deleted.id,
+                    "name": deleted.name,
+                    "permissions": permissions
+                })),
+                true,
+                Some(&ClientInfo::from_headers(&headers)),
+            );
+
+            Ok(Json(ApiResponse::success(json!({
+                "message": format!("Role {} deleted", deleted.name),
+                "role_id": deleted.id
+            }))))
+        }
+        Ok(RoleDeletion::InUse { users }) => Err(role_error(
+            StatusCode::CONFLICT,
+            ErrorCode::ROLE_IN_USE,
+            &ErrorMessage::ROLE_IN_USE.replace("{}", &users.to_string()),
+        )),
+        Ok(RoleDeletion::HasChildren { roles }) => Err(role_error(
+            StatusCode::CONFLICT,
+            ErrorCode::ROLE_IN_USE,
+            &ErrorMessage::ROLE_HAS_CHILDREN.replace("{}", &roles.join(", ")),
+        )),
+        Ok(RoleDeletion::NotFound) => Err(role_error(
+            StatusCode::NOT_FOUND,
+            ErrorCode::ROLE_NOT_FOUND,
+            ErrorMessage::ROLE_NOT_FOUND,
+        )),
+        Err(e) => {
+            tracing::error!("Database error deleting role: {}", e);
+            Err(role_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorCode::DATABASE_ERROR,
+                ErrorMessage::ROLE_UPDATE_FAILED,
+            ))
+        }
+    }
+}
+
+/// Move a user to another role (admin function)
+///
+/// Only super admins can grant a role with admin access or change an admin's role.
+/// The user's sessions are revoked so their next token carries the new permissions.
+pub async fn update_user_role_handler(
+    State(db): State<Arc<Database>>,
+    admin: AdminUser<UsersAssignRole>,
+    Path(user_id): Path<String>,
+    headers: HeaderMap,
+    Json(payload): Json<UpdateUserRoleRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!(
+        "Admin update user role: user_id={}, role={}, reason={:?}",
+        user_id,
+        payload.role,
+        payload.reason
+    );
+
+    let target_user_id: Uuid = match user_id.parse() {
+        Ok(id) => id,
+        Err(_) => {
+            return Err(role_error(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::VALIDATION_ERROR,
+                ErrorMessage::USER_ID_FORMAT_INVALID,
+            ));
+        }
+    };
+    if target_user_id == admin.user_id {
+        return Err(role_error(
+            StatusCode::FORBIDDEN,
+            ErrorCode::CANNOT_CHANGE_OWN_ROLE,
+            ErrorMessage::OWN_ROLE_CHANGE_FORBIDDEN,
+        ));
+    }
+
+    let lookup_failed = |e: anyhow::Error| {
+        tracing::error!("Database error looking up roles: {}", e);
+        role_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::DATABASE_ERROR,
+            ErrorMessage::ROLE_UPDATE_FAILED,
+        )
+    };
+    let new_role = match db.find_role_by_name(&payload.role).map_err(lookup_failed)? {
+        Some(role) => role,
+        None => {
+            return Err(role_error(
+                StatusCode::NOT_FOUND,
+                ErrorCode::ROLE_NOT_FOUND,
+                ErrorMessage::ROLE_NOT_FOUND,
+            ));
+        }
+    };
+    let current_role = match db.get_user_role(target_user_id).map_err(lookup_failed)? {
+        Some(role) => role,
+        None => {
+            return Err(role_error(
+                StatusCode::NOT_FOUND,
+                ErrorCode::USER_NOT_FOUND,
+                ErrorMessage::USER_DOES_NOT_EXIST,
+            ));
+        }
+    };
+
+    // Granting or taking away admin access is reserved for super admins
+    let hierarchy = load_role_hierarchy(&db)?;
+    if !hierarchy.inherits(&admin.role, Roles::SUPER_ADMIN)
+        && (hierarchy.grants_admin_access(&new_role.name)
+            || hierarchy.grants_admin_access(&current_role))
+    {
+        return Err(role_error(
+            StatusCode::FORBIDDEN,
+            ErrorCode::INSUFFICIENT_PERMISSIONS,
+            ErrorMessage::ADMIN_ROLE_GRANT_FORBIDDEN,
+        ));
+    }
+
+    match db.change_user_role(
+        target_user_id,
+        new_role.id,
+        admin.user_id,
+        payload.reason.as_deref(),
+    ) {
+        Ok(UserRoleChange::Changed {
+            previous_role,
+            revoked_sessions,
+        }) => {
+            let _ = db.log_security_event(
+                Some(admin.user_id),
+                "admin_user_role_changed",
+                Some(json!({
+                    "target_user_id": target_user_id,
+                    "old_role": previous_role,
+                    "new_role": new_role.name,
+                    "reason": payload.reason,
+                    "revoked_sessions": revoked_sessions
+                })),
+                true,
+                Some(&ClientInfo::from_headers(&headers)),
+            );
+
+            Ok(Json(ApiResponse::success(json!({
+                "message": format!("User role changed to {}", new_role.name),
+                "user": {
+                    "id": target_user_id,
+                    "role": new_role.name,
+                },
+                "revoked_sessions": revoked_sessions
+            }))))
+        }
+        Ok(UserRoleChange::Unchanged) => Ok(Json(ApiResponse::success(json!({
+            "message": format!("User already has role {}", new_role.name),
+            "user": {
+                "id": target_user_id,
+                "role": new_role.name,
+            },
+            "revoked_sessions": 0
+        })))),
+        Ok(UserRoleChange::UserNotFound) => Err(role_error(
+            StatusCode::NOT_FOUND,
+            ErrorCode::USER_NOT_FOUND,
+            ErrorMessage::USER_DOES_NOT_EXIST,
+        )),
+        Err(e) => {
+            tracing::error!("Database error changing user role: {}", e);
+            Err(role_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorCode::DATABASE_ERROR,
+                ErrorMessage::ROLE_UPDATE_FAILED,
+            ))
+        }
+    }
+}
+
+/// Parse a role ID from the path and load the active role, as 400/404/500 errors
+fn find_role_for_admin(
+    db: &Database,
+    role_id: &str,
+) -> Result<crate::models::database::Role, (StatusCode, Json<Value>)> {
+    let Ok(role_id) = role_id.parse::<Uuid>() else {
+        return Err(role_error(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::VALIDATION_ERROR,
+            ErrorMessage::ROLE_ID_FORMAT_INVALID,
+        ));
+    };
+
+    match db.find_role_by_id(role_id) {
+        Ok(Some(role)) => Ok(role),
+        Ok(None) => Err(role_error(
+            StatusCode::NOT_FOUND,
+            ErrorCode::ROLE_NOT_FOUND,
+            ErrorMessage::ROLE_NOT_FOUND,
+        )),
+        Err(e) => {
+            tracing::error!("Database error getting role: {}", e);
+            Err(role_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorCode::DATABASE_ERROR,
+                ErrorMessage::ROLES_RETRIEVAL_FAILED,
+            ))
+        }
+    }
 }
@@ -1,16 +1,81 @@
-use axum::{extract::State, http::StatusCode, response::Json};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
 use serde_json::{json, Value};
+use std::env;
 use std::sync::Arc;
+use uuid::Uuid;
 
-use crate::database::Database;
-use crate::models::ApiResponse;
+use crate::database::{Database, EmailChangeCancellation, EmailChangeConfirmation, PasswordUpdate};
+use crate::models::{AccountStatus, ApiResponse};
 use crate::proto_generated::*;
-use crate::utils::{JwtService, PasswordService, ProtoValidator};
-use crate::{ErrorCode, ErrorMessage, Roles};
+use crate::utils::{
+    ClientInfo, EmailMessage, EmailTemplate, I18nService, JwtService, Locale, MailService,
+    OneTimeToken, PasswordError, PasswordExpiry, PasswordService, ProtoValidator, TokenScope,
+};
+use crate::{AccountLock, EmailVerification, ErrorCode, ErrorMessage, PasswordReset, Roles};
+
+// Simple request structures (not using proto for now)
+#[derive(Deserialize)]
+pub struct PasswordResetRequest {
+    pub email: String,
+}
+
+#[derive(Deserialize)]
+pub struct PasswordResetConfirmRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+#[derive(Deserialize)]
+pub struct VerifyEmailRequest {
+    pub token: String,
+}
+
+#[derive(Deserialize)]
+pub struct ResendVerificationRequest {
+    pub email: String,
+}
+
+#[derive(Deserialize)]
+pub struct EmailChangeTokenRequest {
+    pub token: String,
+}
+
+/// How sign-in treats accounts whose email isn't verified (`UNVERIFIED_SIGNIN_POLICY`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnverifiedSigninPolicy {
+    /// Sign in normally, with a warning in the response (default)
+    Allow,
+    /// Sign in with a token limited to reading the profile until the email is verified
+    Restrict,
+    /// Refuse to sign in until the email is verified
+    Deny,
+}
+
+impl UnverifiedSigninPolicy {
+    /// Read the policy from the environment (`allow`, `restrict` or `deny`)
+    pub fn from_env() -> Self {
+        match env::var("UNVERIFIED_SIGNIN_POLICY")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "restrict" | "restricted" => UnverifiedSigninPolicy::Restrict,
+            "deny" => UnverifiedSigninPolicy::Deny,
+            _ => UnverifiedSigninPolicy::Allow,
+        }
+    }
+}
 
 /// Handler for user signup
 pub async fn signup_handler(
     State(db): State<Arc<Database>>,
+    headers: HeaderMap,
     Json(payload): Json<AuthSignupRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     tracing::info!(
@@ -19,7 +84,7 @@ pub async fn signup_handler(
     );
 
     // Validate input
-    if let Err(_) = ProtoValidator::validate_signup_request(&payload) {
+    if ProtoValidator::validate_signup_request(&payload).is_err() {
         return Err((
             StatusCode::BAD_REQUEST,
             Json(ApiResponse::error(
@@ -29,15 +94,27 @@ pub async fn signup_handler(
         ));
     }
 
-    // Validate password strength
-    if let Err(_) = PasswordService::validate_password_strength(&payload.password) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ApiResponse::error(
-                ErrorCode::WEAK_PASSWORD,
-                ErrorMessage::PASSWORD_TOO_WEAK,
-            )),
-        ));
+    // Validate password strength and reject breached passwords
+    match PasswordService::validate_new_password(&payload.password).await {
+        Ok(()) => {}
+        Err(PasswordError::Breached) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error(
+                    ErrorCode::PASSWORD_BREACHED,
+                    ErrorMessage::PASSWORD_FOUND_IN_BREACH,
+                )),
+            ));
+        }
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error(
+                    ErrorCode::WEAK_PASSWORD,
+                    ErrorMessage::PASSWORD_TOO_WEAK,
+                )),
+            ));
+        }
     }
 
     // Check if user already exists
@@ -79,8 +156,9 @@ pub async fn signup_handler(
         }
     };
 
-    // Create main user record (business information only)
-    let user = match db.create_user(&payload.email, &payload.name) {
+    // Create main user record (business information only), remembering the browser's language
+    let locale = Locale::from_headers(&headers);
+    let user = match db.create_user(&payload.email, &payload.name, locale.map(Locale::code)) {
         Ok(user) => user,
         Err(e) => {
             tracing::error!("Database error creating user: {}", e);
@@ -106,15 +184,88 @@ pub async fn signup_handler(
         ));
     }
 
+    let _ = db.log_security_event(
+        Some(user.id),
+        "signup",
+        None,
+        true,
+        Some(&ClientInfo::from_headers(&headers)),
+    );
+
+    // Send the verification link; the account exists either way and the user can ask for a resend
+    let locale = locale.unwrap_or_else(I18nService::default_locale);
+    if let Err(e) = send_verification_email(&db, user.id, &user.email, locale) {
+        tracing::error!("Failed to send verification email to {}: {}", user.email, e);
+    }
+
+    // New accounts are unverified, so the sign-in policy decides what token they get
+    let policy = UnverifiedSigninPolicy::from_env();
+    if policy == UnverifiedSigninPolicy::Deny {
+        tracing::info!(
+            "User {} successfully signed up, awaiting email verification",
+            payload.email
+        );
+
+        return Ok(Json(ApiResponse::success(json!({
+            "token": null,
+            "email_verification_required": true,
+            "user": {
+                "id": user.id,
+                "email": user.email,
+                "name": user.name,
+                "created_at": user.created_at
+            }
+        }))));
+    }
+
     // Role is now set directly in the user table during creation
-    // Get user role
+    // Get user role and the permissions it grants
     let role = match db.get_user_role(user.id) {
         Ok(r) => r.unwrap_or(Roles::USER.to_string()),
         Err(_) => Roles::USER.to_string(),
     };
+    let permissions = db.get_user_permissions(user.id).unwrap_or_default();
+
+    // Start a session for this sign-in
+    let client = ClientInfo::from_headers(&headers);
+    let session_id = match db.create_user_session(
+        user.id,
+        client.user_agent.as_deref(),
+        client.ip_address.as_deref(),
+    ) {
+        Ok(session_id) => session_id,
+        Err(e) => {
+            tracing::error!("Database error creating user session: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    ErrorCode::DATABASE_ERROR,
+                    ErrorMessage::SERVER_ERROR_OCCURRED,
+                )),
+            ));
+        }
+    };
 
     // Generate JWT token
-    let token = match JwtService::generate_token(user.id, &user.email, &role) {
+    let token_result = if policy == UnverifiedSigninPolicy::Restrict {
+        JwtService::generate_restricted_token(
+            user.id,
+            &user.email,
+            &role,
+            session_id,
+            TokenScope::UNVERIFIED_EMAIL,
+        )
+    } else {
+        JwtService::generate_session_token(
+            user.id,
+            &user.email,
+            &role,
+            session_id,
+            &permissions,
+            None,
+        )
+    };
+    let token = match token_result {
         Ok(token) => token,
         Err(e) => {
             tracing::error!("JWT generation error: {}", e);
@@ -132,6 +283,7 @@ pub async fn signup_handler(
 
     Ok(Json(ApiResponse::success(json!({
         "token": token,
+        "email_verification_required": true,
         "user": {
             "id": user.id,
             "email": user.email,
@@ -144,6 +296,7 @@ pub async fn signup_handler(
 /// Handler for user signin
 pub async fn signin_handler(
     State(db): State<Arc<Database>>,
+    headers: HeaderMap,
     Json(payload): Json<AuthSigninRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     tracing::info!(
@@ -152,7 +305,7 @@ pub async fn signin_handler(
     );
 
     // Validate input
-    if let Err(_) = ProtoValidator::validate_signin_request(&payload) {
+    if ProtoValidator::validate_signin_request(&payload).is_err() {
         return Err((
             StatusCode::BAD_REQUEST,
             Json(ApiResponse::error(
@@ -162,12 +315,25 @@ pub async fn signin_handler(
         ));
     }
 
+    // Failed sign-ins are logged with the attempted email, since the account may not exist
+    let client = ClientInfo::from_headers(&headers);
+    let log_signin_failure = |user_id: Option<Uuid>, reason: &str| {
+        let _ = db.log_security_event(
+            user_id,
+            "signin_failed",
+            Some(json!({ "email": payload.email, "reason": reason })),
+            false,
+            Some(&client),
+        );
+    };
+
     // Find user by email
     let user = match db.find_user_by_email(&payload.email) {
         Ok(Some(user)) => user,
         Ok(None) => {
             // Increment failed attempts for non-existent users to prevent timing attacks
             let _ = db.increment_failed_login_attempts(&payload.email);
+            log_signin_failure(None, "unknown_email");
             return Err((
                 StatusCode::UNAUTHORIZED,
                 Json(ApiResponse::error(
@@ -215,6 +381,8 @@ pub async fn signin_handler(
     // Check if account is locked (with automatic unlock logic)
     match db.is_account_locked(&payload.email) {
         Ok(true) => {
+            log_signin_failure(Some(user.id), "account_locked");
+
             // Account is still locked, check remaining time
             let remaining_time = db
                 .get_account_lock_remaining_time(&payload.email)
@@ -232,11 +400,8 @@ pub async fn signin_handler(
                         StatusCode::LOCKED,
                         Json(ApiResponse::error(
                             ErrorCode::ACCOUNT_LOCKED,
-                            &format!(
-                                "{}",
-                                ErrorMessage::ACCOUNT_LOCKED_WITH_COUNTDOWN
-                                    .replace("{}", &minutes_remaining.to_string())
-                            ),
+                            &ErrorMessage::ACCOUNT_LOCKED_WITH_COUNTDOWN
+                                .replace("{}", &minutes_remaining.to_string()),
                         )),
                     ));
                 }
@@ -277,8 +442,19 @@ pub async fn signin_handler(
         }
         Ok(false) => {
             // Password is incorrect, increment failed attempts
-            if let Err(e) = db.increment_failed_login_attempts(&payload.email) {
-                tracing::warn!("Could not increment failed login attempts: {}", e);
+            log_signin_failure(Some(user.id), "invalid_password");
+            match db.increment_failed_login_attempts(&payload.email) {
+                Ok(true) => {
+                    let _ = db.log_security_event(
+                        Some(user.id),
+                        "account_locked",
+                        Some(json!({ "failed_attempts": AccountLock::MAX_FAILED_ATTEMPTS })),
+                        true,
+                        Some(&client),
+                    );
+                }
+                Ok(false) => {}
+                Err(e) => tracing::warn!("Could not increment failed login attempts: {}", e),
             }
             return Err((
                 StatusCode::UNAUTHORIZED,
@@ -300,14 +476,143 @@ pub async fn signin_handler(
         }
     }
 
-    // Get user role
+    // Disabled and suspended accounts can't sign in
+    if let Some(rejection) = account_status_rejection(user.status, user.suspended_until) {
+        tracing::warn!(
+            "Signin refused for {} account: {}",
+            user.status,
+            payload.email
+        );
+        let _ = db.log_security_event(
+            Some(user.id),
+            "signin_blocked",
+            Some(json!({ "status": user.status })),
+            false,
+            Some(&client),
+        );
+        return Err(rejection);
+    }
+
+    // Apply the unverified email policy before starting a session
+    let unverified_policy = UnverifiedSigninPolicy::from_env();
+    if !auth_user.email_verified && unverified_policy == UnverifiedSigninPolicy::Deny {
+        tracing::warn!("Signin refused for unverified email: {}", payload.email);
+        log_signin_failure(Some(user.id), "email_not_verified");
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::error(
+                ErrorCode::EMAIL_NOT_VERIFIED,
+                ErrorMessage::EMAIL_VERIFICATION_REQUIRED,
+            )),
+        ));
+    }
+
+    // Get user role and the permissions it grants
     let role = match db.get_user_role(user.id) {
         Ok(r) => r.unwrap_or(Roles::USER.to_string()),
         Err(_) => Roles::USER.to_string(),
     };
+    let permissions = db.get_user_permissions(user.id).unwrap_or_default();
 
-    // Generate JWT token
-    let token = match JwtService::generate_token(user.id, &user.email, &role) {
+    // Start a session for this sign-in, noting whether the client is new for this account
+    let new_client = db
+        .is_new_sign_in_client(
+            user.id,
+            client.user_agent.as_deref(),
+            client.ip_address.as_deref(),
+        )
+        .unwrap_or_else(|e| {
+            tracing::warn!("Could not check sign-in client history: {}", e);
+            false
+        });
+    let session_id = match db.create_user_session(
+        user.id,
+        client.user_agent.as_deref(),
+        client.ip_address.as_deref(),
+    ) {
+        Ok(session_id) => session_id,
+        Err(e) => {
+            tracing::error!("Database error creating user session: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    ErrorCode::DATABASE_ERROR,
+                    ErrorMessage::SERVER_ERROR_OCCURRED,
+                )),
+            ));
+        }
+    };
+
+    // Warnings and emails use the user's language
+    let locale = I18nService::for_request(user.locale.as_deref(), &headers);
+
+    // Check password age against the role's rotation policy
+    let max_age_days = db.get_password_max_age_days(user.id).unwrap_or_else(|e| {
+        tracing::warn!("Could not load password rotation policy: {}", e);
+        None
+    });
+    let mut warnings = Vec::new();
+    let password_expired = match PasswordService::password_expiry(
+        auth_user.password_changed_at,
+        max_age_days,
+        Utc::now(),
+    ) {
+        PasswordExpiry::Expired { expired_at } => {
+            warnings.push(json!({
+                "code": ErrorCode::PASSWORD_EXPIRED,
+                "message": I18nService::translate(locale, ErrorMessage::PASSWORD_EXPIRED),
+                "expires_at": expired_at
+            }));
+            true
+        }
+        PasswordExpiry::ExpiringSoon {
+            expires_at,
+            days_remaining,
+        } => {
+            warnings.push(json!({
+                "code": ErrorCode::PASSWORD_EXPIRING,
+                "message": I18nService::translate(
+                    locale,
+                    &ErrorMessage::PASSWORD_EXPIRING_WITH_COUNTDOWN
+                        .replace("{}", &days_remaining.to_string()),
+                ),
+                "expires_at": expires_at,
+                "days_remaining": days_remaining
+            }));
+            false
+        }
+        PasswordExpiry::Valid { .. } | PasswordExpiry::NotApplicable => false,
+    };
+
+    // Generate JWT token (restricted if a password change or email verification is pending)
+    let password_change_required = auth_user.password_reset_required || password_expired;
+    if !auth_user.email_verified {
+        warnings.push(json!({
+            "code": ErrorCode::EMAIL_NOT_VERIFIED,
+            "message": I18nService::translate(locale, ErrorMessage::EMAIL_NOT_VERIFIED_NOTICE)
+        }));
+    }
+    let scope = if password_change_required {
+        Some(TokenScope::PASSWORD_CHANGE)
+    } else if !auth_user.email_verified && unverified_policy == UnverifiedSigninPolicy::Restrict {
+        Some(TokenScope::UNVERIFIED_EMAIL)
+    } else {
+        None
+    };
+    let token_result = match scope {
+        Some(scope) => {
+            JwtService::generate_restricted_token(user.id, &user.email, &role, session_id, scope)
+        }
+        None => JwtService::generate_session_token(
+            user.id,
+            &user.email,
+            &role,
+            session_id,
+            &permissions,
+            None,
+        ),
+    };
+    let token = match token_result {
         Ok(token) => token,
         Err(e) => {
             tracing::error!("JWT generation error: {}", e);
@@ -321,15 +626,35 @@ pub async fn signin_handler(
         }
     };
 
+    if new_client {
+        send_new_sign_in_alert(&db, &user.email, &client, locale);
+    }
+
     // Update last login
     if let Err(e) = db.update_last_login(user.id) {
         tracing::warn!("Could not update last login time: {}", e);
     }
 
+    let _ = db.log_security_event(
+        Some(user.id),
+        "signin_success",
+        Some(json!({
+            "session_id": session_id,
+            "new_client": new_client,
+            "restricted_scope": scope
+        })),
+        true,
+        Some(&client),
+    );
+
     tracing::info!("User {} successfully signed in", payload.email);
 
     Ok(Json(ApiResponse::success(json!({
         "token": token,
+        "password_change_required": password_change_required,
+        "password_expired": password_expired,
+        "email_verified": auth_user.email_verified,
+        "warnings": warnings,
         "user": {
             "id": user.id,
             "email": user.email,
@@ -341,24 +666,632 @@ pub async fn signin_handler(
 }
 
 /// Handler for user logout
-pub async fn logout_handler(Json(payload): Json<AuthLogoutRequest>) -> Json<Value> {
+pub async fn logout_handler(
+    State(db): State<Arc<Database>>,
+    Json(payload): Json<AuthLogoutRequest>,
+) -> Json<Value> {
     tracing::info!("Logout request received. Token: {:?}", payload.token);
 
-    // In a production system, you might want to:
-    // 1. Add token to a blacklist/revocation list
-    // 2. Clean up any related sessions
-    // 3. Log the logout event with user info
-
-    // For now, we'll just validate the token exists
-    match JwtService::validate_token(&payload.token) {
-        Ok(_) => {
+    // Revoke the session behind the token so it can't be used again
+    match JwtService::extract_session_id(&payload.token) {
+        Ok(Some(session_id)) => {
+            if let Err(e) = db.revoke_user_session(session_id, "logout") {
+                tracing::warn!("Could not revoke session on logout: {}", e);
+            }
             tracing::info!("User successfully logged out");
             Json(ApiResponse::success(json!(null)))
         }
-        Err(_) => {
+        Ok(None) | Err(_) => {
             // Even if token is invalid, we'll return success
             // to avoid leaking information about token validity
             Json(ApiResponse::success(json!(null)))
         }
     }
 }
+
+/// Error response for an account whose status blocks access, or `None` if it may continue
+pub(crate) fn account_status_rejection(
+    status: AccountStatus,
+    suspended_until: Option<DateTime<Utc>>,
+) -> Option<(StatusCode, Json<Value>)> {
+    if !status.blocks_access(suspended_until, Utc::now()) {
+        return None;
+    }
+
+    let error = match (status, suspended_until) {
+        (AccountStatus::Suspended, Some(until)) => ApiResponse::error(
+            ErrorCode::ACCOUNT_SUSPENDED,
+            &ErrorMessage::ACCOUNT_SUSPENDED_UNTIL
+                .replace("{}", &until.format("%Y-%m-%d %H:%M UTC").to_string()),
+        ),
+        (AccountStatus::Suspended, None) => ApiResponse::error(
+            ErrorCode::ACCOUNT_SUSPENDED,
+            ErrorMessage::ACCOUNT_SUSPENDED,
+        ),
+        _ => ApiResponse::error(ErrorCode::ACCOUNT_DISABLED, ErrorMessage::ACCOUNT_DISABLED),
+    };
+
+    Some((StatusCode::FORBIDDEN, Json(error)))
+}
+
+/// Issue a password reset token for a user and email them the reset link
+pub(crate) fn send_password_reset_email(
+    db: &Database,
+    user_id: Uuid,
+    email: &str,
+    requested_by: Option<Uuid>,
+    locale: Locale,
+) -> anyhow::Result<()> {
+    let (token, token_hash) = OneTimeToken::generate();
+
+    let reset_link = format!(
+        "{}/reset-password?token={}",
+        MailService::app_base_url(),
+        EmailMessage::SECRET_PLACEHOLDER
+    );
+    let message = EmailTemplate::PasswordReset
+        .render(
+            locale,
+            email,
+            &[
+                ("link", &reset_link),
+                ("ttl_minutes", &PasswordReset::TOKEN_TTL_MINUTES.to_string()),
+            ],
+        )?
+        .with_secret(&token);
+
+    // The token and its email commit together, so neither exists without the other
+    db.create_password_reset_token(user_id, &token_hash, requested_by, &message)?;
+
+    Ok(())
+}
+
+/// Email the user about a sign-in from a device or address not seen before
+fn send_new_sign_in_alert(db: &Database, email: &str, client: &ClientInfo, locale: Locale) {
+    let time = Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string();
+    let link = format!("{}/forgot-password", MailService::app_base_url());
+
+    let queued = EmailTemplate::NewSignIn
+        .render(
+            locale,
+            email,
+            &[
+                ("time", &time),
+                (
+                    "ip_address",
+                    client.ip_address.as_deref().unwrap_or("unknown"),
+                ),
+                (
+                    "user_agent",
+                    client.user_agent.as_deref().unwrap_or("unknown"),
+                ),
+                ("link", &link),
+            ],
+        )
+        .map_err(anyhow::Error::from)
+        .and_then(|message| db.queue_email(&message));
+
+    if let Err(e) = queued {
+        tracing::warn!("Could not queue new sign-in alert for {}: {}", email, e);
+    }
+}
+
+/// Handler for requesting a password reset email
+///
+/// Always returns the same response, before the account is even looked up, so neither
+/// the body nor the timing can be used to discover registered emails.
+pub async fn password_reset_request_handler(
+    State(db): State<Arc<Database>>,
+    headers: HeaderMap,
+    Json(payload): Json<PasswordResetRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!("Password reset requested");
+
+    if payload.email.is_empty() || !payload.email.contains('@') {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                ErrorCode::VALIDATION_ERROR,
+                ErrorMessage::INVALID_INPUT_DATA,
+            )),
+        ));
+    }
+
+    // Deliberately not awaited: the response must not wait on the account lookup
+    tokio::task::spawn_blocking(move || issue_password_reset(&db, &payload.email, &headers));
+
+    Ok(Json(ApiResponse::success(json!({
+        "message": "If an account exists for this email, a password reset link has been sent."
+    }))))
+}
+
+/// Queue a reset email for the account registered under `email`, if there is one
+fn issue_password_reset(db: &Database, email: &str, headers: &HeaderMap) {
+    match db.find_user_by_email(email) {
+        Ok(Some(user)) => {
+            let now = Utc::now();
+            let (requested_last_hour, last_requested_at) =
+                match db.get_password_reset_request_stats(user.id, now - Duration::hours(1)) {
+                    Ok(stats) => stats,
+                    Err(e) => {
+                        tracing::error!("Database error checking password reset limit: {}", e);
+                        return;
+                    }
+                };
+
+            // A link sent moments ago is still valid, so a repeat request sends nothing
+            let cooling_down = last_requested_at.is_some_and(|requested| {
+                requested + Duration::seconds(PasswordReset::REQUEST_COOLDOWN_SECONDS) > now
+            });
+            if cooling_down || requested_last_hour >= PasswordReset::MAX_REQUESTS_PER_HOUR {
+                tracing::warn!("Password reset request rate limited for user {}", user.id);
+                return;
+            }
+
+            let locale = I18nService::for_request(user.locale.as_deref(), headers);
+            if let Err(e) = send_password_reset_email(db, user.id, &user.email, None, locale) {
+                tracing::error!("Failed to issue password reset for user {}: {}", user.id, e);
+            } else {
+                let _ = db.log_security_event(
+                    Some(user.id),
+                    "password_reset_requested",
+                    None,
+                    true,
+                    Some(&ClientInfo::from_headers(headers)),
+                );
+            }
+        }
+        Ok(None) => {}
+        Err(e) => {
+            tracing::error!("Database error finding user for password reset: {}", e);
+        }
+    }
+}
+
+/// Handler for completing a password reset with an emailed token
+pub async fn password_reset_confirm_handler(
+    State(db): State<Arc<Database>>,
+    headers: HeaderMap,
+    Json(payload): Json<PasswordResetConfirmRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!("Password reset confirmation received");
+
+    let invalid_token = || {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                ErrorCode::RESET_TOKEN_INVALID,
+                ErrorMessage::RESET_TOKEN_INVALID_OR_EXPIRED,
+            )),
+        )
+    };
+
+    if payload.token.trim().is_empty() {
+        return Err(invalid_token());
+    }
+
+    let (token_id, user_id) =
+        match db.find_valid_password_reset_token(&OneTimeToken::hash(&payload.token)) {
+            Ok(Some(token)) => token,
+            Ok(None) => return Err(invalid_token()),
+            Err(e) => {
+                tracing::error!("Database error finding password reset token: {}", e);
+                return Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::error(
+                        ErrorCode::DATABASE_ERROR,
+                        ErrorMessage::SERVER_ERROR_OCCURRED,
+                    )),
+                ));
+            }
+        };
+
+    // Validate new password against the policy
+    match PasswordService::validate_new_password(&payload.new_password).await {
+        Ok(()) => {}
+        Err(PasswordError::Breached) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error(
+                    ErrorCode::PASSWORD_BREACHED,
+                    ErrorMessage::PASSWORD_FOUND_IN_BREACH,
+                )),
+            ));
+        }
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error(
+                    ErrorCode::WEAK_PASSWORD,
+                    ErrorMessage::PASSWORD_TOO_WEAK,
+                )),
+            ));
+        }
+    }
+
+    let password_hash = match PasswordService::hash_password(&payload.new_password) {
+        Ok(hash) => hash,
+        Err(e) => {
+            tracing::error!("Password hashing error: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    ErrorCode::INTERNAL_SERVER_ERROR,
+                    ErrorMessage::SERVER_ERROR_OCCURRED,
+                )),
+            ));
+        }
+    };
+
+    // Checking the password history runs bcrypt, so keep it off the async workers
+    let updater = db.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        updater.complete_password_reset(token_id, user_id, &payload.new_password, &password_hash)
+    })
+    .await
+    .unwrap_or_else(|e| Err(anyhow::anyhow!(e)));
+
+    match result {
+        Ok(PasswordUpdate::Updated) => {}
+        Ok(PasswordUpdate::Reused) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error(
+                    ErrorCode::PASSWORD_REUSED,
+                    ErrorMessage::PASSWORD_RECENTLY_USED,
+                )),
+            ));
+        }
+        Ok(PasswordUpdate::TokenUsed) => return Err(invalid_token()),
+        Err(e) => {
+            tracing::error!("Database error completing password reset: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    ErrorCode::DATABASE_ERROR,
+                    ErrorMessage::PASSWORD_RESET_FAILED,
+                )),
+            ));
+        }
+    }
+
+    // Anyone holding an old session has to sign in with the new password
+    if let Err(e) = db.revoke_all_user_sessions(user_id, "password_reset") {
+        tracing::warn!("Failed to revoke user sessions: {}", e);
+    }
+
+    let _ = db.log_security_event(
+        Some(user_id),
+        "password_reset_completed",
+        None,
+        true,
+        Some(&ClientInfo::from_headers(&headers)),
+    );
+
+    Ok(Json(ApiResponse::success(json!({
+        "message": "Your password has been reset. Please sign in with your new password."
+    }))))
+}
+
+/// Issue an email verification token for a user and email them the verification link
+pub(crate) fn send_verification_email(
+    db: &Database,
+    user_id: Uuid,
+    email: &str,
+    locale: Locale,
+) -> anyhow::Result<()> {
+    let (token, token_hash) = OneTimeToken::generate();
+
+    let verify_link = format!(
+        "{}/verify-email?token={}",
+        MailService::app_base_url(),
+        EmailMessage::SECRET_PLACEHOLDER
+    );
+    let message = EmailTemplate::EmailVerification
+        .render(
+            locale,
+            email,
+            &[
+                ("link", &verify_link),
+                ("ttl_hours", &EmailVerification::TOKEN_TTL_HOURS.to_string()),
+            ],
+        )?
+        .with_secret(&token);
+
+    // The token and its email commit together, so neither exists without the other
+    db.create_email_verification_token(user_id, &token_hash, &message)?;
+
+    Ok(())
+}
+
+/// Handler for verifying an email address with an emailed token
+pub async fn verify_email_handler(
+    State(db): State<Arc<Database>>,
+    headers: HeaderMap,
+    Json(payload): Json<VerifyEmailRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!("Email verification request received");
+
+    let invalid_token = || {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                ErrorCode::VERIFICATION_TOKEN_INVALID,
+                ErrorMessage::VERIFICATION_TOKEN_INVALID_OR_EXPIRED,
+            )),
+        )
+    };
+
+    if payload.token.trim().is_empty() {
+        return Err(invalid_token());
+    }
+
+    match db.verify_email_with_token(&OneTimeToken::hash(&payload.token)) {
+        Ok(Some(user_id)) => {
+            let _ = db.log_security_event(
+                Some(user_id),
+                "email_verified",
+                None,
+                true,
+                Some(&ClientInfo::from_headers(&headers)),
+            );
+
+            Ok(Json(ApiResponse::success(json!({
+                "message": "Your email address has been verified",
+                "email_verified": true
+            }))))
+        }
+        Ok(None) => Err(invalid_token()),
+        Err(e) => {
+            tracing::error!("Database error verifying email: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    ErrorCode::DATABASE_ERROR,
+                    ErrorMessage::SERVER_ERROR_OCCURRED,
+                )),
+            ))
+        }
+    }
+}
+
+/// Handler for resending the verification email
+///
+/// Always returns the same response, before the account is looked up, so unknown,
+/// verified and rate limited emails can't be told apart; sends over the per-account
+/// limit are dropped silently.
+pub async fn resend_verification_handler(
+    State(db): State<Arc<Database>>,
+    headers: HeaderMap,
+    Json(payload): Json<ResendVerificationRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!("Verification email resend requested");
+
+    if payload.email.is_empty() || !payload.email.contains('@') {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                ErrorCode::VALIDATION_ERROR,
+                ErrorMessage::INVALID_INPUT_DATA,
+            )),
+        ));
+    }
+
+    // Deliberately not awaited: the response must not wait on the account lookup
+    tokio::task::spawn_blocking(move || issue_verification_resend(&db, &payload.email, &headers));
+
+    Ok(Json(ApiResponse::success(json!({
+        "message": "If an unverified account exists for this email, a verification link has been sent."
+    }))))
+}
+
+/// Queue a new verification email for the unverified account registered under `email`,
+/// unless it is within its resend limits
+fn issue_verification_resend(db: &Database, email: &str, headers: &HeaderMap) {
+    let auth_user = match db.find_auth_user_by_email(email) {
+        Ok(Some(auth_user)) if !auth_user.email_verified => auth_user,
+        Ok(_) => return,
+        Err(e) => {
+            tracing::error!("Database error finding user for verification resend: {}", e);
+            return;
+        }
+    };
+
+    let now = Utc::now();
+    let (sent_last_hour, last_sent_at) =
+        match db.get_email_verification_send_stats(auth_user.user_id, now - Duration::hours(1)) {
+            Ok(stats) => stats,
+            Err(e) => {
+                tracing::error!("Database error checking verification resend limit: {}", e);
+                return;
+            }
+        };
+
+    let cooling_down = last_sent_at.is_some_and(|sent| {
+        sent + Duration::seconds(EmailVerification::RESEND_COOLDOWN_SECONDS) > now
+    });
+    if cooling_down || sent_last_hour >= EmailVerification::MAX_SENDS_PER_HOUR {
+        tracing::warn!(
+            "Verification resend rate limited for user {}",
+            auth_user.user_id
+        );
+        return;
+    }
+
+    let preference = db.get_user_locale(auth_user.user_id).unwrap_or_default();
+    let locale = I18nService::for_request(preference.as_deref(), headers);
+    if let Err(e) = send_verification_email(db, auth_user.user_id, &auth_user.email, locale) {
+        tracing::error!(
+            "Failed to resend verification email to user {}: {}",
+            auth_user.user_id,
+            e
+        );
+    } else {
+        let _ = db.log_security_event(
+            Some(auth_user.user_id),
+            "email_verification_resent",
+            None,
+            true,
+            Some(&ClientInfo::from_headers(headers)),
+        );
+    }
+}
+
+/// Handler for confirming an email change with the token sent to the new address
+///
+/// Switches the sign-in email and signs the account out everywhere.
+pub async fn confirm_email_change_handler(
+    State(db): State<Arc<Database>>,
+    headers: HeaderMap,
+    Json(payload): Json<EmailChangeTokenRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!("Email change confirmation received");
+
+    let invalid_token = || {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                ErrorCode::EMAIL_CHANGE_TOKEN_INVALID,
+                ErrorMessage::EMAIL_CHANGE_TOKEN_INVALID_OR_EXPIRED,
+            )),
+        )
+    };
+
+    if payload.token.trim().is_empty() {
+        return Err(invalid_token());
+    }
+
+    match db.confirm_email_change(&OneTimeToken::hash(&payload.token)) {
+        Ok(EmailChangeConfirmation::Confirmed {
+            user_id,
+            old_email,
+            new_email,
+        }) => {
+            let revoked_sessions = db
+                .revoke_all_user_sessions(user_id, "email_changed")
+                .unwrap_or_else(|e| {
+                    tracing::warn!("Failed to revoke sessions after email change: {}", e);
+                    0
+                });
+
+            let _ = db.log_security_event(
+                Some(user_id),
+                "email_changed",
+                Some(json!({
+                    "old_email": old_email,
+                    "new_email": new_email,
+                    "revoked_sessions": revoked_sessions
+                })),
+                true,
+                Some(&ClientInfo::from_headers(&headers)),
+            );
+
+            Ok(Json(ApiResponse::success(json!({
+                "message": "Your email address has been changed. Please sign in with the new address.",
+                "email": new_email
+            }))))
+        }
+        Ok(EmailChangeConfirmation::EmailTaken) => Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::error(
+                ErrorCode::USER_ALREADY_EXISTS,
+                ErrorMessage::EMAIL_ALREADY_REGISTERED,
+            )),
+        )),
+        Ok(EmailChangeConfirmation::Invalid) => Err(invalid_token()),
+        Err(e) => {
+            tracing::error!("Database error confirming email change: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    ErrorCode::DATABASE_ERROR,
+                    ErrorMessage::EMAIL_CHANGE_FAILED,
+                )),
+            ))
+        }
+    }
+}
+
+/// Handler for cancelling an email change with the link sent to the old address
+///
+/// A change that was already confirmed is undone and all sessions are revoked.
+pub async fn cancel_email_change_handler(
+    State(db): State<Arc<Database>>,
+    headers: HeaderMap,
+    Json(payload): Json<EmailChangeTokenRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!("Email change cancellation received");
+
+    let invalid_token = || {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                ErrorCode::EMAIL_CHANGE_TOKEN_INVALID,
+                ErrorMessage::EMAIL_CHANGE_TOKEN_INVALID_OR_EXPIRED,
+            )),
+        )
+    };
+
+    if payload.token.trim().is_empty() {
+        return Err(invalid_token());
+    }
+
+    match db.cancel_email_change(&OneTimeToken::hash(&payload.token)) {
+        Ok(EmailChangeCancellation::Cancelled { user_id, reverted }) => {
+            // Whoever confirmed the change may still be signed in
+            let revoked_sessions = if reverted {
+                db.revoke_all_user_sessions(user_id, "email_change_reverted")
+                    .unwrap_or_else(|e| {
+                        tracing::warn!("Failed to revoke sessions after email revert: {}", e);
+                        0
+                    })
+            } else {
+                0
+            };
+
+            let _ = db.log_security_event(
+                Some(user_id),
+                "email_change_cancelled",
+                Some(json!({
+                    "reverted": reverted,
+                    "revoked_sessions": revoked_sessions
+                })),
+                true,
+                Some(&ClientInfo::from_headers(&headers)),
+            );
+
+            Ok(Json(ApiResponse::success(json!({
+                "message": "The email change has been cancelled. We recommend resetting your password.",
+                "reverted": reverted
+            }))))
+        }
+        Ok(EmailChangeCancellation::AddressTaken { user_id }) => {
+            let _ = db.log_security_event(
+                Some(user_id),
+                "email_change_cancelled",
+                Some(json!({ "reverted": false, "reason": "old_email_taken" })),
+                false,
+                Some(&ClientInfo::from_headers(&headers)),
+            );
+
+            Err((
+                StatusCode::CONFLICT,
+                Json(ApiResponse::error(
+                    ErrorCode::USER_ALREADY_EXISTS,
+                    ErrorMessage::EMAIL_CHANGE_REVERT_BLOCKED,
+                )),
+            ))
+        }
+        Ok(EmailChangeCancellation::Invalid) => Err(invalid_token()),
+        Err(e) => {
+            tracing::error!("Database error cancelling email change: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    ErrorCode::DATABASE_ERROR,
+                    ErrorMessage::EMAIL_CHANGE_FAILED,
+                )),
+            ))
+        }
+    }
+}
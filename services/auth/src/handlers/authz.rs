@@ -0,0 +1,181 @@
+use axum::{extract::State, http::StatusCode, response::Json};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::database::{AuthzRequest, Database};
+use crate::models::ApiResponse;
+use crate::{Authz, ErrorCode, ErrorMessage};
+
+// ========================================
+// Request / Response Types
+// ========================================
+
+/// The resource an action is performed on; `organization_id` scopes the check to the
+/// subject's grants in that organization
+///
+/// Decisions are made from the resource type: an `id` is accepted and echoed back, and
+/// the subject may act on that object if they may act on its type. Sharing of single
+/// objects is decided by `/relations/check`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthzResource {
+    #[serde(rename = "type")]
+    pub resource_type: String,
+    pub id: Option<String>,
+    pub organization_id: Option<String>,
+}
+
+/// Can `subject` (a user ID) perform `action` on `resource`?
+///
+/// `action` is either a permission name (`notes:write`) or a bare action (`write`) that
+/// is combined with the resource type.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthzCheckRequest {
+    pub subject: String,
+    pub action: String,
+    pub resource: AuthzResource,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuthzBatchCheckRequest {
+    pub checks: Vec<AuthzCheckRequest>,
+}
+
+// ========================================
+// Helpers
+// ========================================
+
+fn authz_error(status: StatusCode, code: &str, message: &str) -> (StatusCode, Json<Value>) {
+    (status, Json(ApiResponse::error(code, message)))
+}
+
+impl AuthzCheckRequest {
+    /// The permission the check asks about
+    pub fn permission(&self) -> String {
+        if self.action.contains(':') {
+            self.action.clone()
+        } else {
+            format!("{}:{}", self.resource.resource_type, self.action)
+        }
+    }
+
+    /// Parse into a decision request, or `None` if an ID is malformed or a field is empty
+    pub fn to_request(&self) -> Option<AuthzRequest> {
+        if self.action.trim().is_empty() || self.resource.resource_type.trim().is_empty() {
+            return None;
+        }
+
+        let organization_id = match &self.resource.organization_id {
+            Some(id) => Some(id.parse::<Uuid>().ok()?),
+            None => None,
+        };
+
+        Some(AuthzRequest {
+            user_id: self.subject.parse().ok()?,
+            organization_id,
+            permission: self.permission(),
+        })
+    }
+}
+
+/// Parse every check, rejecting the whole request if any is invalid
+fn parse_checks(
+    checks: &[AuthzCheckRequest],
+) -> Result<Vec<AuthzRequest>, (StatusCode, Json<Value>)> {
+    checks
+        .iter()
+        .map(|check| {
+            check.to_request().ok_or_else(|| {
+                authz_error(
+                    StatusCode::BAD_REQUEST,
+                    ErrorCode::VALIDATION_ERROR,
+                    ErrorMessage::AUTHZ_REQUEST_INVALID,
+                )
+            })
+        })
+        .collect()
+}
+
+/// Decide one check and describe the decision
+fn decide(
+    db: &Database,
+    check: &AuthzCheckRequest,
+    request: &AuthzRequest,
+) -> Result<Value, (StatusCode, Json<Value>)> {
+    let (decision, cached) = db.authorize(request).map_err(|e| {
+        tracing::error!("Database error deciding authorization: {}", e);
+        authz_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::DATABASE_ERROR,
+            ErrorMessage::AUTHZ_CHECK_FAILED,
+        )
+    })?;
+
+    tracing::debug!(
+        "Authorization {}: {} {} ({})",
+        if decision.is_allowed() {
+            "allowed"
+        } else {
+            "denied"
+        },
+        request.user_id,
+        request.permission,
+        decision.reason_code()
+    );
+
+    Ok(json!({
+        "allowed": decision.is_allowed(),
+        "reason": decision.reason_code(),
+        "via": decision.granted_via(),
+        "subject": request.user_id,
+        "permission": request.permission,
+        "resource": {
+            "type": check.resource.resource_type,
+            "id": check.resource.id,
+            "organization_id": request.organization_id
+        },
+        "cached": cached
+    }))
+}
+
+// ========================================
+// Authorization Decision Handlers
+// ========================================
+
+/// Decide whether a subject may perform an action on a resource, for other services on
+/// the internal network
+pub async fn authz_check_handler(
+    State(db): State<Arc<Database>>,
+    Json(payload): Json<AuthzCheckRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let requests = parse_checks(std::slice::from_ref(&payload))?;
+
+    let decision = decide(&db, &payload, &requests[0])?;
+    Ok(Json(ApiResponse::success(decision)))
+}
+
+/// Decide several checks at once; results are in the order of the checks
+pub async fn authz_batch_check_handler(
+    State(db): State<Arc<Database>>,
+    Json(payload): Json<AuthzBatchCheckRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    if payload.checks.is_empty() || payload.checks.len() > Authz::MAX_BATCH_SIZE {
+        return Err(authz_error(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::VALIDATION_ERROR,
+            &ErrorMessage::AUTHZ_BATCH_SIZE_INVALID
+                .replace("{}", &Authz::MAX_BATCH_SIZE.to_string()),
+        ));
+    }
+    let requests = parse_checks(&payload.checks)?;
+
+    let results = payload
+        .checks
+        .iter()
+        .zip(&requests)
+        .map(|(check, request)| decide(&db, check, request))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Json(ApiResponse::success(json!({ "results": results }))))
+}
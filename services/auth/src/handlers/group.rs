@@ -0,0 +1,630 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::database::{Database, GroupChanges, GroupMemberAddition, GroupRef, GroupWrite};
+use crate::handlers::admin::present;
+use crate::handlers::organization::{
+    internal_error, member_context, org_error, parse_id, MemberContext,
+};
+use crate::models::database::NewGroup;
+use crate::models::ApiResponse;
+use crate::utils::ClientInfo;
+use crate::{ErrorCode, ErrorMessage, Groups, Permissions};
+
+// ========================================
+// Request / Response Types
+// ========================================
+
+#[derive(Debug, Serialize)]
+pub struct GroupView {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub permissions: Vec<String>,
+    pub member_count: i64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GroupMemberView {
+    pub user_id: String,
+    pub email: String,
+    pub name: String,
+    pub added_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateGroupRequest {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+/// Omitted fields stay as they are; `null` clears `description`
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateGroupRequest {
+    pub name: Option<String>,
+    #[serde(default, deserialize_with = "present")]
+    pub description: Option<Option<String>>,
+    pub permissions: Option<Vec<String>>, // Replaces the group's grants
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateMemberPermissionsRequest {
+    pub permissions: Vec<String>, // Replaces the member's direct grants
+}
+
+/// Either `group_id` or `group` (the name) identifies the group
+#[derive(Debug, Deserialize)]
+pub struct GroupMembershipQuery {
+    pub organization_id: String,
+    pub user_id: String,
+    pub group_id: Option<String>,
+    pub group: Option<String>,
+}
+
+// ========================================
+// Helpers
+// ========================================
+
+fn group_not_found() -> (StatusCode, Json<Value>) {
+    org_error(
+        StatusCode::NOT_FOUND,
+        ErrorCode::GROUP_NOT_FOUND,
+        ErrorMessage::GROUP_NOT_FOUND,
+    )
+}
+
+fn group_name_taken() -> (StatusCode, Json<Value>) {
+    org_error(
+        StatusCode::CONFLICT,
+        ErrorCode::GROUP_ALREADY_EXISTS,
+        ErrorMessage::GROUP_ALREADY_EXISTS,
+    )
+}
+
+fn check_group_name(name: &str) -> Result<(), (StatusCode, Json<Value>)> {
+    if Groups::is_valid_name(name) {
+        Ok(())
+    } else {
+        Err(org_error(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::VALIDATION_ERROR,
+            ErrorMessage::GROUP_NAME_INVALID,
+        ))
+    }
+}
+
+/// Check requested permission names exist as organization permissions, as a 400 if not
+fn check_permissions_exist(
+    db: &Database,
+    permissions: &[String],
+) -> Result<(), (StatusCode, Json<Value>)> {
+    match db.unknown_permissions(permissions, Some(Permissions::ORGANIZATION_SCOPE)) {
+        Ok(unknown) if unknown.is_empty() => Ok(()),
+        Ok(unknown) => {
+            tracing::warn!("Unknown organization permissions requested: {:?}", unknown);
+            Err(org_error(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::VALIDATION_ERROR,
+                ErrorMessage::ORGANIZATION_PERMISSIONS_INVALID,
+            ))
+        }
+        Err(e) => Err(internal_error("checking permissions", e)),
+    }
+}
+
+/// Members can only grant or take away permissions they hold in the organization
+/// themselves, so groups and direct grants can't be used to raise anyone above them
+fn require_permissions_held<'a>(
+    db: &Database,
+    context: &MemberContext,
+    permissions: impl IntoIterator<Item = &'a String>,
+) -> Result<(), (StatusCode, Json<Value>)> {
+    let held = match db.organization_permissions(context.organization.id, context.user_id) {
+        Ok(Some(held)) => held,
+        Ok(None) => {
+            return Err(org_error(
+                StatusCode::NOT_FOUND,
+                ErrorCode::ORGANIZATION_NOT_FOUND,
+                ErrorMessage::ORGANIZATION_NOT_FOUND,
+            ));
+        }
+        Err(e) => return Err(internal_error("loading organization permissions", e)),
+    };
+
+    let missing: Vec<&String> = permissions
+        .into_iter()
+        .filter(|permission| !held.has(permission))
+        .collect();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        tracing::warn!(
+            "User {} tried to grant permissions they don't hold: {:?}",
+            context.user_id,
+            missing
+        );
+        Err(org_error(
+            StatusCode::FORBIDDEN,
+            ErrorCode::INSUFFICIENT_PERMISSIONS,
+            ErrorMessage::ORGANIZATION_PERMISSION_GRANT_FORBIDDEN,
+        ))
+    }
+}
+
+/// Permissions in exactly one of `before` and `after`
+fn changed_permissions(before: &[String], after: &[String]) -> BTreeSet<String> {
+    let before: BTreeSet<&String> = before.iter().collect();
+    let after: BTreeSet<&String> = after.iter().collect();
+    before
+        .symmetric_difference(&after)
+        .map(|permission| permission.to_string())
+        .collect()
+}
+
+/// Load a group of the caller's organization with its permissions, as a 404 if there is none
+fn find_group(
+    db: &Database,
+    context: &MemberContext,
+    group_id: &str,
+) -> Result<(crate::models::database::Group, Vec<String>), (StatusCode, Json<Value>)> {
+    let group_id = parse_id(group_id, ErrorMessage::GROUP_ID_FORMAT_INVALID)?;
+    match db.find_group(context.organization.id, group_id) {
+        Ok(Some(group)) => Ok(group),
+        Ok(None) => Err(group_not_found()),
+        Err(e) => Err(internal_error("loading group", e)),
+    }
+}
+
+// ========================================
+// Group Handlers
+// ========================================
+
+/// List an organization's groups (members only)
+pub async fn get_groups_handler(
+    State(db): State<Arc<Database>>,
+    Path(organization_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let context = member_context(&db, &headers, &organization_id)?;
+
+    match db.list_groups(context.organization.id) {
+        Ok(groups) => Ok(Json(ApiResponse::success(json!({ "groups": groups })))),
+        Err(e) => Err(internal_error("listing groups", e)),
+    }
+}
+
+/// Create a group (organization administrators only)
+pub async fn create_group_handler(
+    State(db): State<Arc<Database>>,
+    Path(organization_id): Path<String>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateGroupRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!(
+        "Create group request: organization_id={}, {:?}",
+        organization_id,
+        payload
+    );
+
+    let context = member_context(&db, &headers, &organization_id)?;
+    context.require_admin()?;
+    check_group_name(&payload.name)?;
+    check_permissions_exist(&db, &payload.permissions)?;
+    require_permissions_held(&db, &context, &payload.permissions)?;
+
+    let group = NewGroup {
+        organization_id: context.organization.id,
+        name: payload.name.clone(),
+        description: payload.description.clone(),
+        created_by: Some(context.user_id),
+    };
+    match db.create_group(&group, &payload.permissions) {
+        Ok(GroupWrite::Saved(group)) => {
+            let _ = db.log_security_event(
+                Some(context.user_id),
+                "organization_group_created",
+                Some(json!({
+                    "organization_id": context.organization.id,
+                    "group_id": group.id,
+                    "name": group.name,
+                    "permissions": payload.permissions
+                })),
+                true,
+                Some(&ClientInfo::from_headers(&headers)),
+            );
+
+            Ok(Json(ApiResponse::success(json!({
+                "group": group,
+                "permissions": payload.permissions
+            }))))
+        }
+        Ok(GroupWrite::NameTaken) => Err(group_name_taken()),
+        Ok(GroupWrite::NotFound) => Err(group_not_found()),
+        Err(e) => Err(internal_error("creating group", e)),
+    }
+}
+
+/// Rename a group, or change its description or permissions (organization administrators only)
+pub async fn update_group_handler(
+    State(db): State<Arc<Database>>,
+    Path((organization_id, group_id)): Path<(String, String)>,
+    headers: HeaderMap,
+    Json(payload): Json<UpdateGroupRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!(
+        "Update group request: organization_id={}, group_id={}, {:?}",
+        organization_id,
+        group_id,
+        payload
+    );
+
+    let context = member_context(&db, &headers, &organization_id)?;
+    context.require_admin()?;
+    let (group, current_permissions) = find_group(&db, &context, &group_id)?;
+
+    if let Some(name) = &payload.name {
+        check_group_name(name)?;
+    }
+    if let Some(permissions) = &payload.permissions {
+        check_permissions_exist(&db, permissions)?;
+        require_permissions_held(
+            &db,
+            &context,
+            &changed_permissions(&current_permissions, permissions),
+        )?;
+    }
+
+    let changes = GroupChanges {
+        name: payload.name.clone(),
+        description: payload.description.clone(),
+        permissions: payload.permissions.clone(),
+    };
+    match db.update_group(context.organization.id, group.id, &changes) {
+        Ok(GroupWrite::Saved(updated)) => {
+            let _ = db.log_security_event(
+                Some(context.user_id),
+                "organization_group_updated",
+                Some(json!({
+                    "organization_id": context.organization.id,
+                    "group_id": group.id,
+                    "old_name": group.name,
+                    "new_name": updated.name,
+                    "old_permissions": current_permissions,
+                    "new_permissions": payload.permissions
+                })),
+                true,
+                Some(&ClientInfo::from_headers(&headers)),
+            );
+
+            Ok(Json(ApiResponse::success(json!({
+                "group": updated,
+                "permissions": payload.permissions.unwrap_or(current_permissions)
+            }))))
+        }
+        Ok(GroupWrite::NameTaken) => Err(group_name_taken()),
+        Ok(GroupWrite::NotFound) => Err(group_not_found()),
+        Err(e) => Err(internal_error("updating group", e)),
+    }
+}
+
+/// Delete a group; its members keep their other grants (organization administrators only)
+pub async fn delete_group_handler(
+    State(db): State<Arc<Database>>,
+    Path((organization_id, group_id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let context = member_context(&db, &headers, &organization_id)?;
+    context.require_admin()?;
+    let (group, permissions) = find_group(&db, &context, &group_id)?;
+    require_permissions_held(&db, &context, &permissions)?;
+
+    match db.delete_group(context.organization.id, group.id) {
+        Ok(Some(deleted)) => {
+            let _ = db.log_security_event(
+                Some(context.user_id),
+                "organization_group_deleted",
+                Some(json!({
+                    "organization_id": context.organization.id,
+                    "group_id": deleted.id,
+                    "name": deleted.name,
+                    "permissions": permissions
+                })),
+                true,
+                Some(&ClientInfo::from_headers(&headers)),
+            );
+
+            Ok(Json(ApiResponse::success(json!({
+                "message": "Group deleted",
+                "group_id": deleted.id
+            }))))
+        }
+        Ok(None) => Err(group_not_found()),
+        Err(e) => Err(internal_error("deleting group", e)),
+    }
+}
+
+// ========================================
+// Group Membership Handlers
+// ========================================
+
+/// List the members of a group (organization members only)
+pub async fn get_group_members_handler(
+    State(db): State<Arc<Database>>,
+    Path((organization_id, group_id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let context = member_context(&db, &headers, &organization_id)?;
+    let (group, permissions) = find_group(&db, &context, &group_id)?;
+
+    match db.list_group_members(group.id) {
+        Ok(members) => Ok(Json(ApiResponse::success(json!({
+            "group": group,
+            "permissions": permissions,
+            "members": members
+        })))),
+        Err(e) => Err(internal_error("listing group members", e)),
+    }
+}
+
+/// Add an organization member to a group (organization administrators only)
+pub async fn add_group_member_handler(
+    State(db): State<Arc<Database>>,
+    Path((organization_id, group_id, member_id)): Path<(String, String, String)>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!(
+        "Add group member request: organization_id={}, group_id={}, user_id={}",
+        organization_id,
+        group_id,
+        member_id
+    );
+
+    let context = member_context(&db, &headers, &organization_id)?;
+    context.require_admin()?;
+    let (group, permissions) = find_group(&db, &context, &group_id)?;
+    let member_id = parse_id(&member_id, ErrorMessage::USER_ID_FORMAT_INVALID)?;
+    require_permissions_held(&db, &context, &permissions)?;
+
+    match db.add_group_member(
+        context.organization.id,
+        group.id,
+        member_id,
+        context.user_id,
+    ) {
+        Ok(GroupMemberAddition::Added) => {
+            let _ = db.log_security_event(
+                Some(context.user_id),
+                "organization_group_member_added",
+                Some(json!({
+                    "organization_id": context.organization.id,
+                    "group_id": group.id,
+                    "group": group.name,
+                    "target_user_id": member_id
+                })),
+                true,
+                Some(&ClientInfo::from_headers(&headers)),
+            );
+
+            Ok(Json(ApiResponse::success(json!({
+                "group_id": group.id,
+                "user_id": member_id,
+                "added": true
+            }))))
+        }
+        Ok(GroupMemberAddition::AlreadyInGroup) => Ok(Json(ApiResponse::success(json!({
+            "group_id": group.id,
+            "user_id": member_id,
+            "added": false
+        })))),
+        Ok(GroupMemberAddition::GroupNotFound) => Err(group_not_found()),
+        Ok(GroupMemberAddition::NotOrganizationMember) => Err(org_error(
+            StatusCode::NOT_FOUND,
+            ErrorCode::USER_NOT_FOUND,
+            ErrorMessage::ORGANIZATION_MEMBER_NOT_FOUND,
+        )),
+        Err(e) => Err(internal_error("adding group member", e)),
+    }
+}
+
+/// Take a member out of a group (organization administrators only)
+pub async fn remove_group_member_handler(
+    State(db): State<Arc<Database>>,
+    Path((organization_id, group_id, member_id)): Path<(String, String, String)>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!(
+        "Remove group member request: organization_id={}, group_id={}, user_id={}",
+        organization_id,
+        group_id,
+        member_id
+    );
+
+    let context = member_context(&db, &headers, &organization_id)?;
+    context.require_admin()?;
+    let (group, permissions) = find_group(&db, &context, &group_id)?;
+    let member_id = parse_id(&member_id, ErrorMessage::USER_ID_FORMAT_INVALID)?;
+    require_permissions_held(&db, &context, &permissions)?;
+
+    match db.remove_group_member(context.organization.id, group.id, member_id) {
+        Ok(true) => {
+            let _ = db.log_security_event(
+                Some(context.user_id),
+                "organization_group_member_removed",
+                Some(json!({
+                    "organization_id": context.organization.id,
+                    "group_id": group.id,
+                    "group": group.name,
+                    "target_user_id": member_id
+                })),
+                true,
+                Some(&ClientInfo::from_headers(&headers)),
+            );
+
+            Ok(Json(ApiResponse::success(json!({
+                "message": "Member removed from the group",
+                "group_id": group.id,
+                "user_id": member_id
+            }))))
+        }
+        Ok(false) => Err(org_error(
+            StatusCode::NOT_FOUND,
+            ErrorCode::USER_NOT_FOUND,
+            ErrorMessage::GROUP_MEMBER_NOT_FOUND,
+        )),
+        Err(e) => Err(internal_error("removing group member", e)),
+    }
+}
+
+// ========================================
+// Member Permission Handlers
+// ========================================
+
+/// A member's effective permissions and where they come from (the member themselves or
+/// organization administrators)
+pub async fn get_member_permissions_handler(
+    State(db): State<Arc<Database>>,
+    Path((organization_id, member_id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let context = member_context(&db, &headers, &organization_id)?;
+    let member_id = parse_id(&member_id, ErrorMessage::USER_ID_FORMAT_INVALID)?;
+    if member_id != context.user_id {
+        context.require_admin()?;
+    }
+
+    match db.organization_permissions(context.organization.id, member_id) {
+        Ok(Some(permissions)) => Ok(Json(ApiResponse::success(json!({
+            "organization_id": context.organization.id,
+            "user_id": member_id,
+            "permissions": permissions
+        })))),
+        Ok(None) => Err(org_error(
+            StatusCode::NOT_FOUND,
+            ErrorCode::USER_NOT_FOUND,
+            ErrorMessage::ORGANIZATION_MEMBER_NOT_FOUND,
+        )),
+        Err(e) => Err(internal_error("loading organization permissions", e)),
+    }
+}
+
+/// Replace the permissions granted directly to a member (organization administrators only)
+pub async fn update_member_permissions_handler(
+    State(db): State<Arc<Database>>,
+    Path((organization_id, member_id)): Path<(String, String)>,
+    headers: HeaderMap,
+    Json(payload): Json<UpdateMemberPermissionsRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!(
+        "Update member permissions request: organization_id={}, user_id={}, {:?}",
+        organization_id,
+        member_id,
+        payload
+    );
+
+    let context = member_context(&db, &headers, &organization_id)?;
+    context.require_admin()?;
+    let member_id = parse_id(&member_id, ErrorMessage::USER_ID_FORMAT_INVALID)?;
+    check_permissions_exist(&db, &payload.permissions)?;
+
+    let current = match db.organization_permissions(context.organization.id, member_id) {
+        Ok(Some(current)) => current.direct_permissions,
+        Ok(None) => {
+            return Err(org_error(
+                StatusCode::NOT_FOUND,
+                ErrorCode::USER_NOT_FOUND,
+                ErrorMessage::ORGANIZATION_MEMBER_NOT_FOUND,
+            ));
+        }
+        Err(e) => return Err(internal_error("loading organization permissions", e)),
+    };
+    require_permissions_held(
+        &db,
+        &context,
+        &changed_permissions(&current, &payload.permissions),
+    )?;
+
+    match db.set_member_permissions(
+        context.organization.id,
+        member_id,
+        &payload.permissions,
+        context.user_id,
+    ) {
+        Ok(true) => {
+            let _ = db.log_security_event(
+                Some(context.user_id),
+                "organization_member_permissions_changed",
+                Some(json!({
+                    "organization_id": context.organization.id,
+                    "target_user_id": member_id,
+                    "old_permissions": current,
+                    "new_permissions": payload.permissions
+                })),
+                true,
+                Some(&ClientInfo::from_headers(&headers)),
+            );
+
+            Ok(Json(ApiResponse::success(json!({
+                "user_id": member_id,
+                "direct_permissions": payload.permissions
+            }))))
+        }
+        Ok(false) => Err(org_error(
+            StatusCode::NOT_FOUND,
+            ErrorCode::USER_NOT_FOUND,
+            ErrorMessage::ORGANIZATION_MEMBER_NOT_FOUND,
+        )),
+        Err(e) => Err(internal_error("changing member permissions", e)),
+    }
+}
+
+// ========================================
+// Service Lookup Handlers
+// ========================================
+
+/// Whether a user is in a group, for other services (e.g. notes) on the internal network
+pub async fn group_membership_handler(
+    State(db): State<Arc<Database>>,
+    Query(query): Query<GroupMembershipQuery>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let invalid = || {
+        org_error(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::VALIDATION_ERROR,
+            ErrorMessage::GROUP_LOOKUP_INVALID,
+        )
+    };
+
+    let organization_id: Uuid = query.organization_id.parse().map_err(|_| invalid())?;
+    let user_id: Uuid = query.user_id.parse().map_err(|_| invalid())?;
+    let group = match (&query.group_id, &query.group) {
+        (Some(group_id), None) => GroupRef::Id(group_id.parse().map_err(|_| invalid())?),
+        (None, Some(name)) => GroupRef::Name(name),
+        _ => return Err(invalid()),
+    };
+
+    match db.is_group_member(organization_id, group, user_id) {
+        Ok(Some((group, member))) => Ok(Json(ApiResponse::success(json!({
+            "member": member,
+            "organization_id": organization_id,
+            "user_id": user_id,
+            "group": {
+                "id": group.id,
+                "name": group.name
+            }
+        })))),
+        Ok(None) => Err(group_not_found()),
+        Err(e) => Err(internal_error("checking group membership", e)),
+    }
+}
@@ -1,9 +1,17 @@
 pub mod admin;
 pub mod auth;
+pub mod authz;
+pub mod group;
+pub mod organization;
+pub mod relation;
 pub mod token;
 pub mod user;
 
 pub use admin::*;
 pub use auth::*;
+pub use authz::*;
+pub use group::*;
+pub use organization::*;
+pub use relation::*;
 pub use token::*;
 pub use user::*;
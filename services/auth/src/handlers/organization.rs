@@ -0,0 +1,756 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::database::{
+    is_organization_admin, Database, InvitationAcceptance, MembershipChange, MembershipRefusal,
+    OrganizationCreation, RoleHierarchy,
+};
+use crate::handlers::user::extract_session_claims;
+use crate::models::database::Organization;
+use crate::models::ApiResponse;
+use crate::utils::{
+    ActiveOrganization, ClientInfo, EmailMessage, EmailTemplate, I18nService, JwtService,
+    MailService, OneTimeToken,
+};
+use crate::{ErrorCode, ErrorMessage, Organizations, Roles};
+
+// ========================================
+// Request / Response Types
+// ========================================
+
+#[derive(Debug, Serialize)]
+pub struct OrganizationView {
+    pub id: String,
+    pub name: String,
+    pub slug: String,
+    pub role: String, // The caller's role in the organization
+    pub joined_at: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrganizationMemberView {
+    pub user_id: String,
+    pub email: String,
+    pub name: String,
+    pub role: String,
+    pub joined_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrganizationInvitationView {
+    pub id: String,
+    pub email: String,
+    pub role: String,
+    pub invited_by: Option<String>,
+    pub expires_at: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateOrganizationRequest {
+    pub name: String,
+    pub slug: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SwitchOrganizationRequest {
+    pub organization_id: Option<String>, // null leaves the current organization
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateMemberRoleRequest {
+    pub role: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateInvitationRequest {
+    pub email: String,
+    pub role: Option<String>, // Defaults to a regular member
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AcceptInvitationRequest {
+    pub token: String,
+}
+
+// ========================================
+// Helpers
+// ========================================
+
+pub(crate) fn org_error(
+    status: StatusCode,
+    code: &str,
+    message: &str,
+) -> (StatusCode, Json<Value>) {
+    (status, Json(ApiResponse::error(code, message)))
+}
+
+pub(crate) fn internal_error(context: &str, e: anyhow::Error) -> (StatusCode, Json<Value>) {
+    tracing::error!("Database error {}: {}", context, e);
+    org_error(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        ErrorCode::DATABASE_ERROR,
+        ErrorMessage::ORGANIZATION_OPERATION_FAILED,
+    )
+}
+
+pub(crate) fn parse_id(id: &str, message: &str) -> Result<Uuid, (StatusCode, Json<Value>)> {
+    id.parse().map_err(|_| {
+        org_error(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::VALIDATION_ERROR,
+            message,
+        )
+    })
+}
+
+/// The signed-in caller and their membership of the organization in the path
+pub(crate) struct MemberContext {
+    pub user_id: Uuid,
+    pub organization: Organization,
+    pub role: String,
+    pub hierarchy: Arc<RoleHierarchy>,
+}
+
+impl MemberContext {
+    pub fn is_admin(&self) -> bool {
+        is_organization_admin(&self.hierarchy, &self.role)
+    }
+
+    /// Organization administrators only
+    pub fn require_admin(&self) -> Result<(), (StatusCode, Json<Value>)> {
+        if self.is_admin() {
+            Ok(())
+        } else {
+            Err(org_error(
+                StatusCode::FORBIDDEN,
+                ErrorCode::INSUFFICIENT_PERMISSIONS,
+                ErrorMessage::ORGANIZATION_ADMIN_REQUIRED,
+            ))
+        }
+    }
+
+    /// Members can only hand out (or take away) roles their own role includes
+    pub fn require_grantable(&self, role: &str) -> Result<(), (StatusCode, Json<Value>)> {
+        if self.hierarchy.inherits(&self.role, role) {
+            Ok(())
+        } else {
+            Err(org_error(
+                StatusCode::FORBIDDEN,
+                ErrorCode::INSUFFICIENT_PERMISSIONS,
+                ErrorMessage::ORGANIZATION_ROLE_NOT_ALLOWED,
+            ))
+        }
+    }
+}
+
+/// Authenticate the caller and load their membership of `organization_id`
+///
+/// Non-members get the same 404 as a missing organization, so IDs can't be probed.
+pub(crate) fn member_context(
+    db: &Database,
+    headers: &HeaderMap,
+    organization_id: &str,
+) -> Result<MemberContext, (StatusCode, Json<Value>)> {
+    let (user_id, _, _) = extract_session_claims(headers, db, None)?;
+    let organization_id = parse_id(
+        organization_id,
+        ErrorMessage::ORGANIZATION_ID_FORMAT_INVALID,
+    )?;
+
+    let (organization, role) = match db.find_organization_membership(organization_id, user_id) {
+        Ok(Some(membership)) => membership,
+        Ok(None) => {
+            return Err(org_error(
+                StatusCode::NOT_FOUND,
+                ErrorCode::ORGANIZATION_NOT_FOUND,
+                ErrorMessage::ORGANIZATION_NOT_FOUND,
+            ));
+        }
+        Err(e) => return Err(internal_error("loading organization membership", e)),
+    };
+    let hierarchy = db
+        .role_hierarchy()
+        .map_err(|e| internal_error("loading role hierarchy", e))?;
+
+    Ok(MemberContext {
+        user_id,
+        organization,
+        role,
+        hierarchy,
+    })
+}
+
+/// Resolve a role name to its ID, as a 404 if there is no such role
+fn find_role_id(hierarchy: &RoleHierarchy, role: &str) -> Result<Uuid, (StatusCode, Json<Value>)> {
+    hierarchy.get(role).map(|role| role.id).ok_or_else(|| {
+        org_error(
+            StatusCode::NOT_FOUND,
+            ErrorCode::ROLE_NOT_FOUND,
+            ErrorMessage::ROLE_NOT_FOUND,
+        )
+    })
+}
+
+fn membership_change_error(refusal: MembershipRefusal) -> (StatusCode, Json<Value>) {
+    match refusal {
+        MembershipRefusal::NotMember => org_error(
+            StatusCode::NOT_FOUND,
+            ErrorCode::USER_NOT_FOUND,
+            ErrorMessage::ORGANIZATION_MEMBER_NOT_FOUND,
+        ),
+        MembershipRefusal::LastAdmin => org_error(
+            StatusCode::CONFLICT,
+            ErrorCode::LAST_ORGANIZATION_ADMIN,
+            ErrorMessage::LAST_ORGANIZATION_ADMIN,
+        ),
+    }
+}
+
+// ========================================
+// Organization Handlers
+// ========================================
+
+/// Create an organization; the caller becomes its first administrator
+pub async fn create_organization_handler(
+    State(db): State<Arc<Database>>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateOrganizationRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!("Create organization request: {:?}", payload);
+
+    let (user_id, _, _) = extract_session_claims(&headers, &db, None)?;
+
+    let name = payload.name.trim();
+    if !Organizations::is_valid_name(name) {
+        return Err(org_error(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::VALIDATION_ERROR,
+            ErrorMessage::ORGANIZATION_NAME_INVALID,
+        ));
+    }
+    if !Organizations::is_valid_slug(&payload.slug) {
+        return Err(org_error(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::VALIDATION_ERROR,
+            ErrorMessage::ORGANIZATION_SLUG_INVALID,
+        ));
+    }
+
+    match db.create_organization(name, &payload.slug, user_id) {
+        Ok(OrganizationCreation::Created(organization)) => {
+            let _ = db.log_security_event(
+                Some(user_id),
+                "organization_created",
+                Some(json!({
+                    "organization_id": organization.id,
+                    "name": organization.name,
+                    "slug": organization.slug
+                })),
+                true,
+                Some(&ClientInfo::from_headers(&headers)),
+            );
+
+            Ok(Json(ApiResponse::success(json!({
+                "organization": organization,
+                "role": Organizations::ADMIN_ROLE
+            }))))
+        }
+        Ok(OrganizationCreation::SlugTaken) => Err(org_error(
+            StatusCode::CONFLICT,
+            ErrorCode::ORGANIZATION_SLUG_TAKEN,
+            ErrorMessage::ORGANIZATION_SLUG_TAKEN,
+        )),
+        Err(e) => Err(internal_error("creating organization", e)),
+    }
+}
+
+/// List the caller's organizations and the one their session is working in
+pub async fn get_organizations_handler(
+    State(db): State<Arc<Database>>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let (user_id, session_id, _) = extract_session_claims(&headers, &db, None)?;
+
+    let organizations = db
+        .list_user_organizations(user_id)
+        .map_err(|e| internal_error("listing organizations", e))?;
+    let active = db
+        .get_session_organization(session_id)
+        .map_err(|e| internal_error("loading active organization", e))?;
+
+    Ok(Json(ApiResponse::success(json!({
+        "organizations": organizations,
+        "active_organization_id": active.map(|organization| organization.id)
+    }))))
+}
+
+/// Switch the session into one of the caller's organizations (or out of any) and
+/// reissue its token with the new `org` claim
+pub async fn switch_organization_handler(
+    State(db): State<Arc<Database>>,
+    headers: HeaderMap,
+    Json(payload): Json<SwitchOrganizationRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!("Switch organization request: {:?}", payload);
+
+    let (user_id, session_id, claims) = extract_session_claims(&headers, &db, None)?;
+
+    let organization = match payload.organization_id.as_deref() {
+        Some(organization_id) => {
+            let organization_id = parse_id(
+                organization_id,
+                ErrorMessage::ORGANIZATION_ID_FORMAT_INVALID,
+            )?;
+            match db.find_organization_membership(organization_id, user_id) {
+                Ok(Some((organization, role))) => Some(ActiveOrganization {
+                    id: organization.id,
+                    role,
+                }),
+                Ok(None) => {
+                    return Err(org_error(
+                        StatusCode::NOT_FOUND,
+                        ErrorCode::ORGANIZATION_NOT_FOUND,
+                        ErrorMessage::ORGANIZATION_NOT_FOUND,
+                    ));
+                }
+                Err(e) => return Err(internal_error("loading organization membership", e)),
+            }
+        }
+        None => None,
+    };
+
+    db.set_session_organization(session_id, organization.as_ref().map(|org| org.id))
+        .map_err(|e| internal_error("switching organization", e))?;
+
+    // The reissued token carries the caller's current global role and permissions too
+    let role = db
+        .get_user_role(user_id)
+        .map_err(|e| internal_error("loading user role", e))?
+        .unwrap_or_else(|| Roles::USER.to_string());
+    let permissions = db
+        .get_user_permissions(user_id)
+        .map_err(|e| internal_error("loading user permissions", e))?;
+
+    let token = match JwtService::generate_session_token(
+        user_id,
+        &claims.email,
+        &role,
+        session_id,
+        &permissions,
+        organization.as_ref(),
+    ) {
+        Ok(token) => token,
+        Err(e) => {
+            tracing::error!("JWT generation error: {}", e);
+            return Err(org_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorCode::INTERNAL_SERVER_ERROR,
+                ErrorMessage::SERVER_ERROR_OCCURRED,
+            ));
+        }
+    };
+
+    let _ = db.log_security_event(
+        Some(user_id),
+        "organization_switched",
+        Some(json!({
+            "session_id": session_id,
+            "from_organization_id": claims.org,
+            "to_organization_id": organization.as_ref().map(|org| org.id)
+        })),
+        true,
+        Some(&ClientInfo::from_headers(&headers)),
+    );
+
+    Ok(Json(ApiResponse::success(json!({
+        "token": token,
+        "organization": organization.map(|organization| json!({
+            "id": organization.id,
+            "role": organization.role
+        }))
+    }))))
+}
+
+// ========================================
+// Membership Handlers
+// ========================================
+
+/// List the members of an organization (members only)
+pub async fn get_organization_members_handler(
+    State(db): State<Arc<Database>>,
+    Path(organization_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let context = member_context(&db, &headers, &organization_id)?;
+
+    match db.list_organization_members(context.organization.id) {
+        Ok(members) => Ok(Json(ApiResponse::success(json!({
+            "organization": context.organization,
+            "members": members
+        })))),
+        Err(e) => Err(internal_error("listing organization members", e)),
+    }
+}
+
+/// Give a member another role (organization administrators only)
+pub async fn update_member_role_handler(
+    State(db): State<Arc<Database>>,
+    Path((organization_id, member_id)): Path<(String, String)>,
+    headers: HeaderMap,
+    Json(payload): Json<UpdateMemberRoleRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!(
+        "Update member role request: organization_id={}, user_id={}, role={}",
+        organization_id,
+        member_id,
+        payload.role
+    );
+
+    let context = member_context(&db, &headers, &organization_id)?;
+    context.require_admin()?;
+    let member_id = parse_id(&member_id, ErrorMessage::USER_ID_FORMAT_INVALID)?;
+    if member_id == context.user_id {
+        return Err(org_error(
+            StatusCode::FORBIDDEN,
+            ErrorCode::CANNOT_CHANGE_OWN_ROLE,
+            ErrorMessage::OWN_ROLE_CHANGE_FORBIDDEN,
+        ));
+    }
+
+    let role_id = find_role_id(&context.hierarchy, &payload.role)?;
+    context.require_grantable(&payload.role)?;
+    let current_role = match db.find_organization_membership(context.organization.id, member_id) {
+        Ok(Some((_, role))) => role,
+        Ok(None) => return Err(membership_change_error(MembershipRefusal::NotMember)),
+        Err(e) => return Err(internal_error("loading organization membership", e)),
+    };
+    context.require_grantable(&current_role)?;
+
+    match db.update_membership_role(context.organization.id, member_id, role_id) {
+        Ok(MembershipChange::Changed { previous_role }) => {
+            let _ = db.log_security_event(
+                Some(context.user_id),
+                "organization_member_role_changed",
+                Some(json!({
+                    "organization_id": context.organization.id,
+                    "target_user_id": member_id,
+                    "old_role": previous_role,
+                    "new_role": payload.role
+                })),
+                true,
+                Some(&ClientInfo::from_headers(&headers)),
+            );
+
+            Ok(Json(ApiResponse::success(json!({
+                "message": format!("Member role changed to {}", payload.role),
+                "member": {
+                    "user_id": member_id,
+                    "role": payload.role
+                }
+            }))))
+        }
+        Ok(MembershipChange::Refused(refusal)) => Err(membership_change_error(refusal)),
+        Err(e) => Err(internal_error("changing member role", e)),
+    }
+}
+
+/// Remove a member (organization administrators), or leave the organization (any member)
+pub async fn remove_member_handler(
+    State(db): State<Arc<Database>>,
+    Path((organization_id, member_id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!(
+        "Remove member request: organization_id={}, user_id={}",
+        organization_id,
+        member_id
+    );
+
+    let context = member_context(&db, &headers, &organization_id)?;
+    let member_id = parse_id(&member_id, ErrorMessage::USER_ID_FORMAT_INVALID)?;
+    let leaving = member_id == context.user_id;
+    if !leaving {
+        context.require_admin()?;
+        match db.find_organization_membership(context.organization.id, member_id) {
+            Ok(Some((_, role))) => context.require_grantable(&role)?,
+            Ok(None) => return Err(membership_change_error(MembershipRefusal::NotMember)),
+            Err(e) => return Err(internal_error("loading organization membership", e)),
+        }
+    }
+
+    match db.remove_membership(context.organization.id, member_id) {
+        Ok(MembershipChange::Changed { previous_role }) => {
+            let _ = db.log_security_event(
+                Some(context.user_id),
+                if leaving {
+                    "organization_left"
+                } else {
+                    "organization_member_removed"
+                },
+                Some(json!({
+                    "organization_id": context.organization.id,
+                    "target_user_id": member_id,
+                    "role": previous_role
+                })),
+                true,
+                Some(&ClientInfo::from_headers(&headers)),
+            );
+
+            Ok(Json(ApiResponse::success(json!({
+                "message": if leaving {
+                    "You have left the organization"
+                } else {
+                    "Member removed from the organization"
+                },
+                "user_id": member_id
+            }))))
+        }
+        Ok(MembershipChange::Refused(refusal)) => Err(membership_change_error(refusal)),
+        Err(e) => Err(internal_error("removing member", e)),
+    }
+}
+
+// ========================================
+// Invitation Handlers
+// ========================================
+
+/// Email an invitation to join the organization (organization administrators only)
+pub async fn create_invitation_handler(
+    State(db): State<Arc<Database>>,
+    Path(organization_id): Path<String>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateInvitationRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!(
+        "Create invitation request: organization_id={}, {:?}",
+        organization_id,
+        payload
+    );
+
+    let context = member_context(&db, &headers, &organization_id)?;
+    context.require_admin()?;
+
+    let email = payload.email.trim().to_lowercase();
+    if email.is_empty() || !email.contains('@') {
+        return Err(org_error(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::VALIDATION_ERROR,
+            ErrorMessage::INVALID_INPUT_DATA,
+        ));
+    }
+    let role = payload
+        .role
+        .as_deref()
+        .unwrap_or(Organizations::DEFAULT_MEMBER_ROLE);
+    let role_id = find_role_id(&context.hierarchy, role)?;
+    context.require_grantable(role)?;
+
+    // Existing members don't need an invitation; their locale is used if they have an account
+    let invitee = db
+        .find_user_by_email(&email)
+        .map_err(|e| internal_error("looking up invitee", e))?;
+    if let Some(invitee) = &invitee {
+        match db.find_organization_membership(context.organization.id, invitee.id) {
+            Ok(Some(_)) => {
+                return Err(org_error(
+                    StatusCode::CONFLICT,
+                    ErrorCode::ALREADY_ORGANIZATION_MEMBER,
+                    ErrorMessage::ALREADY_ORGANIZATION_MEMBER,
+                ));
+            }
+            Ok(None) => {}
+            Err(e) => return Err(internal_error("loading organization membership", e)),
+        }
+    }
+    let inviter = db
+        .find_user_by_id(context.user_id)
+        .map_err(|e| internal_error("loading inviter", e))?
+        .map(|user| user.name)
+        .unwrap_or_default();
+
+    let (token, token_hash) = OneTimeToken::generate();
+    let link = format!(
+        "{}/accept-invitation?token={}",
+        MailService::app_base_url(),
+        EmailMessage::SECRET_PLACEHOLDER
+    );
+    let locale = I18nService::for_request(
+        invitee.as_ref().and_then(|user| user.locale.as_deref()),
+        &headers,
+    );
+    let message = match EmailTemplate::OrganizationInvitation.render(
+        locale,
+        &email,
+        &[
+            ("inviter", &inviter),
+            ("organization", &context.organization.name),
+            ("role", role),
+            ("link", &link),
+            ("ttl_days", &Organizations::INVITATION_TTL_DAYS.to_string()),
+        ],
+    ) {
+        Ok(message) => message.with_secret(&token),
+        Err(e) => {
+            tracing::error!("Failed to render invitation email: {}", e);
+            return Err(org_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorCode::INTERNAL_SERVER_ERROR,
+                ErrorMessage::SERVER_ERROR_OCCURRED,
+            ));
+        }
+    };
+
+    match db.create_organization_invitation(
+        context.organization.id,
+        &email,
+        role_id,
+        context.user_id,
+        &token_hash,
+        &message,
+    ) {
+        Ok(invitation) => {
+            let _ = db.log_security_event(
+                Some(context.user_id),
+                "organization_invitation_created",
+                Some(json!({
+                    "organization_id": context.organization.id,
+                    "invitation_id": invitation.id,
+                    "email": email,
+                    "role": role
+                })),
+                true,
+                Some(&ClientInfo::from_headers(&headers)),
+            );
+
+            Ok(Json(ApiResponse::success(json!({
+                "invitation": {
+                    "id": invitation.id,
+                    "email": invitation.email,
+                    "role": role,
+                    "expires_at": invitation.expires_at.to_rfc3339()
+                }
+            }))))
+        }
+        Err(e) => Err(internal_error("creating invitation", e)),
+    }
+}
+
+/// List invitations that can still be accepted (organization administrators only)
+pub async fn get_invitations_handler(
+    State(db): State<Arc<Database>>,
+    Path(organization_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let context = member_context(&db, &headers, &organization_id)?;
+    context.require_admin()?;
+
+    match db.list_pending_invitations(context.organization.id) {
+        Ok(invitations) => Ok(Json(ApiResponse::success(json!({
+            "invitations": invitations
+        })))),
+        Err(e) => Err(internal_error("listing invitations", e)),
+    }
+}
+
+/// Revoke a pending invitation (organization administrators only)
+pub async fn revoke_invitation_handler(
+    State(db): State<Arc<Database>>,
+    Path((organization_id, invitation_id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let context = member_context(&db, &headers, &organization_id)?;
+    context.require_admin()?;
+    let invitation_id = parse_id(&invitation_id, ErrorMessage::INVITATION_NOT_FOUND)?;
+
+    match db.revoke_organization_invitation(context.organization.id, invitation_id) {
+        Ok(Some(email)) => {
+            let _ = db.log_security_event(
+                Some(context.user_id),
+                "organization_invitation_revoked",
+                Some(json!({
+                    "organization_id": context.organization.id,
+                    "invitation_id": invitation_id,
+                    "email": email
+                })),
+                true,
+                Some(&ClientInfo::from_headers(&headers)),
+            );
+
+            Ok(Json(ApiResponse::success(json!({
+                "message": "Invitation revoked",
+                "invitation_id": invitation_id
+            }))))
+        }
+        Ok(None) => Err(org_error(
+            StatusCode::NOT_FOUND,
+            ErrorCode::INVITATION_INVALID,
+            ErrorMessage::INVITATION_NOT_FOUND,
+        )),
+        Err(e) => Err(internal_error("revoking invitation", e)),
+    }
+}
+
+/// Accept an emailed invitation as the signed-in user it was sent to
+pub async fn accept_invitation_handler(
+    State(db): State<Arc<Database>>,
+    headers: HeaderMap,
+    Json(payload): Json<AcceptInvitationRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let (user_id, _, claims) = extract_session_claims(&headers, &db, None)?;
+    let client = ClientInfo::from_headers(&headers);
+
+    let token_hash = OneTimeToken::hash(&payload.token);
+    match db.accept_organization_invitation(&token_hash, user_id, &claims.email) {
+        Ok(InvitationAcceptance::Accepted { organization, role }) => {
+            let _ = db.log_security_event(
+                Some(user_id),
+                "organization_invitation_accepted",
+                Some(json!({
+                    "organization_id": organization.id,
+                    "role": role
+                })),
+                true,
+                Some(&client),
+            );
+
+            Ok(Json(ApiResponse::success(json!({
+                "organization": organization,
+                "role": role
+            }))))
+        }
+        Ok(InvitationAcceptance::AlreadyMember { .. }) => Err(org_error(
+            StatusCode::CONFLICT,
+            ErrorCode::ALREADY_ORGANIZATION_MEMBER,
+            ErrorMessage::ALREADY_ORGANIZATION_MEMBER,
+        )),
+        Ok(InvitationAcceptance::EmailMismatch) => {
+            let _ = db.log_security_event(
+                Some(user_id),
+                "organization_invitation_accept_failed",
+                Some(json!({ "reason": "email_mismatch" })),
+                false,
+                Some(&client),
+            );
+            Err(org_error(
+                StatusCode::FORBIDDEN,
+                ErrorCode::INVITATION_INVALID,
+                ErrorMessage::INVITATION_EMAIL_MISMATCH,
+            ))
+        }
+        Ok(InvitationAcceptance::Invalid) => Err(org_error(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::INVITATION_INVALID,
+            ErrorMessage::INVITATION_INVALID,
+        )),
+        Err(e) => Err(internal_error("accepting invitation", e)),
+    }
+}
@@ -0,0 +1,274 @@
+use axum::{
+    extract::{Extension, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::database::{
+    namespace, ConsistencyToken, Database, ListCursor, ObjectRef, RelationTuple, SubjectRef,
+};
+use crate::models::ApiResponse;
+use crate::utils::{ClientInfo, ServiceCaller};
+use crate::{ErrorCode, ErrorMessage, Relations};
+
+// ========================================
+// Request / Response Types
+// ========================================
+
+/// Tuples to delete and write, as `object#relation@subject`; deletes are applied first
+#[derive(Debug, Deserialize)]
+pub struct RelationWriteRequest {
+    #[serde(default)]
+    pub writes: Vec<String>,
+    #[serde(default)]
+    pub deletes: Vec<String>,
+}
+
+/// Does `subject` hold `relation` on `object`?
+///
+/// Passing the `consistency_token` from a write guarantees the answer reflects it.
+#[derive(Debug, Deserialize)]
+pub struct RelationCheckRequest {
+    pub object: String,
+    pub relation: String,
+    pub subject: String,
+    pub consistency_token: Option<String>,
+}
+
+/// Who holds `relation` on `object`?
+#[derive(Debug, Deserialize)]
+pub struct RelationExpandRequest {
+    pub object: String,
+    pub relation: String,
+    pub consistency_token: Option<String>,
+}
+
+/// Which objects in `namespace` does `subject` hold `relation` on?
+///
+/// Results come in pages; pass the `next_cursor` of one page, with its
+/// `consistency_token`, to get the next.
+#[derive(Debug, Deserialize)]
+pub struct RelationListObjectsRequest {
+    pub namespace: String,
+    pub relation: String,
+    pub subject: String,
+    pub cursor: Option<String>,
+    pub consistency_token: Option<String>,
+}
+
+// ========================================
+// Helpers
+// ========================================
+
+fn relation_error(status: StatusCode, code: &str, message: &str) -> (StatusCode, Json<Value>) {
+    (status, Json(ApiResponse::error(code, message)))
+}
+
+fn query_invalid() -> (StatusCode, Json<Value>) {
+    relation_error(
+        StatusCode::BAD_REQUEST,
+        ErrorCode::VALIDATION_ERROR,
+        ErrorMessage::RELATION_QUERY_INVALID,
+    )
+}
+
+fn token_invalid() -> (StatusCode, Json<Value>) {
+    relation_error(
+        StatusCode::BAD_REQUEST,
+        ErrorCode::VALIDATION_ERROR,
+        ErrorMessage::CONSISTENCY_TOKEN_INVALID,
+    )
+}
+
+fn store_failed(context: &str, e: anyhow::Error) -> (StatusCode, Json<Value>) {
+    tracing::error!("Database error {}: {}", context, e);
+    relation_error(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        ErrorCode::DATABASE_ERROR,
+        ErrorMessage::RELATION_OPERATION_FAILED,
+    )
+}
+
+fn parse_token(token: Option<&str>) -> Result<Option<ConsistencyToken>, (StatusCode, Json<Value>)> {
+    token
+        .map(|token| ConsistencyToken::decode(token).ok_or_else(token_invalid))
+        .transpose()
+}
+
+/// The relation, if `namespace` defines it
+fn parse_relation(namespace_name: &str, relation: &str) -> Option<String> {
+    let relation = relation.trim();
+    namespace(namespace_name)?.relation(relation)?;
+    Some(relation.to_string())
+}
+
+/// Parse every tuple, rejecting the whole request if any is invalid
+fn parse_tuples(tuples: &[String]) -> Result<Vec<RelationTuple>, (StatusCode, Json<Value>)> {
+    tuples
+        .iter()
+        .map(|tuple| {
+            RelationTuple::parse(tuple).ok_or_else(|| {
+                relation_error(
+                    StatusCode::BAD_REQUEST,
+                    ErrorCode::VALIDATION_ERROR,
+                    ErrorMessage::RELATION_TUPLE_INVALID,
+                )
+            })
+        })
+        .collect()
+}
+
+impl RelationCheckRequest {
+    /// The tuple the check asks about, or `None` if any part is malformed or undefined
+    pub fn to_query(&self) -> Option<RelationTuple> {
+        let object = ObjectRef::parse(&self.object)?;
+        Some(RelationTuple {
+            relation: parse_relation(&object.namespace, &self.relation)?,
+            object,
+            subject: SubjectRef::parse(&self.subject)?,
+        })
+    }
+}
+
+// ========================================
+// Relation Tuple Handlers
+// ========================================
+
+/// Write and delete relation tuples as one revision, for services on the internal network
+pub async fn write_relation_tuples_handler(
+    State(db): State<Arc<Database>>,
+    Extension(ServiceCaller(service)): Extension<ServiceCaller>,
+    headers: HeaderMap,
+    Json(payload): Json<RelationWriteRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let size = payload.writes.len() + payload.deletes.len();
+    if size == 0 || size > Relations::MAX_WRITE_SIZE {
+        return Err(relation_error(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::VALIDATION_ERROR,
+            &ErrorMessage::RELATION_WRITE_SIZE_INVALID
+                .replace("{}", &Relations::MAX_WRITE_SIZE.to_string()),
+        ));
+    }
+    let writes = parse_tuples(&payload.writes)?;
+    let deletes = parse_tuples(&payload.deletes)?;
+
+    let write = db
+        .write_relation_tuples(&writes, &deletes)
+        .map_err(|e| store_failed("writing relation tuples", e))?;
+
+    if write.written + write.deleted > 0 {
+        let _ = db.log_security_event(
+            None,
+            "relation_tuples_changed",
+            Some(json!({
+                "service": service,
+                "written": writes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                "deleted": deletes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                "revision": write.token.0
+            })),
+            true,
+            Some(&ClientInfo::from_headers(&headers)),
+        );
+    }
+
+    Ok(Json(ApiResponse::success(json!({
+        "written": write.written,
+        "deleted": write.deleted,
+        "consistency_token": write.token.encode()
+    }))))
+}
+
+/// Check whether a subject holds a relation on an object, through groups and parent
+/// folders as well as directly
+pub async fn check_relation_handler(
+    State(db): State<Arc<Database>>,
+    Json(payload): Json<RelationCheckRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let query = payload.to_query().ok_or_else(query_invalid)?;
+    let at_least = parse_token(payload.consistency_token.as_deref())?;
+
+    let check = db
+        .check_relation(&query, at_least)
+        .map_err(|e| store_failed("checking relation", e))?
+        .ok_or_else(token_invalid)?;
+
+    Ok(Json(ApiResponse::success(json!({
+        "allowed": check.allowed,
+        "object": query.object.to_string(),
+        "relation": query.relation,
+        "subject": query.subject.to_string(),
+        "consistency_token": check.token.encode(),
+        "cached": check.cached
+    }))))
+}
+
+/// Expand a relation on an object into the tree of subjects and usersets holding it
+pub async fn expand_relation_handler(
+    State(db): State<Arc<Database>>,
+    Json(payload): Json<RelationExpandRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let object = ObjectRef::parse(&payload.object).ok_or_else(query_invalid)?;
+    let relation =
+        parse_relation(&object.namespace, &payload.relation).ok_or_else(query_invalid)?;
+    let at_least = parse_token(payload.consistency_token.as_deref())?;
+
+    let (tree, token) = db
+        .expand_relation(&object, &relation, at_least)
+        .map_err(|e| store_failed("expanding relation", e))?
+        .ok_or_else(token_invalid)?;
+
+    Ok(Json(ApiResponse::success(json!({
+        "subjects": tree.leaves(),
+        "tree": tree,
+        "consistency_token": token.encode()
+    }))))
+}
+
+/// List the objects in a namespace on which a subject holds a relation
+pub async fn list_relation_objects_handler(
+    State(db): State<Arc<Database>>,
+    Json(payload): Json<RelationListObjectsRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let namespace_name = payload.namespace.trim();
+    let relation = parse_relation(namespace_name, &payload.relation).ok_or_else(query_invalid)?;
+    let subject = SubjectRef::parse(&payload.subject).ok_or_else(query_invalid)?;
+    let after = payload
+        .cursor
+        .as_deref()
+        .map(|cursor| {
+            ListCursor::decode(cursor).ok_or_else(|| {
+                relation_error(
+                    StatusCode::BAD_REQUEST,
+                    ErrorCode::VALIDATION_ERROR,
+                    ErrorMessage::LIST_CURSOR_INVALID,
+                )
+            })
+        })
+        .transpose()?;
+    let at_least = parse_token(payload.consistency_token.as_deref())?;
+
+    let (page, token) = db
+        .list_relation_objects(
+            namespace_name,
+            &relation,
+            &subject,
+            after.as_ref(),
+            at_least,
+        )
+        .map_err(|e| store_failed("listing relation objects", e))?
+        .ok_or_else(token_invalid)?;
+
+    Ok(Json(ApiResponse::success(json!({
+        "namespace": namespace_name,
+        "relation": relation,
+        "subject": subject.to_string(),
+        "objects": page.objects,
+        "truncated": page.next_cursor.is_some(),
+        "next_cursor": page.next_cursor.map(|cursor| cursor.encode()),
+        "consistency_token": token.encode()
+    }))))
+}
@@ -34,11 +34,38 @@ pub async fn token_verify_handler(
                 }
             };
 
+            // Reject tokens whose session was revoked (logout, password change, admin action)
+            // or whose account is disabled or suspended
+            match db.is_token_session_active(user_id, &token_data.claims) {
+                Ok(true) => {}
+                Ok(false) => {
+                    return Err((
+                        StatusCode::UNAUTHORIZED,
+                        Json(ApiResponse::error(
+                            ErrorCode::TOKEN_INVALID,
+                            ErrorMessage::TOKEN_INVALID_OR_EXPIRED,
+                        )),
+                    ));
+                }
+                Err(e) => {
+                    tracing::error!("Database error checking session: {}", e);
+                    return Err((
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ApiResponse::error(
+                            ErrorCode::DATABASE_ERROR,
+                            ErrorMessage::SERVER_ERROR_OCCURRED,
+                        )),
+                    ));
+                }
+            }
+
             // Optionally verify user still exists in database
             match db.find_user_by_id(user_id) {
                 Ok(Some(user)) => {
-                    // Default to user role
-                    let role = Roles::USER.to_string();
+                    let role = match db.get_user_role(user_id) {
+                        Ok(r) => r.unwrap_or(Roles::USER.to_string()),
+                        Err(_) => Roles::USER.to_string(),
+                    };
                     Ok(Json(ApiResponse::success(json!({
                         "valid": true,
                         "user_id": user.id,
@@ -107,17 +134,54 @@ pub async fn token_info_handler(
                 }
             };
 
+            // Reject tokens whose session was revoked (logout, password change, admin action)
+            match db.is_token_session_active(user_id, &token_data.claims) {
+                Ok(true) => {}
+                Ok(false) => {
+                    return Err((
+                        StatusCode::UNAUTHORIZED,
+                        Json(ApiResponse::error(
+                            ErrorCode::TOKEN_INVALID,
+                            ErrorMessage::TOKEN_INVALID_OR_EXPIRED,
+                        )),
+                    ));
+                }
+                Err(e) => {
+                    tracing::error!("Database error checking session: {}", e);
+                    return Err((
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ApiResponse::error(
+                            ErrorCode::DATABASE_ERROR,
+                            ErrorMessage::SERVER_ERROR_OCCURRED,
+                        )),
+                    ));
+                }
+            }
+
             // Get fresh user data from database
             match db.find_user_by_id(user_id) {
                 Ok(Some(user)) => {
-                    // Default to user role
-                    let role = Roles::USER.to_string();
+                    let role = match db.get_user_role(user_id) {
+                        Ok(r) => r.unwrap_or(Roles::USER.to_string()),
+                        Err(_) => Roles::USER.to_string(),
+                    };
+
+                    let permissions = db.get_user_permissions(user_id).unwrap_or_default();
+                    let organization = JwtService::session_id_from_claims(&token_data.claims)
+                        .and_then(|session_id| {
+                            db.get_session_organization(session_id).unwrap_or(None)
+                        });
 
                     Ok(Json(ApiResponse::success(json!({
                         "valid": true,
                         "user_id": user.id,
                         "email": user.email,
                         "role": role,
+                        "permissions": permissions,
+                        "organization": organization.map(|organization| json!({
+                            "id": organization.id,
+                            "role": organization.role
+                        })),
                         "issued_at": token_data.claims.iat,
                         "expires_at": token_data.claims.exp
                     }))))
@@ -192,6 +256,43 @@ pub async fn token_refresh_handler(
         }
     };
 
+    let session_id = match JwtService::session_id_from_claims(&token_data.claims) {
+        Some(id) => id,
+        None => {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(ApiResponse::error(
+                    ErrorCode::TOKEN_INVALID,
+                    ErrorMessage::TOKEN_REFRESH_FAILED,
+                )),
+            ));
+        }
+    };
+
+    // Refuse to extend a revoked session
+    match db.is_session_active(user_id, session_id) {
+        Ok(true) => {}
+        Ok(false) => {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(ApiResponse::error(
+                    ErrorCode::TOKEN_INVALID,
+                    ErrorMessage::TOKEN_REFRESH_FAILED,
+                )),
+            ));
+        }
+        Err(e) => {
+            tracing::error!("Database error checking session: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    ErrorCode::DATABASE_ERROR,
+                    ErrorMessage::SERVER_ERROR_OCCURRED,
+                )),
+            ));
+        }
+    }
+
     // Verify user still exists
     let user = match db.find_user_by_id(user_id) {
         Ok(Some(user)) => user,
@@ -216,11 +317,24 @@ pub async fn token_refresh_handler(
         }
     };
 
-    // Default to user role
-    let role = Roles::USER.to_string();
+    // Carry the user's current role and permissions into the new token
+    let role = match db.get_user_role(user_id) {
+        Ok(r) => r.unwrap_or(Roles::USER.to_string()),
+        Err(_) => Roles::USER.to_string(),
+    };
+    let permissions = db.get_user_permissions(user_id).unwrap_or_default();
+    // The session stays in its organization as long as the user is still a member
+    let organization = db.get_session_organization(session_id).unwrap_or(None);
 
-    // Generate new token
-    match JwtService::generate_token(user_id, &user.email, &role) {
+    // Generate new token for the same session
+    match JwtService::generate_session_token(
+        user_id,
+        &user.email,
+        &role,
+        session_id,
+        &permissions,
+        organization.as_ref(),
+    ) {
         Ok(new_token) => {
             tracing::info!("Token successfully refreshed for user: {}", user.email);
 
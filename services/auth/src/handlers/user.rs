@@ -11,8 +11,11 @@ use uuid::Uuid;
 use crate::database::Database;
 use crate::models::ApiResponse;
 use crate::proto_generated::*;
-use crate::utils::JwtService;
-use crate::{ErrorCode, ErrorMessage};
+use crate::utils::{
+    Claims, ClientInfo, EmailMessage, EmailTemplate, I18nService, JwtError, JwtService, Locale,
+    MailService, OneTimeToken, PasswordError, PasswordService, TokenScope,
+};
+use crate::{EmailChange, ErrorCode, ErrorMessage};
 
 // Simple request structures (not using proto for now)
 #[derive(Deserialize)]
@@ -21,8 +24,39 @@ pub struct UserUpdateRequest {
     pub avatar_path: Option<String>,
 }
 
-/// Extract user ID from Authorization header
-fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, (StatusCode, Json<Value>)> {
+#[derive(Deserialize)]
+pub struct UpdateLocaleRequest {
+    pub locale: Option<String>, // "en", "ja", "zh"; null follows Accept-Language
+}
+
+#[derive(Deserialize)]
+pub struct ChangeEmailRequest {
+    pub new_email: String,
+    pub password: String,
+}
+
+#[derive(Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+/// Extract user ID and session ID from Authorization header, rejecting revoked sessions
+fn extract_session_from_token(
+    headers: &HeaderMap,
+    db: &Database,
+) -> Result<(Uuid, Uuid), (StatusCode, Json<Value>)> {
+    extract_session_claims(headers, db, None).map(|(user_id, session_id, _)| (user_id, session_id))
+}
+
+/// Extract user ID, session ID and claims from Authorization header
+///
+/// Restricted tokens are only accepted when their scope matches `allowed_scope`.
+pub(crate) fn extract_session_claims(
+    headers: &HeaderMap,
+    db: &Database,
+    allowed_scope: Option<&str>,
+) -> Result<(Uuid, Uuid, Claims), (StatusCode, Json<Value>)> {
     let auth_header = headers
         .get("authorization")
         .ok_or_else(|| {
@@ -55,8 +89,7 @@ fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, (StatusCode,
         )
     })?;
 
-    JwtService::extract_user_id(token).map_err(|e| {
-        tracing::error!("JWT validation error: {}", e);
+    let invalid_token = || {
         (
             StatusCode::UNAUTHORIZED,
             Json(ApiResponse::error(
@@ -64,7 +97,62 @@ fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, (StatusCode,
                 ErrorMessage::TOKEN_INVALID_OR_EXPIRED,
             )),
         )
-    })
+    };
+
+    let claims = match JwtService::validate_token_for_scope(token, allowed_scope) {
+        Ok(token_data) => token_data.claims,
+        Err(JwtError::ScopeNotPermitted(scope)) => {
+            // Tell the client what it has to do to get a full-access token
+            let (code, message) = match scope.as_str() {
+                TokenScope::PASSWORD_CHANGE => (
+                    ErrorCode::PASSWORD_CHANGE_REQUIRED,
+                    ErrorMessage::PASSWORD_CHANGE_REQUIRED,
+                ),
+                TokenScope::UNVERIFIED_EMAIL => (
+                    ErrorCode::EMAIL_NOT_VERIFIED,
+                    ErrorMessage::EMAIL_VERIFICATION_REQUIRED,
+                ),
+                _ => (
+                    ErrorCode::INSUFFICIENT_PERMISSIONS,
+                    ErrorMessage::TOKEN_INVALID_OR_EXPIRED,
+                ),
+            };
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(ApiResponse::error(code, message)),
+            ));
+        }
+        Err(e) => {
+            tracing::error!("JWT validation error: {}", e);
+            return Err(invalid_token());
+        }
+    };
+
+    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| invalid_token())?;
+    let session_id = JwtService::session_id_from_claims(&claims).ok_or_else(invalid_token)?;
+
+    match db.is_session_active(user_id, session_id) {
+        Ok(true) => Ok((user_id, session_id, claims)),
+        Ok(false) => Err(invalid_token()),
+        Err(e) => {
+            tracing::error!("Database error checking session: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    ErrorCode::DATABASE_ERROR,
+                    ErrorMessage::INTERNAL_SERVER_ERROR,
+                )),
+            ))
+        }
+    }
+}
+
+/// Extract user ID from Authorization header
+fn extract_user_id_from_token(
+    headers: &HeaderMap,
+    db: &Database,
+) -> Result<Uuid, (StatusCode, Json<Value>)> {
+    extract_session_from_token(headers, db).map(|(user_id, _)| user_id)
 }
 
 /// Get current user profile (requires authentication)
@@ -74,8 +162,9 @@ pub async fn get_profile_handler(
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     tracing::info!("Getting user profile");
 
-    // Extract user_id from JWT token
-    let user_id = extract_user_id_from_token(&headers)?;
+    // Extract user_id from JWT token (accounts awaiting email verification may read their profile)
+    let (user_id, _, _) =
+        extract_session_claims(&headers, &db, Some(TokenScope::UNVERIFIED_EMAIL))?;
 
     match db.get_user_profile(user_id) {
         Ok(Some((user, auth_user, role_name))) => {
@@ -134,7 +223,7 @@ pub async fn update_profile_handler(
     tracing::info!("Updating user profile");
 
     // Extract user_id from JWT token
-    let user_id = extract_user_id_from_token(&headers)?;
+    let user_id = extract_user_id_from_token(&headers, &db)?;
 
     // Validate input data
     if let Some(ref name) = payload.name {
@@ -185,3 +274,437 @@ pub async fn update_profile_handler(
         }
     }
 }
+
+/// Set the current user's display language for API messages and emails
+pub async fn update_locale_handler(
+    State(db): State<Arc<Database>>,
+    headers: HeaderMap,
+    Json(payload): Json<UpdateLocaleRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!("Updating user locale: {:?}", payload.locale);
+
+    let user_id = extract_user_id_from_token(&headers, &db)?;
+
+    let locale = match payload.locale.as_deref() {
+        None => None,
+        Some(tag) => match Locale::parse(tag) {
+            Some(locale) => Some(locale),
+            None => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(ApiResponse::error(
+                        ErrorCode::VALIDATION_ERROR,
+                        &ErrorMessage::LOCALE_NOT_SUPPORTED
+                            .replace("{}", &I18nService::supported_codes().join(", ")),
+                    )),
+                ));
+            }
+        },
+    };
+
+    match db.update_user_locale(user_id, locale.map(Locale::code)) {
+        Ok(user) => Ok(Json(ApiResponse::success(json!({
+            "locale": user.locale,
+            "supported_locales": I18nService::supported_codes()
+        })))),
+        Err(e) => {
+            tracing::error!("Database error updating user locale: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    ErrorCode::DATABASE_ERROR,
+                    ErrorMessage::INTERNAL_SERVER_ERROR,
+                )),
+            ))
+        }
+    }
+}
+
+/// Change the current user's password (requires the current password)
+pub async fn change_password_handler(
+    State(db): State<Arc<Database>>,
+    headers: HeaderMap,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!("Changing user password");
+
+    // Extract user_id and current session from JWT token (a password change token is enough here)
+    let (user_id, session_id, claims) =
+        extract_session_claims(&headers, &db, Some(TokenScope::PASSWORD_CHANGE))?;
+
+    if payload.current_password.is_empty() || payload.new_password.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                ErrorCode::VALIDATION_ERROR,
+                ErrorMessage::INVALID_INPUT_DATA,
+            )),
+        ));
+    }
+
+    let auth_user = match db.find_auth_user_by_user_id(user_id) {
+        Ok(Some(auth_user)) => auth_user,
+        Ok(None) => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::error(
+                    ErrorCode::USER_NOT_FOUND,
+                    ErrorMessage::USER_NOT_FOUND,
+                )),
+            ));
+        }
+        Err(e) => {
+            tracing::error!("Database error finding auth user: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    ErrorCode::DATABASE_ERROR,
+                    ErrorMessage::INTERNAL_SERVER_ERROR,
+                )),
+            ));
+        }
+    };
+
+    // Verify current password
+    match PasswordService::verify_password(&payload.current_password, &auth_user.password_hash) {
+        Ok(true) => {}
+        Ok(false) => {
+            let _ = db.log_security_event(
+                Some(user_id),
+                "password_change_failed",
+                Some(json!({ "reason": "invalid_current_password" })),
+                false,
+                Some(&ClientInfo::from_headers(&headers)),
+            );
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(ApiResponse::error(
+                    ErrorCode::INVALID_CREDENTIALS,
+                    ErrorMessage::CURRENT_PASSWORD_INCORRECT,
+                )),
+            ));
+        }
+        Err(e) => {
+            tracing::error!("Password verification error: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    ErrorCode::INTERNAL_SERVER_ERROR,
+                    ErrorMessage::SERVER_ERROR_OCCURRED,
+                )),
+            ));
+        }
+    }
+
+    // Validate new password against the policy
+    match PasswordService::validate_new_password(&payload.new_password).await {
+        Ok(()) => {}
+        Err(PasswordError::Breached) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error(
+                    ErrorCode::PASSWORD_BREACHED,
+                    ErrorMessage::PASSWORD_FOUND_IN_BREACH,
+                )),
+            ));
+        }
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error(
+                    ErrorCode::WEAK_PASSWORD,
+                    ErrorMessage::PASSWORD_TOO_WEAK,
+                )),
+            ));
+        }
+    }
+
+    let password_hash = match PasswordService::hash_password(&payload.new_password) {
+        Ok(hash) => hash,
+        Err(e) => {
+            tracing::error!("Password hashing error: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    ErrorCode::INTERNAL_SERVER_ERROR,
+                    ErrorMessage::SERVER_ERROR_OCCURRED,
+                )),
+            ));
+        }
+    };
+
+    // Reject the current password and anything in recent history, in the same
+    // transaction as the update; checking runs bcrypt, so keep it off the async workers
+    let updater = db.clone();
+    let new_password = payload.new_password.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        updater.set_password_hash(user_id, &new_password, &password_hash)
+    })
+    .await
+    .unwrap_or_else(|e| Err(anyhow::anyhow!(e)));
+
+    match result {
+        Ok(true) => {}
+        Ok(false) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error(
+                    ErrorCode::PASSWORD_REUSED,
+                    ErrorMessage::PASSWORD_RECENTLY_USED,
+                )),
+            ));
+        }
+        Err(e) => {
+            tracing::error!("Database error updating password: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    ErrorCode::DATABASE_ERROR,
+                    ErrorMessage::PASSWORD_CHANGE_FAILED,
+                )),
+            ));
+        }
+    }
+
+    // Sign out everywhere else; the session making this request stays valid
+    let revoked_sessions = db
+        .revoke_other_user_sessions(user_id, session_id, "password_changed")
+        .unwrap_or_else(|e| {
+            tracing::warn!("Failed to revoke other sessions: {}", e);
+            0
+        });
+
+    let client = ClientInfo::from_headers(&headers);
+    let _ = db.log_security_event(
+        Some(user_id),
+        "password_changed",
+        Some(json!({ "revoked_sessions": revoked_sessions })),
+        true,
+        Some(&client),
+    );
+
+    // A restricted session is upgraded to a full-access token now that the password is compliant
+    let token = if claims.scope.is_some() {
+        let permissions = db.get_user_permissions(user_id).unwrap_or_default();
+        match JwtService::generate_session_token(
+            user_id,
+            &claims.email,
+            &claims.role,
+            session_id,
+            &permissions,
+            None, // Restricted sessions can't have switched organization yet
+        ) {
+            Ok(token) => Some(token),
+            Err(e) => {
+                tracing::error!("JWT generation error: {}", e);
+                return Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::error(
+                        ErrorCode::JWT_ERROR,
+                        ErrorMessage::SERVER_ERROR_OCCURRED,
+                    )),
+                ));
+            }
+        }
+    } else {
+        None
+    };
+
+    Ok(Json(ApiResponse::success(json!({
+        "message": "Password changed successfully",
+        "revoked_sessions": revoked_sessions,
+        "token": token
+    }))))
+}
+
+/// Start changing the current user's email address (requires the password)
+///
+/// Nothing changes until the link sent to the new address is opened; the old
+/// address is told about the request and gets a link to cancel it.
+pub async fn request_email_change_handler(
+    State(db): State<Arc<Database>>,
+    headers: HeaderMap,
+    Json(payload): Json<ChangeEmailRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracing::info!("Email change requested");
+
+    let user_id = extract_user_id_from_token(&headers, &db)?;
+    let client = ClientInfo::from_headers(&headers);
+
+    let new_email = payload.new_email.trim();
+    if new_email.is_empty() || !new_email.contains('@') || payload.password.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                ErrorCode::VALIDATION_ERROR,
+                ErrorMessage::INVALID_INPUT_DATA,
+            )),
+        ));
+    }
+
+    let (user, auth_user) = match db.get_user_profile(user_id) {
+        Ok(Some((user, auth_user, _))) => (user, auth_user),
+        Ok(None) => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::error(
+                    ErrorCode::USER_NOT_FOUND,
+                    ErrorMessage::USER_NOT_FOUND,
+                )),
+            ));
+        }
+        Err(e) => {
+            tracing::error!("Database error finding user for email change: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    ErrorCode::DATABASE_ERROR,
+                    ErrorMessage::INTERNAL_SERVER_ERROR,
+                )),
+            ));
+        }
+    };
+
+    // A stolen token alone must not be enough to move the account to another inbox
+    match PasswordService::verify_password(&payload.password, &auth_user.password_hash) {
+        Ok(true) => {}
+        Ok(false) => {
+            let _ = db.log_security_event(
+                Some(user_id),
+                "email_change_failed",
+                Some(json!({ "reason": "invalid_password" })),
+                false,
+                Some(&client),
+            );
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(ApiResponse::error(
+                    ErrorCode::INVALID_CREDENTIALS,
+                    ErrorMessage::CURRENT_PASSWORD_INCORRECT,
+                )),
+            ));
+        }
+        Err(e) => {
+            tracing::error!("Password verification error: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    ErrorCode::INTERNAL_SERVER_ERROR,
+                    ErrorMessage::SERVER_ERROR_OCCURRED,
+                )),
+            ));
+        }
+    }
+
+    if new_email.eq_ignore_ascii_case(&user.email) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                ErrorCode::VALIDATION_ERROR,
+                ErrorMessage::EMAIL_UNCHANGED,
+            )),
+        ));
+    }
+
+    match db.email_exists(new_email) {
+        Ok(false) => {}
+        Ok(true) => {
+            return Err((
+                StatusCode::CONFLICT,
+                Json(ApiResponse::error(
+                    ErrorCode::USER_ALREADY_EXISTS,
+                    ErrorMessage::EMAIL_ALREADY_REGISTERED,
+                )),
+            ));
+        }
+        Err(e) => {
+            tracing::error!("Database error checking email availability: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    ErrorCode::DATABASE_ERROR,
+                    ErrorMessage::INTERNAL_SERVER_ERROR,
+                )),
+            ));
+        }
+    }
+
+    let (token, token_hash) = OneTimeToken::generate();
+    let (cancel_token, cancel_token_hash) = OneTimeToken::generate();
+    let base_url = MailService::app_base_url();
+    let confirm_link = format!(
+        "{}/confirm-email-change?token={}",
+        base_url,
+        EmailMessage::SECRET_PLACEHOLDER
+    );
+    let cancel_link = format!(
+        "{}/cancel-email-change?token={}",
+        base_url,
+        EmailMessage::SECRET_PLACEHOLDER
+    );
+    let locale = I18nService::for_request(user.locale.as_deref(), &headers);
+
+    let emails = EmailTemplate::EmailChangeConfirmation
+        .render(
+            locale,
+            new_email,
+            &[
+                ("link", &confirm_link),
+                ("ttl_hours", &EmailChange::TOKEN_TTL_HOURS.to_string()),
+            ],
+        )
+        .and_then(|confirmation| {
+            let confirmation = confirmation.with_secret(&token);
+            let notice = EmailTemplate::EmailChangeNotice
+                .render(
+                    locale,
+                    &user.email,
+                    &[
+                        ("new_email", new_email),
+                        ("link", &cancel_link),
+                        ("cancel_ttl_days", &EmailChange::CANCEL_TTL_DAYS.to_string()),
+                    ],
+                )?
+                .with_secret(&cancel_token);
+            Ok((confirmation, notice))
+        });
+
+    let expires_at = match emails
+        .map_err(anyhow::Error::from)
+        .and_then(|(confirmation, notice)| {
+            db.create_email_change_request(
+                user_id,
+                new_email,
+                &token_hash,
+                &cancel_token_hash,
+                &confirmation,
+                &notice,
+            )
+        }) {
+        Ok(expires_at) => expires_at,
+        Err(e) => {
+            tracing::error!("Failed to create email change request: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(
+                    ErrorCode::DATABASE_ERROR,
+                    ErrorMessage::EMAIL_CHANGE_FAILED,
+                )),
+            ));
+        }
+    };
+
+    let _ = db.log_security_event(
+        Some(user_id),
+        "email_change_requested",
+        Some(json!({ "new_email": new_email })),
+        true,
+        Some(&client),
+    );
+
+    Ok(Json(ApiResponse::success(json!({
+        "message": "A confirmation link has been sent to the new email address",
+        "pending_email": new_email,
+        "expires_at": expires_at.to_rfc3339()
+    }))))
+}
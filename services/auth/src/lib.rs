@@ -3,6 +3,7 @@ pub mod database;
 pub mod handlers;
 pub mod models;
 pub mod proto_generated;
+pub mod routes;
 pub mod utils;
 
 // Re-export commonly used items
@@ -1,26 +1,13 @@
-use axum::{
-    http::Request,
-    routing::{get, patch, post, put},
-    Router,
-};
+use axum::http::Request;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-// Import handlers and database
 use venomous_dashboard_auth::{
     database::Database,
-    handlers::{
-        admin::{
-            get_account_lock_status_handler, get_security_logs_handler, get_users_handler,
-            reset_user_password_handler, revoke_user_sessions_handler, unlock_user_account_handler,
-            update_user_status_handler,
-        },
-        auth::{logout_handler, signin_handler, signup_handler},
-        token::{token_info_handler, token_refresh_handler, token_verify_handler},
-        user::{get_profile_handler, update_profile_handler},
-    },
+    routes::router,
+    utils::{AuditChain, MailService, OutboxWorker},
 };
 
 #[tokio::main]
@@ -46,44 +33,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
+    // Without a signing key the audit chain is only protected by its own hashes
+    if AuditChain::signing_key().is_none() {
+        tracing::warn!("AUDIT_SIGNING_KEY is not set; audit checkpoints will not be signed");
+    }
+
+    // Start delivering queued email from the outbox
+    OutboxWorker::spawn(database.clone(), MailService::mailer());
+
     // Build application router with all handlers and shared state
-    let app = Router::new()
-        // Health check route
-        .route(
-            "/health",
-            get(|| async { axum::Json(serde_json::json!({"status": "ok", "service": "auth"})) }),
-        )
-        // Authentication routes
-        .route("/signup", post(signup_handler))
-        .route("/signin", post(signin_handler))
-        .route("/logout", post(logout_handler))
-        // Token management routes
-        .route("/token-verify", post(token_verify_handler))
-        .route("/token-info", post(token_info_handler))
-        .route("/token-refresh", post(token_refresh_handler))
-        // Admin routes (require admin authentication)
-        .route("/admin/users", get(get_users_handler))
-        .route(
-            "/admin/users/:user_id/status",
-            put(update_user_status_handler),
-        )
-        .route(
-            "/admin/users/:user_id/reset-password",
-            post(reset_user_password_handler),
-        )
-        .route("/admin/security-logs", get(get_security_logs_handler))
-        .route("/admin/sessions/revoke", post(revoke_user_sessions_handler))
-        // Account unlock routes
-        .route("/admin/account/unlock", post(unlock_user_account_handler))
-        .route(
-            "/admin/users/:user_id/lock-status",
-            get(get_account_lock_status_handler),
-        )
-        // User management routes
-        .route("/user/profile", get(get_profile_handler))
-        .route("/user/profile", patch(update_profile_handler))
-        // Add shared state (database connection pool)
-        .with_state(database)
+    let app = router(database)
         // Add logging middleware - skip /health endpoint
         .layer(
             TraceLayer::new_for_http().make_span_with(|request: &Request<_>| {
@@ -1,9 +1,21 @@
 use chrono::{DateTime, Utc};
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::pg::{Pg, PgValue};
 use diesel::prelude::*;
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Text;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 use uuid::Uuid;
 
-use crate::database::schema::{auth_users, roles, users};
+use crate::database::schema::{
+    audit_checkpoints, auth_users, email_change_requests, email_outbox, email_verification_tokens,
+    groups, memberships, organization_invitations, organizations, password_history,
+    password_reset_tokens, permissions, relation_tuples, roles, security_events, user_sessions,
+    users,
+};
 
 /// Role model for database
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
@@ -15,6 +27,8 @@ pub struct Role {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub deleted_at: Option<DateTime<Utc>>,
+    pub password_max_age_days: Option<i32>, // None disables password rotation
+    pub parent_role_id: Option<Uuid>,       // Permissions are inherited from the parent
 }
 
 /// Role insert model
@@ -23,6 +37,84 @@ pub struct Role {
 pub struct NewRole {
     pub name: String,
     pub description: Option<String>,
+    pub password_max_age_days: Option<i32>,
+    pub parent_role_id: Option<Uuid>,
+}
+
+/// Permission model (`resource:action`)
+#[derive(Debug, Clone, Serialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = permissions)]
+pub struct Permission {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub scope: String, // `Permissions::GLOBAL_SCOPE` or `Permissions::ORGANIZATION_SCOPE`
+}
+
+/// Account status stored in `users.status`
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, AsExpression, FromSqlRow,
+)]
+#[diesel(sql_type = Text)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountStatus {
+    Active,
+    Disabled,
+    Suspended,
+}
+
+impl AccountStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AccountStatus::Active => "active",
+            AccountStatus::Disabled => "disabled",
+            AccountStatus::Suspended => "suspended",
+        }
+    }
+
+    /// Whether the account may not sign in or use its tokens at `now`
+    ///
+    /// A suspension without an end date lasts until an admin lifts it.
+    pub fn blocks_access(self, suspended_until: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+        match self {
+            AccountStatus::Active => false,
+            AccountStatus::Disabled => true,
+            AccountStatus::Suspended => suspended_until.is_none_or(|until| until > now),
+        }
+    }
+}
+
+impl fmt::Display for AccountStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AccountStatus {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "active" => Ok(AccountStatus::Active),
+            "disabled" => Ok(AccountStatus::Disabled),
+            "suspended" => Ok(AccountStatus::Suspended),
+            other => Err(format!("Unknown account status: {}", other)),
+        }
+    }
+}
+
+impl ToSql<Text, Pg> for AccountStatus {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        <str as ToSql<Text, Pg>>::to_sql(self.as_str(), out)
+    }
+}
+
+impl FromSql<Text, Pg> for AccountStatus {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let value = <String as FromSql<Text, Pg>>::from_sql(bytes)?;
+        Ok(value.parse()?)
+    }
 }
 
 /// User model for database (business information only)
@@ -37,6 +129,18 @@ pub struct User {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub deleted_at: Option<DateTime<Utc>>,
+    pub locale: Option<String>,
+    pub status: AccountStatus,
+    pub status_reason: Option<String>,
+    pub suspended_until: Option<DateTime<Utc>>, // None with Suspended means until lifted
+    pub status_changed_at: Option<DateTime<Utc>>,
+}
+
+impl User {
+    /// Whether the account's status currently blocks sign-in and token use
+    pub fn is_access_blocked(&self, now: DateTime<Utc>) -> bool {
+        self.status.blocks_access(self.suspended_until, now)
+    }
 }
 
 /// User insert model
@@ -47,6 +151,7 @@ pub struct NewUser {
     pub name: String,
     pub avatar_path: Option<String>,
     pub role_id: Uuid,
+    pub locale: Option<String>,
 }
 
 /// Auth user model for authentication data
@@ -65,6 +170,8 @@ pub struct AuthUser {
     pub login_failure_count: i32,
     pub is_login_locked: bool,
     pub deleted_at: Option<DateTime<Utc>>,
+    pub password_reset_required: bool, // Must set a new password at next sign-in
+    pub password_changed_at: DateTime<Utc>,
 }
 
 /// Auth user insert model
@@ -76,3 +183,290 @@ pub struct NewAuthUser {
     pub password_hash: String,
     pub email_verified: bool,
 }
+
+/// Password history model (previous password hashes per auth user)
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable, Associations)]
+#[diesel(table_name = password_history)]
+#[diesel(belongs_to(AuthUser))]
+pub struct PasswordHistory {
+    pub id: Uuid,
+    pub auth_user_id: Uuid,
+    pub password_hash: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Password history insert model
+#[derive(Debug, Insertable)]
+#[diesel(table_name = password_history)]
+pub struct NewPasswordHistory {
+    pub auth_user_id: Uuid,
+    pub password_hash: String,
+}
+
+/// User session model (one per sign-in, referenced by the JWT `sid` claim)
+#[derive(Debug, Clone, Serialize, Queryable, Identifiable, Selectable, Associations)]
+#[diesel(table_name = user_sessions)]
+#[diesel(belongs_to(User))]
+pub struct UserSession {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub revoked_reason: Option<String>,
+    pub organization_id: Option<Uuid>, // Active organization, carried as the `org` claim
+}
+
+/// User session insert model
+#[derive(Debug, Insertable)]
+#[diesel(table_name = user_sessions)]
+pub struct NewUserSession {
+    pub user_id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+/// Password reset token insert model (the plain token is only ever emailed)
+#[derive(Debug, Insertable)]
+#[diesel(table_name = password_reset_tokens)]
+pub struct NewPasswordResetToken {
+    pub user_id: Uuid,
+    pub token_hash: String,
+    pub requested_by: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Email verification token insert model
+#[derive(Debug, Insertable)]
+#[diesel(table_name = email_verification_tokens)]
+pub struct NewEmailVerificationToken {
+    pub user_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Email change request model
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable, Associations)]
+#[diesel(table_name = email_change_requests)]
+#[diesel(belongs_to(User))]
+pub struct EmailChangeRequest {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub old_email: String,
+    pub new_email: String,
+    pub token_hash: String,
+    pub cancel_token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub cancel_expires_at: DateTime<Utc>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Email change request insert model (the plain tokens are only ever emailed)
+#[derive(Debug, Insertable)]
+#[diesel(table_name = email_change_requests)]
+pub struct NewEmailChangeRequest {
+    pub user_id: Uuid,
+    pub old_email: String,
+    pub new_email: String,
+    pub token_hash: String,
+    pub cancel_token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub cancel_expires_at: DateTime<Utc>,
+}
+
+/// Email outbox model (messages waiting for or past delivery)
+#[derive(Debug, Clone, Serialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = email_outbox)]
+pub struct EmailOutboxEntry {
+    pub id: Uuid,
+    pub to_address: String,
+    pub subject: String,
+    pub body: String,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub secret: Option<String>, // One-time token for the body, cleared once sent or dead
+}
+
+/// Email outbox insert model
+#[derive(Debug, Insertable)]
+#[diesel(table_name = email_outbox)]
+pub struct NewEmailOutboxEntry {
+    pub to_address: String,
+    pub subject: String,
+    pub body: String,
+    pub secret: Option<String>,
+}
+
+/// Security event model (sign-ins, lockouts, account and admin actions)
+#[derive(Debug, Clone, Serialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = security_events)]
+pub struct SecurityEvent {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>, // None for events about unknown accounts
+    pub event_type: String,
+    pub success: bool,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+    pub sequence: Option<i64>, // Position in the audit chain, None for events older than the chain
+    pub prev_hash: Option<String>,
+    pub entry_hash: Option<String>,
+    pub stream: i16, // Which of the parallel chains the event belongs to
+}
+
+/// Security event insert model (id and time are set up front because they are hashed)
+#[derive(Debug, Insertable)]
+#[diesel(table_name = security_events)]
+pub struct NewSecurityEvent {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub event_type: String,
+    pub success: bool,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+    pub sequence: Option<i64>,
+    pub prev_hash: Option<String>,
+    pub entry_hash: Option<String>,
+    pub stream: i16,
+}
+
+/// Signed audit chain checkpoint
+#[derive(Debug, Clone, Serialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = audit_checkpoints)]
+pub struct AuditCheckpoint {
+    pub id: Uuid,
+    pub sequence: i64,
+    pub entry_hash: String,
+    pub signature: String, // HMAC-SHA256 of sequence and hash
+    pub created_at: DateTime<Utc>,
+    pub stream: i16,
+}
+
+/// Audit checkpoint insert model
+#[derive(Debug, Insertable)]
+#[diesel(table_name = audit_checkpoints)]
+pub struct NewAuditCheckpoint {
+    pub stream: i16,
+    pub sequence: i64,
+    pub entry_hash: String,
+    pub signature: String,
+}
+
+/// Organization (tenant) model
+#[derive(Debug, Clone, Serialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = organizations)]
+pub struct Organization {
+    pub id: Uuid,
+    pub name: String,
+    pub slug: String, // Unique, URL-safe name
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+/// Organization insert model
+#[derive(Debug, Insertable)]
+#[diesel(table_name = organizations)]
+pub struct NewOrganization {
+    pub name: String,
+    pub slug: String,
+    pub created_by: Option<Uuid>,
+}
+
+/// A user's membership and role in an organization
+#[derive(Debug, Clone, Serialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = memberships)]
+pub struct Membership {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub user_id: Uuid,
+    pub role_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Membership insert model
+#[derive(Debug, Insertable)]
+#[diesel(table_name = memberships)]
+pub struct NewMembership {
+    pub organization_id: Uuid,
+    pub user_id: Uuid,
+    pub role_id: Uuid,
+}
+
+/// Organization invitation model (the plain token is only ever emailed)
+#[derive(Debug, Clone, Serialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = organization_invitations)]
+pub struct OrganizationInvitation {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub email: String,
+    pub role_id: Uuid,
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    pub invited_by: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub accepted_by: Option<Uuid>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Organization invitation insert model
+#[derive(Debug, Insertable)]
+#[diesel(table_name = organization_invitations)]
+pub struct NewOrganizationInvitation {
+    pub organization_id: Uuid,
+    pub email: String,
+    pub role_id: Uuid,
+    pub token_hash: String,
+    pub invited_by: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Group of members within an organization
+#[derive(Debug, Clone, Serialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = groups)]
+pub struct Group {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Group insert model
+#[derive(Debug, Insertable)]
+#[diesel(table_name = groups)]
+pub struct NewGroup {
+    pub organization_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_by: Option<Uuid>,
+}
+
+/// Relation tuple insert model; the subject relation is set for usersets like `group:9#member`
+#[derive(Debug, Insertable)]
+#[diesel(table_name = relation_tuples)]
+pub struct NewRelationTuple {
+    pub namespace: String,
+    pub object_id: String,
+    pub relation: String,
+    pub subject_namespace: String,
+    pub subject_id: String,
+    pub subject_relation: Option<String>,
+    pub created_revision: i64,
+}
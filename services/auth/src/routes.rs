@@ -0,0 +1,205 @@
+use axum::{
+    middleware,
+    routing::{delete, get, patch, post, put},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::database::Database;
+use crate::handlers::{
+    admin::{
+        create_role_handler, delete_role_handler, export_audit_log_handler,
+        get_account_lock_status_handler, get_email_outbox_handler, get_expired_passwords_handler,
+        get_permissions_handler, get_roles_handler, get_security_logs_handler, get_users_handler,
+        require_password_change_handler, reset_user_password_handler, revoke_user_sessions_handler,
+        stream_security_events_handler, unlock_user_account_handler, update_role_handler,
+        update_user_role_handler, update_user_status_handler, verify_audit_log_handler,
+    },
+    auth::{
+        cancel_email_change_handler, confirm_email_change_handler, logout_handler,
+        password_reset_confirm_handler, password_reset_request_handler,
+        resend_verification_handler, signin_handler, signup_handler, verify_email_handler,
+    },
+    authz::{authz_batch_check_handler, authz_check_handler},
+    group::{
+        add_group_member_handler, create_group_handler, delete_group_handler,
+        get_group_members_handler, get_groups_handler, get_member_permissions_handler,
+        group_membership_handler, remove_group_member_handler, update_group_handler,
+        update_member_permissions_handler,
+    },
+    organization::{
+        accept_invitation_handler, create_invitation_handler, create_organization_handler,
+        get_invitations_handler, get_organization_members_handler, get_organizations_handler,
+        remove_member_handler, revoke_invitation_handler, switch_organization_handler,
+        update_member_role_handler,
+    },
+    relation::{
+        check_relation_handler, expand_relation_handler, list_relation_objects_handler,
+        write_relation_tuples_handler,
+    },
+    token::{token_info_handler, token_refresh_handler, token_verify_handler},
+    user::{
+        change_password_handler, get_profile_handler, request_email_change_handler,
+        update_locale_handler, update_profile_handler,
+    },
+};
+use crate::utils::i18n::localize_error_responses;
+use crate::utils::service_auth::require_service_key;
+
+/// Application router with all handlers and shared state
+pub fn router(database: Arc<Database>) -> Router {
+    Router::new()
+        // Health check route
+        .route(
+            "/health",
+            get(|| async { axum::Json(serde_json::json!({"status": "ok", "service": "auth"})) }),
+        )
+        // Authentication routes
+        .route("/signup", post(signup_handler))
+        .route("/signin", post(signin_handler))
+        .route("/logout", post(logout_handler))
+        .route(
+            "/password-reset/request",
+            post(password_reset_request_handler),
+        )
+        .route(
+            "/password-reset/confirm",
+            post(password_reset_confirm_handler),
+        )
+        .route("/verify-email", post(verify_email_handler))
+        .route("/verify-email/resend", post(resend_verification_handler))
+        .route("/email-change/confirm", post(confirm_email_change_handler))
+        .route("/email-change/cancel", post(cancel_email_change_handler))
+        // Token management routes
+        .route("/token-verify", post(token_verify_handler))
+        .route("/token-info", post(token_info_handler))
+        .route("/token-refresh", post(token_refresh_handler))
+        // Admin routes (require admin authentication)
+        .route("/admin/users", get(get_users_handler))
+        .route(
+            "/admin/users/password-expired",
+            get(get_expired_passwords_handler),
+        )
+        .route(
+            "/admin/users/:user_id/status",
+            put(update_user_status_handler),
+        )
+        .route(
+            "/admin/users/:user_id/reset-password",
+            post(reset_user_password_handler),
+        )
+        .route(
+            "/admin/users/:user_id/require-password-change",
+            post(require_password_change_handler),
+        )
+        .route("/admin/security-logs", get(get_security_logs_handler))
+        .route(
+            "/admin/security-logs/stream",
+            get(stream_security_events_handler),
+        )
+        .route("/admin/audit/verify", get(verify_audit_log_handler))
+        .route("/admin/audit/export", get(export_audit_log_handler))
+        .route("/admin/email-outbox", get(get_email_outbox_handler))
+        .route("/admin/sessions/revoke", post(revoke_user_sessions_handler))
+        // Account unlock routes
+        .route("/admin/account/unlock", post(unlock_user_account_handler))
+        .route(
+            "/admin/users/:user_id/lock-status",
+            get(get_account_lock_status_handler),
+        )
+        // Role management routes
+        .route("/admin/permissions", get(get_permissions_handler))
+        .route(
+            "/admin/roles",
+            get(get_roles_handler).post(create_role_handler),
+        )
+        .route(
+            "/admin/roles/:role_id",
+            patch(update_role_handler).delete(delete_role_handler),
+        )
+        .route("/admin/users/:user_id/role", put(update_user_role_handler))
+        // User management routes
+        .route("/user/profile", get(get_profile_handler))
+        .route("/user/profile", patch(update_profile_handler))
+        .route("/user/password", post(change_password_handler))
+        .route("/user/locale", put(update_locale_handler))
+        .route("/user/email", post(request_email_change_handler))
+        // Organization routes
+        .route(
+            "/orgs",
+            get(get_organizations_handler).post(create_organization_handler),
+        )
+        .route("/orgs/invitations/accept", post(accept_invitation_handler))
+        .route("/switch-org", post(switch_organization_handler))
+        .route(
+            "/orgs/:org_id/members",
+            get(get_organization_members_handler),
+        )
+        .route(
+            "/orgs/:org_id/members/:user_id",
+            delete(remove_member_handler),
+        )
+        .route(
+            "/orgs/:org_id/members/:user_id/role",
+            put(update_member_role_handler),
+        )
+        .route(
+            "/orgs/:org_id/invitations",
+            get(get_invitations_handler).post(create_invitation_handler),
+        )
+        .route(
+            "/orgs/:org_id/invitations/:invitation_id",
+            delete(revoke_invitation_handler),
+        )
+        .route(
+            "/orgs/:org_id/members/:user_id/permissions",
+            get(get_member_permissions_handler).put(update_member_permissions_handler),
+        )
+        // Group routes
+        .route(
+            "/orgs/:org_id/groups",
+            get(get_groups_handler).post(create_group_handler),
+        )
+        .route(
+            "/orgs/:org_id/groups/:group_id",
+            patch(update_group_handler).delete(delete_group_handler),
+        )
+        .route(
+            "/orgs/:org_id/groups/:group_id/members",
+            get(get_group_members_handler),
+        )
+        .route(
+            "/orgs/:org_id/groups/:group_id/members/:user_id",
+            put(add_group_member_handler).delete(remove_group_member_handler),
+        )
+        // Internal service routes (not exposed through the gateway)
+        .merge(internal_routes())
+        // Translate error messages into the caller's language
+        .layer(middleware::from_fn_with_state(
+            database.clone(),
+            localize_error_responses,
+        ))
+        // Add shared state (database connection pool)
+        .with_state(database)
+}
+
+/// Routes for other services on the internal network, never for end users
+///
+/// These take no user token. The gateway must not expose them, and each request must
+/// carry the calling service's key from `INTERNAL_SERVICE_KEYS` in `X-Service-Key`;
+/// others get a 401 `SERVICE_UNAUTHORIZED`. Handlers can read the caller from the
+/// `ServiceCaller` extension.
+fn internal_routes() -> Router<Arc<Database>> {
+    Router::new()
+        .route("/internal/group-membership", get(group_membership_handler))
+        .route("/authz/check", post(authz_check_handler))
+        .route("/authz/check/batch", post(authz_batch_check_handler))
+        .route("/relations/write", post(write_relation_tuples_handler))
+        .route("/relations/check", post(check_relation_handler))
+        .route("/relations/expand", post(expand_relation_handler))
+        .route(
+            "/relations/list-objects",
+            post(list_relation_objects_handler),
+        )
+        .route_layer(middleware::from_fn(require_service_key))
+}
@@ -0,0 +1,499 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::models::database::{AuditCheckpoint, SecurityEvent};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Fields of a security event covered by its chain hash
+#[derive(Debug, Clone, Copy)]
+pub struct AuditRecord<'a> {
+    pub stream: i16,
+    pub sequence: i64,
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub event_type: &'a str,
+    pub success: bool,
+    pub ip_address: Option<&'a str>,
+    pub user_agent: Option<&'a str>,
+    pub metadata: Option<&'a Value>,
+    pub created_at: DateTime<Utc>, // Microsecond precision, as stored by Postgres
+}
+
+impl<'a> AuditRecord<'a> {
+    /// Hashed fields of a stored event, or `None` if it predates the chain
+    pub fn from_event(event: &'a SecurityEvent) -> Option<Self> {
+        Some(AuditRecord {
+            stream: event.stream,
+            sequence: event.sequence?,
+            id: event.id,
+            user_id: event.user_id,
+            event_type: &event.event_type,
+            success: event.success,
+            ip_address: event.ip_address.as_deref(),
+            user_agent: event.user_agent.as_deref(),
+            metadata: event.metadata.as_ref(),
+            created_at: event.created_at,
+        })
+    }
+}
+
+/// Hash chain over security events
+///
+/// Every event stores the hash of the entry before it, so editing or deleting
+/// a row breaks every later link. Events are spread over several independent
+/// streams, each its own chain, so writers only contend within a stream.
+/// Checkpoints sign a stream's head with a key kept outside the database, so the
+/// tail can't be silently recomputed, and are also appended to an anchor file
+/// outside the database, so deleting the tail together with its checkpoints
+/// still shows.
+pub struct AuditChain;
+
+impl AuditChain {
+    /// `prev_hash` of the first chained entry
+    pub const GENESIS_HASH: &'static str =
+        "0000000000000000000000000000000000000000000000000000000000000000";
+
+    /// SHA-256 over the previous hash and the record, as hex
+    ///
+    /// The stream is hashed too, so an entry can't be moved to another stream's chain.
+    pub fn entry_hash(prev_hash: &str, record: &AuditRecord) -> String {
+        // A JSON array keeps field boundaries unambiguous; metadata keys are sorted
+        let canonical = json!([
+            prev_hash,
+            record.stream,
+            record.sequence,
+            record.id,
+            record.user_id,
+            record.event_type,
+            record.success,
+            record.ip_address,
+            record.user_agent,
+            record.metadata.map(canonicalize),
+            record
+                .created_at
+                .to_rfc3339_opts(SecondsFormat::Micros, true),
+        ]);
+
+        hex_encode(&Sha256::digest(canonical.to_string().as_bytes()))
+    }
+
+    /// Key for checkpoint signatures, from `AUDIT_SIGNING_KEY`
+    ///
+    /// Deliberately separate from `JWT_SECRET`: a service that can mint tokens
+    /// must not also be able to re-sign a rewritten audit trail.
+    pub fn signing_key() -> Option<Vec<u8>> {
+        env::var("AUDIT_SIGNING_KEY")
+            .ok()
+            .filter(|key| !key.is_empty())
+            .map(String::into_bytes)
+    }
+
+    /// Append-only file outside the database that checkpoints are copied to, from `AUDIT_ANCHOR_FILE`
+    pub fn anchor_file() -> Option<PathBuf> {
+        env::var("AUDIT_ANCHOR_FILE")
+            .ok()
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+    }
+
+    /// Append one anchor as a JSON line
+    pub fn append_anchor(path: &Path, anchor: &AuditAnchor) -> io::Result<()> {
+        let mut line = serde_json::to_string(anchor)?;
+        line.push('\n');
+        // One write per line, so concurrent appenders don't interleave
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(line.as_bytes())
+    }
+
+    /// Every anchor in the file; a missing file has none
+    pub fn read_anchors(path: &Path) -> io::Result<Vec<AuditAnchor>> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut anchors = Vec::new();
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let anchor = serde_json::from_str(&line).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("anchor file line {}: {}", index + 1, e),
+                )
+            })?;
+            anchors.push(anchor);
+        }
+        Ok(anchors)
+    }
+
+    /// HMAC-SHA256 of a checkpoint, as hex
+    pub fn sign_checkpoint(key: &[u8], stream: i16, sequence: i64, entry_hash: &str) -> String {
+        hex_encode(
+            &Self::checkpoint_mac(key, stream, sequence, entry_hash)
+                .finalize()
+                .into_bytes(),
+        )
+    }
+
+    /// Check a checkpoint signature in constant time
+    pub fn checkpoint_signature_valid(
+        key: &[u8],
+        stream: i16,
+        sequence: i64,
+        entry_hash: &str,
+        signature: &str,
+    ) -> bool {
+        match hex_decode(signature) {
+            Some(signature) => Self::checkpoint_mac(key, stream, sequence, entry_hash)
+                .verify_slice(&signature)
+                .is_ok(),
+            None => false,
+        }
+    }
+
+    fn checkpoint_mac(key: &[u8], stream: i16, sequence: i64, entry_hash: &str) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+        mac.update(format!("audit-checkpoint:{}:{}:{}", stream, sequence, entry_hash).as_bytes());
+        mac
+    }
+}
+
+/// A chain position recorded outside the database when it was checkpointed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditAnchor {
+    pub stream: i16,
+    pub sequence: i64,
+    pub entry_hash: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&AuditCheckpoint> for AuditAnchor {
+    fn from(checkpoint: &AuditCheckpoint) -> Self {
+        AuditAnchor {
+            stream: checkpoint.stream,
+            sequence: checkpoint.sequence,
+            entry_hash: checkpoint.entry_hash.clone(),
+            created_at: checkpoint.created_at,
+        }
+    }
+}
+
+/// What is wrong with the first bad entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditFailureKind {
+    /// A sequence number is missing (deleted entry)
+    Gap,
+    /// `prev_hash` doesn't match the entry before it (deleted or reordered entries)
+    BrokenLink,
+    /// The stored hash doesn't match the entry's content (edited entry)
+    Modified,
+    /// A checkpoint's signature or hash, or an anchor's hash, doesn't match the chain (rewritten chain)
+    BadCheckpoint,
+    /// A checkpoint or anchor covers entries that no longer exist (deleted tail)
+    Truncated,
+    /// An entry without a chain position was added after chaining began
+    Unchained,
+}
+
+/// The first place verification failed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuditFailure {
+    pub kind: AuditFailureKind,
+    pub stream: Option<i16>,
+    pub sequence: Option<i64>,
+    pub event_id: Option<Uuid>,
+    pub detail: String,
+}
+
+/// Result of walking one stream of the audit chain
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditVerification {
+    pub stream: i16,
+    pub valid: bool,
+    pub checked_entries: u64,
+    pub verified_checkpoints: u64,
+    pub verified_anchors: u64,
+    pub last_sequence: Option<i64>,
+    pub head_hash: Option<String>,
+    /// Entries after the last checkpoint, only protected by the chain itself
+    pub unanchored_entries: u64,
+    pub first_failure: Option<AuditFailure>,
+}
+
+/// Result of walking every stream of the audit chain
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditChainReport {
+    pub valid: bool,
+    pub checked_entries: u64,
+    /// Whether heads were compared against an anchor file outside the database
+    pub externally_anchored: bool,
+    pub first_failure: Option<AuditFailure>,
+    pub streams: Vec<AuditVerification>,
+}
+
+impl AuditChainReport {
+    /// Combine per-stream results; the first failure is the earliest stream's
+    pub fn new(
+        streams: Vec<AuditVerification>,
+        externally_anchored: bool,
+        failure: Option<AuditFailure>,
+    ) -> Self {
+        let first_failure = streams
+            .iter()
+            .find_map(|stream| stream.first_failure.clone())
+            .or(failure);
+
+        AuditChainReport {
+            valid: first_failure.is_none(),
+            checked_entries: streams.iter().map(|stream| stream.checked_entries).sum(),
+            externally_anchored,
+            first_failure,
+            streams,
+        }
+    }
+}
+
+/// Checks one stream's entries fed in sequence order, stopping at the first failure
+pub struct AuditVerifier {
+    key: Vec<u8>,
+    stream: i16,
+    checkpoints: BTreeMap<i64, AuditCheckpoint>,
+    anchors: BTreeMap<i64, AuditAnchor>,
+    next_sequence: i64,
+    prev_hash: String,
+    checked_entries: u64,
+    verified_checkpoints: u64,
+    verified_anchors: u64,
+    last_checkpoint: Option<i64>,
+    failure: Option<AuditFailure>,
+}
+
+impl AuditVerifier {
+    /// Verifier for one stream; checkpoints and anchors of other streams are ignored
+    pub fn new(
+        key: &[u8],
+        stream: i16,
+        checkpoints: Vec<AuditCheckpoint>,
+        anchors: Vec<AuditAnchor>,
+    ) -> Self {
+        AuditVerifier {
+            key: key.to_vec(),
+            stream,
+            checkpoints: checkpoints
+                .into_iter()
+                .filter(|checkpoint| checkpoint.stream == stream)
+                .map(|checkpoint| (checkpoint.sequence, checkpoint))
+                .collect(),
+            anchors: anchors
+                .into_iter()
+                .filter(|anchor| anchor.stream == stream)
+                .map(|anchor| (anchor.sequence, anchor))
+                .collect(),
+            next_sequence: 1,
+            prev_hash: AuditChain::GENESIS_HASH.to_string(),
+            checked_entries: 0,
+            verified_checkpoints: 0,
+            verified_anchors: 0,
+            last_checkpoint: None,
+            failure: None,
+        }
+    }
+
+    /// Whether a failure has been found (later entries are ignored)
+    pub fn failed(&self) -> bool {
+        self.failure.is_some()
+    }
+
+    /// Check the next entry; returns false once the chain is known to be bad
+    pub fn push(&mut self, event: &SecurityEvent) -> bool {
+        if self.failed() {
+            return false;
+        }
+
+        let stream = self.stream;
+        let fail = |kind, sequence, detail: String| AuditFailure {
+            kind,
+            stream: Some(stream),
+            sequence,
+            event_id: Some(event.id),
+            detail,
+        };
+        let Some(record) = AuditRecord::from_event(event) else {
+            self.failure = Some(fail(
+                AuditFailureKind::Unchained,
+                None,
+                "entry has no chain position".to_string(),
+            ));
+            return false;
+        };
+
+        let sequence = record.sequence;
+        if event.stream != self.stream {
+            self.failure = Some(fail(
+                AuditFailureKind::BrokenLink,
+                Some(sequence),
+                format!("entry belongs to stream {}", event.stream),
+            ));
+            return false;
+        }
+        if sequence != self.next_sequence {
+            self.failure = Some(fail(
+                AuditFailureKind::Gap,
+                Some(sequence),
+                format!(
+                    "expected sequence {} but found {}",
+                    self.next_sequence, sequence
+                ),
+            ));
+            return false;
+        }
+        if event.prev_hash.as_deref() != Some(self.prev_hash.as_str()) {
+            self.failure = Some(fail(
+                AuditFailureKind::BrokenLink,
+                Some(sequence),
+                "previous hash does not match the entry before it".to_string(),
+            ));
+            return false;
+        }
+        let entry_hash = AuditChain::entry_hash(&self.prev_hash, &record);
+        if event.entry_hash.as_deref() != Some(entry_hash.as_str()) {
+            self.failure = Some(fail(
+                AuditFailureKind::Modified,
+                Some(sequence),
+                "entry content does not match its hash".to_string(),
+            ));
+            return false;
+        }
+
+        if let Some(checkpoint) = self.checkpoints.get(&sequence) {
+            let signed = AuditChain::checkpoint_signature_valid(
+                &self.key,
+                checkpoint.stream,
+                checkpoint.sequence,
+                &checkpoint.entry_hash,
+                &checkpoint.signature,
+            );
+            if !signed || checkpoint.entry_hash != entry_hash {
+                self.failure = Some(fail(
+                    AuditFailureKind::BadCheckpoint,
+                    Some(sequence),
+                    if signed {
+                        "signed checkpoint does not match the chain".to_string()
+                    } else {
+                        "checkpoint signature is invalid".to_string()
+                    },
+                ));
+                return false;
+            }
+            self.verified_checkpoints += 1;
+            self.last_checkpoint = Some(sequence);
+        }
+        if let Some(anchor) = self.anchors.get(&sequence) {
+            if anchor.entry_hash != entry_hash {
+                self.failure = Some(fail(
+                    AuditFailureKind::BadCheckpoint,
+                    Some(sequence),
+                    "externally anchored hash does not match the chain".to_string(),
+                ));
+                return false;
+            }
+            self.verified_anchors += 1;
+        }
+
+        self.prev_hash = entry_hash;
+        self.next_sequence += 1;
+        self.checked_entries += 1;
+        true
+    }
+
+    /// Finish the walk, flagging checkpoints or anchors past the last entry seen
+    pub fn finish(mut self) -> AuditVerification {
+        let last_sequence = (self.checked_entries > 0).then_some(self.next_sequence - 1);
+
+        if self.failure.is_none() {
+            let beyond_head = [
+                self.checkpoints
+                    .range(self.next_sequence..)
+                    .next()
+                    .map(|(&sequence, _)| ("checkpoint", sequence)),
+                self.anchors
+                    .range(self.next_sequence..)
+                    .next()
+                    .map(|(&sequence, _)| ("anchor", sequence)),
+            ];
+            if let Some((source, sequence)) = beyond_head.into_iter().flatten().next() {
+                self.failure = Some(AuditFailure {
+                    kind: AuditFailureKind::Truncated,
+                    stream: Some(self.stream),
+                    sequence: Some(self.next_sequence),
+                    event_id: None,
+                    detail: format!(
+                        "{} at sequence {} covers entries from {} that are missing",
+                        source, sequence, self.next_sequence
+                    ),
+                });
+            }
+        }
+
+        AuditVerification {
+            stream: self.stream,
+            valid: self.failure.is_none(),
+            checked_entries: self.checked_entries,
+            verified_checkpoints: self.verified_checkpoints,
+            verified_anchors: self.verified_anchors,
+            last_sequence,
+            head_hash: last_sequence.map(|_| self.prev_hash.clone()),
+            unanchored_entries: (last_sequence.unwrap_or(0) - self.last_checkpoint.unwrap_or(0))
+                .max(0) as u64,
+            first_failure: self.failure,
+        }
+    }
+}
+
+/// Copy of a JSON value with object keys sorted at every level
+fn canonicalize(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let sorted: BTreeMap<&String, Value> = map
+                .iter()
+                .map(|(key, value)| (key, canonicalize(value)))
+                .collect();
+            json!(sorted)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(canonicalize).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Lowercase hex encoding
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn hex_decode(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) || !value.is_ascii() {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&value[i..i + 2], 16).ok())
+        .collect()
+}
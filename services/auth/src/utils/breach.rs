@@ -0,0 +1,476 @@
+use sha1::{Digest, Sha1};
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::os::unix::fs::FileExt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use thiserror::Error;
+
+/// Size of a raw SHA-1 digest in bytes
+const DIGEST_LEN: usize = 20;
+
+/// Length of the hash prefix that names an HIBP range file
+const RANGE_PREFIX_LEN: usize = 5;
+
+type Digest20 = [u8; DIGEST_LEN];
+
+#[derive(Debug, Error)]
+pub enum BreachCorpusError {
+    #[error("Failed to read breach corpus: {0}")]
+    Io(#[from] io::Error),
+    #[error("Malformed breach corpus entry on line {0}")]
+    MalformedEntry(usize),
+    #[error("Breach corpus is not sorted by hash at line {0}")]
+    Unsorted(usize),
+    #[error("Binary breach corpus size is not a multiple of {DIGEST_LEN} bytes")]
+    MalformedBinary,
+}
+
+/// Where the sorted digests of a corpus live
+///
+/// Only `Memory` holds digests in memory; the on-disk formats are searched with
+/// positional reads, so lookups from several threads don't contend on a lock.
+enum CorpusBacking {
+    /// Sorted, deduplicated digests held in memory (small corpora read with `from_text`)
+    Memory(Vec<Digest20>),
+    /// `HASH:COUNT` text file sorted by hash, binary searched line by line on disk
+    Text {
+        path: PathBuf,
+        file: File,
+        size: u64,
+        entries: u64,
+    },
+    /// Directory of range files; a lookup reads only the file for the digest's prefix
+    RangeDir { dir: PathBuf, entries: u64 },
+    /// Sorted binary file searched on disk, so only one digest is read per probe
+    Binary {
+        path: PathBuf,
+        file: File,
+        entries: u64,
+    },
+}
+
+/// Set of breached password SHA-1 digests from a HIBP-style corpus
+///
+/// Supported sources, none of which are loaded into memory:
+/// - a text file of `HASH:COUNT` lines sorted by hash (the HIBP "ordered by hash"
+///   download)
+/// - a directory of HIBP range files named `ABCDE.txt` by their 5 character prefix,
+///   each holding `SUFFIX:COUNT` lines (the format served by the range API)
+/// - a `.bin` file of sorted raw 20-byte digests
+///
+/// Lookups do blocking file reads; async code should go through
+/// `BreachedPasswordService::is_breached`, which runs them on the blocking pool.
+pub struct BreachCorpus {
+    backing: CorpusBacking,
+}
+
+impl BreachCorpus {
+    /// Open a corpus from a file or range directory, choosing the format by path
+    pub fn load(path: &Path) -> Result<Self, BreachCorpusError> {
+        if path.is_dir() {
+            return Self::open_range_dir(path);
+        }
+
+        if path.extension().is_some_and(|ext| ext == "bin") {
+            return Self::open_binary(path);
+        }
+
+        Self::open_text(path)
+    }
+
+    /// Parse `HASH:COUNT` lines into memory (the count is optional and ignored); meant
+    /// for small corpora, larger ones should be opened with `load`
+    pub fn from_text<R: BufRead>(reader: R) -> Result<Self, BreachCorpusError> {
+        let mut digests = Vec::new();
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let digest =
+                parse_entry(&line, "").ok_or(BreachCorpusError::MalformedEntry(index + 1))?;
+            digests.push(digest);
+        }
+
+        Ok(Self::from_digests(digests))
+    }
+
+    /// Open a text corpus sorted by hash, checking its order and counting its entries
+    /// in one streaming pass
+    pub fn open_text(path: &Path) -> Result<Self, BreachCorpusError> {
+        let file = File::open(path)?;
+        let size = file.metadata()?.len();
+
+        let mut entries = 0;
+        let mut previous: Option<Digest20> = None;
+        for (index, line) in BufReader::new(&file).lines().enumerate() {
+            let digest =
+                parse_entry(&line?, "").ok_or(BreachCorpusError::MalformedEntry(index + 1))?;
+            match previous {
+                Some(previous) if digest < previous => {
+                    return Err(BreachCorpusError::Unsorted(index + 1));
+                }
+                Some(previous) if digest == previous => {}
+                _ => entries += 1,
+            }
+            previous = Some(digest);
+        }
+
+        Ok(BreachCorpus {
+            backing: CorpusBacking::Text {
+                path: path.to_path_buf(),
+                file,
+                size,
+                entries,
+            },
+        })
+    }
+
+    /// Open a directory of HIBP range files, counting their entries one file at a time
+    pub fn open_range_dir(dir: &Path) -> Result<Self, BreachCorpusError> {
+        let mut entries = 0;
+
+        for dir_entry in fs::read_dir(dir)? {
+            let path = dir_entry?.path();
+            let Some(prefix) = range_prefix(&path) else {
+                continue; // Not a range file
+            };
+            entries += read_range_file(&path, &prefix)?.len() as u64;
+        }
+
+        Ok(BreachCorpus {
+            backing: CorpusBacking::RangeDir {
+                dir: dir.to_path_buf(),
+                entries,
+            },
+        })
+    }
+
+    /// Open a sorted binary corpus without loading it into memory
+    pub fn open_binary(path: &Path) -> Result<Self, BreachCorpusError> {
+        let file = File::open(path)?;
+        let size = file.metadata()?.len();
+        if size % DIGEST_LEN as u64 != 0 {
+            return Err(BreachCorpusError::MalformedBinary);
+        }
+
+        Ok(BreachCorpus {
+            backing: CorpusBacking::Binary {
+                path: path.to_path_buf(),
+                file,
+                entries: size / DIGEST_LEN as u64,
+            },
+        })
+    }
+
+    /// Convert any supported corpus into the sorted binary format
+    pub fn convert_to_binary(source: &Path, destination: &Path) -> Result<u64, BreachCorpusError> {
+        let corpus = Self::load(source)?;
+        let mut writer = BufWriter::new(File::create(destination)?);
+        let written = corpus.write_binary(&mut writer)?;
+        writer.flush()?;
+        Ok(written)
+    }
+
+    /// Write the corpus as sorted raw digests, streaming from disk, returning the number
+    /// of entries
+    pub fn write_binary<W: Write>(&self, writer: &mut W) -> Result<u64, BreachCorpusError> {
+        match &self.backing {
+            CorpusBacking::Memory(digests) => {
+                for digest in digests {
+                    writer.write_all(digest)?;
+                }
+                Ok(digests.len() as u64)
+            }
+            CorpusBacking::Text { path, .. } => {
+                let mut written = 0;
+                let mut previous: Option<Digest20> = None;
+                for (index, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+                    let digest = parse_entry(&line?, "")
+                        .ok_or(BreachCorpusError::MalformedEntry(index + 1))?;
+                    if previous != Some(digest) {
+                        writer.write_all(&digest)?;
+                        written += 1;
+                    }
+                    previous = Some(digest);
+                }
+                Ok(written)
+            }
+            CorpusBacking::RangeDir { dir, .. } => {
+                // Range files in prefix order give digests in sorted order
+                let mut written = 0;
+                for prefix in 0..1u32 << (4 * RANGE_PREFIX_LEN) {
+                    let prefix = format!("{:05X}", prefix);
+                    let Some(path) = find_range_file(dir, &prefix) else {
+                        continue;
+                    };
+                    for digest in read_range_file(&path, &prefix)? {
+                        writer.write_all(&digest)?;
+                        written += 1;
+                    }
+                }
+                Ok(written)
+            }
+            CorpusBacking::Binary { path, entries, .. } => {
+                io::copy(&mut File::open(path)?, writer)?;
+                Ok(*entries)
+            }
+        }
+    }
+
+    /// Number of distinct digests in the corpus
+    pub fn len(&self) -> u64 {
+        match &self.backing {
+            CorpusBacking::Memory(digests) => digests.len() as u64,
+            CorpusBacking::Text { entries, .. }
+            | CorpusBacking::RangeDir { entries, .. }
+            | CorpusBacking::Binary { entries, .. } => *entries,
+        }
+    }
+
+    /// Check whether the corpus has no entries
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Check whether a password appears in the corpus
+    pub fn contains_password(&self, password: &str) -> bool {
+        let digest: Digest20 = Sha1::digest(password.as_bytes()).into();
+        self.contains_digest(&digest)
+    }
+
+    /// Check whether a raw SHA-1 digest appears in the corpus
+    pub fn contains_digest(&self, digest: &Digest20) -> bool {
+        let found = match &self.backing {
+            CorpusBacking::Memory(digests) => return digests.binary_search(digest).is_ok(),
+            CorpusBacking::Text { file, size, .. } => search_text(file, *size, digest),
+            CorpusBacking::RangeDir { dir, .. } => search_range_dir(dir, digest),
+            CorpusBacking::Binary { file, entries, .. } => search_binary(file, *entries, digest),
+        };
+
+        found.unwrap_or_else(|e| {
+            tracing::error!("Failed to search breach corpus: {}", e);
+            false
+        })
+    }
+
+    fn from_digests(mut digests: Vec<Digest20>) -> Self {
+        digests.sort_unstable();
+        digests.dedup();
+        digests.shrink_to_fit();
+        BreachCorpus {
+            backing: CorpusBacking::Memory(digests),
+        }
+    }
+}
+
+/// Binary search a sorted digest file by reading each probe in place
+fn search_binary(file: &File, entries: u64, digest: &Digest20) -> Result<bool, BreachCorpusError> {
+    let mut low = 0u64;
+    let mut high = entries;
+    let mut probe = [0u8; DIGEST_LEN];
+
+    while low < high {
+        let mid = low + (high - low) / 2;
+        file.read_exact_at(&mut probe, mid * DIGEST_LEN as u64)?;
+
+        match probe.cmp(digest) {
+            std::cmp::Ordering::Equal => return Ok(true),
+            std::cmp::Ordering::Less => low = mid + 1,
+            std::cmp::Ordering::Greater => high = mid,
+        }
+    }
+
+    Ok(false)
+}
+
+/// Binary search a sorted text file by byte offset, reading the first whole line at or
+/// after each probe
+fn search_text(file: &File, size: u64, digest: &Digest20) -> Result<bool, BreachCorpusError> {
+    // Every line starting before `low` is less than the digest, and every line starting
+    // at or after `high` is greater
+    let mut low = 0u64;
+    let mut high = size;
+
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let start = if mid == 0 {
+            0
+        } else {
+            read_line_at(file, mid - 1, size)?.1
+        };
+        if start >= high {
+            high = mid;
+            continue;
+        }
+
+        let (line, end) = read_line_at(file, start, size)?;
+        let entry = String::from_utf8_lossy(&line);
+        let probe = parse_entry(&entry, "").ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "malformed breach corpus line")
+        })?;
+
+        match probe.cmp(digest) {
+            std::cmp::Ordering::Equal => return Ok(true),
+            std::cmp::Ordering::Less => low = end,
+            std::cmp::Ordering::Greater => high = mid,
+        }
+    }
+
+    Ok(false)
+}
+
+/// Read from `position` up to the next newline, returning the bytes before it and the
+/// offset just past it
+fn read_line_at(file: &File, mut position: u64, size: u64) -> io::Result<(Vec<u8>, u64)> {
+    let mut line = Vec::new();
+    let mut chunk = [0u8; 128];
+
+    while position < size {
+        let wanted = chunk.len().min((size - position) as usize);
+        let read = file.read_at(&mut chunk[..wanted], position)?;
+        if read == 0 {
+            break;
+        }
+
+        if let Some(newline) = chunk[..read].iter().position(|&byte| byte == b'\n') {
+            line.extend_from_slice(&chunk[..newline]);
+            return Ok((line, position + newline as u64 + 1));
+        }
+        line.extend_from_slice(&chunk[..read]);
+        position += read as u64;
+    }
+
+    Ok((line, size))
+}
+
+/// Look for a digest in the one range file for its prefix
+fn search_range_dir(dir: &Path, digest: &Digest20) -> Result<bool, BreachCorpusError> {
+    let hex = to_hex(digest);
+    let prefix = &hex[..RANGE_PREFIX_LEN];
+    let Some(path) = find_range_file(dir, prefix) else {
+        return Ok(false);
+    };
+
+    for line in BufReader::new(File::open(path)?).lines() {
+        if parse_entry(&line?, prefix).as_ref() == Some(digest) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// The range file for an upper-case prefix, named in either case
+fn find_range_file(dir: &Path, prefix: &str) -> Option<PathBuf> {
+    [prefix.to_string(), prefix.to_ascii_lowercase()]
+        .into_iter()
+        .map(|name| dir.join(format!("{}.txt", name)))
+        .find(|path| path.is_file())
+}
+
+/// The upper-case prefix a range file is named by, if `path` is one
+fn range_prefix(path: &Path) -> Option<String> {
+    if path.extension().is_none_or(|ext| ext != "txt") {
+        return None;
+    }
+    let stem = path.file_stem()?.to_str()?;
+    (stem.len() == RANGE_PREFIX_LEN && stem.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| stem.to_ascii_uppercase())
+}
+
+/// Sorted, deduplicated digests of one range file; these hold a few thousand entries
+fn read_range_file(path: &Path, prefix: &str) -> Result<Vec<Digest20>, BreachCorpusError> {
+    let mut digests = Vec::new();
+
+    for (index, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        digests
+            .push(parse_entry(&line, prefix).ok_or(BreachCorpusError::MalformedEntry(index + 1))?);
+    }
+
+    digests.sort_unstable();
+    digests.dedup();
+    Ok(digests)
+}
+
+/// Parse a `HASH:COUNT` line, the hash completed by `prefix` for range files
+fn parse_entry(line: &str, prefix: &str) -> Option<Digest20> {
+    let hash = line.trim().split(':').next().unwrap_or_default();
+    parse_hex_digest(&format!("{}{}", prefix, hash))
+}
+
+/// Decode a 40 character hex SHA-1 (either case) into raw bytes
+fn parse_hex_digest(hex: &str) -> Option<Digest20> {
+    let bytes = hex.as_bytes();
+    if bytes.len() != DIGEST_LEN * 2 {
+        return None;
+    }
+
+    let mut digest = [0u8; DIGEST_LEN];
+    for (i, pair) in bytes.chunks(2).enumerate() {
+        let high = (pair[0] as char).to_digit(16)?;
+        let low = (pair[1] as char).to_digit(16)?;
+        digest[i] = (high * 16 + low) as u8;
+    }
+
+    Some(digest)
+}
+
+/// Encode a digest as upper-case hex, as HIBP names it
+fn to_hex(digest: &Digest20) -> String {
+    digest.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+/// Process-wide breached password check configured from the environment
+pub struct BreachedPasswordService;
+
+impl BreachedPasswordService {
+    /// Get the configured corpus, opening it on first use
+    /// (`BREACHED_PASSWORDS_FILE`; the check is disabled when unset)
+    fn corpus() -> Option<&'static BreachCorpus> {
+        static CORPUS: OnceLock<Option<BreachCorpus>> = OnceLock::new();
+
+        CORPUS
+            .get_or_init(|| {
+                let path = env::var("BREACHED_PASSWORDS_FILE").ok()?;
+                match BreachCorpus::load(Path::new(&path)) {
+                    Ok(corpus) => {
+                        tracing::info!(
+                            "Opened breached password corpus {} ({} entries)",
+                            path,
+                            corpus.len()
+                        );
+                        Some(corpus)
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to open breached password corpus {}: {}", path, e);
+                        None
+                    }
+                }
+            })
+            .as_ref()
+    }
+
+    /// Check whether the password appears in the configured breach corpus
+    ///
+    /// Opening the corpus and searching it read from disk, so both run on the blocking
+    /// pool rather than the async worker threads.
+    pub async fn is_breached(password: &str) -> bool {
+        let password = password.to_string();
+        tokio::task::spawn_blocking(move || {
+            Self::corpus().is_some_and(|corpus| corpus.contains_password(&password))
+        })
+        .await
+        .unwrap_or_else(|e| {
+            tracing::error!("Breached password check failed: {}", e);
+            false
+        })
+    }
+}
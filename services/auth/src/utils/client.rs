@@ -0,0 +1,66 @@
+use axum::http::HeaderMap;
+use std::net::IpAddr;
+
+/// Client details taken from request headers (for sessions and security events)
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl ClientInfo {
+    /// Read the client IP and user agent
+    ///
+    /// The gateway's reverse proxy appends the address it received the request from to
+    /// `X-Forwarded-For`, so only the last hop is trusted; earlier hops come from the
+    /// client and can say anything. `X-Real-IP` is used when the header is absent.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+
+        let ip_address = header("x-forwarded-for")
+            .and_then(|forwarded| {
+                forwarded
+                    .rsplit(',')
+                    .map(str::trim)
+                    .find(|ip| !ip.is_empty())
+                    .map(str::to_string)
+            })
+            .or_else(|| header("x-real-ip"));
+
+        ClientInfo {
+            ip_address,
+            user_agent: header("user-agent"),
+        }
+    }
+
+    /// The device a user agent describes, ignoring version numbers so browser and OS
+    /// updates keep the same device
+    pub fn device_key(user_agent: &str) -> String {
+        user_agent
+            .chars()
+            .filter(|c| !c.is_ascii_digit() && *c != '.' && *c != '_')
+            .collect()
+    }
+
+    /// The network an address belongs to (IPv4 /24, IPv6 /48), so a new address from
+    /// the same provider block counts as the same network
+    pub fn network_prefix(ip_address: &str) -> String {
+        match ip_address.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) => {
+                let [a, b, c, _] = ip.octets();
+                format!("{}.{}.{}.0/24", a, b, c)
+            }
+            Ok(IpAddr::V6(ip)) => {
+                let segments = ip.segments();
+                format!("{:x}:{:x}:{:x}::/48", segments[0], segments[1], segments[2])
+            }
+            Err(_) => ip_address.to_string(),
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use chrono::SecondsFormat;
+use serde_json::json;
+use std::str::FromStr;
+
+use crate::models::database::SecurityEvent;
+
+/// Vendor and product reported in CEF headers
+const CEF_VENDOR: &str = "Venomous Dashboard";
+const CEF_PRODUCT: &str = "Auth Service";
+
+/// Columns of the CSV export, in order
+const CSV_COLUMNS: [&str; 11] = [
+    "id",
+    "created_at",
+    "event_type",
+    "success",
+    "user_id",
+    "ip_address",
+    "user_agent",
+    "metadata",
+    "sequence",
+    "prev_hash",
+    "entry_hash",
+];
+
+/// Output format of a security event export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One JSON object per line
+    JsonLines,
+    /// RFC 4180 CSV with a header row
+    Csv,
+    /// ArcSight Common Event Format, one event per line
+    Cef,
+}
+
+impl ExportFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExportFormat::JsonLines => "jsonl",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Cef => "cef",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::JsonLines => "application/x-ndjson",
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Cef => "text/plain; charset=utf-8",
+        }
+    }
+
+    /// Text written before the first event, if any
+    pub fn header(self) -> Option<String> {
+        match self {
+            ExportFormat::Csv => Some(format!("{}\r\n", CSV_COLUMNS.join(","))),
+            ExportFormat::JsonLines | ExportFormat::Cef => None,
+        }
+    }
+
+    /// One event as a complete line, including the line ending
+    pub fn format_event(self, event: &SecurityEvent) -> String {
+        let created_at = event
+            .created_at
+            .to_rfc3339_opts(SecondsFormat::Micros, true);
+        match self {
+            ExportFormat::JsonLines => {
+                let line = json!({
+                    "id": event.id,
+                    "created_at": created_at,
+                    "event_type": event.event_type,
+                    "success": event.success,
+                    "user_id": event.user_id,
+                    "ip_address": event.ip_address,
+                    "user_agent": event.user_agent,
+                    "metadata": event.metadata,
+                    "sequence": event.sequence,
+                    "prev_hash": event.prev_hash,
+                    "entry_hash": event.entry_hash,
+                });
+                format!("{}\n", line)
+            }
+            ExportFormat::Csv => {
+                let fields = [
+                    event.id.to_string(),
+                    created_at,
+                    event.event_type.clone(),
+                    event.success.to_string(),
+                    optional(event.user_id),
+                    event.ip_address.clone().unwrap_or_default(),
+                    event.user_agent.clone().unwrap_or_default(),
+                    optional(event.metadata.as_ref()),
+                    optional(event.sequence),
+                    event.prev_hash.clone().unwrap_or_default(),
+                    event.entry_hash.clone().unwrap_or_default(),
+                ];
+                let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+                format!("{}\r\n", row.join(","))
+            }
+            ExportFormat::Cef => {
+                let mut extension = vec![
+                    ("rt", event.created_at.timestamp_millis().to_string()),
+                    ("externalId", event.id.to_string()),
+                    (
+                        "outcome",
+                        if event.success { "success" } else { "failure" }.to_string(),
+                    ),
+                ];
+                // Custom fields carry a label naming them
+                let optional_fields = [
+                    ("suid", None, event.user_id.map(|id| id.to_string())),
+                    ("src", None, event.ip_address.clone()),
+                    ("requestClientApplication", None, event.user_agent.clone()),
+                    (
+                        "cs1",
+                        Some(("cs1Label", "metadata")),
+                        event.metadata.as_ref().map(|metadata| metadata.to_string()),
+                    ),
+                    (
+                        "cn1",
+                        Some(("cn1Label", "sequence")),
+                        event.sequence.map(|sequence| sequence.to_string()),
+                    ),
+                    (
+                        "cs2",
+                        Some(("cs2Label", "entryHash")),
+                        event.entry_hash.clone(),
+                    ),
+                ];
+                for (key, label, value) in optional_fields {
+                    if let Some(value) = value {
+                        if let Some((label_key, label)) = label {
+                            extension.push((label_key, label.to_string()));
+                        }
+                        extension.push((key, value));
+                    }
+                }
+                let extension: Vec<String> = extension
+                    .into_iter()
+                    .map(|(key, value)| format!("{}={}", key, cef_extension_value(&value)))
+                    .collect();
+
+                format!(
+                    "CEF:0|{}|{}|{}|{}|{}|{}|{}\n",
+                    cef_header_value(CEF_VENDOR),
+                    cef_header_value(CEF_PRODUCT),
+                    cef_header_value(env!("CARGO_PKG_VERSION")),
+                    cef_header_value(&event.event_type),
+                    cef_header_value(&event.event_type.replace('_', " ")),
+                    cef_severity(event),
+                    extension.join(" ")
+                )
+            }
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "jsonl" | "ndjson" => Ok(ExportFormat::JsonLines),
+            "csv" => Ok(ExportFormat::Csv),
+            "cef" => Ok(ExportFormat::Cef),
+            other => Err(format!("Unknown export format: {}", other)),
+        }
+    }
+}
+
+/// CEF severity (0-10): admin actions and failures rank above routine successes
+fn cef_severity(event: &SecurityEvent) -> u8 {
+    match (event.event_type.as_str(), event.success) {
+        ("account_locked", _) => 7,
+        (_, false) => 5,
+        (event_type, true) if event_type.starts_with("admin_") => 4,
+        _ => 2,
+    }
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Escape a CEF header field (`\` and `|`)
+fn cef_header_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace(['\r', '\n'], " ")
+}
+
+/// Escape a CEF extension value (`\`, `=` and line breaks)
+fn cef_extension_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('=', "\\=")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
@@ -0,0 +1,91 @@
+use axum::{
+    body::{to_bytes, Body, HttpBody},
+    extract::{Request, State},
+    http::{
+        header::{ACCEPT_LANGUAGE, AUTHORIZATION, CONTENT_LANGUAGE, CONTENT_LENGTH},
+        HeaderValue,
+    },
+    middleware::Next,
+    response::Response,
+};
+use serde_json::Value;
+use std::sync::Arc;
+
+use super::{I18nService, Locale};
+use crate::database::Database;
+use crate::utils::JwtService;
+
+/// Error bodies are small JSON documents; anything bigger is passed through untouched
+const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
+
+/// Translate `error.message` in error responses into the caller's locale
+///
+/// The locale comes from the signed-in user's saved preference, then `Accept-Language`.
+/// Error codes are never changed, so clients can keep matching on them.
+pub async fn localize_error_responses(
+    State(db): State<Arc<Database>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let accept_language = request
+        .headers()
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_owned);
+    let token = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_owned);
+
+    let response = next.run(request).await;
+    if !response.status().is_client_error() && !response.status().is_server_error() {
+        return response;
+    }
+
+    // Only look up the saved preference once we know there is something to translate
+    let preference = token
+        .and_then(|token| JwtService::extract_subject(&token).ok())
+        .and_then(|user_id| db.get_user_locale(user_id).ok().flatten());
+    let locale = I18nService::resolve(preference.as_deref(), accept_language.as_deref());
+    if locale == Locale::En {
+        return response;
+    }
+
+    // Bodies that may be larger than the limit (or of unknown size) are never buffered,
+    // so they always reach the client intact
+    let (mut parts, body) = response.into_parts();
+    if body
+        .size_hint()
+        .upper()
+        .is_none_or(|size| size > MAX_ERROR_BODY_BYTES as u64)
+    {
+        return Response::from_parts(parts, body);
+    }
+    let bytes = match to_bytes(body, MAX_ERROR_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!("Could not buffer error response for localization: {}", e);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+
+    let mut value: Value = match serde_json::from_slice(&bytes) {
+        Ok(value) => value,
+        Err(_) => return Response::from_parts(parts, Body::from(bytes)),
+    };
+    let Some(message) = value.pointer_mut("/error/message") else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    if let Some(text) = message.as_str() {
+        *message = Value::String(I18nService::translate(locale, text));
+    }
+
+    parts.headers.remove(CONTENT_LENGTH);
+    parts
+        .headers
+        .insert(CONTENT_LANGUAGE, HeaderValue::from_static(locale.code()));
+
+    Response::from_parts(parts, Body::from(value.to_string()))
+}
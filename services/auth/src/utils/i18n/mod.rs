@@ -0,0 +1,204 @@
+//! Message catalogs for API error messages and email templates
+//!
+//! English text is the message key (the `ErrorMessage` constants and the built-in
+//! email templates); `locales/*.json` bundles map it to translations. Messages
+//! without a translation fall back to English.
+
+mod middleware;
+
+use axum::http::{header::ACCEPT_LANGUAGE, HeaderMap};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::sync::OnceLock;
+
+pub use middleware::localize_error_responses;
+
+/// Supported display languages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Locale {
+    En,
+    Ja,
+    Zh,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 3] = [Locale::En, Locale::Ja, Locale::Zh];
+
+    /// Language code stored in `users.locale` and sent in `Content-Language`
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Ja => "ja",
+            Locale::Zh => "zh",
+        }
+    }
+
+    /// Parse a language tag such as `ja`, `ja-JP` or `zh-Hans-CN` by its primary subtag
+    pub fn parse(tag: &str) -> Option<Locale> {
+        let primary = tag.trim().split(['-', '_']).next()?;
+
+        Locale::ALL
+            .into_iter()
+            .find(|locale| locale.code().eq_ignore_ascii_case(primary))
+    }
+
+    /// Pick the highest weighted supported language from an `Accept-Language` header
+    pub fn from_accept_language(header: &str) -> Option<Locale> {
+        let mut best: Option<(Locale, f32)> = None;
+
+        for entry in header.split(',') {
+            let mut parts = entry.split(';');
+            let tag = parts.next().unwrap_or_default();
+            let weight = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            if weight <= 0.0 {
+                continue;
+            }
+            if let Some(locale) = Locale::parse(tag) {
+                // Ties keep the earlier entry, as listed by the client
+                if best.is_none_or(|(_, best_weight)| weight > best_weight) {
+                    best = Some((locale, weight));
+                }
+            }
+        }
+
+        best.map(|(locale, _)| locale)
+    }
+
+    /// Read the preferred supported language from request headers
+    pub fn from_headers(headers: &HeaderMap) -> Option<Locale> {
+        headers
+            .get(ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(Locale::from_accept_language)
+    }
+
+    fn bundle(self) -> Option<&'static str> {
+        match self {
+            Locale::En => None,
+            Locale::Ja => Some(include_str!("locales/ja.json")),
+            Locale::Zh => Some(include_str!("locales/zh.json")),
+        }
+    }
+}
+
+/// Translated subject and body of an email template
+#[derive(Debug, Deserialize)]
+pub struct EmailText {
+    pub subject: String,
+    pub body: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Catalog {
+    #[serde(default)]
+    messages: HashMap<String, String>,
+    #[serde(default)]
+    emails: HashMap<String, EmailText>,
+}
+
+pub struct I18nService;
+
+impl I18nService {
+    /// Get the locale used when neither the user nor the request asks for one (default: en)
+    pub fn default_locale() -> Locale {
+        env::var("DEFAULT_LOCALE")
+            .ok()
+            .and_then(|tag| Locale::parse(&tag))
+            .unwrap_or(Locale::En)
+    }
+
+    /// Choose a locale: the user's saved preference, then `Accept-Language`, then the default
+    pub fn resolve(preference: Option<&str>, accept_language: Option<&str>) -> Locale {
+        preference
+            .and_then(Locale::parse)
+            .or_else(|| accept_language.and_then(Locale::from_accept_language))
+            .unwrap_or_else(Self::default_locale)
+    }
+
+    /// Choose a locale for a request: the user's saved preference, then its headers
+    pub fn for_request(preference: Option<&str>, headers: &HeaderMap) -> Locale {
+        preference
+            .and_then(Locale::parse)
+            .or_else(|| Locale::from_headers(headers))
+            .unwrap_or_else(Self::default_locale)
+    }
+
+    fn catalog(locale: Locale) -> &'static Catalog {
+        static CATALOGS: OnceLock<HashMap<Locale, Catalog>> = OnceLock::new();
+
+        CATALOGS
+            .get_or_init(|| {
+                Locale::ALL
+                    .into_iter()
+                    .map(|locale| {
+                        let catalog = locale
+                            .bundle()
+                            .map(|source| {
+                                serde_json::from_str(source).unwrap_or_else(|e| {
+                                    panic!("Invalid {} message catalog: {}", locale.code(), e)
+                                })
+                            })
+                            .unwrap_or_default();
+                        (locale, catalog)
+                    })
+                    .collect()
+            })
+            .get(&locale)
+            .expect("every locale has a catalog")
+    }
+
+    /// Translate an English message, including ones with `{}` values already filled in
+    pub fn translate(locale: Locale, message: &str) -> String {
+        let messages = &Self::catalog(locale).messages;
+
+        if let Some(translation) = messages.get(message) {
+            return translation.clone();
+        }
+
+        for (source, translation) in messages.iter().filter(|(source, _)| source.contains("{}")) {
+            if let Some(values) = match_placeholders(source, message) {
+                return values.into_iter().fold(translation.clone(), |text, value| {
+                    text.replacen("{}", value, 1)
+                });
+            }
+        }
+
+        message.to_string()
+    }
+
+    /// Translated text for an email template, or `None` to use the English source
+    pub fn email_text(locale: Locale, template: &str) -> Option<&'static EmailText> {
+        Self::catalog(locale).emails.get(template)
+    }
+
+    /// Language codes that have a catalog, for validating user preferences
+    pub fn supported_codes() -> Vec<&'static str> {
+        Locale::ALL.into_iter().map(Locale::code).collect()
+    }
+}
+
+/// Match `message` against a `{}` template and return the filled-in values in order
+fn match_placeholders<'a>(template: &str, message: &'a str) -> Option<Vec<&'a str>> {
+    let mut parts = template.split("{}");
+    let mut rest = message.strip_prefix(parts.next()?)?;
+    let mut values = Vec::new();
+    let parts: Vec<&str> = parts.collect();
+
+    for (index, part) in parts.iter().enumerate() {
+        let end = if index == parts.len() - 1 {
+            // The last literal must end the message
+            rest.strip_suffix(part)?.len()
+        } else {
+            rest.find(part)?
+        };
+        values.push(&rest[..end]);
+        rest = &rest[end + part.len()..];
+    }
+
+    Some(values)
+}
@@ -13,9 +13,22 @@ pub enum JwtError {
     TokenExpired,
     #[error("Missing JWT secret")]
     MissingSecret,
+    #[error("Token scope {0} not permitted for this operation")]
+    ScopeNotPermitted(String),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Scopes for restricted tokens (tokens without a scope have full access)
+pub struct TokenScope;
+
+impl TokenScope {
+    /// Only allowed to set a new password (issued when a password change is required)
+    pub const PASSWORD_CHANGE: &'static str = "password_change";
+
+    /// Signed in with an unverified email under the restricted sign-in policy
+    pub const UNVERIFIED_EMAIL: &'static str = "unverified_email";
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,   // Subject (user ID)
     pub email: String, // User email
@@ -23,8 +36,28 @@ pub struct Claims {
     pub exp: i64,      // Expiration time (Unix timestamp)
     pub iat: i64,      // Issued at (Unix timestamp)
     pub iss: String,   // Issuer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>, // Session ID (user_sessions row backing this token)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>, // Restricted scope (see TokenScope), none for full access
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<Vec<String>>, // Granted at issue time; none on restricted and older tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org: Option<String>, // Active organization ID, none until the session switches to one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org_role: Option<String>, // The user's role in the active organization
+}
+
+/// The organization a session is working in, as carried by its tokens
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveOrganization {
+    pub id: Uuid,
+    pub role: String,
 }
 
+/// Lifetime of password change tokens in minutes
+const PASSWORD_CHANGE_TOKEN_MINUTES: i64 = 15;
+
 pub struct JwtService;
 
 impl JwtService {
@@ -41,34 +74,122 @@ impl JwtService {
             .unwrap_or(24)
     }
 
-    /// Generate a new JWT token
+    /// Generate a new JWT token not bound to a session
+    ///
+    /// Endpoints that check the session refuse it; signed-in users get session tokens.
     pub fn generate_token(user_id: Uuid, email: &str, role: &str) -> Result<String, JwtError> {
-        let secret = Self::get_secret()?;
-        let exp_hours = Self::get_expiration_hours();
+        let claims = Self::build_claims(user_id, email, role, Self::default_lifetime());
+        Self::encode_claims(&claims)
+    }
 
+    /// Generate a new JWT token bound to a user session, carrying the role's permissions
+    /// and the session's active organization
+    pub fn generate_session_token(
+        user_id: Uuid,
+        email: &str,
+        role: &str,
+        session_id: Uuid,
+        permissions: &[String],
+        organization: Option<&ActiveOrganization>,
+    ) -> Result<String, JwtError> {
+        let mut claims = Self::build_claims(user_id, email, role, Self::default_lifetime());
+        claims.sid = Some(session_id.to_string());
+        claims.permissions = Some(permissions.to_vec());
+        if let Some(organization) = organization {
+            claims.org = Some(organization.id.to_string());
+            claims.org_role = Some(organization.role.clone());
+        }
+        Self::encode_claims(&claims)
+    }
+
+    /// Parse the active organization carried by already validated claims
+    pub fn organization_from_claims(claims: &Claims) -> Option<ActiveOrganization> {
+        let id = claims
+            .org
+            .as_deref()
+            .and_then(|id| Uuid::parse_str(id).ok())?;
+        let role = claims.org_role.clone()?;
+        Some(ActiveOrganization { id, role })
+    }
+
+    /// Generate a short-lived session token limited to a single scope
+    pub fn generate_restricted_token(
+        user_id: Uuid,
+        email: &str,
+        role: &str,
+        session_id: Uuid,
+        scope: &str,
+    ) -> Result<String, JwtError> {
+        // Password change tokens are short-lived; other scopes last as long as a normal session
+        let lifetime = if scope == TokenScope::PASSWORD_CHANGE {
+            Duration::minutes(PASSWORD_CHANGE_TOKEN_MINUTES)
+        } else {
+            Self::default_lifetime()
+        };
+        let mut claims = Self::build_claims(user_id, email, role, lifetime);
+        claims.sid = Some(session_id.to_string());
+        claims.scope = Some(scope.to_string());
+        Self::encode_claims(&claims)
+    }
+
+    fn default_lifetime() -> Duration {
+        Duration::hours(Self::get_expiration_hours())
+    }
+
+    /// Build full-access claims issued now
+    fn build_claims(user_id: Uuid, email: &str, role: &str, lifetime: Duration) -> Claims {
         let now = Utc::now();
-        let exp = now + Duration::hours(exp_hours);
+        let exp = now + lifetime;
 
-        let claims = Claims {
+        Claims {
             sub: user_id.to_string(),
             email: email.to_string(),
             role: role.to_string(),
             exp: exp.timestamp(),
             iat: now.timestamp(),
             iss: "venomous-dashboard-auth".to_string(),
-        };
+            sid: None,
+            scope: None,
+            permissions: None,
+            org: None,
+            org_role: None,
+        }
+    }
+
+    /// Sign claims into a token
+    fn encode_claims(claims: &Claims) -> Result<String, JwtError> {
+        let secret = Self::get_secret()?;
 
         let token = encode(
             &Header::default(),
-            &claims,
+            claims,
             &EncodingKey::from_secret(secret.as_ref()),
         )?;
 
         Ok(token)
     }
 
-    /// Validate and decode a JWT token
+    /// Validate and decode a full-access JWT token (restricted tokens are rejected)
     pub fn validate_token(token: &str) -> Result<TokenData<Claims>, JwtError> {
+        Self::validate_token_for_scope(token, None)
+    }
+
+    /// Validate a token that may be full-access or restricted to the given scope
+    pub fn validate_token_for_scope(
+        token: &str,
+        allowed_scope: Option<&str>,
+    ) -> Result<TokenData<Claims>, JwtError> {
+        let token_data = Self::decode_token(token)?;
+
+        match token_data.claims.scope.as_deref() {
+            None => Ok(token_data),
+            Some(scope) if Some(scope) == allowed_scope => Ok(token_data),
+            Some(scope) => Err(JwtError::ScopeNotPermitted(scope.to_string())),
+        }
+    }
+
+    /// Decode and verify signature, issuer and expiry
+    fn decode_token(token: &str) -> Result<TokenData<Claims>, JwtError> {
         let secret = Self::get_secret()?;
 
         let mut validation = Validation::default();
@@ -100,6 +221,35 @@ impl JwtService {
         Ok(user_id)
     }
 
+    /// Extract user ID from a token of any scope
+    ///
+    /// Only for lookups that grant nothing (such as the user's locale preference);
+    /// use `extract_user_id` when the caller is being authorized.
+    pub fn extract_subject(token: &str) -> Result<Uuid, JwtError> {
+        let token_data = Self::decode_token(token)?;
+        Uuid::parse_str(&token_data.claims.sub).map_err(|_| {
+            JwtError::InvalidToken(jsonwebtoken::errors::Error::from(
+                jsonwebtoken::errors::ErrorKind::InvalidSubject,
+            ))
+        })
+    }
+
+    /// Extract session ID from token (tokens issued without a session have none)
+    ///
+    /// Restricted tokens are accepted too, so they can still be signed out.
+    pub fn extract_session_id(token: &str) -> Result<Option<Uuid>, JwtError> {
+        let token_data = Self::decode_token(token)?;
+        Ok(Self::session_id_from_claims(&token_data.claims))
+    }
+
+    /// Parse the session ID carried by already validated claims
+    pub fn session_id_from_claims(claims: &Claims) -> Option<Uuid> {
+        claims
+            .sid
+            .as_deref()
+            .and_then(|sid| Uuid::parse_str(sid).ok())
+    }
+
     /// Extract user email from token
     pub fn extract_email(token: &str) -> Result<String, JwtError> {
         let token_data = Self::validate_token(token)?;
@@ -124,15 +274,19 @@ impl JwtService {
     /// Refresh a token (generate new token with same claims but new expiration)
     pub fn refresh_token(token: &str) -> Result<String, JwtError> {
         let token_data = Self::validate_token(token)?;
-        let claims = token_data.claims;
+        let mut claims = token_data.claims;
 
         // Generate new token with same user data
-        let user_id = Uuid::parse_str(&claims.sub).map_err(|_| {
+        Uuid::parse_str(&claims.sub).map_err(|_| {
             JwtError::InvalidToken(jsonwebtoken::errors::Error::from(
                 jsonwebtoken::errors::ErrorKind::InvalidSubject,
             ))
         })?;
 
-        Self::generate_token(user_id, &claims.email, &claims.role)
+        let now = Utc::now();
+        claims.iat = now.timestamp();
+        claims.exp = (now + Self::default_lifetime()).timestamp();
+
+        Self::encode_claims(&claims)
     }
 }
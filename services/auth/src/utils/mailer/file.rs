@@ -0,0 +1,43 @@
+use chrono::Utc;
+use lettre::message::Mailbox;
+use std::fs;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use super::{build_message, parse_mailbox, EmailMessage, Mailer, MailerError};
+
+/// Mailer that drops each message as an `.eml` file into a directory (development)
+pub struct FileMailer {
+    dir: PathBuf,
+    from: Mailbox,
+}
+
+impl FileMailer {
+    /// Create the mailer, creating the drop directory if needed
+    pub fn new(dir: impl Into<PathBuf>, from: &str) -> Result<Self, MailerError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        Ok(FileMailer {
+            dir,
+            from: parse_mailbox(from)?,
+        })
+    }
+}
+
+impl Mailer for FileMailer {
+    fn send(&self, message: &EmailMessage) -> Result<(), MailerError> {
+        let email = build_message(&self.from, message)?;
+
+        // Timestamp first so a directory listing reads in send order
+        let path = self.dir.join(format!(
+            "{}-{}.eml",
+            Utc::now().format("%Y%m%dT%H%M%S%.6f"),
+            Uuid::new_v4()
+        ));
+        fs::write(&path, email.formatted())?;
+
+        tracing::info!("Email to {} written to {}", message.to, path.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use std::sync::{Arc, Mutex};
+
+use super::{EmailMessage, Mailer, MailerError};
+
+/// Mailer that keeps sent messages in memory (tests)
+///
+/// Clones share the same outbox, so a test can keep one handle and give another to the queue.
+#[derive(Clone, Default)]
+pub struct MemoryMailer {
+    sent: Arc<Mutex<Vec<EmailMessage>>>,
+}
+
+impl MemoryMailer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Messages sent so far, oldest first
+    pub fn sent(&self) -> Vec<EmailMessage> {
+        self.sent.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Forget all sent messages
+    pub fn clear(&self) {
+        self.sent.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+impl Mailer for MemoryMailer {
+    fn send(&self, message: &EmailMessage) -> Result<(), MailerError> {
+        self.sent
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(message.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+//! Outbound email: delivery backends, templates, the async send queue and the outbox worker
+
+mod file;
+mod memory;
+mod outbox;
+mod queue;
+mod smtp;
+mod template;
+
+pub use file::FileMailer;
+pub use memory::MemoryMailer;
+pub use outbox::OutboxWorker;
+pub use queue::MailQueue;
+pub use smtp::{SmtpConfig, SmtpMailer, SmtpSecurity};
+pub use template::{render_template, EmailTemplate};
+
+use lettre::message::{header::ContentType, Mailbox};
+use lettre::Message;
+use std::env;
+use std::sync::{Arc, OnceLock};
+use thiserror::Error;
+
+use crate::utils::i18n::Locale;
+
+#[derive(Debug, Error)]
+pub enum MailerError {
+    #[error("Failed to deliver email: {0}")]
+    DeliveryFailed(String),
+    #[error("Invalid email address: {0}")]
+    InvalidAddress(String),
+    #[error("Missing template variable: {0}")]
+    MissingVariable(String),
+    #[error("Invalid mailer configuration: {0}")]
+    Config(String),
+    #[error("Mail queue is full")]
+    QueueFull,
+    #[error("Mail queue is not running")]
+    QueueClosed,
+    #[error("Failed to write email: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Outbound email message
+///
+/// A message carrying a one-time link has `SECRET_PLACEHOLDER` in its body where the
+/// token goes and the token itself in `secret`. The outbox stores the two apart and only
+/// fills the token in when sending, so stored bodies never hold a live link.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+    pub secret: Option<String>,
+}
+
+impl EmailMessage {
+    /// Stands in for the one-time token in a stored body
+    pub const SECRET_PLACEHOLDER: &'static str = "{outbox-secret}";
+
+    /// Attach the token that replaces `SECRET_PLACEHOLDER` at send time
+    pub fn with_secret(mut self, secret: &str) -> Self {
+        self.secret = Some(secret.to_string());
+        self
+    }
+
+    /// The message as delivered, with the token filled into the body
+    pub fn resolved(&self) -> EmailMessage {
+        EmailMessage {
+            to: self.to.clone(),
+            subject: self.subject.clone(),
+            body: match &self.secret {
+                Some(secret) => self.body.replace(Self::SECRET_PLACEHOLDER, secret),
+                None => self.body.clone(),
+            },
+            secret: None,
+        }
+    }
+}
+
+/// Delivery backend for outbound email
+///
+/// Implementations may block; the mail queue runs them off the async runtime.
+pub trait Mailer: Send + Sync {
+    fn send(&self, message: &EmailMessage) -> Result<(), MailerError>;
+}
+
+/// Mailer that only writes messages to the application log (development default)
+pub struct LogMailer;
+
+impl Mailer for LogMailer {
+    fn send(&self, message: &EmailMessage) -> Result<(), MailerError> {
+        tracing::info!("Email to {}: {}", message.to, message.subject);
+        tracing::debug!("Email body:\n{}", message.body);
+        Ok(())
+    }
+}
+
+/// Parse a `Name <address>` or bare address into a mailbox
+fn parse_mailbox(address: &str) -> Result<Mailbox, MailerError> {
+    address
+        .parse::<Mailbox>()
+        .map_err(|_| MailerError::InvalidAddress(address.to_string()))
+}
+
+/// Build a plain text RFC 5322 message
+fn build_message(from: &Mailbox, message: &EmailMessage) -> Result<Message, MailerError> {
+    Message::builder()
+        .from(from.clone())
+        .to(parse_mailbox(&message.to)?)
+        .subject(message.subject.as_str())
+        .header(ContentType::TEXT_PLAIN)
+        .body(message.body.clone())
+        .map_err(|e| MailerError::DeliveryFailed(e.to_string()))
+}
+
+/// Process-wide access to the configured mailer and its send queue
+pub struct MailService;
+
+impl MailService {
+    /// Create the mailer selected by `MAIL_BACKEND` (`log`, `smtp`, `file` or `memory`)
+    pub fn mailer_from_env() -> Result<Arc<dyn Mailer>, MailerError> {
+        let backend = env::var("MAIL_BACKEND").unwrap_or_else(|_| "log".to_string());
+
+        match backend.to_lowercase().as_str() {
+            "log" => Ok(Arc::new(LogMailer)),
+            "smtp" => Ok(Arc::new(SmtpMailer::new(SmtpConfig::from_env()?)?)),
+            "file" => Ok(Arc::new(FileMailer::new(
+                env::var("MAIL_DROP_DIR").unwrap_or_else(|_| "./mail-drop".to_string()),
+                &Self::from_address(),
+            )?)),
+            "memory" => Ok(Arc::new(MemoryMailer::new())),
+            other => Err(MailerError::Config(format!(
+                "unknown MAIL_BACKEND {}",
+                other
+            ))),
+        }
+    }
+
+    /// Get the configured mailer, creating it on first use
+    pub fn mailer() -> Arc<dyn Mailer> {
+        static MAILER: OnceLock<Arc<dyn Mailer>> = OnceLock::new();
+
+        MAILER
+            .get_or_init(|| {
+                Self::mailer_from_env().unwrap_or_else(|e| {
+                    tracing::error!(
+                        "Mailer configuration error, falling back to log mailer: {}",
+                        e
+                    );
+                    Arc::new(LogMailer)
+                })
+            })
+            .clone()
+    }
+
+    /// Get the in-memory send queue, starting it on first use (must be called inside the Tokio runtime)
+    fn queue() -> &'static MailQueue {
+        static QUEUE: OnceLock<MailQueue> = OnceLock::new();
+        QUEUE.get_or_init(|| MailQueue::start(Self::mailer()))
+    }
+
+    /// Queue an email for best-effort delivery without waiting for it to be sent
+    ///
+    /// The in-memory queue is lost if the process stops; mail that must arrive
+    /// belongs in the database outbox (`Database::queue_email`).
+    pub fn send(message: EmailMessage) -> Result<(), MailerError> {
+        Self::queue().enqueue(message)
+    }
+
+    /// Render a template in `locale` and queue it for delivery
+    pub fn send_template(
+        to: &str,
+        template: EmailTemplate,
+        locale: Locale,
+        vars: &[(&str, &str)],
+    ) -> Result<(), MailerError> {
+        Self::send(template.render(locale, to, vars)?)
+    }
+
+    /// Sender address for outgoing mail (default: Venomous Dashboard <no-reply@localhost>)
+    pub fn from_address() -> String {
+        env::var("MAIL_FROM")
+            .unwrap_or_else(|_| "Venomous Dashboard <no-reply@localhost>".to_string())
+    }
+
+    /// Base URL of the dashboard, used to build links in emails (default: http://localhost:3000)
+    pub fn app_base_url() -> String {
+        env::var("APP_BASE_URL")
+            .unwrap_or_else(|_| "http://localhost:3000".to_string())
+            .trim_end_matches('/')
+            .to_string()
+    }
+}
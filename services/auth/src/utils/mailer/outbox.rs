@@ -0,0 +1,109 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+use super::{EmailMessage, Mailer};
+use crate::database::constants::EmailOutbox;
+use crate::database::Database;
+
+/// Background delivery of messages queued in the `email_outbox` table
+///
+/// Failed sends are retried with exponential backoff until `EmailOutbox::MAX_ATTEMPTS`,
+/// after which the message is marked dead and left for an administrator.
+pub struct OutboxWorker;
+
+impl OutboxWorker {
+    /// Start the worker loop (must be called inside the Tokio runtime)
+    pub fn spawn(db: Arc<Database>, mailer: Arc<dyn Mailer>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let pass_db = db.clone();
+                let pass_mailer = mailer.clone();
+                let attempted = match tokio::task::spawn_blocking(move || {
+                    Self::deliver_due(&pass_db, pass_mailer.as_ref())
+                })
+                .await
+                {
+                    Ok(Ok(attempted)) => attempted,
+                    Ok(Err(e)) => {
+                        tracing::error!("Email outbox pass failed: {}", e);
+                        0
+                    }
+                    Err(e) => {
+                        tracing::error!("Email outbox task failed: {}", e);
+                        0
+                    }
+                };
+
+                // Keep going while there is a backlog, otherwise wait for more mail to come due
+                if attempted < EmailOutbox::BATCH_SIZE as usize {
+                    tokio::time::sleep(std::time::Duration::from_secs(
+                        EmailOutbox::POLL_INTERVAL_SECONDS,
+                    ))
+                    .await;
+                }
+            }
+        })
+    }
+
+    /// Deliver one batch of due messages, returning how many were attempted
+    pub fn deliver_due(db: &Database, mailer: &dyn Mailer) -> Result<usize> {
+        let batch = db.claim_due_emails(EmailOutbox::BATCH_SIZE)?;
+
+        for entry in &batch {
+            let attempts = entry.attempts + 1;
+            let message = EmailMessage {
+                to: entry.to_address.clone(),
+                subject: entry.subject.clone(),
+                body: entry.body.clone(),
+                secret: entry.secret.clone(),
+            };
+
+            let updated = match mailer.send(&message.resolved()) {
+                Ok(()) => db.mark_email_sent(entry.id, attempts),
+                Err(e) => {
+                    let retry_at = Self::retry_delay(attempts).map(|delay| Utc::now() + delay);
+                    match retry_at {
+                        Some(retry_at) => tracing::warn!(
+                            "Email {} to {} failed (attempt {}), retrying at {}: {}",
+                            entry.id,
+                            entry.to_address,
+                            attempts,
+                            retry_at,
+                            e
+                        ),
+                        None => tracing::error!(
+                            "Email {} to {} failed {} times, giving up: {}",
+                            entry.id,
+                            entry.to_address,
+                            attempts,
+                            e
+                        ),
+                    }
+                    db.mark_email_failed(entry.id, attempts, &e.to_string(), retry_at)
+                }
+            };
+
+            if let Err(e) = updated {
+                tracing::error!("Failed to update email outbox entry {}: {}", entry.id, e);
+            }
+        }
+
+        Ok(batch.len())
+    }
+
+    /// Delay before retrying after `attempts` failed sends, or `None` once attempts are used up
+    pub fn retry_delay(attempts: i32) -> Option<Duration> {
+        if attempts >= EmailOutbox::MAX_ATTEMPTS {
+            return None;
+        }
+
+        let exponent = (attempts - 1).clamp(0, 30) as u32;
+        let seconds = EmailOutbox::BASE_RETRY_SECONDS
+            .saturating_mul(1i64 << exponent)
+            .min(EmailOutbox::MAX_RETRY_SECONDS);
+
+        Some(Duration::seconds(seconds))
+    }
+}
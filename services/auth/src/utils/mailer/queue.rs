@@ -0,0 +1,55 @@
+use std::sync::Arc;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::task::JoinHandle;
+
+use super::{EmailMessage, Mailer, MailerError};
+
+/// Maximum number of messages waiting for delivery
+const QUEUE_CAPACITY: usize = 1024;
+
+/// Async send queue so request handlers never wait on mail delivery
+///
+/// A single worker task delivers messages in order, running the (possibly
+/// blocking) mailer on the blocking thread pool.
+pub struct MailQueue {
+    sender: mpsc::Sender<EmailMessage>,
+    worker: JoinHandle<()>,
+}
+
+impl MailQueue {
+    /// Start the delivery worker (must be called inside the Tokio runtime)
+    pub fn start(mailer: Arc<dyn Mailer>) -> Self {
+        let (sender, mut receiver) = mpsc::channel::<EmailMessage>(QUEUE_CAPACITY);
+
+        let worker = tokio::spawn(async move {
+            while let Some(message) = receiver.recv().await {
+                let mailer = mailer.clone();
+                let to = message.to.clone();
+
+                match tokio::task::spawn_blocking(move || mailer.send(&message.resolved())).await {
+                    Ok(Ok(())) => tracing::debug!("Email delivered to {}", to),
+                    Ok(Err(e)) => tracing::error!("Failed to send email to {}: {}", to, e),
+                    Err(e) => tracing::error!("Mailer task failed for {}: {}", to, e),
+                }
+            }
+        });
+
+        MailQueue { sender, worker }
+    }
+
+    /// Queue a message, failing immediately instead of waiting if the queue is full
+    pub fn enqueue(&self, message: EmailMessage) -> Result<(), MailerError> {
+        self.sender.try_send(message).map_err(|e| match e {
+            TrySendError::Full(_) => MailerError::QueueFull,
+            TrySendError::Closed(_) => MailerError::QueueClosed,
+        })
+    }
+
+    /// Stop accepting messages and wait until everything queued has been handled
+    pub async fn shutdown(self) {
+        drop(self.sender);
+        if let Err(e) = self.worker.await {
+            tracing::error!("Mail queue worker failed: {}", e);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{SmtpTransport, Transport};
+use std::env;
+use std::time::Duration;
+
+use super::{build_message, parse_mailbox, EmailMessage, MailService, Mailer, MailerError};
+
+/// How the SMTP connection is secured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    /// Plain connection (local test servers such as Mailpit or MailHog)
+    None,
+    /// Upgrade a plain connection with STARTTLS
+    StartTls,
+    /// TLS from the first byte (SMTPS)
+    Tls,
+}
+
+/// SMTP server settings
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    pub timeout: Duration,
+}
+
+impl SmtpConfig {
+    /// Read `SMTP_HOST`, `SMTP_PORT`, `SMTP_SECURITY`, `SMTP_USERNAME`, `SMTP_PASSWORD` and `MAIL_FROM`
+    ///
+    /// Defaults target a local test server on `localhost:1025` without TLS.
+    pub fn from_env() -> Result<Self, MailerError> {
+        let security = match env::var("SMTP_SECURITY")
+            .unwrap_or_else(|_| "none".to_string())
+            .to_lowercase()
+            .as_str()
+        {
+            "none" => SmtpSecurity::None,
+            "starttls" => SmtpSecurity::StartTls,
+            "tls" => SmtpSecurity::Tls,
+            other => {
+                return Err(MailerError::Config(format!(
+                    "unknown SMTP_SECURITY {}",
+                    other
+                )))
+            }
+        };
+
+        let port = match env::var("SMTP_PORT") {
+            Ok(port) => port
+                .parse()
+                .map_err(|_| MailerError::Config(format!("invalid SMTP_PORT {}", port)))?,
+            Err(_) => match security {
+                SmtpSecurity::None => 1025,
+                SmtpSecurity::StartTls => 587,
+                SmtpSecurity::Tls => 465,
+            },
+        };
+
+        Ok(SmtpConfig {
+            host: env::var("SMTP_HOST").unwrap_or_else(|_| "localhost".to_string()),
+            port,
+            security,
+            username: env::var("SMTP_USERNAME").ok(),
+            password: env::var("SMTP_PASSWORD").ok(),
+            from: MailService::from_address(),
+            timeout: Duration::from_secs(10),
+        })
+    }
+}
+
+/// Mailer that delivers through an SMTP server
+pub struct SmtpMailer {
+    transport: SmtpTransport,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(config: SmtpConfig) -> Result<Self, MailerError> {
+        let builder = match config.security {
+            SmtpSecurity::None => SmtpTransport::builder_dangerous(&config.host),
+            SmtpSecurity::StartTls => SmtpTransport::starttls_relay(&config.host)
+                .map_err(|e| MailerError::Config(e.to_string()))?,
+            SmtpSecurity::Tls => SmtpTransport::relay(&config.host)
+                .map_err(|e| MailerError::Config(e.to_string()))?,
+        };
+
+        let mut builder = builder.port(config.port).timeout(Some(config.timeout));
+        if let (Some(username), Some(password)) = (config.username, config.password) {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Ok(SmtpMailer {
+            transport: builder.build(),
+            from: parse_mailbox(&config.from)?,
+        })
+    }
+}
+
+impl Mailer for SmtpMailer {
+    fn send(&self, message: &EmailMessage) -> Result<(), MailerError> {
+        let email = build_message(&self.from, message)?;
+        self.transport
+            .send(&email)
+            .map_err(|e| MailerError::DeliveryFailed(e.to_string()))?;
+        Ok(())
+    }
+}
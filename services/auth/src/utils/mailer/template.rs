@@ -0,0 +1,143 @@
+use super::{EmailMessage, MailerError};
+use crate::utils::i18n::{I18nService, Locale};
+
+/// Built-in email templates
+///
+/// Subjects and bodies use `{{name}}` placeholders filled from template variables.
+/// The English source lives here; translations come from the message catalogs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmailTemplate {
+    /// Variables: `link`, `ttl_hours`
+    EmailVerification,
+    /// Variables: `link`, `ttl_minutes`
+    PasswordReset,
+    /// Variables: `time`, `ip_address`, `user_agent`, `link`
+    NewSignIn,
+    /// Sent to the new address. Variables: `link`, `ttl_hours`
+    EmailChangeConfirmation,
+    /// Sent to the old address. Variables: `new_email`, `link`, `cancel_ttl_days`
+    EmailChangeNotice,
+    /// Variables: `inviter`, `organization`, `role`, `link`, `ttl_days`
+    OrganizationInvitation,
+}
+
+impl EmailTemplate {
+    pub const ALL: [EmailTemplate; 6] = [
+        EmailTemplate::EmailVerification,
+        EmailTemplate::PasswordReset,
+        EmailTemplate::NewSignIn,
+        EmailTemplate::EmailChangeConfirmation,
+        EmailTemplate::EmailChangeNotice,
+        EmailTemplate::OrganizationInvitation,
+    ];
+
+    /// Catalog key for the template's translations
+    pub fn key(self) -> &'static str {
+        match self {
+            EmailTemplate::EmailVerification => "email_verification",
+            EmailTemplate::PasswordReset => "password_reset",
+            EmailTemplate::NewSignIn => "new_sign_in",
+            EmailTemplate::EmailChangeConfirmation => "email_change_confirmation",
+            EmailTemplate::EmailChangeNotice => "email_change_notice",
+            EmailTemplate::OrganizationInvitation => "organization_invitation",
+        }
+    }
+
+    /// Subject and body source text (English)
+    fn source(self) -> (&'static str, &'static str) {
+        match self {
+            EmailTemplate::EmailVerification => (
+                "Verify your Venomous Dashboard email address",
+                "Please confirm this email address for your Venomous Dashboard account.\n\n\
+                 Open the link below to verify it. It expires in {{ttl_hours}} hours and can only be used once.\n\n\
+                 {{link}}\n\n\
+                 If you didn't create an account, you can ignore this email.",
+            ),
+            EmailTemplate::PasswordReset => (
+                "Reset your Venomous Dashboard password",
+                "We received a request to reset your password.\n\n\
+                 Open the link below to choose a new password. It expires in {{ttl_minutes}} minutes and can only be used once.\n\n\
+                 {{link}}\n\n\
+                 If you didn't request this, you can ignore this email.",
+            ),
+            EmailTemplate::NewSignIn => (
+                "New sign-in to your Venomous Dashboard account",
+                "We noticed a sign-in to your account from a new device or location.\n\n\
+                 Time: {{time}}\n\
+                 IP address: {{ip_address}}\n\
+                 Device: {{user_agent}}\n\n\
+                 If this was you, no action is needed. If not, reset your password right away:\n\n\
+                 {{link}}",
+            ),
+            EmailTemplate::EmailChangeConfirmation => (
+                "Confirm your new Venomous Dashboard email address",
+                "You asked to use this address to sign in to your Venomous Dashboard account.\n\n\
+                 Open the link below to confirm the change. It expires in {{ttl_hours}} hours and can only be used once.\n\n\
+                 {{link}}\n\n\
+                 If you didn't ask for this, you can ignore this email and nothing will change.",
+            ),
+            EmailTemplate::EmailChangeNotice => (
+                "Your Venomous Dashboard email address is being changed",
+                "Someone asked to change the sign-in email for your account to {{new_email}}.\n\n\
+                 If this was you, no action is needed. If not, open the link below within {{cancel_ttl_days}} days \
+                 to cancel the change (or undo it if it was already confirmed), then reset your password:\n\n\
+                 {{link}}",
+            ),
+            EmailTemplate::OrganizationInvitation => (
+                "You're invited to join {{organization}} on Venomous Dashboard",
+                "{{inviter}} invited you to join the {{organization}} organization on Venomous Dashboard as {{role}}.\n\n\
+                 Sign in with this email address and open the link below to accept. It expires in {{ttl_days}} days and can only be used once.\n\n\
+                 {{link}}\n\n\
+                 If you weren't expecting this invitation, you can ignore this email.",
+            ),
+        }
+    }
+
+    /// Render the template in `locale` into a message for `to`
+    pub fn render(
+        self,
+        locale: Locale,
+        to: &str,
+        vars: &[(&str, &str)],
+    ) -> Result<EmailMessage, MailerError> {
+        let (subject, body) = match I18nService::email_text(locale, self.key()) {
+            Some(text) => (text.subject.as_str(), text.body.as_str()),
+            None => self.source(),
+        };
+
+        Ok(EmailMessage {
+            to: to.to_string(),
+            subject: render_template(subject, vars)?,
+            body: render_template(body, vars)?,
+            secret: None,
+        })
+    }
+}
+
+/// Replace `{{name}}` placeholders (surrounding spaces allowed) with variable values
+///
+/// Unknown placeholders are an error so a missing variable never reaches a user's inbox.
+pub fn render_template(template: &str, vars: &[(&str, &str)]) -> Result<String, MailerError> {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(length) = rest[start + 2..].find("}}") else {
+            break; // Unclosed braces are kept as literal text
+        };
+
+        output.push_str(&rest[..start]);
+        let name = rest[start + 2..start + 2 + length].trim();
+        let value = vars
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| *value)
+            .ok_or_else(|| MailerError::MissingVariable(name.to_string()))?;
+        output.push_str(value);
+
+        rest = &rest[start + 2 + length + 2..];
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
@@ -1,7 +1,29 @@
+pub mod audit;
+pub mod breach;
+pub mod client;
+pub mod export;
+pub mod i18n;
 pub mod jwt;
+pub mod mailer;
+pub mod one_time_token;
 pub mod password;
+pub mod service_auth;
 pub mod validation;
 
-pub use jwt::{Claims, JwtError, JwtService};
-pub use password::{PasswordError, PasswordService};
+pub use audit::{
+    AuditAnchor, AuditChain, AuditChainReport, AuditFailure, AuditFailureKind, AuditRecord,
+    AuditVerification, AuditVerifier,
+};
+pub use breach::{BreachCorpus, BreachCorpusError, BreachedPasswordService};
+pub use client::ClientInfo;
+pub use export::ExportFormat;
+pub use i18n::{I18nService, Locale};
+pub use jwt::{ActiveOrganization, Claims, JwtError, JwtService, TokenScope};
+pub use mailer::{
+    EmailMessage, EmailTemplate, FileMailer, LogMailer, MailQueue, MailService, Mailer,
+    MailerError, MemoryMailer, OutboxWorker, SmtpConfig, SmtpMailer, SmtpSecurity,
+};
+pub use one_time_token::OneTimeToken;
+pub use password::{PasswordError, PasswordExpiry, PasswordService};
+pub use service_auth::{ServiceCaller, ServiceKeys};
 pub use validation::ProtoValidator;
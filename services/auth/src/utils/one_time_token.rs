@@ -0,0 +1,33 @@
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+/// Number of random bytes in a one-time token (256 bits)
+const TOKEN_BYTES: usize = 32;
+
+/// Single-use tokens sent to users by email (password reset, verification, ...)
+///
+/// Only the SHA-256 hash of a token is stored, so a database leak can't be
+/// used to redeem outstanding tokens.
+pub struct OneTimeToken;
+
+impl OneTimeToken {
+    /// Generate a new token, returning `(plain_token, token_hash)`
+    pub fn generate() -> (String, String) {
+        let mut bytes = [0u8; TOKEN_BYTES];
+        rand::thread_rng().fill_bytes(&mut bytes);
+
+        let token = hex_encode(&bytes);
+        let hash = Self::hash(&token);
+        (token, hash)
+    }
+
+    /// Hash a plain token for storage or lookup
+    pub fn hash(token: &str) -> String {
+        hex_encode(&Sha256::digest(token.trim().as_bytes()))
+    }
+}
+
+/// Lowercase hex encoding
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -1,7 +1,10 @@
 use bcrypt::{hash, verify, DEFAULT_COST};
+use chrono::{DateTime, Duration, Utc};
 use std::env;
 use thiserror::Error;
 
+use super::breach::BreachedPasswordService;
+
 #[derive(Debug, Error)]
 pub enum PasswordError {
     #[error("Failed to hash password: {0}")]
@@ -10,6 +13,24 @@ pub enum PasswordError {
     InvalidPassword,
     #[error("Password verification failed")]
     VerificationFailed,
+    #[error("Password appears in a known data breach")]
+    Breached,
+}
+
+/// Where a password stands against its role's maximum age
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordExpiry {
+    /// The role has no maximum password age
+    NotApplicable,
+    /// The password is valid until `expires_at`
+    Valid { expires_at: DateTime<Utc> },
+    /// The password expires within the warning window
+    ExpiringSoon {
+        expires_at: DateTime<Utc>,
+        days_remaining: i64,
+    },
+    /// The password is past its maximum age
+    Expired { expired_at: DateTime<Utc> },
 }
 
 pub struct PasswordService;
@@ -23,6 +44,52 @@ impl PasswordService {
             .unwrap_or(DEFAULT_COST)
     }
 
+    /// Get number of previous password hashes kept per account (default: 5)
+    pub fn get_history_size() -> i64 {
+        env::var("PASSWORD_HISTORY_SIZE")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .unwrap_or(5)
+    }
+
+    /// Get number of days before expiry that sign-in starts warning (default: 14)
+    pub fn get_expiry_warning_days() -> i64 {
+        env::var("PASSWORD_EXPIRY_WARNING_DAYS")
+            .unwrap_or_else(|_| "14".to_string())
+            .parse()
+            .unwrap_or(14)
+    }
+
+    /// Work out whether a password changed at `changed_at` has expired under a maximum age
+    pub fn password_expiry(
+        changed_at: DateTime<Utc>,
+        max_age_days: Option<i32>,
+        now: DateTime<Utc>,
+    ) -> PasswordExpiry {
+        let max_age_days = match max_age_days {
+            Some(days) if days > 0 => days,
+            _ => return PasswordExpiry::NotApplicable,
+        };
+
+        let expires_at = changed_at + Duration::days(max_age_days as i64);
+        if expires_at <= now {
+            return PasswordExpiry::Expired {
+                expired_at: expires_at,
+            };
+        }
+
+        // Round partial days up so "expires later today" still reads as 1 day
+        let days_remaining = ((expires_at - now).num_seconds() + 86_399) / 86_400;
+        if days_remaining <= Self::get_expiry_warning_days() {
+            PasswordExpiry::ExpiringSoon {
+                expires_at,
+                days_remaining,
+            }
+        } else {
+            PasswordExpiry::Valid { expires_at }
+        }
+    }
+
     /// Hash a password using bcrypt
     pub fn hash_password(password: &str) -> Result<String, PasswordError> {
         if password.is_empty() {
@@ -44,6 +111,17 @@ impl PasswordService {
         Ok(is_valid)
     }
 
+    /// Check whether a password matches any of the given hashes (reuse detection)
+    pub fn matches_any(password: &str, hashes: &[String]) -> Result<bool, PasswordError> {
+        for hash in hashes {
+            if Self::verify_password(password, hash)? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
     /// Validate password strength
     pub fn validate_password_strength(password: &str) -> Result<(), PasswordError> {
         if password.len() < 8 {
@@ -61,6 +139,17 @@ impl PasswordService {
         Ok(())
     }
 
+    /// Validate a password that is about to be set (strength and breach corpus)
+    pub async fn validate_new_password(password: &str) -> Result<(), PasswordError> {
+        Self::validate_password_strength(password)?;
+
+        if BreachedPasswordService::is_breached(password).await {
+            return Err(PasswordError::Breached);
+        }
+
+        Ok(())
+    }
+
     /// Generate a random password (for testing or temporary passwords)
     pub fn generate_random_password(length: usize) -> String {
         use rand::Rng;
@@ -0,0 +1,117 @@
+use axum::{
+    extract::Request,
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sha2::{Digest, Sha256};
+use std::env;
+use subtle::ConstantTimeEq;
+
+use crate::models::ApiResponse;
+use crate::{ErrorCode, ErrorMessage};
+
+/// The service that made an internal request, added to the request's extensions by
+/// `require_service_key`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceCaller(pub String);
+
+/// Credentials of the services allowed to call the internal routes
+#[derive(Debug, Clone, Default)]
+pub struct ServiceKeys {
+    /// Service name and SHA-256 of its key
+    keys: Vec<(String, [u8; 32])>,
+}
+
+impl ServiceKeys {
+    /// Header carrying the calling service's key
+    pub const HEADER: &'static str = "x-service-key";
+
+    /// Parse `name=secret` pairs separated by commas; malformed pairs are skipped
+    pub fn parse(value: &str) -> Self {
+        let keys = value
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+            .filter_map(|pair| match pair.split_once('=') {
+                Some((name, secret)) if !name.trim().is_empty() && !secret.trim().is_empty() => {
+                    Some((name.trim().to_string(), digest(secret.trim())))
+                }
+                _ => {
+                    tracing::warn!("Ignoring malformed INTERNAL_SERVICE_KEYS entry");
+                    None
+                }
+            })
+            .collect();
+
+        ServiceKeys { keys }
+    }
+
+    /// Keys from `INTERNAL_SERVICE_KEYS`; none when unset, so every internal request is refused
+    pub fn from_env() -> Self {
+        env::var("INTERNAL_SERVICE_KEYS")
+            .map(|value| Self::parse(&value))
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// The service holding `key`. Every configured key is compared, in constant time and on
+    /// digests, so neither the position of a match nor the key's length shows in the timing.
+    pub fn authenticate(&self, key: &str) -> Option<&str> {
+        let presented = digest(key);
+
+        self.keys.iter().fold(None, |found, (name, expected)| {
+            let matches: bool = presented.ct_eq(expected).into();
+            if matches && found.is_none() {
+                Some(name.as_str())
+            } else {
+                found
+            }
+        })
+    }
+}
+
+fn digest(value: &str) -> [u8; 32] {
+    Sha256::digest(value.as_bytes()).into()
+}
+
+/// Let a request through only with a service key from `INTERNAL_SERVICE_KEYS` in
+/// `X-Service-Key`, recording the calling service as a `ServiceCaller`
+pub async fn require_service_key(mut request: Request, next: Next) -> Response {
+    let keys = ServiceKeys::from_env();
+    if keys.is_empty() {
+        tracing::error!("INTERNAL_SERVICE_KEYS is not set; refusing internal request");
+    }
+
+    let caller = request
+        .headers()
+        .get(ServiceKeys::HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|key| keys.authenticate(key))
+        .map(|name| ServiceCaller(name.to_string()));
+
+    match caller {
+        Some(caller) => {
+            request.extensions_mut().insert(caller);
+            next.run(request).await
+        }
+        None => {
+            tracing::warn!(
+                "Internal request to {} without a valid service key",
+                request.uri().path()
+            );
+            (
+                StatusCode::UNAUTHORIZED,
+                Json(ApiResponse::error(
+                    ErrorCode::SERVICE_UNAUTHORIZED,
+                    ErrorMessage::SERVICE_KEY_INVALID,
+                )),
+            )
+                .into_response()
+        }
+    }
+}
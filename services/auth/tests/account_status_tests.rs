@@ -0,0 +1,36 @@
+use chrono::{Duration, Utc};
+use venomous_dashboard_auth::AccountStatus;
+
+#[test]
+fn test_account_status_parsing() {
+    for status in [
+        AccountStatus::Active,
+        AccountStatus::Disabled,
+        AccountStatus::Suspended,
+    ] {
+        assert_eq!(status.as_str().parse::<AccountStatus>(), Ok(status));
+        assert_eq!(
+            serde_json::to_value(status).unwrap(),
+            serde_json::json!(status.as_str())
+        );
+    }
+
+    assert!("banned".parse::<AccountStatus>().is_err());
+    assert!("Active".parse::<AccountStatus>().is_err());
+}
+
+#[test]
+fn test_account_status_blocks_access() {
+    let now = Utc::now();
+
+    assert!(!AccountStatus::Active.blocks_access(None, now));
+    assert!(AccountStatus::Disabled.blocks_access(None, now));
+
+    // Open-ended suspensions last until lifted
+    assert!(AccountStatus::Suspended.blocks_access(None, now));
+
+    // Timed suspensions end on their own
+    assert!(AccountStatus::Suspended.blocks_access(Some(now + Duration::hours(1)), now));
+    assert!(!AccountStatus::Suspended.blocks_access(Some(now), now));
+    assert!(!AccountStatus::Suspended.blocks_access(Some(now - Duration::hours(1)), now));
+}
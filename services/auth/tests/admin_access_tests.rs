@@ -0,0 +1,245 @@
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+use uuid::Uuid;
+use venomous_dashboard_auth::utils::{JwtService, TokenScope};
+use venomous_dashboard_auth::{ErrorCode, Permissions, Roles};
+
+use crate::support::{app, call};
+
+/// Every admin route with the permission it requires; path parameters get a user ID
+fn admin_routes() -> Vec<(Method, String, &'static str)> {
+    let user_id = Uuid::new_v4();
+    let role_id = Uuid::new_v4();
+    vec![
+        (
+            Method::GET,
+            "/admin/users".to_string(),
+            Permissions::USERS_READ,
+        ),
+        (
+            Method::GET,
+            "/admin/users/password-expired".to_string(),
+            Permissions::USERS_READ,
+        ),
+        (
+            Method::PUT,
+            format!("/admin/users/{}/status", user_id),
+            Permissions::USERS_DISABLE,
+        ),
+        (
+            Method::POST,
+            format!("/admin/users/{}/reset-password", user_id),
+            Permissions::USERS_RESET_PASSWORD,
+        ),
+        (
+            Method::POST,
+            format!("/admin/users/{}/require-password-change", user_id),
+            Permissions::USERS_RESET_PASSWORD,
+        ),
+        (
+            Method::GET,
+            format!("/admin/users/{}/lock-status", user_id),
+            Permissions::USERS_READ,
+        ),
+        (
+            Method::GET,
+            "/admin/security-logs".to_string(),
+            Permissions::AUDIT_READ,
+        ),
+        (
+            Method::GET,
+            "/admin/security-logs/stream".to_string(),
+            Permissions::AUDIT_READ,
+        ),
+        (
+            Method::GET,
+            "/admin/audit/verify".to_string(),
+            Permissions::AUDIT_VERIFY,
+        ),
+        (
+            Method::GET,
+            "/admin/audit/export".to_string(),
+            Permissions::AUDIT_EXPORT,
+        ),
+        (
+            Method::GET,
+            "/admin/email-outbox".to_string(),
+            Permissions::EMAIL_OUTBOX_READ,
+        ),
+        (
+            Method::POST,
+            "/admin/sessions/revoke".to_string(),
+            Permissions::SESSIONS_REVOKE,
+        ),
+        (
+            Method::POST,
+            "/admin/account/unlock".to_string(),
+            Permissions::USERS_UNLOCK,
+        ),
+        (
+            Method::GET,
+            "/admin/permissions".to_string(),
+            Permissions::ROLES_READ,
+        ),
+        (
+            Method::GET,
+            "/admin/roles".to_string(),
+            Permissions::ROLES_READ,
+        ),
+        (
+            Method::POST,
+            "/admin/roles".to_string(),
+            Permissions::ROLES_MANAGE,
+        ),
+        (
+            Method::PATCH,
+            format!("/admin/roles/{}", role_id),
+            Permissions::ROLES_MANAGE,
+        ),
+        (
+            Method::DELETE,
+            format!("/admin/roles/{}", role_id),
+            Permissions::ROLES_MANAGE,
+        ),
+        (
+            Method::PUT,
+            format!("/admin/users/{}/role", user_id),
+            Permissions::USERS_ASSIGN_ROLE,
+        ),
+    ]
+}
+
+fn session_token(role: &str, permissions: &[&str]) -> String {
+    let permissions: Vec<String> = permissions.iter().map(|name| name.to_string()).collect();
+    JwtService::generate_session_token(
+        Uuid::new_v4(),
+        "someone@example.com",
+        role,
+        Uuid::new_v4(),
+        &permissions,
+        None,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_regular_users_are_forbidden_on_every_admin_route() {
+    let app = app();
+    let token = session_token(Roles::USER, &[]);
+
+    for (method, uri, _) in admin_routes() {
+        let (status, body) = call(&app, method.clone(), &uri, Some(&token), json!({})).await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{} {}", method, uri);
+        assert_eq!(
+            body["error"]["code"],
+            ErrorCode::INSUFFICIENT_PERMISSIONS,
+            "{} {}",
+            method,
+            uri
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_admin_routes_require_a_token() {
+    let app = app();
+
+    for (method, uri, _) in admin_routes() {
+        let (status, body) = call(&app, method.clone(), &uri, None, json!({})).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{} {}", method, uri);
+        assert_eq!(body["error"]["code"], ErrorCode::TOKEN_INVALID);
+
+        let (status, _) = call(&app, method.clone(), &uri, Some("not-a-jwt"), json!({})).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{} {}", method, uri);
+    }
+}
+
+#[tokio::test]
+async fn test_restricted_admin_tokens_are_rejected() {
+    let app = app();
+    let token = JwtService::generate_restricted_token(
+        Uuid::new_v4(),
+        "admin@example.com",
+        Roles::ADMIN,
+        Uuid::new_v4(),
+        TokenScope::PASSWORD_CHANGE,
+    )
+    .unwrap();
+
+    let (status, _) = call(&app, Method::GET, "/admin/users", Some(&token), json!({})).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_each_admin_route_requires_its_permission() {
+    let app = app();
+
+    for (method, uri, required) in admin_routes() {
+        // Every other permission is not enough
+        let others: Vec<&str> = Permissions::ALL
+            .into_iter()
+            .filter(|permission| *permission != required)
+            .collect();
+        let (status, _) = call(
+            &app,
+            method.clone(),
+            &uri,
+            Some(&session_token(Roles::ADMIN, &others)),
+            json!({}),
+        )
+        .await;
+        assert_eq!(
+            status,
+            StatusCode::FORBIDDEN,
+            "{} {} without {}",
+            method,
+            uri,
+            required
+        );
+
+        // The required permission alone passes the token check and moves on to the
+        // database, which is unreachable here
+        let (status, body) = call(
+            &app,
+            method.clone(),
+            &uri,
+            Some(&session_token(Roles::USER, &[required])),
+            json!({}),
+        )
+        .await;
+        assert_eq!(
+            status,
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "{} {} with {}",
+            method,
+            uri,
+            required
+        );
+        assert_eq!(body["error"]["code"], ErrorCode::DATABASE_ERROR);
+    }
+}
+
+#[tokio::test]
+async fn test_permissions_are_confirmed_against_the_database() {
+    let app = app();
+
+    // Permission claims alone are not trusted: the session and current grants are looked
+    // up, which fails here because the database is unreachable. Tokens issued before
+    // permissions existed carry no claim and go straight to the database.
+    let all = session_token(Roles::SUPER_ADMIN, &Permissions::ALL);
+    let mut claims = JwtService::validate_token(&session_token(Roles::ADMIN, &[]))
+        .unwrap()
+        .claims;
+    claims.permissions = None;
+    let legacy = jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        &claims,
+        &jsonwebtoken::EncodingKey::from_secret(b"test-secret-key"),
+    )
+    .unwrap();
+    for token in [all, legacy] {
+        let (status, body) = call(&app, Method::GET, "/admin/users", Some(&token), json!({})).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body["error"]["code"], ErrorCode::DATABASE_ERROR);
+    }
+}
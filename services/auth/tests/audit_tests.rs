@@ -0,0 +1,308 @@
+use chrono::{SubsecRound, TimeZone, Utc};
+use serde_json::json;
+use std::env;
+use std::fs;
+use uuid::Uuid;
+use venomous_dashboard_auth::utils::{
+    AuditAnchor, AuditChain, AuditChainReport, AuditFailureKind, AuditRecord, AuditVerification,
+    AuditVerifier,
+};
+use venomous_dashboard_auth::{AuditCheckpoint, SecurityEvent};
+
+const KEY: &[u8] = b"test-audit-signing-key";
+
+/// A correctly chained log of `count` events in stream 0
+fn chain(count: i64) -> Vec<SecurityEvent> {
+    chain_in(0, count)
+}
+
+fn chain_in(stream: i16, count: i64) -> Vec<SecurityEvent> {
+    let mut prev_hash = AuditChain::GENESIS_HASH.to_string();
+    (1..=count)
+        .map(|sequence| {
+            let mut event = SecurityEvent {
+                id: Uuid::new_v4(),
+                user_id: Some(Uuid::new_v4()),
+                event_type: "signin_failed".to_string(),
+                success: false,
+                ip_address: Some("203.0.113.7".to_string()),
+                user_agent: Some("curl/8.0".to_string()),
+                metadata: Some(json!({ "reason": "invalid_password", "attempt": sequence })),
+                created_at: Utc::now().trunc_subsecs(6),
+                sequence: Some(sequence),
+                prev_hash: Some(prev_hash.clone()),
+                entry_hash: None,
+                stream,
+            };
+            rehash(&mut event);
+            prev_hash = event.entry_hash.clone().unwrap();
+            event
+        })
+        .collect()
+}
+
+/// Recompute an entry's hash after editing it, as someone covering their tracks would
+fn rehash(event: &mut SecurityEvent) {
+    let hash = AuditChain::entry_hash(
+        event.prev_hash.as_deref().unwrap(),
+        &AuditRecord::from_event(event).unwrap(),
+    );
+    event.entry_hash = Some(hash);
+}
+
+fn checkpoint(event: &SecurityEvent) -> AuditCheckpoint {
+    let sequence = event.sequence.unwrap();
+    let entry_hash = event.entry_hash.clone().unwrap();
+    AuditCheckpoint {
+        id: Uuid::new_v4(),
+        sequence,
+        signature: AuditChain::sign_checkpoint(KEY, event.stream, sequence, &entry_hash),
+        entry_hash,
+        created_at: Utc::now(),
+        stream: event.stream,
+    }
+}
+
+fn verify(events: &[SecurityEvent], checkpoints: Vec<AuditCheckpoint>) -> AuditVerification {
+    verify_anchored(events, checkpoints, vec![])
+}
+
+fn verify_anchored(
+    events: &[SecurityEvent],
+    checkpoints: Vec<AuditCheckpoint>,
+    anchors: Vec<AuditAnchor>,
+) -> AuditVerification {
+    let stream = events.first().map_or(0, |event| event.stream);
+    let mut verifier = AuditVerifier::new(KEY, stream, checkpoints, anchors);
+    for event in events {
+        if !verifier.push(event) {
+            break;
+        }
+    }
+    verifier.finish()
+}
+
+fn failure(report: &AuditVerification) -> (AuditFailureKind, Option<i64>) {
+    let failure = report
+        .first_failure
+        .as_ref()
+        .expect("chain should be invalid");
+    (failure.kind, failure.sequence)
+}
+
+#[test]
+fn test_intact_chain_verifies() {
+    let events = chain(10);
+    let report = verify(&events, vec![checkpoint(&events[4])]);
+
+    assert!(report.valid, "{:?}", report.first_failure);
+    assert_eq!(report.checked_entries, 10);
+    assert_eq!(report.verified_checkpoints, 1);
+    assert_eq!(report.last_sequence, Some(10));
+    assert_eq!(report.head_hash, events[9].entry_hash);
+    assert_eq!(report.unanchored_entries, 5);
+
+    let empty = verify(&[], vec![]);
+    assert!(empty.valid);
+    assert_eq!(empty.last_sequence, None);
+}
+
+#[test]
+fn test_entry_hash_is_stable() {
+    let events = chain(1);
+    let mut event = events[0].clone();
+
+    // Metadata key order (e.g. after a JSONB round trip) doesn't change the hash
+    event.metadata = Some(json!({ "attempt": 1, "reason": "invalid_password" }));
+    let reordered: serde_json::Value =
+        serde_json::from_str(r#"{"reason":"invalid_password","attempt":1}"#).unwrap();
+    let mut other = event.clone();
+    other.metadata = Some(reordered);
+    rehash(&mut event);
+    rehash(&mut other);
+    assert_eq!(event.entry_hash, other.entry_hash);
+
+    // Every field is covered
+    let mut changed = event.clone();
+    changed.created_at = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+    rehash(&mut changed);
+    assert_ne!(changed.entry_hash, event.entry_hash);
+
+    let mut moved = event.clone();
+    moved.stream = 1;
+    rehash(&mut moved);
+    assert_ne!(moved.entry_hash, event.entry_hash);
+}
+
+#[test]
+fn test_edited_entry_is_reported() {
+    let mut events = chain(10);
+    events[3].success = true;
+
+    assert_eq!(
+        failure(&verify(&events, vec![])),
+        (AuditFailureKind::Modified, Some(4))
+    );
+
+    // Re-hashing the edited entry breaks the link from the next one
+    rehash(&mut events[3]);
+    assert_eq!(
+        failure(&verify(&events, vec![])),
+        (AuditFailureKind::BrokenLink, Some(5))
+    );
+}
+
+#[test]
+fn test_deleted_entry_is_reported() {
+    let mut events = chain(10);
+    events.remove(6);
+
+    let report = verify(&events, vec![]);
+    assert_eq!(failure(&report), (AuditFailureKind::Gap, Some(8)));
+    assert_eq!(report.checked_entries, 6);
+}
+
+#[test]
+fn test_rewritten_chain_fails_at_checkpoint() {
+    let mut events = chain(10);
+    let checkpoints = vec![checkpoint(&events[7])];
+
+    // Edit entry 3 and recompute every later hash: only the signed checkpoint catches it
+    events[2].metadata = Some(json!({ "reason": "nothing to see here" }));
+    for i in 2..events.len() {
+        if i > 2 {
+            events[i].prev_hash = events[i - 1].entry_hash.clone();
+        }
+        rehash(&mut events[i]);
+    }
+    assert!(verify(&events, vec![]).valid);
+    assert_eq!(
+        failure(&verify(&events, checkpoints)),
+        (AuditFailureKind::BadCheckpoint, Some(8))
+    );
+}
+
+#[test]
+fn test_forged_checkpoint_is_rejected() {
+    let events = chain(5);
+    let mut forged = checkpoint(&events[2]);
+    forged.signature = AuditChain::sign_checkpoint(b"wrong key", 0, 3, &forged.entry_hash);
+
+    assert_eq!(
+        failure(&verify(&events, vec![forged])),
+        (AuditFailureKind::BadCheckpoint, Some(3))
+    );
+    assert!(!AuditChain::checkpoint_signature_valid(
+        KEY,
+        0,
+        3,
+        AuditChain::GENESIS_HASH,
+        "not hex"
+    ));
+
+    // A signature names its stream, so it can't vouch for the same position in another
+    let genuine = checkpoint(&events[2]);
+    assert!(!AuditChain::checkpoint_signature_valid(
+        KEY,
+        1,
+        genuine.sequence,
+        &genuine.entry_hash,
+        &genuine.signature
+    ));
+}
+
+#[test]
+fn test_truncated_tail_is_reported() {
+    let events = chain(10);
+    let checkpoints = vec![checkpoint(&events[9])];
+
+    let report = verify(&events[..7], checkpoints);
+    assert_eq!(failure(&report), (AuditFailureKind::Truncated, Some(8)));
+}
+
+#[test]
+fn test_truncated_tail_is_caught_by_anchor() {
+    let events = chain(10);
+    let anchors = vec![AuditAnchor::from(&checkpoint(&events[9]))];
+
+    // Tail and its checkpoint both gone from the database: only the anchor file remembers
+    assert!(verify(&events[..7], vec![]).valid);
+    let report = verify_anchored(&events[..7], vec![], anchors.clone());
+    assert_eq!(failure(&report), (AuditFailureKind::Truncated, Some(8)));
+
+    let intact = verify_anchored(&events, vec![], anchors);
+    assert!(intact.valid, "{:?}", intact.first_failure);
+    assert_eq!(intact.verified_anchors, 1);
+}
+
+#[test]
+fn test_rewritten_chain_fails_at_anchor() {
+    let mut events = chain(5);
+    let anchors = vec![AuditAnchor::from(&checkpoint(&events[3]))];
+
+    events[1].success = true;
+    for i in 1..events.len() {
+        if i > 1 {
+            events[i].prev_hash = events[i - 1].entry_hash.clone();
+        }
+        rehash(&mut events[i]);
+    }
+    assert_eq!(
+        failure(&verify_anchored(&events, vec![], anchors)),
+        (AuditFailureKind::BadCheckpoint, Some(4))
+    );
+}
+
+#[test]
+fn test_streams_are_verified_independently() {
+    let first = chain_in(0, 5);
+    let second = chain_in(1, 3);
+    let checkpoints = vec![checkpoint(&first[4]), checkpoint(&second[2])];
+
+    // Each stream only looks at its own checkpoints
+    let report = AuditChainReport::new(
+        vec![
+            verify(&first, checkpoints.clone()),
+            verify(&second, checkpoints.clone()),
+        ],
+        false,
+        None,
+    );
+    assert!(report.valid, "{:?}", report.first_failure);
+    assert_eq!(report.checked_entries, 8);
+    assert_eq!(report.streams[1].last_sequence, Some(3));
+
+    // An entry moved into another stream doesn't fit there
+    let mut mixed = first.clone();
+    mixed.push(second[0].clone());
+    let mut verifier = AuditVerifier::new(KEY, 0, vec![], vec![]);
+    for event in &mixed {
+        verifier.push(event);
+    }
+    let mixed = verifier.finish();
+    assert_eq!(failure(&mixed), (AuditFailureKind::BrokenLink, Some(1)));
+
+    let report = AuditChainReport::new(vec![verify(&second, vec![]), mixed], false, None);
+    assert!(!report.valid);
+    assert_eq!(report.first_failure.unwrap().stream, Some(0));
+}
+
+#[test]
+fn test_anchor_file_round_trip() {
+    let path = env::temp_dir().join(format!("audit-anchors-{}.jsonl", Uuid::new_v4()));
+    assert!(AuditChain::read_anchors(&path).unwrap().is_empty());
+
+    let events = chain_in(2, 3);
+    let anchors: Vec<AuditAnchor> = events
+        .iter()
+        .map(|event| AuditAnchor::from(&checkpoint(event)))
+        .collect();
+    for anchor in &anchors {
+        AuditChain::append_anchor(&path, anchor).unwrap();
+    }
+    assert_eq!(AuditChain::read_anchors(&path).unwrap(), anchors);
+
+    fs::write(&path, "not json\n").unwrap();
+    assert!(AuditChain::read_anchors(&path).is_err());
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,199 @@
+use axum::http::{Method, StatusCode};
+use axum::Router;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+use venomous_dashboard_auth::database::{
+    AuthzDecision, DenyReason, EffectivePermissions, PermissionSource,
+};
+use venomous_dashboard_auth::handlers::AuthzCheckRequest;
+use venomous_dashboard_auth::{Authz, ErrorCode, Permissions, Roles};
+
+use crate::support::{app, hierarchy, internal_call, names, post};
+
+fn grants(role: &str) -> EffectivePermissions {
+    let mut groups = BTreeMap::new();
+    groups.insert("auditors".to_string(), names(&[Permissions::AUDIT_READ]));
+    groups.insert(
+        "reviewers".to_string(),
+        names(&[Permissions::AUDIT_READ, Permissions::AUDIT_EXPORT]),
+    );
+    EffectivePermissions::resolve(
+        role,
+        hierarchy().effective_permissions(role).to_vec(),
+        groups,
+        names(&[Permissions::AUDIT_EXPORT, Permissions::SESSIONS_REVOKE]),
+    )
+}
+
+#[test]
+fn test_decisions_name_where_the_permission_comes_from() {
+    let admin = grants(Roles::ADMIN);
+
+    let by_role = AuthzDecision::from_grants(Some(&admin), Permissions::USERS_READ);
+    assert_eq!(
+        by_role,
+        AuthzDecision::Allow(PermissionSource::Role(Roles::ADMIN.to_string()))
+    );
+    assert!(by_role.is_allowed());
+    assert_eq!(by_role.reason_code(), "granted_by_role");
+    assert_eq!(by_role.granted_via(), Some(Roles::ADMIN));
+
+    // Direct grants are reported ahead of groups holding the same permission
+    let direct = AuthzDecision::from_grants(Some(&admin), Permissions::AUDIT_EXPORT);
+    assert_eq!(direct, AuthzDecision::Allow(PermissionSource::Direct));
+    assert_eq!(direct.reason_code(), "granted_directly");
+    assert_eq!(direct.granted_via(), None);
+
+    // The first group by name wins
+    let by_group = AuthzDecision::from_grants(Some(&admin), Permissions::AUDIT_READ);
+    assert_eq!(
+        by_group,
+        AuthzDecision::Allow(PermissionSource::Group("auditors".to_string()))
+    );
+    assert_eq!(by_group.reason_code(), "granted_by_group");
+    assert_eq!(by_group.granted_via(), Some("auditors"));
+}
+
+#[test]
+fn test_decisions_deny_with_a_reason() {
+    let user = grants(Roles::USER);
+
+    let denied = AuthzDecision::from_grants(Some(&user), Permissions::USERS_READ);
+    assert_eq!(
+        denied,
+        AuthzDecision::Deny(DenyReason::PermissionNotGranted)
+    );
+    assert!(!denied.is_allowed());
+    assert_eq!(denied.reason_code(), "permission_not_granted");
+
+    let outsider = AuthzDecision::from_grants(None, Permissions::USERS_READ);
+    assert_eq!(
+        outsider,
+        AuthzDecision::Deny(DenyReason::NotOrganizationMember)
+    );
+    assert_eq!(outsider.reason_code(), "not_organization_member");
+
+    for (reason, code) in [
+        (DenyReason::SubjectNotFound, "subject_not_found"),
+        (DenyReason::SubjectInactive, "subject_inactive"),
+        (DenyReason::UnknownAction, "unknown_action"),
+    ] {
+        let decision = AuthzDecision::Deny(reason);
+        assert!(!decision.is_allowed());
+        assert_eq!(decision.reason_code(), code);
+        assert_eq!(decision.granted_via(), None);
+    }
+}
+
+#[test]
+fn test_check_requests_name_a_permission() {
+    let user_id = Uuid::new_v4();
+    let org_id = Uuid::new_v4();
+
+    let bare: AuthzCheckRequest = serde_json::from_value(json!({
+        "subject": user_id.to_string(),
+        "action": "read",
+        "resource": { "type": "users", "organization_id": org_id.to_string() }
+    }))
+    .unwrap();
+    let request = bare.to_request().unwrap();
+    assert_eq!(request.user_id, user_id);
+    assert_eq!(request.organization_id, Some(org_id));
+    assert_eq!(request.permission, Permissions::USERS_READ);
+
+    // A full permission name is used as given
+    let full: AuthzCheckRequest = serde_json::from_value(json!({
+        "subject": user_id.to_string(),
+        "action": Permissions::AUDIT_READ,
+        "resource": { "type": "security_event" }
+    }))
+    .unwrap();
+    let request = full.to_request().unwrap();
+    assert_eq!(request.organization_id, None);
+    assert_eq!(request.permission, Permissions::AUDIT_READ);
+
+    for invalid in [
+        json!({ "subject": "nope", "action": "read", "resource": { "type": "users" } }),
+        json!({ "subject": user_id.to_string(), "action": " ", "resource": { "type": "users" } }),
+        json!({ "subject": user_id.to_string(), "action": "read", "resource": { "type": "" } }),
+        json!({
+            "subject": user_id.to_string(),
+            "action": "read",
+            "resource": { "type": "users", "organization_id": "nope" }
+        }),
+    ] {
+        let check: AuthzCheckRequest = serde_json::from_value(invalid.clone()).unwrap();
+        assert!(check.to_request().is_none(), "{}", invalid);
+    }
+
+    // A check about one object is decided by the permission on its type
+    let object: AuthzCheckRequest = serde_json::from_value(json!({
+        "subject": user_id.to_string(),
+        "action": "write",
+        "resource": { "type": "notes", "id": "42", "organization_id": org_id.to_string() }
+    }))
+    .unwrap();
+    let request = object.to_request().unwrap();
+    assert_eq!(request.organization_id, Some(org_id));
+    assert_eq!(request.permission, Permissions::NOTES_WRITE);
+}
+
+async fn check_as_service(app: &Router, uri: &str, body: Value) -> (StatusCode, Value) {
+    internal_call(app, Method::POST, uri, body).await
+}
+
+fn check(subject: &str) -> Value {
+    json!({ "subject": subject, "action": "read", "resource": { "type": "users" } })
+}
+
+#[tokio::test]
+async fn test_check_endpoints_validate_requests() {
+    let app = app();
+    let user_id = Uuid::new_v4().to_string();
+
+    let (status, body) = check_as_service(&app, "/authz/check", check("nope")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], ErrorCode::VALIDATION_ERROR);
+
+    let too_many: Vec<Value> = (0..=Authz::MAX_BATCH_SIZE)
+        .map(|_| check(&user_id))
+        .collect();
+    for checks in [Vec::new(), too_many] {
+        let (status, body) =
+            check_as_service(&app, "/authz/check/batch", json!({ "checks": checks })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], ErrorCode::VALIDATION_ERROR);
+    }
+
+    // One bad check rejects the whole batch before anything is decided
+    let (status, _) = check_as_service(
+        &app,
+        "/authz/check/batch",
+        json!({ "checks": [check(&user_id), check("nope")] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Valid checks reach the database
+    let (status, body) = check_as_service(&app, "/authz/check", check(&user_id)).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(body["error"]["code"], ErrorCode::DATABASE_ERROR);
+}
+
+#[tokio::test]
+async fn test_check_endpoints_require_a_service_key() {
+    let app = app();
+    let user_id = Uuid::new_v4().to_string();
+
+    // Refused before the subject is looked up, so decisions such as `subject_not_found`
+    // never reach callers without a key
+    for (uri, body) in [
+        ("/authz/check", check(&user_id)),
+        ("/authz/check/batch", json!({ "checks": [check(&user_id)] })),
+    ] {
+        let (status, body) = post(&app, uri, body).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", uri);
+        assert_eq!(body["error"]["code"], ErrorCode::SERVICE_UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,124 @@
+use std::env;
+use std::io::Cursor;
+use std::path::PathBuf;
+use venomous_dashboard_auth::utils::{BreachCorpus, BreachCorpusError};
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+}
+
+#[test]
+fn test_text_corpus_lookup() {
+    let corpus = BreachCorpus::load(&fixture("breached_passwords.txt")).unwrap();
+
+    // Duplicate lines are collapsed
+    assert_eq!(corpus.len(), 6);
+
+    assert!(corpus.contains_password("password123"));
+    assert!(corpus.contains_password("dragon2024"));
+    assert!(!corpus.contains_password("Password123"));
+    assert!(!corpus.contains_password("correct horse battery staple 42"));
+}
+
+#[test]
+fn test_range_directory_lookup() {
+    let corpus = BreachCorpus::load(&fixture("breached_ranges")).unwrap();
+
+    assert_eq!(corpus.len(), 3);
+    assert!(corpus.contains_password("qwerty123"));
+    assert!(corpus.contains_password("letmein1"));
+    assert!(!corpus.contains_password("dragon2024"));
+}
+
+#[test]
+fn test_binary_corpus_roundtrip() {
+    let binary_path = env::temp_dir().join(format!("breach-{}.bin", uuid::Uuid::new_v4()));
+
+    let written =
+        BreachCorpus::convert_to_binary(&fixture("breached_passwords.txt"), &binary_path).unwrap();
+    assert_eq!(written, 6);
+    assert_eq!(std::fs::metadata(&binary_path).unwrap().len(), 6 * 20);
+
+    let corpus = BreachCorpus::load(&binary_path).unwrap();
+    assert_eq!(corpus.len(), 6);
+    for password in [
+        "password123",
+        "qwerty123",
+        "letmein1",
+        "iloveyou2",
+        "dragon2024",
+        "monkey123",
+    ] {
+        assert!(
+            corpus.contains_password(password),
+            "{} should be found",
+            password
+        );
+    }
+    assert!(!corpus.contains_password("not-in-the-corpus-1"));
+
+    std::fs::remove_file(&binary_path).unwrap();
+}
+
+#[test]
+fn test_malformed_corpus_is_rejected() {
+    let result = BreachCorpus::from_text(Cursor::new(
+        "CBFDAC6008F9CAB4083784CBD1874F76618D2A97:1\nnot-a-hash:3\n",
+    ));
+    assert!(matches!(result, Err(BreachCorpusError::MalformedEntry(2))));
+
+    let binary_path = env::temp_dir().join(format!("breach-{}.bin", uuid::Uuid::new_v4()));
+    std::fs::write(&binary_path, [0u8; 21]).unwrap();
+    assert!(matches!(
+        BreachCorpus::load(&binary_path),
+        Err(BreachCorpusError::MalformedBinary)
+    ));
+    std::fs::remove_file(&binary_path).unwrap();
+}
+
+#[test]
+fn test_large_text_corpus_is_searched_on_disk() {
+    use sha1::{Digest, Sha1};
+
+    let mut lines: Vec<String> = (0..2000)
+        .map(|i| {
+            let digest = Sha1::digest(format!("breached-{}", i).as_bytes());
+            let hex: String = digest.iter().map(|b| format!("{:02X}", b)).collect();
+            format!("{}:{}", hex, i + 1)
+        })
+        .collect();
+    lines.sort();
+
+    let text_path = env::temp_dir().join(format!("breach-{}.txt", uuid::Uuid::new_v4()));
+    std::fs::write(&text_path, lines.join("\n")).unwrap();
+
+    let corpus = BreachCorpus::load(&text_path).unwrap();
+    assert_eq!(corpus.len(), 2000);
+    for i in [0, 1, 999, 1998, 1999] {
+        assert!(corpus.contains_password(&format!("breached-{}", i)));
+    }
+    assert!(!corpus.contains_password("breached-2000"));
+    assert!(!corpus.contains_password(""));
+
+    std::fs::remove_file(&text_path).unwrap();
+}
+
+#[test]
+fn test_unsorted_text_corpus_is_rejected() {
+    let text_path = env::temp_dir().join(format!("breach-{}.txt", uuid::Uuid::new_v4()));
+    std::fs::write(
+        &text_path,
+        "CBFDAC6008F9CAB4083784CBD1874F76618D2A97:1\n098C3FDEA75EA905A838BC4833ABCB13CA6CDCFC:2\n",
+    )
+    .unwrap();
+
+    assert!(matches!(
+        BreachCorpus::load(&text_path),
+        Err(BreachCorpusError::Unsorted(2))
+    ));
+
+    std::fs::remove_file(&text_path).unwrap();
+}
@@ -0,0 +1,146 @@
+use chrono::{TimeZone, Utc};
+use serde_json::{json, Value};
+use uuid::Uuid;
+use venomous_dashboard_auth::utils::ExportFormat;
+use venomous_dashboard_auth::SecurityEvent;
+
+fn event() -> SecurityEvent {
+    SecurityEvent {
+        id: Uuid::new_v4(),
+        user_id: Some(Uuid::new_v4()),
+        event_type: "signin_failed".to_string(),
+        success: false,
+        ip_address: Some("203.0.113.7".to_string()),
+        user_agent: Some("Mozilla/5.0 (X11; Linux x86_64)".to_string()),
+        metadata: Some(json!({ "reason": "invalid_password" })),
+        created_at: Utc.with_ymd_and_hms(2026, 3, 14, 9, 26, 53).unwrap(),
+        sequence: Some(42),
+        prev_hash: Some("a".repeat(64)),
+        entry_hash: Some("b".repeat(64)),
+        stream: 3,
+    }
+}
+
+#[test]
+fn test_export_format_parsing() {
+    assert_eq!("jsonl".parse(), Ok(ExportFormat::JsonLines));
+    assert_eq!("ndjson".parse(), Ok(ExportFormat::JsonLines));
+    assert_eq!("csv".parse(), Ok(ExportFormat::Csv));
+    assert_eq!("cef".parse(), Ok(ExportFormat::Cef));
+    assert!("xml".parse::<ExportFormat>().is_err());
+    assert!("CSV".parse::<ExportFormat>().is_err());
+}
+
+#[test]
+fn test_json_lines_round_trip() {
+    let event = event();
+    let line = ExportFormat::JsonLines.format_event(&event);
+
+    assert!(line.ends_with('\n'));
+    assert_eq!(line.matches('\n').count(), 1);
+    let parsed: Value = serde_json::from_str(line.trim_end()).unwrap();
+    assert_eq!(parsed["id"], json!(event.id));
+    assert_eq!(parsed["event_type"], "signin_failed");
+    assert_eq!(parsed["created_at"], "2026-03-14T09:26:53.000000Z");
+    assert_eq!(parsed["metadata"]["reason"], "invalid_password");
+    assert_eq!(parsed["sequence"], 42);
+    assert!(ExportFormat::JsonLines.header().is_none());
+}
+
+#[test]
+fn test_csv_header_matches_rows() {
+    let header = ExportFormat::Csv.header().unwrap();
+    let mut plain = event();
+    plain.metadata = None;
+    plain.user_agent = Some("curl/8.0".to_string());
+    let row = ExportFormat::Csv.format_event(&plain);
+
+    assert!(header.ends_with("\r\n"));
+    assert!(row.ends_with("\r\n"));
+    assert!(header.starts_with("id,created_at,event_type,success,user_id"));
+    assert_eq!(
+        header.trim_end().split(',').count(),
+        row.trim_end().split(',').count()
+    );
+}
+
+#[test]
+fn test_csv_quotes_fields_with_separators() {
+    let mut event = event();
+    event.metadata = Some(json!({ "note": "a,b" }));
+    let row = ExportFormat::Csv.format_event(&event);
+
+    // Metadata JSON has quotes and a comma, so it's quoted with doubled quotes
+    assert!(row.contains(r#","{""note"":""a,b""}","#));
+    // Spaces and semicolons need no quoting
+    assert!(row.contains(",Mozilla/5.0 (X11; Linux x86_64),"));
+
+    event.user_agent = Some("line\nbreak".to_string());
+    let row = ExportFormat::Csv.format_event(&event);
+    assert!(row.contains(",\"line\nbreak\","));
+}
+
+#[test]
+fn test_cef_line_layout() {
+    let event = event();
+    let line = ExportFormat::Cef.format_event(&event);
+
+    assert!(line.starts_with("CEF:0|Venomous Dashboard|Auth Service|"));
+    assert!(line.contains("|signin_failed|signin failed|5|"));
+    assert!(line.contains(&format!("rt={}", event.created_at.timestamp_millis())));
+    assert!(line.contains(&format!("externalId={}", event.id)));
+    assert!(line.contains("outcome=failure"));
+    assert!(line.contains("src=203.0.113.7"));
+    assert!(line.contains("cn1Label=sequence cn1=42"));
+    assert_eq!(line.matches('\n').count(), 1);
+}
+
+#[test]
+fn test_cef_escaping() {
+    let mut event = event();
+    event.event_type = "custom|type".to_string();
+    event.user_agent = Some("agent=x\\y\nz".to_string());
+    let line = ExportFormat::Cef.format_event(&event);
+
+    assert!(line.contains("|custom\\|type|"));
+    assert!(line.contains("requestClientApplication=agent\\=x\\\\y\\nz"));
+    assert_eq!(line.matches('\n').count(), 1);
+}
+
+#[test]
+fn test_cef_severity() {
+    let mut event = event();
+    let severity = |event: &SecurityEvent| {
+        let line = ExportFormat::Cef.format_event(event);
+        line.split('|').nth(6).unwrap().to_string()
+    };
+
+    assert_eq!(severity(&event), "5");
+    event.success = true;
+    event.event_type = "signin_success".to_string();
+    assert_eq!(severity(&event), "2");
+    event.event_type = "admin_user_status_changed".to_string();
+    assert_eq!(severity(&event), "4");
+    event.event_type = "account_locked".to_string();
+    assert_eq!(severity(&event), "7");
+}
+
+#[test]
+fn test_optional_fields_are_omitted() {
+    let mut event = event();
+    event.user_id = None;
+    event.ip_address = None;
+    event.metadata = None;
+    event.sequence = None;
+    event.entry_hash = None;
+
+    let line = ExportFormat::Cef.format_event(&event);
+    assert!(!line.contains("suid="));
+    assert!(!line.contains("src="));
+    assert!(!line.contains("cn1Label"));
+
+    let parsed: Value =
+        serde_json::from_str(ExportFormat::JsonLines.format_event(&event).trim_end()).unwrap();
+    assert!(parsed["user_id"].is_null());
+    assert!(parsed["sequence"].is_null());
+}
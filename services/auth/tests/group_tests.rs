@@ -0,0 +1,227 @@
+use axum::http::{Method, StatusCode};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+use venomous_dashboard_auth::database::EffectivePermissions;
+use venomous_dashboard_auth::{ErrorCode, Groups, Permissions, Roles};
+
+use crate::support::{app, call, hierarchy, internal_call, names};
+
+#[test]
+fn test_group_names() {
+    for name in ["editors", "reviewers", "ops_team", "team-east", "qa2"] {
+        assert!(Groups::is_valid_name(name), "{}", name);
+    }
+
+    let too_long = "a".repeat(Groups::MAX_NAME_LENGTH + 1);
+    for name in [
+        "e",
+        "2nd-team",
+        "_editors",
+        "Editors",
+        "the editors",
+        too_long.as_str(),
+    ] {
+        assert!(!Groups::is_valid_name(name), "{}", name);
+    }
+}
+
+#[test]
+fn test_effective_permissions_combine_role_groups_and_direct_grants() {
+    let mut groups = BTreeMap::new();
+    groups.insert(
+        "editors".to_string(),
+        names(&[Permissions::MEDIAS_WRITE, Permissions::NOTES_WRITE]),
+    );
+    groups.insert("empty".to_string(), Vec::new());
+
+    let effective = EffectivePermissions::resolve(
+        Roles::SUPER_ADMIN,
+        organization_permissions(Roles::SUPER_ADMIN),
+        groups,
+        names(&[Permissions::WORKFLOWS_RUN]),
+    );
+
+    assert_eq!(effective.role, Roles::SUPER_ADMIN);
+    // The role's permissions include inherited ones
+    assert_eq!(
+        effective.role_permissions,
+        names(&[Permissions::NOTES_READ, Permissions::NOTES_WRITE])
+    );
+    assert_eq!(
+        effective.direct_permissions,
+        names(&[Permissions::WORKFLOWS_RUN])
+    );
+    assert_eq!(effective.group_permissions["empty"], Vec::<String>::new());
+    // Combined, sorted and without duplicates
+    assert_eq!(
+        effective.permissions,
+        names(&[
+            Permissions::MEDIAS_WRITE,
+            Permissions::NOTES_READ,
+            Permissions::NOTES_WRITE,
+            Permissions::WORKFLOWS_RUN,
+        ])
+    );
+
+    assert!(effective.has(Permissions::MEDIAS_WRITE));
+    assert!(effective.has(Permissions::WORKFLOWS_RUN));
+    assert!(effective.has(Permissions::NOTES_READ));
+    assert!(!effective.has(Permissions::MEDIAS_READ));
+}
+
+fn organization_permissions(role: &str) -> Vec<String> {
+    hierarchy()
+        .effective_organization_permissions(role)
+        .to_vec()
+}
+
+#[test]
+fn test_roles_hold_no_global_permissions_in_organizations() {
+    let hierarchy = hierarchy();
+
+    // An organization admin gets the admin role's organization grants, never its global ones
+    assert_eq!(
+        hierarchy.effective_organization_permissions(Roles::ADMIN),
+        names(&[Permissions::NOTES_READ, Permissions::NOTES_WRITE])
+    );
+    assert_eq!(
+        hierarchy.effective_permissions(Roles::ADMIN),
+        names(&[Permissions::USERS_READ])
+    );
+    assert!(!hierarchy.has_permission(Roles::USER, Permissions::NOTES_READ));
+
+    for permission in Permissions::ORGANIZATION {
+        assert!(!Permissions::ALL.contains(&permission), "{}", permission);
+    }
+}
+
+#[test]
+fn test_effective_permissions_without_grants() {
+    let user = EffectivePermissions::resolve(Roles::USER, Vec::new(), BTreeMap::new(), Vec::new());
+    assert!(user.permissions.is_empty());
+    assert!(!user.has(Permissions::NOTES_READ));
+
+    // Groups still grant permissions to a member whose role has none, or an unknown role
+    let mut groups = BTreeMap::new();
+    groups.insert("reviewers".to_string(), names(&[Permissions::NOTES_READ]));
+    let unknown = EffectivePermissions::resolve(
+        "deleted_role",
+        organization_permissions("deleted_role"),
+        groups,
+        Vec::new(),
+    );
+    assert!(unknown.role_permissions.is_empty());
+    assert_eq!(unknown.permissions, names(&[Permissions::NOTES_READ]));
+}
+
+#[tokio::test]
+async fn test_group_routes_require_a_token() {
+    let app = app();
+    let org_id = Uuid::new_v4();
+    let group_id = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+    let routes = [
+        (Method::GET, format!("/orgs/{}/groups", org_id)),
+        (Method::POST, format!("/orgs/{}/groups", org_id)),
+        (
+            Method::PATCH,
+            format!("/orgs/{}/groups/{}", org_id, group_id),
+        ),
+        (
+            Method::DELETE,
+            format!("/orgs/{}/groups/{}", org_id, group_id),
+        ),
+        (
+            Method::GET,
+            format!("/orgs/{}/groups/{}/members", org_id, group_id),
+        ),
+        (
+            Method::PUT,
+            format!("/orgs/{}/groups/{}/members/{}", org_id, group_id, user_id),
+        ),
+        (
+            Method::DELETE,
+            format!("/orgs/{}/groups/{}/members/{}", org_id, group_id, user_id),
+        ),
+        (
+            Method::GET,
+            format!("/orgs/{}/members/{}/permissions", org_id, user_id),
+        ),
+        (
+            Method::PUT,
+            format!("/orgs/{}/members/{}/permissions", org_id, user_id),
+        ),
+    ];
+
+    for (method, uri) in routes {
+        let (status, body) = call(
+            &app,
+            method.clone(),
+            &uri,
+            None,
+            json!({ "name": "editors", "permissions": [] }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{} {}", method, uri);
+        assert_eq!(
+            body["error"]["code"],
+            ErrorCode::TOKEN_NOT_FOUND,
+            "{} {}",
+            method,
+            uri
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_group_membership_lookup_validates_its_query() {
+    let app = app();
+    let org_id = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+
+    // Missing IDs are rejected by the query extractor
+    for query in [
+        format!("user_id={}&group=editors", user_id),
+        format!("organization_id={}&group=editors", org_id),
+    ] {
+        let uri = format!("/internal/group-membership?{}", query);
+        let (status, _) = internal_call(&app, Method::GET, &uri, Value::Null).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+    }
+
+    // Malformed IDs, or not exactly one of group_id and group
+    for query in [
+        format!("organization_id=nope&user_id={}&group=editors", user_id),
+        format!("organization_id={}&user_id={}", org_id, user_id),
+        format!(
+            "organization_id={}&user_id={}&group=editors&group_id={}",
+            org_id,
+            user_id,
+            Uuid::new_v4()
+        ),
+        format!(
+            "organization_id={}&user_id={}&group_id=nope",
+            org_id, user_id
+        ),
+    ] {
+        let uri = format!("/internal/group-membership?{}", query);
+        let (status, body) = internal_call(&app, Method::GET, &uri, Value::Null).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+        assert_eq!(
+            body["error"]["code"],
+            ErrorCode::VALIDATION_ERROR,
+            "{}",
+            uri
+        );
+    }
+
+    // A well-formed lookup reaches the database
+    let uri = format!(
+        "/internal/group-membership?organization_id={}&user_id={}&group=editors",
+        org_id, user_id
+    );
+    let (status, body) = internal_call(&app, Method::GET, &uri, Value::Null).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(body["error"]["code"], ErrorCode::DATABASE_ERROR);
+}
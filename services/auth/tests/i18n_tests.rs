@@ -0,0 +1,172 @@
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use axum::{middleware, routing::get, Router};
+use tower::ServiceExt;
+use venomous_dashboard_auth::utils::i18n::localize_error_responses;
+use venomous_dashboard_auth::utils::{EmailTemplate, I18nService, Locale};
+use venomous_dashboard_auth::ErrorMessage;
+
+use crate::support::unreachable_database;
+
+/// Every English message declared in `ErrorMessage`
+fn error_messages() -> Vec<String> {
+    let source = include_str!("../src/constants/error_messages.rs");
+
+    source
+        .split("pub const ")
+        .skip(1)
+        .filter_map(|declaration| {
+            let start = declaration.find('"')? + 1;
+            let end = start + declaration[start..].find("\";")?;
+            Some(declaration[start..end].replace("\\\"", "\""))
+        })
+        .collect()
+}
+
+#[test]
+fn test_locale_parsing() {
+    assert_eq!(Locale::parse("ja"), Some(Locale::Ja));
+    assert_eq!(Locale::parse("ja-JP"), Some(Locale::Ja));
+    assert_eq!(Locale::parse("zh-Hans-CN"), Some(Locale::Zh));
+    assert_eq!(Locale::parse("zh_TW"), Some(Locale::Zh));
+    assert_eq!(Locale::parse("EN-us"), Some(Locale::En));
+    assert_eq!(Locale::parse("fr"), None);
+    assert_eq!(Locale::parse(""), None);
+}
+
+#[test]
+fn test_accept_language_negotiation() {
+    assert_eq!(
+        Locale::from_accept_language("ja-JP,ja;q=0.9,en-US;q=0.8,en;q=0.7"),
+        Some(Locale::Ja)
+    );
+    assert_eq!(
+        Locale::from_accept_language("en;q=0.5, zh-CN;q=0.8"),
+        Some(Locale::Zh)
+    );
+    assert_eq!(
+        Locale::from_accept_language("fr-FR, de;q=0.9, ja;q=0.1"),
+        Some(Locale::Ja)
+    );
+    assert_eq!(Locale::from_accept_language("ja;q=0, fr"), None);
+    assert_eq!(Locale::from_accept_language("*"), None);
+}
+
+#[test]
+fn test_locale_resolution_order() {
+    // A saved preference beats the browser, which beats the default
+    assert_eq!(I18nService::resolve(Some("zh"), Some("ja")), Locale::Zh);
+    assert_eq!(I18nService::resolve(None, Some("ja")), Locale::Ja);
+    assert_eq!(I18nService::resolve(Some("xx"), Some("ja")), Locale::Ja);
+    assert_eq!(
+        I18nService::resolve(None, None),
+        I18nService::default_locale()
+    );
+}
+
+#[test]
+fn test_every_error_message_is_translated() {
+    let messages = error_messages();
+    assert!(messages.len() > 40);
+
+    for message in &messages {
+        assert_eq!(&I18nService::translate(Locale::En, message), message);
+
+        for locale in [Locale::Ja, Locale::Zh] {
+            let translated = I18nService::translate(locale, message);
+            assert_ne!(
+                &translated,
+                message,
+                "missing {} translation for: {}",
+                locale.code(),
+                message
+            );
+            assert_eq!(
+                translated.matches("{}").count(),
+                message.matches("{}").count(),
+                "placeholder mismatch in {} translation for: {}",
+                locale.code(),
+                message
+            );
+        }
+    }
+}
+
+#[test]
+fn test_translate_fills_placeholders() {
+    let message = ErrorMessage::ACCOUNT_LOCKED_WITH_COUNTDOWN.replace("{}", "17");
+
+    let japanese = I18nService::translate(Locale::Ja, &message);
+    assert!(japanese.contains("17 分"));
+    assert!(!japanese.contains("{}"));
+
+    let chinese = I18nService::translate(Locale::Zh, &message);
+    assert!(chinese.contains("17 分钟"));
+
+    // Text that isn't in the catalog comes back unchanged
+    assert_eq!(
+        I18nService::translate(Locale::Ja, "Something else entirely"),
+        "Something else entirely"
+    );
+}
+
+#[test]
+fn test_email_templates_in_every_locale() {
+    let vars = [
+        ("link", "http://localhost:3000/x?token=abc"),
+        ("ttl_hours", "24"),
+        ("ttl_minutes", "30"),
+        ("time", "2026-10-19 09:00:00 UTC"),
+        ("ip_address", "203.0.113.7"),
+        ("user_agent", "Firefox"),
+        ("new_email", "new@example.com"),
+        ("cancel_ttl_days", "7"),
+        ("inviter", "Alice"),
+        ("organization", "Acme"),
+        ("role", "user"),
+        ("ttl_days", "7"),
+    ];
+
+    for template in EmailTemplate::ALL {
+        let english = template
+            .render(Locale::En, "user@example.com", &vars)
+            .unwrap();
+
+        for locale in [Locale::Ja, Locale::Zh] {
+            let email = template.render(locale, "user@example.com", &vars).unwrap();
+            assert_eq!(email.to, "user@example.com");
+            assert_ne!(email.subject, english.subject);
+            assert!(email.body.contains("token=abc"));
+            assert!(!email.body.contains("{{"));
+        }
+    }
+
+    let reset = EmailTemplate::PasswordReset
+        .render(Locale::Ja, "user@example.com", &vars)
+        .unwrap();
+    assert!(reset.body.contains("30 分"));
+}
+
+#[tokio::test]
+async fn test_oversized_error_bodies_pass_through_untranslated() {
+    let db = unreachable_database();
+    let large = "x".repeat(100 * 1024);
+    let body = large.clone();
+    let app = Router::new()
+        .route(
+            "/large",
+            get(move || async move { (StatusCode::BAD_REQUEST, body) }),
+        )
+        .layer(middleware::from_fn_with_state(db, localize_error_responses));
+
+    let request = Request::builder()
+        .uri("/large")
+        .header("accept-language", "ja")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(bytes.len(), large.len());
+}
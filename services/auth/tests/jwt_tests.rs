@@ -1,7 +1,7 @@
 use std::env;
 use uuid::Uuid;
-use venomous_dashboard_auth::utils::JwtService;
-use venomous_dashboard_auth::Roles;
+use venomous_dashboard_auth::utils::{JwtError, JwtService, TokenScope};
+use venomous_dashboard_auth::{Permissions, Roles};
 
 #[test]
 fn test_jwt_generation_and_validation() {
@@ -38,3 +38,175 @@ fn test_invalid_token() {
     let result = JwtService::validate_token("invalid-token");
     assert!(result.is_err());
 }
+
+#[test]
+fn test_session_token_carries_session_id() {
+    env::set_var("JWT_SECRET", "test-secret-key");
+
+    let user_id = Uuid::new_v4();
+    let session_id = Uuid::new_v4();
+
+    let token = JwtService::generate_session_token(
+        user_id,
+        "test@example.com",
+        Roles::USER,
+        session_id,
+        &[],
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        JwtService::extract_session_id(&token).unwrap(),
+        Some(session_id)
+    );
+
+    // Refreshing keeps the token bound to the same session
+    let refreshed = JwtService::refresh_token(&token).unwrap();
+    assert_eq!(
+        JwtService::extract_session_id(&refreshed).unwrap(),
+        Some(session_id)
+    );
+
+    // Tokens issued without a session carry no session ID
+    let plain = JwtService::generate_token(user_id, "test@example.com", Roles::USER).unwrap();
+    assert_eq!(JwtService::extract_session_id(&plain).unwrap(), None);
+}
+
+#[test]
+fn test_restricted_token_scope() {
+    env::set_var("JWT_SECRET", "test-secret-key");
+
+    let user_id = Uuid::new_v4();
+    let session_id = Uuid::new_v4();
+
+    let token = JwtService::generate_restricted_token(
+        user_id,
+        "test@example.com",
+        Roles::USER,
+        session_id,
+        TokenScope::PASSWORD_CHANGE,
+    )
+    .unwrap();
+
+    // Not usable as a full-access token
+    assert!(matches!(
+        JwtService::validate_token(&token),
+        Err(JwtError::ScopeNotPermitted(_))
+    ));
+    assert!(JwtService::refresh_token(&token).is_err());
+
+    // Accepted where its scope is allowed, and still identifies the session
+    let claims = JwtService::validate_token_for_scope(&token, Some(TokenScope::PASSWORD_CHANGE))
+        .unwrap()
+        .claims;
+    assert_eq!(claims.scope.as_deref(), Some(TokenScope::PASSWORD_CHANGE));
+    assert_eq!(
+        JwtService::extract_session_id(&token).unwrap(),
+        Some(session_id)
+    );
+
+    // Full-access tokens are accepted wherever a scope is allowed
+    let full = JwtService::generate_session_token(
+        user_id,
+        "test@example.com",
+        Roles::USER,
+        session_id,
+        &[],
+        None,
+    )
+    .unwrap();
+    assert!(JwtService::validate_token_for_scope(&full, Some(TokenScope::PASSWORD_CHANGE)).is_ok());
+}
+
+#[test]
+fn test_unverified_email_token_lifetime() {
+    env::set_var("JWT_SECRET", "test-secret-key");
+
+    let user_id = Uuid::new_v4();
+    let session_id = Uuid::new_v4();
+
+    let password_change = JwtService::generate_restricted_token(
+        user_id,
+        "test@example.com",
+        Roles::USER,
+        session_id,
+        TokenScope::PASSWORD_CHANGE,
+    )
+    .unwrap();
+    let unverified = JwtService::generate_restricted_token(
+        user_id,
+        "test@example.com",
+        Roles::USER,
+        session_id,
+        TokenScope::UNVERIFIED_EMAIL,
+    )
+    .unwrap();
+
+    let lifetime = |token: &str, scope: &str| {
+        let claims = JwtService::validate_token_for_scope(token, Some(scope))
+            .unwrap()
+            .claims;
+        claims.exp - claims.iat
+    };
+
+    // Password change tokens are short-lived, unverified email tokens last a normal session
+    assert_eq!(
+        lifetime(&password_change, TokenScope::PASSWORD_CHANGE),
+        15 * 60
+    );
+    assert!(lifetime(&unverified, TokenScope::UNVERIFIED_EMAIL) > 15 * 60);
+}
+
+#[test]
+fn test_session_token_carries_permissions() {
+    env::set_var("JWT_SECRET", "test-secret-key");
+
+    let granted = vec![
+        Permissions::AUDIT_READ.to_string(),
+        Permissions::USERS_READ.to_string(),
+    ];
+    let token = JwtService::generate_session_token(
+        Uuid::new_v4(),
+        "admin@example.com",
+        Roles::ADMIN,
+        Uuid::new_v4(),
+        &granted,
+        None,
+    )
+    .unwrap();
+    let claims = JwtService::validate_token(&token).unwrap().claims;
+    assert_eq!(claims.permissions, Some(granted.clone()));
+
+    // Refreshing keeps the permissions; restricted tokens never carry any
+    let refreshed = JwtService::refresh_token(&token).unwrap();
+    let claims = JwtService::validate_token(&refreshed).unwrap().claims;
+    assert_eq!(claims.permissions, Some(granted));
+
+    let restricted = JwtService::generate_restricted_token(
+        Uuid::new_v4(),
+        "admin@example.com",
+        Roles::ADMIN,
+        Uuid::new_v4(),
+        TokenScope::PASSWORD_CHANGE,
+    )
+    .unwrap();
+    let claims =
+        JwtService::validate_token_for_scope(&restricted, Some(TokenScope::PASSWORD_CHANGE))
+            .unwrap()
+            .claims;
+    assert!(claims.permissions.is_none());
+}
+
+#[test]
+fn test_permission_names() {
+    let mut names = Permissions::ALL.to_vec();
+    names.sort();
+    names.dedup();
+    assert_eq!(names.len(), Permissions::ALL.len());
+
+    for name in Permissions::ALL {
+        let (resource, action) = name.split_once(':').unwrap();
+        assert!(!resource.is_empty() && !action.is_empty(), "{}", name);
+        assert!(name.len() <= 100, "{}", name);
+    }
+}
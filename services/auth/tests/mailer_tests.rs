@@ -0,0 +1,264 @@
+use std::env;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use venomous_dashboard_auth::utils::{
+    mailer::render_template, EmailMessage, EmailTemplate, FileMailer, Locale, MailQueue, Mailer,
+    MailerError, MemoryMailer, OutboxWorker, SmtpConfig, SmtpMailer, SmtpSecurity,
+};
+use venomous_dashboard_auth::EmailOutbox;
+
+fn message(to: &str, subject: &str) -> EmailMessage {
+    EmailMessage {
+        to: to.to_string(),
+        subject: subject.to_string(),
+        body: "Hello from the test suite".to_string(),
+        secret: None,
+    }
+}
+
+/// Accept one SMTP conversation and return everything the client sent
+fn spawn_smtp_server() -> (u16, thread::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut writer = stream.try_clone().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut transcript = String::new();
+        let mut in_data = false;
+
+        writer.write_all(b"220 localhost test SMTP\r\n").unwrap();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap() == 0 {
+                break;
+            }
+            transcript.push_str(&line);
+
+            if in_data {
+                if line == ".\r\n" {
+                    in_data = false;
+                    writer.write_all(b"250 OK queued\r\n").unwrap();
+                }
+                continue;
+            }
+
+            let command = line.to_uppercase();
+            if command.starts_with("EHLO") || command.starts_with("HELO") {
+                writer.write_all(b"250 localhost\r\n").unwrap();
+            } else if command.starts_with("DATA") {
+                in_data = true;
+                writer
+                    .write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n")
+                    .unwrap();
+            } else if command.starts_with("QUIT") {
+                writer.write_all(b"221 Bye\r\n").unwrap();
+                break;
+            } else {
+                writer.write_all(b"250 OK\r\n").unwrap();
+            }
+        }
+
+        transcript
+    });
+
+    (port, handle)
+}
+
+#[test]
+fn test_render_template() {
+    assert_eq!(
+        render_template(
+            "Hi {{name}}, see {{ link }}!",
+            &[("name", "Ada"), ("link", "x")]
+        )
+        .unwrap(),
+        "Hi Ada, see x!"
+    );
+
+    // Unclosed braces are left alone
+    assert_eq!(render_template("a {{b", &[]).unwrap(), "a {{b");
+
+    assert!(matches!(
+        render_template("Hi {{name}}", &[]),
+        Err(MailerError::MissingVariable(name)) if name == "name"
+    ));
+}
+
+#[test]
+fn test_builtin_templates_render() {
+    let email = EmailTemplate::PasswordReset
+        .render(
+            Locale::En,
+            "user@example.com",
+            &[
+                ("link", "http://localhost:3000/reset-password?token=abc"),
+                ("ttl_minutes", "30"),
+            ],
+        )
+        .unwrap();
+
+    assert_eq!(email.to, "user@example.com");
+    assert_eq!(email.subject, "Reset your Venomous Dashboard password");
+    assert!(email.body.contains("reset-password?token=abc"));
+    assert!(email.body.contains("30 minutes"));
+    assert!(!email.body.contains("{{"));
+
+    // Every template reports its missing variables
+    for template in EmailTemplate::ALL {
+        assert!(template
+            .render(Locale::En, "user@example.com", &[])
+            .is_err());
+    }
+}
+
+#[tokio::test]
+async fn test_queue_delivers_through_memory_mailer() {
+    let mailer = MemoryMailer::new();
+    let queue = MailQueue::start(Arc::new(mailer.clone()));
+
+    queue.enqueue(message("a@example.com", "First")).unwrap();
+    queue.enqueue(message("b@example.com", "Second")).unwrap();
+    queue
+        .enqueue(
+            EmailTemplate::PasswordReset
+                .render(
+                    Locale::En,
+                    "c@example.com",
+                    &[
+                        ("link", EmailMessage::SECRET_PLACEHOLDER),
+                        ("ttl_minutes", "30"),
+                    ],
+                )
+                .unwrap()
+                .with_secret("reset-token"),
+        )
+        .unwrap();
+    queue.shutdown().await;
+
+    let sent = mailer.sent();
+    assert_eq!(sent.len(), 3);
+    assert_eq!(sent[0].subject, "First");
+    assert_eq!(sent[1].to, "b@example.com");
+
+    // The token is filled in on delivery, like the outbox does
+    assert!(sent[2].body.contains("reset-token"));
+    assert!(!sent[2].body.contains(EmailMessage::SECRET_PLACEHOLDER));
+    assert_eq!(sent[2].secret, None);
+
+    mailer.clear();
+    assert!(mailer.sent().is_empty());
+}
+
+#[test]
+fn test_memory_mailer_clones_share_sent_messages() {
+    let mailer = MemoryMailer::new();
+    let handle: Arc<dyn Mailer> = Arc::new(mailer.clone());
+
+    handle.send(&message("a@example.com", "Shared")).unwrap();
+
+    assert_eq!(mailer.sent(), vec![message("a@example.com", "Shared")]);
+}
+
+#[test]
+fn test_file_mailer_drops_eml() {
+    let dir = env::temp_dir().join(format!("mail-drop-{}", uuid::Uuid::new_v4()));
+    let mailer = FileMailer::new(&dir, "Venomous Dashboard <no-reply@localhost>").unwrap();
+
+    mailer
+        .send(&message("user@example.com", "Dropped to disk"))
+        .unwrap();
+
+    let files: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+    assert_eq!(files.len(), 1);
+    let path = files[0].as_ref().unwrap().path();
+    assert_eq!(path.extension().unwrap(), "eml");
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert!(contents.contains("To: user@example.com"));
+    assert!(contents.contains("Subject: Dropped to disk"));
+    assert!(contents.contains("Hello from the test suite"));
+
+    assert!(matches!(
+        mailer.send(&message("not an address", "Bad")),
+        Err(MailerError::InvalidAddress(_))
+    ));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_smtp_mailer_against_local_server() {
+    let (port, server) = spawn_smtp_server();
+
+    let mailer = SmtpMailer::new(SmtpConfig {
+        host: "127.0.0.1".to_string(),
+        port,
+        security: SmtpSecurity::None,
+        username: None,
+        password: None,
+        from: "Venomous Dashboard <no-reply@localhost>".to_string(),
+        timeout: Duration::from_secs(5),
+    })
+    .unwrap();
+
+    mailer
+        .send(&message("user@example.com", "Over SMTP"))
+        .unwrap();
+    drop(mailer); // Closes the pooled connection so the server sees QUIT
+
+    let transcript = server.join().unwrap();
+    assert!(transcript.contains("MAIL FROM:<no-reply@localhost>"));
+    assert!(transcript.contains("RCPT TO:<user@example.com>"));
+    assert!(transcript.contains("Subject: Over SMTP"));
+    assert!(transcript.contains("Hello from the test suite"));
+}
+
+#[test]
+fn test_outbox_retry_backoff() {
+    let delays: Vec<i64> = (1..EmailOutbox::MAX_ATTEMPTS)
+        .map(|attempts| {
+            OutboxWorker::retry_delay(attempts)
+                .expect("attempts remain")
+                .num_seconds()
+        })
+        .collect();
+
+    assert_eq!(delays[0], EmailOutbox::BASE_RETRY_SECONDS);
+    assert_eq!(delays[1], EmailOutbox::BASE_RETRY_SECONDS * 2);
+    assert!(delays.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert!(delays
+        .iter()
+        .all(|&delay| delay <= EmailOutbox::MAX_RETRY_SECONDS));
+
+    // Out of attempts: the message is marked dead rather than rescheduled
+    assert!(OutboxWorker::retry_delay(EmailOutbox::MAX_ATTEMPTS).is_none());
+}
+
+#[test]
+fn test_secret_is_filled_in_only_when_sending() {
+    let link = format!(
+        "https://dashboard.example.com/verify-email?token={}",
+        EmailMessage::SECRET_PLACEHOLDER
+    );
+    let message = EmailTemplate::EmailVerification
+        .render(
+            Locale::En,
+            "user@example.com",
+            &[("link", &link), ("ttl_hours", "24")],
+        )
+        .unwrap()
+        .with_secret("abc123");
+
+    // The stored body keeps only the placeholder
+    assert!(!message.body.contains("abc123"));
+
+    let delivered = message.resolved();
+    assert!(delivered.body.contains("verify-email?token=abc123"));
+    assert!(!delivered.body.contains(EmailMessage::SECRET_PLACEHOLDER));
+    assert_eq!(delivered.secret, None);
+}
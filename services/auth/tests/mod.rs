@@ -1,4 +1,22 @@
 // Integration tests for auth service
 
+mod support;
+
+mod account_status_tests;
+mod admin_access_tests;
+mod audit_tests;
+mod authz_tests;
+mod breach_tests;
+mod export_tests;
+mod group_tests;
+mod i18n_tests;
 mod jwt_tests;
+mod mailer_tests;
+mod one_time_token_tests;
+mod organization_tests;
 mod password_tests;
+mod relation_tests;
+mod role_tests;
+mod security_log_tests;
+mod service_auth_tests;
+mod user_listing_tests;
@@ -0,0 +1,24 @@
+use venomous_dashboard_auth::utils::OneTimeToken;
+
+#[test]
+fn test_token_generation_and_hashing() {
+    let (token, hash) = OneTimeToken::generate();
+
+    // 32 random bytes, hex encoded; the hash is a hex SHA-256
+    assert_eq!(token.len(), 64);
+    assert_eq!(hash.len(), 64);
+    assert_ne!(token, hash);
+
+    // Hashing is deterministic and tolerant of surrounding whitespace
+    assert_eq!(OneTimeToken::hash(&token), hash);
+    assert_eq!(OneTimeToken::hash(&format!(" {}\n", token)), hash);
+}
+
+#[test]
+fn test_tokens_are_unique() {
+    let (first_token, first_hash) = OneTimeToken::generate();
+    let (second_token, second_hash) = OneTimeToken::generate();
+
+    assert_ne!(first_token, second_token);
+    assert_ne!(first_hash, second_hash);
+}
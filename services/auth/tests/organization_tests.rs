@@ -0,0 +1,176 @@
+use axum::http::{Method, StatusCode};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde_json::json;
+use std::env;
+use uuid::Uuid;
+use venomous_dashboard_auth::database::{is_organization_admin, RoleHierarchy};
+use venomous_dashboard_auth::utils::{ActiveOrganization, JwtService};
+use venomous_dashboard_auth::{ErrorCode, Organizations, Roles};
+
+use crate::support::{app, call, node};
+
+fn setup() {
+    env::set_var("JWT_SECRET", "test-secret-key");
+}
+
+#[test]
+fn test_organization_slugs() {
+    for slug in ["acme", "a1", "acme-corp", "team-42-east"] {
+        assert!(Organizations::is_valid_slug(slug), "{}", slug);
+    }
+
+    let too_long = "a".repeat(Organizations::MAX_SLUG_LENGTH + 1);
+    for slug in [
+        "a",
+        "1acme",
+        "-acme",
+        "acme-",
+        "acme--corp",
+        "Acme",
+        "acme corp",
+        "acme_corp",
+        too_long.as_str(),
+    ] {
+        assert!(!Organizations::is_valid_slug(slug), "{}", slug);
+    }
+}
+
+#[test]
+fn test_organization_names() {
+    assert!(Organizations::is_valid_name("Acme"));
+    assert!(Organizations::is_valid_name("  Acme Corp  "));
+    assert!(Organizations::is_valid_name(
+        &"名".repeat(Organizations::MAX_NAME_LENGTH)
+    ));
+
+    assert!(!Organizations::is_valid_name(""));
+    assert!(!Organizations::is_valid_name("   "));
+    assert!(!Organizations::is_valid_name(
+        &"a".repeat(Organizations::MAX_NAME_LENGTH + 1)
+    ));
+}
+
+#[test]
+fn test_organization_admin_follows_the_role_hierarchy() {
+    let user = node(Roles::USER, None, &[]);
+    let admin = node(Roles::ADMIN, Some(&user), &[]);
+    let super_admin = node(Roles::SUPER_ADMIN, Some(&admin), &[]);
+    let editor = node("editor", Some(&user), &[]);
+    let hierarchy = RoleHierarchy::new(vec![user, admin, super_admin, editor]);
+
+    assert!(is_organization_admin(&hierarchy, Roles::ADMIN));
+    assert!(is_organization_admin(&hierarchy, Roles::SUPER_ADMIN));
+    assert!(!is_organization_admin(&hierarchy, Roles::USER));
+    assert!(!is_organization_admin(&hierarchy, "editor"));
+    assert!(!is_organization_admin(&hierarchy, "unknown"));
+}
+
+#[test]
+fn test_session_tokens_carry_the_active_organization() {
+    setup();
+    let organization = ActiveOrganization {
+        id: Uuid::new_v4(),
+        role: Roles::ADMIN.to_string(),
+    };
+
+    let token = JwtService::generate_session_token(
+        Uuid::new_v4(),
+        "member@example.com",
+        Roles::USER,
+        Uuid::new_v4(),
+        &[],
+        Some(&organization),
+    )
+    .unwrap();
+    let claims = JwtService::validate_token(&token).unwrap().claims;
+
+    assert_eq!(claims.org, Some(organization.id.to_string()));
+    assert_eq!(claims.org_role.as_deref(), Some(Roles::ADMIN));
+    // The organization role is separate from the global one
+    assert_eq!(claims.role, Roles::USER);
+    assert_eq!(
+        JwtService::organization_from_claims(&claims),
+        Some(organization)
+    );
+
+    // Refreshing keeps the organization
+    let refreshed = JwtService::refresh_token(&token).unwrap();
+    let refreshed = JwtService::validate_token(&refreshed).unwrap().claims;
+    assert_eq!(refreshed.org, claims.org);
+    assert_eq!(refreshed.org_role, claims.org_role);
+}
+
+#[test]
+fn test_session_tokens_without_organization_omit_the_claims() {
+    setup();
+    let token = JwtService::generate_session_token(
+        Uuid::new_v4(),
+        "member@example.com",
+        Roles::USER,
+        Uuid::new_v4(),
+        &[],
+        None,
+    )
+    .unwrap();
+    let claims = JwtService::validate_token(&token).unwrap().claims;
+
+    assert_eq!(claims.org, None);
+    assert_eq!(claims.org_role, None);
+    assert_eq!(JwtService::organization_from_claims(&claims), None);
+
+    let payload = URL_SAFE_NO_PAD
+        .decode(token.split('.').nth(1).unwrap())
+        .unwrap();
+    let payload = String::from_utf8(payload).unwrap();
+    assert!(!payload.contains("\"org\""));
+    assert!(!payload.contains("org_role"));
+}
+
+#[tokio::test]
+async fn test_organization_routes_require_a_token() {
+    let app = app();
+    let org_id = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+    let routes = [
+        (Method::GET, "/orgs".to_string()),
+        (Method::POST, "/orgs".to_string()),
+        (Method::POST, "/switch-org".to_string()),
+        (Method::POST, "/orgs/invitations/accept".to_string()),
+        (Method::GET, format!("/orgs/{}/members", org_id)),
+        (
+            Method::DELETE,
+            format!("/orgs/{}/members/{}", org_id, user_id),
+        ),
+        (
+            Method::PUT,
+            format!("/orgs/{}/members/{}/role", org_id, user_id),
+        ),
+        (Method::GET, format!("/orgs/{}/invitations", org_id)),
+        (Method::POST, format!("/orgs/{}/invitations", org_id)),
+        (
+            Method::DELETE,
+            format!("/orgs/{}/invitations/{}", org_id, Uuid::new_v4()),
+        ),
+    ];
+
+    let body = json!({
+        "name": "Acme",
+        "slug": "acme",
+        "organization_id": null,
+        "token": "t",
+        "role": "user",
+        "email": "a@example.com"
+    });
+    for (method, uri) in routes {
+        let (status, body) = call(&app, method.clone(), &uri, None, body.clone()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{} {}", method, uri);
+        assert_eq!(
+            body["error"]["code"],
+            ErrorCode::TOKEN_NOT_FOUND,
+            "{} {}",
+            method,
+            uri
+        );
+    }
+}
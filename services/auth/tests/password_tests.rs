@@ -1,4 +1,5 @@
-use venomous_dashboard_auth::utils::PasswordService;
+use chrono::{Duration, Utc};
+use venomous_dashboard_auth::utils::{PasswordExpiry, PasswordService};
 
 #[test]
 fn test_password_hashing_and_verification() {
@@ -45,3 +46,55 @@ fn test_empty_password_handling() {
     assert!(PasswordService::verify_password("", "hash").is_err());
     assert!(PasswordService::verify_password("password", "").is_err());
 }
+
+#[test]
+fn test_password_reuse_detection() {
+    let old_hashes = vec![
+        PasswordService::hash_password("first_password1").unwrap(),
+        PasswordService::hash_password("second_password2").unwrap(),
+    ];
+
+    assert!(PasswordService::matches_any("first_password1", &old_hashes).unwrap());
+    assert!(PasswordService::matches_any("second_password2", &old_hashes).unwrap());
+    assert!(!PasswordService::matches_any("third_password3", &old_hashes).unwrap());
+    assert!(!PasswordService::matches_any("first_password1", &[]).unwrap());
+}
+
+#[test]
+fn test_password_expiry() {
+    let now = Utc::now();
+
+    // Roles without a maximum age never expire
+    assert_eq!(
+        PasswordService::password_expiry(now - Duration::days(1000), None, now),
+        PasswordExpiry::NotApplicable
+    );
+
+    // Well within the maximum age
+    let changed_at = now - Duration::days(10);
+    assert_eq!(
+        PasswordService::password_expiry(changed_at, Some(90), now),
+        PasswordExpiry::Valid {
+            expires_at: changed_at + Duration::days(90)
+        }
+    );
+
+    // Inside the default 14 day warning window
+    let changed_at = now - Duration::days(80);
+    assert_eq!(
+        PasswordService::password_expiry(changed_at, Some(90), now),
+        PasswordExpiry::ExpiringSoon {
+            expires_at: changed_at + Duration::days(90),
+            days_remaining: 10
+        }
+    );
+
+    // Past the maximum age
+    let changed_at = now - Duration::days(91);
+    assert_eq!(
+        PasswordService::password_expiry(changed_at, Some(90), now),
+        PasswordExpiry::Expired {
+            expired_at: changed_at + Duration::days(90)
+        }
+    );
+}
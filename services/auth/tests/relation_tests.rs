@@ -0,0 +1,565 @@
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use venomous_dashboard_auth::database::{
+    check_relation_in, expand_relation_in, list_relation_objects_in, ConsistencyToken, ExpandTree,
+    ListCursor, MemoryTuples, ObjectPage, ObjectRef, RelationTuple, SubjectRef,
+};
+use venomous_dashboard_auth::{ErrorCode, Relations};
+
+use crate::support::{app, internal_call, post};
+
+fn tuples(tuples: &[&str]) -> MemoryTuples {
+    MemoryTuples {
+        tuples: tuples
+            .iter()
+            .map(|tuple| RelationTuple::parse(tuple).unwrap_or_else(|| panic!("{}", tuple)))
+            .collect(),
+        groups: BTreeMap::new(),
+    }
+}
+
+fn object(value: &str) -> ObjectRef {
+    ObjectRef::parse(value).unwrap()
+}
+
+fn subject(value: &str) -> SubjectRef {
+    SubjectRef::parse(value).unwrap()
+}
+
+/// Parse `object#relation@subject` for a check, which unlike a written tuple may ask
+/// about any relation, such as `group:9#member`
+fn allowed(store: &mut MemoryTuples, tuple: &str) -> bool {
+    let (userset, who) = tuple.split_once('@').unwrap();
+    let (what, relation) = userset.split_once('#').unwrap();
+    check_relation_in(store, &object(what), relation, &subject(who)).unwrap()
+}
+
+/// Two teams, one of them on call, sharing a tree of folders
+fn workspace() -> MemoryTuples {
+    let mut store = tuples(&[
+        // Folders: docs > runbooks > database
+        "folder:docs#owner@user:alice",
+        "folder:docs#viewer@group:engineering#member",
+        "folder:runbooks#parent@folder:docs",
+        "folder:runbooks#editor@group:oncall#member",
+        "folder:database#parent@folder:runbooks",
+        // Notes and articles in the folders
+        "note:failover#parent@folder:database",
+        "note:welcome#parent@folder:docs",
+        "note:draft#owner@user:dana",
+        "article:launch#editor@group:design#member",
+        "article:launch#parent@folder:docs",
+    ]);
+
+    // Organization groups and their members
+    for (group, members) in [
+        ("engineering", &["olive", "paul"][..]),
+        ("oncall", &["olive"]),
+        ("design", &["dana"]),
+    ] {
+        store.groups.insert(
+            group.to_string(),
+            members.iter().map(|member| member.to_string()).collect(),
+        );
+    }
+    store
+}
+
+#[test]
+fn test_tuples_parse_and_display() {
+    for value in [
+        "note:42#viewer@user:7c1f4a2e-95d1-4c3b-a0f6-1d2e3f4a5b6c",
+        "folder:docs#editor@group:eng#member",
+        "note:42#parent@folder:docs",
+        "article:a1#owner@group:eng#member",
+    ] {
+        let tuple = RelationTuple::parse(value).unwrap();
+        assert_eq!(tuple.to_string(), value);
+    }
+
+    let tuple = RelationTuple::parse(" article:a1#owner@user:bob ").unwrap();
+    assert_eq!(tuple.object, ObjectRef::new("article", "a1"));
+    assert_eq!(tuple.relation, "owner");
+    assert_eq!(
+        tuple.subject,
+        SubjectRef::object(ObjectRef::new("user", "bob"))
+    );
+    assert_eq!(tuple.subject.type_name(), "user");
+    assert_eq!(subject("group:eng#member").type_name(), "group#member");
+}
+
+#[test]
+fn test_tuples_must_fit_the_namespace_configuration() {
+    for invalid in [
+        "",
+        "note:42#viewer",
+        "note:42@user:bob",
+        "note42#viewer@user:bob",
+        // Unknown namespaces and relations
+        "page:42#viewer@user:bob",
+        "note:42#commenter@user:bob",
+        "note:42#viewer@team:eng",
+        "note:42#viewer@group:eng#owner",
+        // Relations only accept some subject types
+        "note:42#parent@user:bob",
+        "note:42#parent@note:41",
+        "note:42#viewer@folder:docs",
+        "note:42#viewer@folder:docs#viewer",
+        "group:eng#member@note:42",
+        "user:bob#member@user:alice",
+        // Malformed IDs
+        "note:#viewer@user:bob",
+        "note:4 2#viewer@user:bob",
+        "note:42#viewer@user:bob:alice",
+    ] {
+        assert!(RelationTuple::parse(invalid).is_none(), "{}", invalid);
+    }
+
+    let too_long = "x".repeat(Relations::MAX_ID_LENGTH + 1);
+    assert!(RelationTuple::parse(&format!("note:{}#viewer@user:bob", too_long)).is_none());
+    assert!(Relations::is_valid_id(&too_long[1..]));
+}
+
+#[test]
+fn test_consistency_tokens_round_trip() {
+    for revision in [0, 1, 42, i64::MAX] {
+        let token = ConsistencyToken(revision);
+        assert_eq!(ConsistencyToken::decode(&token.encode()), Some(token));
+    }
+    assert!(ConsistencyToken(2) > ConsistencyToken(1));
+
+    for invalid in ["", "rev:1", "not base64!", "cmV2Oi0x", "cmV2OmFiYw"] {
+        assert_eq!(ConsistencyToken::decode(invalid), None, "{}", invalid);
+    }
+}
+
+#[test]
+fn test_group_members_come_from_organization_groups() {
+    let mut store = workspace();
+
+    assert!(allowed(&mut store, "group:engineering#member@user:olive"));
+    assert!(allowed(&mut store, "group:engineering#member@user:paul"));
+    assert!(allowed(&mut store, "group:oncall#member@user:olive"));
+    assert!(!allowed(&mut store, "group:oncall#member@user:paul"));
+    assert!(!allowed(&mut store, "group:engineering#member@user:dana"));
+    assert!(!allowed(&mut store, "group:unknown#member@user:olive"));
+
+    // Engineering can view docs, so every member can
+    assert!(allowed(&mut store, "folder:docs#viewer@user:olive"));
+    assert!(allowed(&mut store, "folder:docs#viewer@user:paul"));
+    assert!(!allowed(&mut store, "folder:docs#viewer@user:dana"));
+
+    // A group's members as a whole hold what is shared with the group
+    assert!(allowed(
+        &mut store,
+        "folder:runbooks#viewer@group:oncall#member"
+    ));
+    assert!(!allowed(
+        &mut store,
+        "folder:docs#viewer@group:oncall#member"
+    ));
+
+    // Membership changes apply without writing tuples
+    store
+        .groups
+        .get_mut("design")
+        .unwrap()
+        .insert("paul".to_string());
+    assert!(allowed(&mut store, "article:launch#editor@user:paul"));
+
+    // Members are only managed through groups, never written as tuples
+    for invalid in [
+        "group:engineering#member@user:olive",
+        "group:engineering#member@group:oncall#member",
+    ] {
+        assert!(RelationTuple::parse(invalid).is_none(), "{}", invalid);
+    }
+}
+
+#[test]
+fn test_folder_access_is_inherited_by_contents() {
+    let mut store = workspace();
+
+    // alice owns docs, so she owns, edits and views everything beneath it
+    for relation in ["owner", "editor", "viewer"] {
+        for item in [
+            "folder:runbooks",
+            "folder:database",
+            "note:failover",
+            "note:welcome",
+            "article:launch",
+        ] {
+            assert!(
+                allowed(&mut store, &format!("{}#{}@user:alice", item, relation)),
+                "{} {}",
+                item,
+                relation
+            );
+        }
+    }
+
+    // Oncall edits runbooks and what's in it, two levels down, but not its siblings
+    assert!(allowed(&mut store, "note:failover#editor@user:olive"));
+    assert!(allowed(&mut store, "folder:database#editor@user:olive"));
+    assert!(!allowed(&mut store, "note:welcome#editor@user:olive"));
+    assert!(!allowed(&mut store, "folder:docs#editor@user:olive"));
+    assert!(!allowed(&mut store, "note:failover#owner@user:olive"));
+
+    // The rest of engineering can only view
+    assert!(allowed(&mut store, "note:failover#viewer@user:paul"));
+    assert!(!allowed(&mut store, "note:failover#editor@user:paul"));
+
+    // Access granted on an item doesn't spread to its folder
+    assert!(allowed(&mut store, "article:launch#editor@user:dana"));
+    assert!(!allowed(&mut store, "folder:docs#viewer@user:dana"));
+
+    // Items outside any folder only have their own tuples
+    assert!(allowed(&mut store, "note:draft#viewer@user:dana"));
+    assert!(!allowed(&mut store, "note:draft#viewer@user:alice"));
+}
+
+#[test]
+fn test_folder_cycles_terminate() {
+    let mut store = tuples(&[
+        "folder:a#parent@folder:b",
+        "folder:b#parent@folder:a",
+        "folder:b#viewer@user:bob",
+    ]);
+
+    assert!(allowed(&mut store, "folder:a#viewer@user:bob"));
+    assert!(!allowed(&mut store, "folder:a#editor@user:bob"));
+    assert!(!allowed(&mut store, "folder:a#viewer@user:eve"));
+}
+
+#[test]
+fn test_deep_nesting_is_rejected() {
+    let mut chain: Vec<String> = (0..=Relations::MAX_DEPTH)
+        .map(|level| format!("folder:f{}#parent@folder:f{}", level + 1, level))
+        .collect();
+    chain.push("folder:f0#viewer@user:bob".to_string());
+    let refs: Vec<&str> = chain.iter().map(String::as_str).collect();
+    let mut store = tuples(&refs);
+
+    let deepest = object(&format!("folder:f{}", Relations::MAX_DEPTH + 1));
+    assert!(check_relation_in(&mut store, &deepest, "viewer", &subject("user:bob")).is_err());
+    assert!(check_relation_in(
+        &mut store,
+        &object("folder:f5"),
+        "viewer",
+        &subject("user:bob")
+    )
+    .unwrap());
+}
+
+#[test]
+fn test_expand_shows_where_access_comes_from() {
+    let mut store = workspace();
+
+    let tree = expand_relation_in(&mut store, &object("note:failover"), "editor").unwrap();
+    assert_eq!(tree.userset, "note:failover#editor");
+    assert!(tree.subjects.is_empty());
+    assert_eq!(
+        tree.leaves(),
+        BTreeSet::from(["user:alice".to_string(), "user:olive".to_string()])
+    );
+
+    let viewers = expand_relation_in(&mut store, &object("note:failover"), "viewer").unwrap();
+    assert_eq!(
+        viewers.leaves(),
+        BTreeSet::from([
+            "user:alice".to_string(),
+            "user:olive".to_string(),
+            "user:paul".to_string()
+        ])
+    );
+
+    // Direct subjects are listed on the node they're written on
+    let docs = expand_relation_in(&mut store, &object("folder:docs"), "owner").unwrap();
+    assert_eq!(docs.subjects, vec!["user:alice".to_string()]);
+
+    let parent = expand_relation_in(&mut store, &object("note:welcome"), "parent").unwrap();
+    assert_eq!(parent.subjects, vec!["folder:docs".to_string()]);
+    assert!(parent.children.is_empty());
+
+    // Everyone expand finds passes check
+    for leaf in viewers.leaves() {
+        assert!(allowed(
+            &mut store,
+            &format!("note:failover#viewer@{}", leaf)
+        ));
+    }
+}
+
+#[test]
+fn test_list_objects_follows_groups_and_folders() {
+    let mut store = workspace();
+
+    let list = |store: &mut MemoryTuples, namespace: &str, relation: &str, who: &str| {
+        list_relation_objects_in(store, namespace, relation, &subject(who), None, 100)
+            .unwrap()
+            .objects
+    };
+
+    assert_eq!(
+        list(&mut store, "note", "viewer", "user:paul"),
+        vec!["failover".to_string(), "welcome".to_string()]
+    );
+    assert_eq!(
+        list(&mut store, "note", "editor", "user:olive"),
+        vec!["failover".to_string()]
+    );
+    assert_eq!(
+        list(&mut store, "folder", "editor", "user:olive"),
+        vec!["database".to_string(), "runbooks".to_string()]
+    );
+    assert_eq!(
+        list(&mut store, "note", "viewer", "user:dana"),
+        vec!["draft".to_string()]
+    );
+    assert_eq!(
+        list(&mut store, "article", "editor", "user:dana"),
+        vec!["launch".to_string()]
+    );
+    assert!(list(&mut store, "note", "viewer", "user:nobody").is_empty());
+
+    // Usersets and the groups themselves can be listed for too
+    assert_eq!(
+        list(&mut store, "note", "editor", "group:oncall#member"),
+        vec!["failover".to_string()]
+    );
+    assert_eq!(
+        list(&mut store, "group", "member", "user:olive"),
+        vec!["engineering".to_string(), "oncall".to_string()]
+    );
+
+    // Every listed object passes check, and every other one fails it
+    for who in ["user:alice", "user:olive", "user:paul", "user:dana"] {
+        for (namespace, ids) in [
+            ("note", &["draft", "failover", "welcome"][..]),
+            ("folder", &["database", "docs", "runbooks"]),
+            ("article", &["launch"]),
+        ] {
+            for relation in ["owner", "editor", "viewer"] {
+                let listed = list(&mut store, namespace, relation, who);
+                for id in ids {
+                    assert_eq!(
+                        allowed(
+                            &mut store,
+                            &format!("{}:{}#{}@{}", namespace, id, relation, who)
+                        ),
+                        listed.contains(&id.to_string()),
+                        "{}:{}#{}@{}",
+                        namespace,
+                        id,
+                        relation,
+                        who
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_list_objects_pages_with_a_cursor() {
+    let mut store = workspace();
+    let alice = subject("user:alice");
+    let mut page = |after: Option<&ListCursor>, limit: usize| -> ObjectPage {
+        list_relation_objects_in(&mut store, "folder", "viewer", &alice, after, limit).unwrap()
+    };
+
+    let first = page(None, 2);
+    assert_eq!(
+        first.objects,
+        vec!["database".to_string(), "docs".to_string()]
+    );
+    let cursor = first.next_cursor.unwrap();
+    assert_eq!(cursor, ListCursor("docs".to_string()));
+
+    // The last page is exactly full, and says there is nothing after it
+    let last = page(Some(&cursor), 1);
+    assert_eq!(last.objects, vec!["runbooks".to_string()]);
+    assert_eq!(last.next_cursor, None);
+
+    let all = page(None, 3);
+    assert_eq!(all.objects.len(), 3);
+    assert_eq!(all.next_cursor, None);
+
+    // Cursors are opaque, and only decode from `encode`
+    assert_eq!(ListCursor::decode(&cursor.encode()), Some(cursor));
+    for invalid in ["", "docs", "not base64!", "YWZ0ZXI6"] {
+        assert_eq!(ListCursor::decode(invalid), None, "{}", invalid);
+    }
+}
+
+/// Nodes in the tree, references included
+fn tree_size(tree: &ExpandTree) -> usize {
+    1 + tree.children.iter().map(tree_size).sum::<usize>()
+}
+
+#[test]
+fn test_expand_lists_each_userset_once() {
+    // Every folder on a level is in both folders of the level above, so there are 2^30
+    // paths from the top folder to the bottom one
+    let mut ladder = vec!["folder:a0#viewer@user:bob".to_string()];
+    for level in 1..=30 {
+        for folder in ["a", "b"] {
+            for parent in ["a", "b"] {
+                ladder.push(format!(
+                    "folder:{}{}#parent@folder:{}{}",
+                    folder,
+                    level,
+                    parent,
+                    level - 1
+                ));
+            }
+        }
+    }
+    let refs: Vec<&str> = ladder.iter().map(String::as_str).collect();
+    let mut store = tuples(&refs);
+
+    let tree = expand_relation_in(&mut store, &object("folder:a30"), "viewer").unwrap();
+    assert_eq!(tree.leaves(), BTreeSet::from(["user:bob".to_string()]));
+    // Three relations for each of the 62 folders, plus one reference per repeat
+    assert!(tree_size(&tree) < 1000, "{}", tree_size(&tree));
+
+    fn references(tree: &ExpandTree) -> usize {
+        usize::from(tree.reference) + tree.children.iter().map(references).sum::<usize>()
+    }
+    assert!(references(&tree) > 0);
+
+    // References are marked as such and nothing else is
+    let json = serde_json::to_value(&tree).unwrap();
+    assert!(json.get("reference").is_none());
+    assert!(allowed(&mut store, "folder:a30#viewer@user:bob"));
+}
+
+#[tokio::test]
+async fn test_relation_endpoints_validate_requests() {
+    let app = app();
+    let token = ConsistencyToken(3).encode();
+
+    let too_many: Vec<String> = (0..=Relations::MAX_WRITE_SIZE)
+        .map(|n| format!("note:{}#viewer@user:bob", n))
+        .collect();
+    let invalid = [
+        ("/relations/write", json!({})),
+        ("/relations/write", json!({ "writes": too_many })),
+        (
+            "/relations/write",
+            json!({ "writes": ["note:1#viewer@user:bob"], "deletes": ["note:1#parent@user:bob"] }),
+        ),
+        (
+            "/relations/check",
+            json!({ "object": "note:1", "relation": "commenter", "subject": "user:bob" }),
+        ),
+        (
+            "/relations/check",
+            json!({ "object": "note:1", "relation": "viewer", "subject": "bob" }),
+        ),
+        (
+            "/relations/check",
+            json!({
+                "object": "note:1",
+                "relation": "viewer",
+                "subject": "user:bob",
+                "consistency_token": "nope"
+            }),
+        ),
+        (
+            "/relations/expand",
+            json!({ "object": "page:1", "relation": "viewer" }),
+        ),
+        (
+            "/relations/list-objects",
+            json!({ "namespace": "note", "relation": "member", "subject": "user:bob" }),
+        ),
+        (
+            "/relations/list-objects",
+            json!({ "namespace": "note", "relation": "viewer", "subject": "group:eng#owner" }),
+        ),
+        (
+            "/relations/list-objects",
+            json!({ "namespace": "note", "relation": "viewer", "subject": "user:bob", "cursor": "nope" }),
+        ),
+        (
+            "/relations/write",
+            json!({ "writes": ["group:eng#member@user:bob"] }),
+        ),
+    ];
+    for (uri, body) in invalid {
+        let (status, response) = internal_call(&app, Method::POST, uri, body.clone()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{} {}", uri, body);
+        assert_eq!(response["error"]["code"], ErrorCode::VALIDATION_ERROR);
+    }
+
+    // Valid requests reach the database
+    let valid = [
+        (
+            "/relations/write",
+            json!({ "writes": ["note:1#viewer@group:eng#member"], "deletes": ["note:1#viewer@user:bob"] }),
+        ),
+        (
+            "/relations/check",
+            json!({
+                "object": "note:1",
+                "relation": "viewer",
+                "subject": "user:bob",
+                "consistency_token": token
+            }),
+        ),
+        (
+            "/relations/expand",
+            json!({ "object": "folder:docs", "relation": "editor" }),
+        ),
+        (
+            "/relations/list-objects",
+            json!({
+                "namespace": "article",
+                "relation": "viewer",
+                "subject": "user:bob",
+                "cursor": ListCursor("a1".to_string()).encode()
+            }),
+        ),
+    ];
+    for (uri, body) in valid {
+        let (status, response) = internal_call(&app, Method::POST, uri, body.clone()).await;
+        assert_eq!(
+            status,
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "{} {}",
+            uri,
+            body
+        );
+        assert_eq!(response["error"]["code"], ErrorCode::DATABASE_ERROR);
+    }
+}
+
+#[tokio::test]
+async fn test_relation_endpoints_require_a_service_key() {
+    let app = app();
+
+    for (uri, body) in [
+        (
+            "/relations/write",
+            json!({ "writes": ["note:1#viewer@user:bob"] }),
+        ),
+        (
+            "/relations/check",
+            json!({ "object": "note:1", "relation": "viewer", "subject": "user:bob" }),
+        ),
+        (
+            "/relations/expand",
+            json!({ "object": "note:1", "relation": "viewer" }),
+        ),
+        (
+            "/relations/list-objects",
+            json!({ "namespace": "note", "relation": "viewer", "subject": "user:bob" }),
+        ),
+    ] {
+        let (status, response) = post(&app, uri, body).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", uri);
+        assert_eq!(response["error"]["code"], ErrorCode::SERVICE_UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,179 @@
+use venomous_dashboard_auth::database::{RoleHierarchy, RoleNode};
+use venomous_dashboard_auth::handlers::admin::UpdateRoleRequest;
+use venomous_dashboard_auth::{Permissions, Roles};
+
+use crate::support::node;
+
+/// The built-in chain as migrated: super_admin -> admin -> user, plus a support role under user
+fn built_in_hierarchy() -> (Vec<RoleNode>, RoleHierarchy) {
+    let user = node(Roles::USER, None, &[]);
+    let admin = node(Roles::ADMIN, Some(&user), &Permissions::ALL);
+    let super_admin = node(Roles::SUPER_ADMIN, Some(&admin), &[]);
+    let support = node("support", Some(&user), &[Permissions::USERS_READ]);
+    let nodes = vec![user, admin, super_admin, support];
+
+    (nodes.clone(), RoleHierarchy::new(nodes))
+}
+
+#[test]
+fn test_role_name_validation() {
+    for name in ["editor", "support_agent", "l2", "ab"] {
+        assert!(Roles::is_valid_name(name), "{}", name);
+    }
+
+    for name in [
+        "",
+        "a",
+        "Editor",
+        "2nd_line",
+        "_hidden",
+        "support-agent",
+        "read only",
+        "管理者",
+    ] {
+        assert!(!Roles::is_valid_name(name), "{}", name);
+    }
+
+    assert!(Roles::is_valid_name(&"a".repeat(Roles::MAX_NAME_LENGTH)));
+    assert!(!Roles::is_valid_name(
+        &"a".repeat(Roles::MAX_NAME_LENGTH + 1)
+    ));
+}
+
+#[test]
+fn test_built_in_roles() {
+    for name in [Roles::USER, Roles::ADMIN, Roles::SUPER_ADMIN] {
+        assert!(Roles::is_built_in(name));
+        assert!(Roles::is_valid_name(name));
+    }
+    assert!(!Roles::is_built_in("editor"));
+}
+
+#[test]
+fn test_update_role_request_tells_null_from_missing() {
+    let request: UpdateRoleRequest = serde_json::from_str("{}").unwrap();
+    assert_eq!(request.name, None);
+    assert_eq!(request.description, None);
+    assert_eq!(request.password_max_age_days, None);
+    assert_eq!(request.permissions, None);
+
+    assert_eq!(request.parent, None);
+
+    let request: UpdateRoleRequest = serde_json::from_str(
+        r#"{"description": null, "password_max_age_days": null, "parent": null}"#,
+    )
+    .unwrap();
+    assert_eq!(request.description, Some(None));
+    assert_eq!(request.password_max_age_days, Some(None));
+    assert_eq!(request.parent, Some(None));
+
+    let request: UpdateRoleRequest = serde_json::from_str(
+        r#"{"name": "editor", "description": "Edits notes", "password_max_age_days": 90, "permissions": []}"#,
+    )
+    .unwrap();
+    assert_eq!(request.name.as_deref(), Some("editor"));
+    assert_eq!(request.description, Some(Some("Edits notes".to_string())));
+    assert_eq!(request.password_max_age_days, Some(Some(90)));
+    assert_eq!(request.permissions, Some(vec![]));
+}
+
+#[test]
+fn test_roles_inherit_their_parents_permissions() {
+    let (_, hierarchy) = built_in_hierarchy();
+
+    assert!(hierarchy.effective_permissions(Roles::USER).is_empty());
+    assert_eq!(
+        hierarchy.effective_permissions(Roles::SUPER_ADMIN),
+        hierarchy.effective_permissions(Roles::ADMIN)
+    );
+    assert_eq!(
+        hierarchy.effective_permissions(Roles::ADMIN).len(),
+        Permissions::ALL.len()
+    );
+    assert!(hierarchy.has_permission(Roles::SUPER_ADMIN, Permissions::AUDIT_EXPORT));
+    assert_eq!(
+        hierarchy.effective_permissions("support"),
+        [Permissions::USERS_READ.to_string()]
+    );
+    assert!(!hierarchy.has_permission("support", Permissions::USERS_DISABLE));
+
+    // Unknown (e.g. deleted) roles grant nothing
+    assert!(hierarchy.effective_permissions("ghost").is_empty());
+    assert!(hierarchy.ancestors("ghost").is_empty());
+}
+
+#[test]
+fn test_effective_permissions_are_sorted_without_duplicates() {
+    let base = node(
+        "base",
+        None,
+        &[Permissions::USERS_READ, Permissions::AUDIT_READ],
+    );
+    let child = node("child", Some(&base), &[Permissions::USERS_READ]);
+    let hierarchy = RoleHierarchy::new(vec![base, child]);
+
+    assert_eq!(
+        hierarchy.effective_permissions("child"),
+        [
+            Permissions::AUDIT_READ.to_string(),
+            Permissions::USERS_READ.to_string()
+        ]
+    );
+}
+
+#[test]
+fn test_inherits_walks_the_whole_chain() {
+    let (_, hierarchy) = built_in_hierarchy();
+
+    assert_eq!(
+        hierarchy.ancestors(Roles::SUPER_ADMIN),
+        [Roles::SUPER_ADMIN, Roles::ADMIN, Roles::USER]
+    );
+    assert!(hierarchy.inherits(Roles::SUPER_ADMIN, Roles::SUPER_ADMIN));
+    assert!(hierarchy.inherits(Roles::SUPER_ADMIN, Roles::ADMIN));
+    assert!(hierarchy.inherits(Roles::SUPER_ADMIN, Roles::USER));
+    assert!(!hierarchy.inherits(Roles::ADMIN, Roles::SUPER_ADMIN));
+    assert!(!hierarchy.inherits("support", Roles::ADMIN));
+
+    assert!(hierarchy.grants_admin_access(Roles::SUPER_ADMIN));
+    assert!(hierarchy.grants_admin_access("support"));
+    assert!(!hierarchy.grants_admin_access(Roles::USER));
+}
+
+#[test]
+fn test_custom_roles_under_super_admin_count_as_super_admins() {
+    let (mut nodes, _) = built_in_hierarchy();
+    let owner = node("owner", nodes.get(2), &[]);
+    nodes.push(owner);
+    let hierarchy = RoleHierarchy::new(nodes);
+
+    assert!(hierarchy.inherits("owner", Roles::SUPER_ADMIN));
+    assert!(hierarchy.has_permission("owner", Permissions::USERS_DISABLE));
+}
+
+#[test]
+fn test_cycles_are_detected() {
+    let (nodes, hierarchy) = built_in_hierarchy();
+    let [user, admin, super_admin, support] = [&nodes[0], &nodes[1], &nodes[2], &nodes[3]];
+
+    // A role can't be its own parent or inherit from its descendants
+    assert!(hierarchy.would_create_cycle(admin.id, admin.id));
+    assert!(hierarchy.would_create_cycle(user.id, super_admin.id));
+    assert!(hierarchy.would_create_cycle(admin.id, super_admin.id));
+
+    // Re-parenting sideways or further up is fine
+    assert!(!hierarchy.would_create_cycle(support.id, admin.id));
+    assert!(!hierarchy.would_create_cycle(super_admin.id, user.id));
+}
+
+#[test]
+fn test_existing_cycles_do_not_loop_forever() {
+    let mut a = node("a", None, &[Permissions::USERS_READ]);
+    let b = node("b", Some(&a), &[Permissions::AUDIT_READ]);
+    a.parent_id = Some(b.id);
+    let hierarchy = RoleHierarchy::new(vec![a, b]);
+
+    assert_eq!(hierarchy.ancestors("a"), ["a", "b"]);
+    assert_eq!(hierarchy.effective_permissions("a").len(), 2);
+    assert_eq!(hierarchy.effective_permissions("b").len(), 2);
+}
@@ -0,0 +1,174 @@
+use chrono::{TimeZone, Utc};
+use serde_json::json;
+use uuid::Uuid;
+use venomous_dashboard_auth::database::SecurityEventFilter;
+use venomous_dashboard_auth::handlers::admin::{SecurityLogsQuery, SecurityStreamQuery};
+use venomous_dashboard_auth::utils::ClientInfo;
+use venomous_dashboard_auth::SecurityEvent;
+
+fn query(params: serde_json::Value) -> SecurityLogsQuery {
+    serde_json::from_value(params).unwrap()
+}
+
+#[test]
+fn test_security_log_filter_parsing() {
+    let user_id = Uuid::new_v4();
+    let filter = query(json!({
+        "event_type": "signin_failed",
+        "user_id": user_id.to_string(),
+        "start_date": "2026-10-01",
+        "end_date": "2026-10-18"
+    }))
+    .filter()
+    .unwrap();
+
+    assert_eq!(filter.event_type.as_deref(), Some("signin_failed"));
+    assert_eq!(filter.user_id, Some(user_id));
+    assert_eq!(
+        filter.since,
+        Some(Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap())
+    );
+    // A plain end date includes that whole day
+    assert_eq!(
+        filter.until,
+        Some(Utc.with_ymd_and_hms(2026, 10, 19, 0, 0, 0).unwrap())
+    );
+
+    // Timestamps are taken as given, in any offset
+    let filter = query(json!({ "end_date": "2026-10-18T09:30:00+09:00" }))
+        .filter()
+        .unwrap();
+    assert_eq!(
+        filter.until,
+        Some(Utc.with_ymd_and_hms(2026, 10, 18, 0, 30, 0).unwrap())
+    );
+
+    // Empty values don't filter
+    let filter = query(json!({ "event_type": "", "user_id": " " }))
+        .filter()
+        .unwrap();
+    assert!(filter.event_type.is_none() && filter.user_id.is_none());
+}
+
+#[test]
+fn test_security_log_filter_rejects_invalid_values() {
+    assert!(query(json!({ "user_id": "not-a-uuid" })).filter().is_none());
+    assert!(query(json!({ "start_date": "yesterday" }))
+        .filter()
+        .is_none());
+    assert!(query(json!({ "end_date": "2026-13-01" }))
+        .filter()
+        .is_none());
+}
+
+fn event(event_type: &str, user_id: Option<Uuid>) -> SecurityEvent {
+    SecurityEvent {
+        id: Uuid::new_v4(),
+        user_id,
+        event_type: event_type.to_string(),
+        success: false,
+        ip_address: None,
+        user_agent: None,
+        metadata: None,
+        created_at: Utc.with_ymd_and_hms(2026, 10, 18, 12, 0, 0).unwrap(),
+        sequence: Some(1),
+        prev_hash: None,
+        entry_hash: None,
+        stream: 0,
+    }
+}
+
+#[test]
+fn test_security_event_filter_matches() {
+    let user_id = Uuid::new_v4();
+    let other_user = Uuid::new_v4();
+
+    assert!(SecurityEventFilter::default().matches(&event("signup", None)));
+
+    let filter = SecurityEventFilter {
+        event_type: Some("signin_failed".to_string()),
+        user_id: Some(user_id),
+        ..Default::default()
+    };
+    assert!(filter.matches(&event("signin_failed", Some(user_id))));
+    assert!(!filter.matches(&event("signin_failed", Some(other_user))));
+    assert!(!filter.matches(&event("signin_failed", None)));
+    assert!(!filter.matches(&event("signin_success", Some(user_id))));
+
+    // Same bounds as the database query: since inclusive, until exclusive
+    let at = event("signup", None).created_at;
+    let window = |since, until| SecurityEventFilter {
+        since: Some(since),
+        until: Some(until),
+        ..Default::default()
+    };
+    assert!(window(at, at + chrono::Duration::seconds(1)).matches(&event("signup", None)));
+    assert!(!window(at - chrono::Duration::seconds(1), at).matches(&event("signup", None)));
+}
+
+#[test]
+fn test_security_stream_filter_parsing() {
+    let user_id = Uuid::new_v4();
+    let stream: SecurityStreamQuery = serde_json::from_value(json!({
+        "event_type": "account_locked",
+        "user_id": user_id.to_string()
+    }))
+    .unwrap();
+    let filter = stream.filter().unwrap();
+
+    assert_eq!(filter.event_type.as_deref(), Some("account_locked"));
+    assert_eq!(filter.user_id, Some(user_id));
+    assert!(filter.since.is_none() && filter.until.is_none());
+
+    let invalid: SecurityStreamQuery =
+        serde_json::from_value(json!({ "user_id": "not-a-uuid" })).unwrap();
+    assert!(invalid.filter().is_none());
+}
+
+#[test]
+fn test_client_ip_uses_the_proxy_appended_hop() {
+    let mut headers = axum::http::HeaderMap::new();
+    headers.insert("x-forwarded-for", "6.6.6.6, 203.0.113.7".parse().unwrap());
+    headers.insert("x-real-ip", "10.0.0.1".parse().unwrap());
+    assert_eq!(
+        ClientInfo::from_headers(&headers).ip_address.as_deref(),
+        Some("203.0.113.7")
+    );
+
+    headers.remove("x-forwarded-for");
+    assert_eq!(
+        ClientInfo::from_headers(&headers).ip_address.as_deref(),
+        Some("10.0.0.1")
+    );
+}
+
+#[test]
+fn test_sign_in_client_keys_ignore_versions_and_host_bits() {
+    let chrome = |version: &str| {
+        format!(
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/{} Safari/537.36",
+            version
+        )
+    };
+    assert_eq!(
+        ClientInfo::device_key(&chrome("120.0.0.0")),
+        ClientInfo::device_key(&chrome("121.0.6167.85"))
+    );
+    assert_ne!(
+        ClientInfo::device_key(&chrome("120.0.0.0")),
+        ClientInfo::device_key(
+            "Mozilla/5.0 (X11; Linux x86_64; rv:121.0) Gecko/20100101 Firefox/121.0"
+        )
+    );
+
+    assert_eq!(ClientInfo::network_prefix("203.0.113.7"), "203.0.113.0/24");
+    assert_eq!(
+        ClientInfo::network_prefix("203.0.113.7"),
+        ClientInfo::network_prefix("203.0.113.200")
+    );
+    assert_eq!(
+        ClientInfo::network_prefix("2001:db8:abcd:12::1"),
+        "2001:db8:abcd::/48"
+    );
+    assert_eq!(ClientInfo::network_prefix("unknown"), "unknown");
+}
@@ -0,0 +1,61 @@
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode};
+use serde_json::Value;
+use uuid::Uuid;
+use venomous_dashboard_auth::utils::ServiceKeys;
+use venomous_dashboard_auth::{ErrorCode, ErrorMessage};
+
+use crate::support::{app, call, send, SERVICE_KEY};
+
+#[test]
+fn test_service_keys_name_the_calling_service() {
+    let keys =
+        ServiceKeys::parse(" notes=first-key ,workflows=second=key,, broken, =orphan, medias= ");
+
+    assert_eq!(keys.authenticate("first-key"), Some("notes"));
+    // Only the first `=` separates the name, so secrets may contain one
+    assert_eq!(keys.authenticate("second=key"), Some("workflows"));
+
+    for key in ["", "orphan", "broken", "first-key ", "FIRST-KEY", "first"] {
+        assert_eq!(keys.authenticate(key), None, "{:?}", key);
+    }
+
+    assert!(ServiceKeys::parse("").is_empty());
+    assert!(ServiceKeys::parse("notes").is_empty());
+}
+
+#[tokio::test]
+async fn test_internal_routes_require_a_service_key() {
+    let app = app();
+    let uri = format!(
+        "/internal/group-membership?organization_id={}&user_id={}&group=editors",
+        Uuid::new_v4(),
+        Uuid::new_v4()
+    );
+
+    let with_key = |key: &str| {
+        Request::builder()
+            .uri(&uri)
+            .header(ServiceKeys::HEADER, key)
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    // A user token is no substitute for a service key
+    for (status, body) in [
+        call(&app, Method::GET, &uri, Some("user-token"), Value::Null).await,
+        send(&app, with_key("wrong-key")).await,
+        send(&app, with_key("")).await,
+    ] {
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"]["code"], ErrorCode::SERVICE_UNAUTHORIZED);
+        assert_eq!(body["error"]["message"], ErrorMessage::SERVICE_KEY_INVALID);
+    }
+
+    // Any configured service gets through to the handler, which then needs the database
+    for key in [SERVICE_KEY, "workflows-service-key"] {
+        let (status, body) = send(&app, with_key(key)).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body["error"]["code"], ErrorCode::DATABASE_ERROR);
+    }
+}
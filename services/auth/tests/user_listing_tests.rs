@@ -0,0 +1,78 @@
+use chrono::Utc;
+use serde_json::json;
+use uuid::Uuid;
+use venomous_dashboard_auth::database::{SortOrder, UserListCursor, UserSortField};
+
+#[test]
+fn test_sort_field_parsing() {
+    for field in [
+        UserSortField::Email,
+        UserSortField::Name,
+        UserSortField::Role,
+        UserSortField::Status,
+        UserSortField::SuspendedUntil,
+        UserSortField::EmailVerified,
+        UserSortField::LoginFailureCount,
+        UserSortField::LastLoginAt,
+        UserSortField::CreatedAt,
+    ] {
+        assert_eq!(field.as_str().parse::<UserSortField>(), Ok(field));
+    }
+    assert!("password_hash".parse::<UserSortField>().is_err());
+
+    assert_eq!("asc".parse::<SortOrder>(), Ok(SortOrder::Asc));
+    assert_eq!("desc".parse::<SortOrder>(), Ok(SortOrder::Desc));
+    assert!("DESC".parse::<SortOrder>().is_err());
+}
+
+#[test]
+fn test_cursor_round_trip() {
+    let cursors = [
+        (UserSortField::Email, json!("alice@example.com")),
+        (UserSortField::Status, json!("suspended")),
+        (UserSortField::EmailVerified, json!(false)),
+        (UserSortField::LoginFailureCount, json!(3)),
+        (UserSortField::CreatedAt, json!(Utc::now())),
+        (UserSortField::LastLoginAt, json!(null)),
+    ];
+
+    for (sort, value) in cursors {
+        let cursor = UserListCursor {
+            sort,
+            order: SortOrder::Desc,
+            value,
+            id: Uuid::new_v4(),
+        };
+        let token = cursor.encode();
+
+        // URL-safe without escaping
+        assert!(token
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(UserListCursor::decode(&token), Some(cursor));
+    }
+}
+
+#[test]
+fn test_cursor_rejects_malformed_tokens() {
+    assert_eq!(UserListCursor::decode(""), None);
+    assert_eq!(UserListCursor::decode("not a cursor"), None);
+
+    // Values must have the sort column's type
+    let mismatched = [
+        (UserSortField::CreatedAt, json!(null)),
+        (UserSortField::CreatedAt, json!("yesterday")),
+        (UserSortField::Status, json!("banned")),
+        (UserSortField::LoginFailureCount, json!(1u64 << 40)),
+        (UserSortField::Email, json!(42)),
+    ];
+    for (sort, value) in mismatched {
+        let cursor = UserListCursor {
+            sort,
+            order: SortOrder::Asc,
+            value,
+            id: Uuid::new_v4(),
+        };
+        assert_eq!(UserListCursor::decode(&cursor.encode()), None);
+    }
+}